@@ -1,9 +1,20 @@
 //! Commitment scheme for zero-knowledge liquidity proofs
 
+use std::collections::HashSet;
+
 use blake2::{Blake2b512, Digest};
 use rand::RngCore;
 
-use super::types::{CommitmentOpening, Hash, LiquidityCommitment, Nullifier};
+use super::merkle::verify_commitment_membership;
+use super::range_proof::{
+    amount_tier, commit_amount, generate_bound_range_proof, prove_hidden_minimum, tier_range,
+    verify_bound_range_proof, verify_hidden_minimum, LiquidityRangeProof,
+};
+use super::types::{
+    CommitmentOpening, Hash, LiquidityCommitment, MinAmountDisclosure, Nullifier,
+    OrderCommitRequest,
+};
+use crate::error::{BlackTraceError, Result};
 
 /// Generate a liquidity commitment
 pub fn generate_commitment(
@@ -13,8 +24,8 @@ pub fn generate_commitment(
     viewing_key: &[u8],
     order_id: &str,
 ) -> LiquidityCommitment {
-    // Generate commitment hash: Hash(amount || salt)
-    let commitment_hash = compute_commitment_hash(amount, salt);
+    // Generate commitment hash: Hash(amount || salt || order_id)
+    let commitment_hash = compute_commitment_hash(amount, salt, order_id);
 
     // Generate nullifier: Hash(viewing_key || order_id)
     let nullifier = generate_nullifier(viewing_key, order_id);
@@ -24,6 +35,8 @@ pub fn generate_commitment(
         commitment_hash,
         nullifier,
         min_amount,
+        hidden_min: None,
+        range_proof: None,
         timestamp: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -31,11 +44,168 @@ pub fn generate_commitment(
     }
 }
 
-/// Compute commitment hash from amount and salt
-pub fn compute_commitment_hash(amount: u64, salt: &[u8; 32]) -> Hash {
+/// Generate a liquidity commitment, publishing the minimum as the disclosure policy allows
+///
+/// Under `MinAmountDisclosure::Tier` the public `min_amount` is only the lower bound of the
+/// minimum's tier, and the real minimum is enforced by a range proof. This needs the
+/// `range-proofs` feature.
+pub fn generate_commitment_with_disclosure(
+    amount: u64,
+    salt: &[u8; 32],
+    min_amount: u64,
+    viewing_key: &[u8],
+    order_id: &str,
+    disclosure: MinAmountDisclosure,
+) -> Result<LiquidityCommitment> {
+    let mut commitment = generate_commitment(amount, salt, min_amount, viewing_key, order_id);
+    if disclosure == MinAmountDisclosure::Tier {
+        let tier = amount_tier(min_amount);
+        let proof = prove_hidden_minimum(
+            amount,
+            salt,
+            min_amount,
+            tier,
+            order_id,
+            commitment.commitment_hash.as_bytes(),
+        )?;
+        commitment.min_amount = tier_range(tier).map_or(0, |(tier_min, _)| tier_min);
+        commitment.hidden_min = Some(proof);
+    }
+    Ok(commitment)
+}
+
+/// Verify a commitment's hidden-minimum proof without its opening
+///
+/// The proof must have been made for this order and this commitment hash. Commitments that
+/// publish their exact minimum have nothing to prove and pass.
+pub fn verify_min_amount(commitment: &LiquidityCommitment, order_id: &str) -> bool {
+    let Some(proof) = &commitment.hidden_min else {
+        return true;
+    };
+    let tier_min = match tier_range(proof.tier) {
+        Some((tier_min, _)) => tier_min,
+        None => return false,
+    };
+    commitment.min_amount == tier_min
+        && verify_hidden_minimum(proof, order_id, commitment.commitment_hash.as_bytes()).is_ok()
+}
+
+/// Generate a liquidity commitment carrying a range proof that `amount` is in `[min_amount, max_amount]`
+///
+/// The hash commitment is unchanged, so the commitment still opens as one from
+/// `generate_commitment`. The proof is bound to `order_id` and the commitment hash, so it does
+/// not verify on any other commitment. This needs the `range-proofs` feature.
+pub fn generate_commitment_with_range_proof(
+    amount: u64,
+    salt: &[u8; 32],
+    min_amount: u64,
+    max_amount: u64,
+    viewing_key: &[u8],
+    order_id: &str,
+) -> Result<LiquidityCommitment> {
+    let mut commitment = generate_commitment(amount, salt, min_amount, viewing_key, order_id);
+    let proof = generate_bound_range_proof(
+        amount,
+        salt,
+        min_amount,
+        max_amount,
+        order_id,
+        commitment.commitment_hash.as_bytes(),
+    )?;
+    commitment.range_proof = Some(LiquidityRangeProof {
+        min_amount,
+        max_amount,
+        proof,
+    });
+    Ok(commitment)
+}
+
+/// Verify without the opening that a commitment's amount lies in `[min_amount, max_amount]`
+///
+/// Passes when the commitment's range proof covers a range inside the one asked for and was made
+/// for this order and this commitment hash. Commitments without a range proof fail: only their
+/// opening can show the amount.
+pub fn verify_commitment_range(
+    commitment: &LiquidityCommitment,
+    order_id: &str,
+    min_amount: u64,
+    max_amount: u64,
+) -> bool {
+    let Some(range) = &commitment.range_proof else {
+        return false;
+    };
+    range.min_amount >= min_amount
+        && range.max_amount <= max_amount
+        && verify_bound_range_proof(
+            &range.proof,
+            range.min_amount,
+            range.max_amount,
+            order_id,
+            commitment.commitment_hash.as_bytes(),
+        )
+        .is_ok()
+}
+
+/// Generate commitments for many orders at once, each with its own fresh salt
+///
+/// All requests are checked before anything is returned: an amount below its minimum, a
+/// repeated order ID or a salt collision fails the whole batch. Returns each commitment with
+/// the opening the maker keeps to reveal it later.
+pub fn generate_commitments_batch(
+    requests: &[OrderCommitRequest],
+) -> Result<Vec<(LiquidityCommitment, CommitmentOpening)>> {
+    let invalid = |msg: String| BlackTraceError::InvalidCommitmentRequest(msg);
+
+    let mut order_ids = HashSet::with_capacity(requests.len());
+    for request in requests {
+        if request.amount < request.min_amount {
+            return Err(invalid(format!(
+                "order {}: amount {} is below minimum {}",
+                request.order_id, request.amount, request.min_amount
+            )));
+        }
+        if !order_ids.insert(request.order_id.as_str()) {
+            return Err(invalid(format!(
+                "order {} requested twice",
+                request.order_id
+            )));
+        }
+    }
+
+    let mut salts = HashSet::with_capacity(requests.len());
+    let mut batch = Vec::with_capacity(requests.len());
+    for request in requests {
+        let salt = generate_random_salt();
+        if !salts.insert(salt) {
+            return Err(invalid("salt collision".to_string()));
+        }
+
+        let commitment = generate_commitment(
+            request.amount,
+            &salt,
+            request.min_amount,
+            &request.viewing_key,
+            &request.order_id,
+        );
+        let opening = CommitmentOpening {
+            amount: request.amount,
+            salt,
+            merkle_proof: None,
+        };
+        batch.push((commitment, opening));
+    }
+
+    Ok(batch)
+}
+
+/// Compute commitment hash from amount, salt and the order it is bound to
+///
+/// Including the order ID stops a commitment from being lifted onto another order.
+pub fn compute_commitment_hash(amount: u64, salt: &[u8; 32], order_id: &str) -> Hash {
     let mut hasher = Blake2b512::new();
     hasher.update(amount.to_be_bytes());
     hasher.update(salt);
+    hasher.update(order_id.as_bytes());
     let result = hasher.finalize();
     Hash::from_bytes(&result[..32])
 }
@@ -50,25 +220,104 @@ pub fn generate_nullifier(viewing_key: &[u8], order_id: &str) -> Nullifier {
     Nullifier::new(hash)
 }
 
-/// Verify a commitment opening
+/// Verify a commitment opening for the given order
 pub fn verify_commitment(
     commitment: &LiquidityCommitment,
     opening: &CommitmentOpening,
+    order_id: &str,
+) -> bool {
+    if !verify_commitment_raw(
+        commitment.commitment_hash.as_bytes(),
+        commitment.min_amount,
+        opening.amount,
+        &opening.salt,
+        order_id,
+    ) {
+        return false;
+    }
+
+    // A hidden minimum is only enforced by its proof, which must be over this amount
+    if let Some(proof) = &commitment.hidden_min {
+        if commit_amount(opening.amount, &opening.salt) != Ok(proof.amount_commitment) {
+            return false;
+        }
+        if !verify_min_amount(commitment, order_id) {
+            return false;
+        }
+    }
+
+    // Likewise a range proof must be over this amount, and the amount inside its range
+    if let Some(range) = &commitment.range_proof {
+        if commit_amount(opening.amount, &opening.salt) != Ok(range.proof.commitment)
+            || opening.amount < range.min_amount
+            || opening.amount > range.max_amount
+        {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Verify a commitment opening from primitive inputs, for callers across an FFI boundary
+///
+/// Performs the same hash and minimum-amount checks as `verify_commitment`. The order ID is
+/// required because the commitment hash is bound to it. Hidden-minimum proofs cannot be passed
+/// as primitives, so commitments carrying one must go through `verify_commitment`.
+pub fn verify_commitment_raw(
+    commitment_hash: &[u8; 32],
+    min_amount: u64,
+    opening_amount: u64,
+    salt: &[u8; 32],
+    order_id: &str,
 ) -> bool {
     // Recompute commitment hash
-    let computed_hash = compute_commitment_hash(opening.amount, &opening.salt);
+    let computed_hash = compute_commitment_hash(opening_amount, salt, order_id);
 
     // Check if it matches
-    if computed_hash != commitment.commitment_hash {
+    if computed_hash.as_bytes() != commitment_hash {
         return false;
     }
 
     // Check if amount meets minimum
-    if opening.amount < commitment.min_amount {
-        return false;
-    }
+    opening_amount >= min_amount
+}
 
-    true
+/// Verify that a commitment's nullifier derives from the given viewing key and order
+pub fn verify_nullifier(
+    commitment: &LiquidityCommitment,
+    viewing_key: &[u8],
+    order_id: &str,
+) -> bool {
+    generate_nullifier(viewing_key, order_id) == commitment.nullifier
+}
+
+/// Verify a commitment opening together with the nullifier derivation
+pub fn verify_commitment_full(
+    commitment: &LiquidityCommitment,
+    opening: &CommitmentOpening,
+    viewing_key: &[u8],
+    order_id: &str,
+) -> bool {
+    verify_commitment(commitment, opening, order_id)
+        && verify_nullifier(commitment, viewing_key, order_id)
+}
+
+/// Verify a commitment opening and that the commitment is in the set the maker published
+///
+/// For a verifier that holds only the maker's published `CommitmentTree` root: the opening
+/// must carry a membership proof for the commitment against that root.
+pub fn verify_commitment_published(
+    commitment: &LiquidityCommitment,
+    opening: &CommitmentOpening,
+    order_id: &str,
+    published_root: &Hash,
+) -> bool {
+    let Some(proof) = &opening.merkle_proof else {
+        return false;
+    };
+    verify_commitment(commitment, opening, order_id)
+        && verify_commitment_membership(&commitment.commitment_hash, proof, published_root)
 }
 
 /// Generate random salt for commitments
@@ -94,8 +343,12 @@ impl CommitmentScheme {
     }
 
     /// Verify a commitment opening
-    pub fn verify(commitment: &LiquidityCommitment, opening: &CommitmentOpening) -> bool {
-        verify_commitment(commitment, opening)
+    pub fn verify(
+        commitment: &LiquidityCommitment,
+        opening: &CommitmentOpening,
+        order_id: &str,
+    ) -> bool {
+        verify_commitment(commitment, opening, order_id)
     }
 
     /// Generate random salt
@@ -103,3 +356,418 @@ impl CommitmentScheme {
         generate_random_salt()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::merkle::CommitmentTree;
+
+    #[test]
+    fn test_commitment_bound_to_order() {
+        let salt = generate_random_salt();
+        let commitment = generate_commitment(10_000, &salt, 5_000, b"viewing-key", "order_A");
+        let opening = CommitmentOpening {
+            amount: 10_000,
+            salt,
+            merkle_proof: None,
+        };
+
+        assert!(verify_commitment(&commitment, &opening, "order_A"));
+        assert!(!verify_commitment(&commitment, &opening, "order_B"));
+    }
+
+    #[test]
+    fn test_verify_nullifier() {
+        let salt = generate_random_salt();
+        let opening = CommitmentOpening {
+            amount: 10_000,
+            salt,
+            merkle_proof: None,
+        };
+        let commitment = generate_commitment(10_000, &salt, 5_000, b"viewing-key", "order_A");
+
+        assert!(verify_nullifier(&commitment, b"viewing-key", "order_A"));
+        assert!(verify_commitment_full(
+            &commitment,
+            &opening,
+            b"viewing-key",
+            "order_A"
+        ));
+
+        // Same commitment carrying another order's nullifier
+        let mut swapped = commitment.clone();
+        swapped.nullifier = generate_nullifier(b"viewing-key", "order_B");
+        assert!(!verify_nullifier(&swapped, b"viewing-key", "order_A"));
+        assert!(!verify_commitment_full(
+            &swapped,
+            &opening,
+            b"viewing-key",
+            "order_A"
+        ));
+        assert!(!verify_nullifier(&commitment, b"other-key", "order_A"));
+    }
+
+    #[cfg(feature = "range-proofs")]
+    #[test]
+    fn test_tier_disclosure_reveals_only_tier() {
+        let salt = generate_random_salt();
+        let commitment = generate_commitment_with_disclosure(
+            60_000,
+            &salt,
+            42_000,
+            b"viewing-key",
+            "order_A",
+            MinAmountDisclosure::Tier,
+        )
+        .unwrap();
+
+        assert_eq!(commitment.min_amount, 10_000);
+        assert_eq!(commitment.hidden_min.as_ref().unwrap().tier, 4);
+        let announcement = serde_json::to_string(&commitment).unwrap();
+        assert!(!announcement.contains("42000"));
+
+        assert!(verify_min_amount(&commitment, "order_A"));
+        let opening = CommitmentOpening {
+            amount: 60_000,
+            salt,
+            merkle_proof: None,
+        };
+        assert!(verify_commitment(&commitment, &opening, "order_A"));
+    }
+
+    #[cfg(feature = "range-proofs")]
+    #[test]
+    fn test_tier_disclosure_enforces_hidden_minimum() {
+        let salt = generate_random_salt();
+
+        // Above the tier floor but below the hidden minimum: no proof can be made
+        assert!(generate_commitment_with_disclosure(
+            20_000,
+            &salt,
+            42_000,
+            b"viewing-key",
+            "order_A",
+            MinAmountDisclosure::Tier,
+        )
+        .is_err());
+
+        // A proof over a lower minimum cannot be passed off with another amount's opening
+        let low = generate_commitment_with_disclosure(
+            20_000,
+            &salt,
+            15_000,
+            b"viewing-key",
+            "order_A",
+            MinAmountDisclosure::Tier,
+        )
+        .unwrap();
+        let high = generate_commitment_with_disclosure(
+            60_000,
+            &salt,
+            42_000,
+            b"viewing-key",
+            "order_A",
+            MinAmountDisclosure::Tier,
+        )
+        .unwrap();
+        let mut forged = low.clone();
+        forged.hidden_min.as_mut().unwrap().min_in_tier =
+            high.hidden_min.as_ref().unwrap().min_in_tier.clone();
+        assert!(!verify_min_amount(&forged, "order_A"));
+
+        // Claiming a tier floor the proof does not cover fails
+        let mut relabelled = high.clone();
+        relabelled.min_amount = 1_000;
+        assert!(!verify_min_amount(&relabelled, "order_A"));
+
+        // The proof is bound to its order and commitment: it verifies for neither another order
+        // ID nor another order's commitment
+        assert!(!verify_min_amount(&high, "order_B"));
+        let other = generate_commitment_with_disclosure(
+            60_000,
+            &salt,
+            42_000,
+            b"viewing-key",
+            "order_B",
+            MinAmountDisclosure::Tier,
+        )
+        .unwrap();
+        let mut moved = other;
+        moved.hidden_min = high.hidden_min.clone();
+        assert!(!verify_min_amount(&moved, "order_B"));
+    }
+
+    #[cfg(not(feature = "range-proofs"))]
+    #[test]
+    fn test_tier_disclosure_requires_feature() {
+        let salt = generate_random_salt();
+        let exact = generate_commitment_with_disclosure(
+            10_000,
+            &salt,
+            5_000,
+            b"viewing-key",
+            "order_A",
+            MinAmountDisclosure::Exact,
+        )
+        .unwrap();
+        assert_eq!(exact.min_amount, 5_000);
+        assert!(exact.hidden_min.is_none());
+
+        assert!(generate_commitment_with_disclosure(
+            10_000,
+            &salt,
+            5_000,
+            b"viewing-key",
+            "order_A",
+            MinAmountDisclosure::Tier,
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "range-proofs")]
+    #[test]
+    fn test_range_proof_checked_before_opening() {
+        let salt = generate_random_salt();
+        let commitment = generate_commitment_with_range_proof(
+            60_000,
+            &salt,
+            50_000,
+            100_000,
+            b"viewing-key",
+            "order_A",
+        )
+        .unwrap();
+
+        // The taker can trust the range, or any wider one, without the opening
+        assert!(verify_commitment_range(
+            &commitment,
+            "order_A",
+            50_000,
+            100_000
+        ));
+        assert!(verify_commitment_range(
+            &commitment,
+            "order_A",
+            10_000,
+            u64::MAX
+        ));
+        assert!(!verify_commitment_range(
+            &commitment,
+            "order_A",
+            55_000,
+            100_000
+        ));
+
+        // Claiming other bounds, or another order, than the proof was made for does not verify
+        let mut relabelled = commitment.clone();
+        relabelled.range_proof.as_mut().unwrap().min_amount = 55_000;
+        assert!(!verify_commitment_range(
+            &relabelled,
+            "order_A",
+            50_000,
+            100_000
+        ));
+        assert!(!verify_commitment_range(
+            &commitment,
+            "order_B",
+            50_000,
+            100_000
+        ));
+
+        let opening = CommitmentOpening {
+            amount: 60_000,
+            salt,
+            merkle_proof: None,
+        };
+        assert!(verify_commitment(&commitment, &opening, "order_A"));
+
+        // A proof made over another amount cannot ride on this commitment
+        let other = generate_commitment_with_range_proof(
+            90_000,
+            &generate_random_salt(),
+            50_000,
+            100_000,
+            b"viewing-key",
+            "order_A",
+        )
+        .unwrap();
+        let mut swapped = commitment.clone();
+        swapped.range_proof = other.range_proof;
+        assert!(!verify_commitment_range(
+            &swapped, "order_A", 50_000, 100_000
+        ));
+        assert!(!verify_commitment(&swapped, &opening, "order_A"));
+
+        assert!(generate_commitment_with_range_proof(
+            40_000,
+            &salt,
+            50_000,
+            100_000,
+            b"viewing-key",
+            "order_A",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_hash_only_commitment_has_no_range() {
+        let salt = generate_random_salt();
+        let commitment = generate_commitment(60_000, &salt, 50_000, b"viewing-key", "order_A");
+        assert!(!verify_commitment_range(
+            &commitment,
+            "order_A",
+            0,
+            u64::MAX
+        ));
+
+        // Announcements without a proof serialize as before
+        let announcement = serde_json::to_string(&commitment).unwrap();
+        assert!(!announcement.contains("range_proof"));
+    }
+
+    #[test]
+    fn test_commitments_batch_distinct() {
+        let requests: Vec<OrderCommitRequest> = (0..64)
+            .map(|i| OrderCommitRequest {
+                order_id: format!("order_{}", i),
+                amount: 10_000 + i,
+                min_amount: 5_000,
+                viewing_key: b"viewing-key".to_vec(),
+            })
+            .collect();
+
+        let batch = generate_commitments_batch(&requests).unwrap();
+        assert_eq!(batch.len(), requests.len());
+
+        let salts: HashSet<_> = batch.iter().map(|(_, opening)| opening.salt).collect();
+        let nullifiers: HashSet<_> = batch.iter().map(|(c, _)| c.nullifier.clone()).collect();
+        assert_eq!(salts.len(), requests.len());
+        assert_eq!(nullifiers.len(), requests.len());
+
+        for (request, (commitment, opening)) in requests.iter().zip(&batch) {
+            assert!(verify_commitment(commitment, opening, &request.order_id));
+        }
+    }
+
+    #[test]
+    fn test_commitments_batch_atomic() {
+        let mut requests: Vec<OrderCommitRequest> = (0..3)
+            .map(|i| OrderCommitRequest {
+                order_id: format!("order_{}", i),
+                amount: 10_000,
+                min_amount: 5_000,
+                viewing_key: b"viewing-key".to_vec(),
+            })
+            .collect();
+        requests[2].amount = 1_000;
+        assert!(matches!(
+            generate_commitments_batch(&requests),
+            Err(BlackTraceError::InvalidCommitmentRequest(_))
+        ));
+
+        requests[2].amount = 10_000;
+        requests[2].order_id = "order_0".to_string();
+        assert!(generate_commitments_batch(&requests).is_err());
+    }
+
+    #[test]
+    fn test_verify_commitment_raw_matches_struct_api() {
+        let salt = generate_random_salt();
+        let commitment = generate_commitment(10_000, &salt, 5_000, b"viewing-key", "order_A");
+        let raw = |amount: u64, salt: &[u8; 32], order_id: &str| {
+            verify_commitment_raw(
+                commitment.commitment_hash.as_bytes(),
+                commitment.min_amount,
+                amount,
+                salt,
+                order_id,
+            )
+        };
+
+        let cases = [
+            (10_000, salt, "order_A"),      // Valid opening
+            (9_999, salt, "order_A"),       // Wrong amount
+            (10_000, [7u8; 32], "order_A"), // Wrong salt
+            (10_000, salt, "order_B"),      // Another order
+        ];
+        for (amount, salt, order_id) in cases {
+            let opening = CommitmentOpening {
+                amount,
+                salt,
+                merkle_proof: None,
+            };
+            assert_eq!(
+                raw(amount, &salt, order_id),
+                verify_commitment(&commitment, &opening, order_id),
+                "amount {amount}, order {order_id}"
+            );
+        }
+        assert!(raw(10_000, &salt, "order_A"));
+
+        // Opening below the committed minimum
+        let low = generate_commitment(4_000, &salt, 5_000, b"viewing-key", "order_A");
+        let opening = CommitmentOpening {
+            amount: 4_000,
+            salt,
+            merkle_proof: None,
+        };
+        assert!(!verify_commitment(&low, &opening, "order_A"));
+        assert!(!verify_commitment_raw(
+            low.commitment_hash.as_bytes(),
+            low.min_amount,
+            4_000,
+            &salt,
+            "order_A"
+        ));
+    }
+
+    #[test]
+    fn test_opening_proves_published_membership() {
+        let requests: Vec<OrderCommitRequest> = (0..3)
+            .map(|i| OrderCommitRequest {
+                order_id: format!("order_{}", i),
+                amount: 10_000,
+                min_amount: 5_000,
+                viewing_key: b"viewing-key".to_vec(),
+            })
+            .collect();
+        let batch = generate_commitments_batch(&requests).unwrap();
+        let hashes: Vec<Hash> = batch.iter().map(|(c, _)| c.commitment_hash).collect();
+        let tree = CommitmentTree::new(&hashes);
+        let root = tree.root();
+
+        let (commitment, opening) = &batch[1];
+        let mut revealed = opening.clone();
+        assert!(!verify_commitment_published(
+            commitment, &revealed, "order_1", &root
+        ));
+
+        revealed.merkle_proof = tree.prove(&commitment.commitment_hash);
+        assert!(verify_commitment_published(
+            commitment, &revealed, "order_1", &root
+        ));
+
+        // A commitment outside the published set cannot borrow a member's proof
+        let salt = generate_random_salt();
+        let outsider = generate_commitment(10_000, &salt, 5_000, b"viewing-key", "order_x");
+        let borrowed = CommitmentOpening {
+            amount: 10_000,
+            salt,
+            merkle_proof: revealed.merkle_proof.clone(),
+        };
+        assert!(verify_commitment(&outsider, &borrowed, "order_x"));
+        assert!(!verify_commitment_published(
+            &outsider, &borrowed, "order_x", &root
+        ));
+    }
+
+    #[test]
+    fn test_commitment_hash_vector() {
+        // Pinned so the Go node's ComputeCommitmentHash stays byte-compatible
+        let hash = compute_commitment_hash(1, &[0u8; 32], "order_A");
+        assert_eq!(
+            hash.to_hex(),
+            "0926310ab221e21caf61ca6e0ed3249a0c384a1c718e3545480ec2bc2b7ccb0a"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Merkle tree over a maker's published commitment hashes
+//!
+//! A maker publishes only the root; a verifier holding the root checks that one order's
+//! commitment belongs to the published set from a short membership proof.
+
+use blake2::{Blake2b512, Digest};
+use serde::{Deserialize, Serialize};
+
+use super::types::Hash;
+
+/// Domain prefixes keep leaf and interior hashes apart, so a node can never pass as a leaf
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Side of the path a sibling hash sits on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MerkleSide {
+    Left,
+    Right,
+}
+
+/// One level of a membership proof
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleStep {
+    /// Hash of the sibling subtree
+    pub sibling: Hash,
+    /// Which side of the path the sibling is on
+    pub side: MerkleSide,
+}
+
+/// Proof that a commitment hash is a leaf of a `CommitmentTree`
+///
+/// Steps run from the leaf up to the root. A level where the path has no sibling (the last
+/// node of an odd-sized level, which is carried up unchanged) has no step.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub path: Vec<MerkleStep>,
+}
+
+fn leaf_hash(commitment_hash: &Hash) -> Hash {
+    let mut hasher = Blake2b512::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(commitment_hash.as_bytes());
+    Hash::from_bytes(&hasher.finalize()[..32])
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Blake2b512::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    Hash::from_bytes(&hasher.finalize()[..32])
+}
+
+/// Binary Merkle tree over commitment hashes, in the order they were added
+#[derive(Clone, Debug)]
+pub struct CommitmentTree {
+    commitments: Vec<Hash>,
+    /// Hashes per level, leaves first; the last level holds the root
+    levels: Vec<Vec<Hash>>,
+}
+
+impl CommitmentTree {
+    /// Build the tree over a set of commitment hashes
+    pub fn new(commitments: &[Hash]) -> Self {
+        let mut levels = vec![commitments.iter().map(leaf_hash).collect::<Vec<_>>()];
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [odd] => *odd,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        CommitmentTree {
+            commitments: commitments.to_vec(),
+            levels,
+        }
+    }
+
+    /// Root to publish; all zeroes for an empty tree
+    pub fn root(&self) -> Hash {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_else(|| Hash::from_bytes(&[]))
+    }
+
+    /// Membership proof for a commitment, or `None` if it is not in the tree
+    pub fn prove(&self, commitment_hash: &Hash) -> Option<MerkleProof> {
+        let mut index = self.commitments.iter().position(|c| c == commitment_hash)?;
+
+        let mut path = Vec::with_capacity(self.levels.len());
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < level.len() {
+                let side = if sibling < index {
+                    MerkleSide::Left
+                } else {
+                    MerkleSide::Right
+                };
+                path.push(MerkleStep {
+                    sibling: level[sibling],
+                    side,
+                });
+            }
+            index /= 2;
+        }
+        Some(MerkleProof { path })
+    }
+
+    /// Number of commitments in the tree
+    pub fn len(&self) -> usize {
+        self.commitments.len()
+    }
+
+    /// Whether the tree holds no commitments
+    pub fn is_empty(&self) -> bool {
+        self.commitments.is_empty()
+    }
+}
+
+/// Verify that a commitment hash belongs to the set a maker published the root of
+pub fn verify_commitment_membership(
+    commitment_hash: &Hash,
+    merkle_proof: &MerkleProof,
+    published_root: &Hash,
+) -> bool {
+    let root = merkle_proof
+        .path
+        .iter()
+        .fold(leaf_hash(commitment_hash), |hash, step| match step.side {
+            MerkleSide::Left => node_hash(&step.sibling, &hash),
+            MerkleSide::Right => node_hash(&hash, &step.sibling),
+        });
+    root == *published_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::compute_commitment_hash;
+
+    fn commitments(n: u64) -> Vec<Hash> {
+        (0..n)
+            .map(|i| compute_commitment_hash(10_000 + i, &[i as u8; 32], &format!("order_{}", i)))
+            .collect()
+    }
+
+    #[test]
+    fn test_membership_proof_verifies() {
+        // Odd sizes exercise the levels where the last node is carried up
+        for n in [1, 2, 5, 8] {
+            let set = commitments(n);
+            let tree = CommitmentTree::new(&set);
+            let root = tree.root();
+
+            for commitment in &set {
+                let proof = tree.prove(commitment).unwrap();
+                assert!(
+                    verify_commitment_membership(commitment, &proof, &root),
+                    "tree of {n}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_member_rejected() {
+        let set = commitments(5);
+        let tree = CommitmentTree::new(&set);
+        let root = tree.root();
+        let outsider = compute_commitment_hash(99, &[9u8; 32], "order_x");
+
+        assert!(tree.prove(&outsider).is_none());
+
+        // A member's proof does not carry another commitment to the root
+        let proof = tree.prove(&set[2]).unwrap();
+        assert!(!verify_commitment_membership(&outsider, &proof, &root));
+
+        // Nor does a valid proof against a root the maker did not publish
+        let other_root = CommitmentTree::new(&commitments(4)).root();
+        assert!(!verify_commitment_membership(&set[2], &proof, &other_root));
+    }
+}
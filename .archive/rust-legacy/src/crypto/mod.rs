@@ -1,10 +1,29 @@
 //! Cryptography module for BlackTrace
 
 pub mod commitment;
+pub mod merkle;
+pub mod nullifier;
+pub mod range_proof;
 pub mod types;
 
 pub use commitment::{
-    CommitmentScheme, compute_commitment_hash, generate_commitment, generate_nullifier,
-    generate_random_salt, verify_commitment,
+    CommitmentScheme, compute_commitment_hash, generate_commitment,
+    generate_commitment_with_disclosure, generate_commitment_with_range_proof,
+    generate_commitments_batch, generate_nullifier, generate_random_salt, verify_commitment,
+    verify_commitment_full, verify_commitment_published, verify_commitment_range,
+    verify_commitment_raw, verify_min_amount, verify_nullifier,
+};
+pub use merkle::{
+    verify_commitment_membership, CommitmentTree, MerkleProof, MerkleSide, MerkleStep,
+};
+pub use nullifier::NullifierSet;
+pub use range_proof::{
+    aggregate_liquidity_proof, amount_tier, commit_amount, generate_range_proof, prove_equal,
+    prove_hidden_minimum, tier_range, verify_aggregate_proof, verify_equal, verify_hidden_minimum,
+    verify_range_proof, AggregateOrder, AggregateProof, EqualityProof, HiddenMinimumProof,
+    LiquidityRangeProof, RangeProof,
+};
+pub use types::{
+    CommitmentOpening, Hash, LiquidityCommitment, MinAmountDisclosure, Nullifier,
+    OrderCommitRequest, OrderID, Salt, SecretPreimage, ViewingKey,
 };
-pub use types::{CommitmentOpening, Hash, LiquidityCommitment, Nullifier, Salt, ViewingKey};
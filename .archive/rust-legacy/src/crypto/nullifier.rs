@@ -0,0 +1,253 @@
+//! Spent-nullifier tracking
+
+use std::collections::HashMap;
+
+use blake2::digest::Mac;
+use blake2::Blake2bMac512;
+use serde::{Deserialize, Serialize};
+
+use super::types::{Nullifier, OrderID};
+use crate::error::{BlackTraceError, Result};
+
+/// Domain tag for the persisted set's manifest MAC
+const MANIFEST_DOMAIN: &[u8] = b"blacktrace-nullifier-set-v1";
+
+/// One consumed nullifier as persisted
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PersistedEntry {
+    nullifier: Nullifier,
+    order_id: OrderID,
+}
+
+/// Manifest authenticating the persisted entries
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Manifest {
+    count: u64,
+    /// Hex-encoded keyed Blake2b-512 over the count and every entry
+    mac: String,
+}
+
+/// On-disk layout of a `NullifierSet`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PersistedNullifierSet {
+    entries: Vec<PersistedEntry>,
+    manifest: Manifest,
+}
+
+/// Keyed MAC over a set's entries; entries must already be in persisted (sorted) order
+fn manifest_mac(key: &[u8; 32], entries: &[PersistedEntry]) -> Blake2bMac512 {
+    let mut mac =
+        Blake2bMac512::new_from_slice(key).expect("32-byte key is within Blake2b's limit");
+    mac.update(MANIFEST_DOMAIN);
+    mac.update(&(entries.len() as u64).to_le_bytes());
+    for entry in entries {
+        mac.update(entry.nullifier.0.as_bytes());
+        mac.update(&(entry.order_id.len() as u64).to_le_bytes());
+        mac.update(entry.order_id.as_bytes());
+    }
+    mac
+}
+
+/// Set of nullifiers already consumed, each mapped to the order that consumed it
+///
+/// Only the nullifier (a one-way hash of viewing key and order ID) and the
+/// order ID are stored, so the reverse index reveals nothing about the viewing key.
+#[derive(Clone, Debug, Default)]
+pub struct NullifierSet {
+    spent: HashMap<Nullifier, OrderID>,
+}
+
+impl NullifierSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a nullifier as consumed by an order
+    ///
+    /// Returns `false` (leaving the existing entry untouched) if it was already consumed.
+    pub fn insert(&mut self, nullifier: Nullifier, order_id: OrderID) -> bool {
+        if self.spent.contains_key(&nullifier) {
+            return false;
+        }
+        self.spent.insert(nullifier, order_id);
+        true
+    }
+
+    /// Check whether a nullifier has been consumed
+    pub fn contains(&self, nullifier: &Nullifier) -> bool {
+        self.spent.contains_key(nullifier)
+    }
+
+    /// Look up the order that consumed a nullifier (for audit)
+    pub fn order_for_nullifier(&self, nullifier: &Nullifier) -> Option<OrderID> {
+        self.spent.get(nullifier).cloned()
+    }
+
+    /// Number of consumed nullifiers
+    pub fn len(&self) -> usize {
+        self.spent.len()
+    }
+
+    /// Whether no nullifier has been consumed
+    pub fn is_empty(&self) -> bool {
+        self.spent.is_empty()
+    }
+
+    /// Serialize the set for storage, with a manifest MAC'd under `integrity_key`
+    ///
+    /// Entries are written in nullifier order, so the same set always persists to the same bytes.
+    pub fn persist(&self, integrity_key: &[u8; 32]) -> Vec<u8> {
+        let mut entries: Vec<PersistedEntry> = self
+            .spent
+            .iter()
+            .map(|(nullifier, order_id)| PersistedEntry {
+                nullifier: nullifier.clone(),
+                order_id: order_id.clone(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.nullifier.0.as_bytes().cmp(b.nullifier.0.as_bytes()));
+
+        let mac = manifest_mac(integrity_key, &entries)
+            .finalize()
+            .into_bytes();
+        let persisted = PersistedNullifierSet {
+            manifest: Manifest {
+                count: entries.len() as u64,
+                mac: hex::encode(mac),
+            },
+            entries,
+        };
+        serde_json::to_vec(&persisted).expect("nullifier set serializes")
+    }
+
+    /// Load a set written by `persist`, verifying its manifest first
+    ///
+    /// Returns `StateCorruption` if the data does not parse or the manifest does not match
+    /// the entries. A missing nullifier would allow a double-spend and a spurious one would
+    /// block a valid order, so the caller must not fall back to a partially loaded set.
+    pub fn load(data: &[u8], integrity_key: &[u8; 32]) -> Result<Self> {
+        let corrupt =
+            |msg: &str| BlackTraceError::StateCorruption(format!("nullifier set: {}", msg));
+
+        let persisted: PersistedNullifierSet =
+            serde_json::from_slice(data).map_err(|e| corrupt(&e.to_string()))?;
+        if persisted.manifest.count != persisted.entries.len() as u64 {
+            return Err(corrupt("entry count does not match manifest"));
+        }
+        let expected =
+            hex::decode(&persisted.manifest.mac).map_err(|_| corrupt("malformed manifest MAC"))?;
+        manifest_mac(integrity_key, &persisted.entries)
+            .verify_slice(&expected)
+            .map_err(|_| corrupt("manifest MAC does not match entries"))?;
+
+        let mut set = NullifierSet::new();
+        for entry in persisted.entries {
+            if !set.insert(entry.nullifier, entry.order_id) {
+                return Err(corrupt("duplicate nullifier"));
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::generate_nullifier;
+
+    #[test]
+    fn test_order_for_nullifier() {
+        let mut set = NullifierSet::new();
+        let nullifier = generate_nullifier(b"viewing-key", "order_A");
+
+        assert!(set.insert(nullifier.clone(), "order_A".to_string()));
+        assert_eq!(
+            set.order_for_nullifier(&nullifier),
+            Some("order_A".to_string())
+        );
+
+        let other = generate_nullifier(b"viewing-key", "order_B");
+        assert_eq!(set.order_for_nullifier(&other), None);
+    }
+
+    #[test]
+    fn test_nullifier_reuse_rejected() {
+        let mut set = NullifierSet::new();
+        let nullifier = generate_nullifier(b"viewing-key", "order_A");
+
+        assert!(set.insert(nullifier.clone(), "order_A".to_string()));
+        assert!(!set.insert(nullifier.clone(), "order_B".to_string()));
+        assert_eq!(
+            set.order_for_nullifier(&nullifier),
+            Some("order_A".to_string())
+        );
+    }
+
+    fn spent_set() -> NullifierSet {
+        let mut set = NullifierSet::new();
+        for order in ["order_A", "order_B", "order_C"] {
+            set.insert(generate_nullifier(b"viewing-key", order), order.to_string());
+        }
+        set
+    }
+
+    #[test]
+    fn test_persisted_set_round_trips() {
+        let key = [7u8; 32];
+        let set = spent_set();
+
+        let loaded = NullifierSet::load(&set.persist(&key), &key).unwrap();
+        assert_eq!(loaded.len(), 3);
+        let nullifier = generate_nullifier(b"viewing-key", "order_B");
+        assert_eq!(
+            loaded.order_for_nullifier(&nullifier),
+            Some("order_B".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tampered_persisted_set_detected() {
+        let key = [7u8; 32];
+        let data = spent_set().persist(&key);
+        let is_corruption = |result: Result<NullifierSet>| {
+            matches!(result, Err(BlackTraceError::StateCorruption(_)))
+        };
+
+        // Dropping a nullifier (re-enabling a double-spend), with the count patched to match
+        let mut dropped: PersistedNullifierSet = serde_json::from_slice(&data).unwrap();
+        dropped.entries.pop();
+        dropped.manifest.count -= 1;
+        assert!(is_corruption(NullifierSet::load(
+            &serde_json::to_vec(&dropped).unwrap(),
+            &key
+        )));
+
+        // Adding a spurious nullifier that would block a valid order
+        let mut added: PersistedNullifierSet = serde_json::from_slice(&data).unwrap();
+        added.entries.push(PersistedEntry {
+            nullifier: generate_nullifier(b"viewing-key", "order_D"),
+            order_id: "order_D".to_string(),
+        });
+        added.manifest.count += 1;
+        assert!(is_corruption(NullifierSet::load(
+            &serde_json::to_vec(&added).unwrap(),
+            &key
+        )));
+
+        // Reassigning a nullifier to another order
+        let mut reassigned: PersistedNullifierSet = serde_json::from_slice(&data).unwrap();
+        reassigned.entries[0].order_id = "order_X".to_string();
+        assert!(is_corruption(NullifierSet::load(
+            &serde_json::to_vec(&reassigned).unwrap(),
+            &key
+        )));
+
+        // Truncated data, and an intact set under the wrong key
+        assert!(is_corruption(NullifierSet::load(
+            &data[..data.len() / 2],
+            &key
+        )));
+        assert!(is_corruption(NullifierSet::load(&data, &[8u8; 32])));
+    }
+}
@@ -0,0 +1,832 @@
+//! Range proofs showing a committed amount lies in `[min, max]` without opening it
+//!
+//! Backed by Bulletproofs over Pedersen commitments when the `range-proofs` feature
+//! is enabled. Without it, the functions are stubs returning `NotImplemented`.
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{CommitmentOpening, OrderID, Salt};
+use crate::error::Result;
+
+/// Range proof over a Pedersen commitment to an amount
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeProof {
+    /// Compressed Pedersen commitment to the amount
+    pub commitment: [u8; 32],
+    /// Proof that `amount - min` fits in 64 bits
+    pub lower: Vec<u8>,
+    /// Proof that `max - amount` fits in 64 bits
+    pub upper: Vec<u8>,
+}
+
+/// Range proof published with a liquidity commitment, with the bounds it was made for
+///
+/// Lets a taker check the maker's amount lies in `[min_amount, max_amount]` before the
+/// commitment is opened.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiquidityRangeProof {
+    /// Lower bound the committed amount is proven to meet
+    pub min_amount: u64,
+    /// Upper bound the committed amount is proven not to exceed
+    pub max_amount: u64,
+    /// Proof over a Pedersen commitment to the amount under the commitment salt's blinding,
+    /// bound to the order ID and the commitment hash it is published with
+    pub proof: RangeProof,
+}
+
+/// Proof that a committed amount meets a hidden minimum lying in a public tier
+///
+/// Published instead of the exact minimum under `MinAmountDisclosure::Tier`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HiddenMinimumProof {
+    /// Tier the hidden minimum falls in (see `tier_range`)
+    pub tier: u8,
+    /// Compressed Pedersen commitment to the amount
+    pub amount_commitment: [u8; 32],
+    /// Range proof placing the committed minimum inside the tier
+    pub min_in_tier: RangeProof,
+    /// Proof that the amount minus the minimum fits in 64 bits
+    pub above_min: Vec<u8>,
+}
+
+/// One order counted in an `AggregateProof`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateOrder {
+    /// Order the amount belongs to
+    pub order_id: OrderID,
+    /// Hash commitment published with the order (see `compute_commitment_hash`)
+    pub commitment_hash: [u8; 32],
+    /// Compressed Pedersen commitment to the order's amount (see `commit_amount`)
+    pub amount_commitment: [u8; 32],
+}
+
+/// Proof of reserves: the amounts committed across a node's orders add up to at least a threshold
+///
+/// Only the total is proven; no single order's amount is revealed. Every order's ID and hash
+/// commitment is bound into the proof, so it cannot be presented for other orders.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateProof {
+    /// Public amount the committed total meets
+    pub threshold: u64,
+    /// The orders whose amounts make up the total
+    pub orders: Vec<AggregateOrder>,
+    /// Proof that the total minus the threshold fits in 64 bits
+    pub above_threshold: Vec<u8>,
+}
+
+/// Proof that two Pedersen commitments (see `commit_amount`) hide the same amount
+///
+/// Links two orders as backed by the same funds without revealing the amount: a Schnorr proof
+/// that the commitments differ only in their blinding.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EqualityProof {
+    /// Compressed nonce point `k * B_blinding`
+    pub nonce: [u8; 32],
+    /// Response scalar `k + e * (blinding1 - blinding2)`
+    pub response: [u8; 32],
+}
+
+/// Highest tier; it runs up to `u64::MAX`
+pub const MAX_TIER: u8 = 19;
+
+/// Tier of an amount: the number of decimal digits minus one
+pub fn amount_tier(amount: u64) -> u8 {
+    amount.checked_ilog10().unwrap_or(0) as u8
+}
+
+/// Inclusive range of amounts in a tier: `[10^tier, 10^(tier+1) - 1]`, with tier 0 starting at 0
+pub fn tier_range(tier: u8) -> Option<(u64, u64)> {
+    if tier > MAX_TIER {
+        return None;
+    }
+    let min = if tier == 0 { 0 } else { 10u64.pow(tier as u32) };
+    let max = 10u64
+        .checked_pow(tier as u32 + 1)
+        .map_or(u64::MAX, |next| next - 1);
+    Some((min, max))
+}
+
+#[cfg(feature = "range-proofs")]
+mod bulletproof {
+    use blake2::{Blake2b512, Digest};
+    use bulletproofs::{BulletproofGens, PedersenGens, RangeProof as Bulletproof};
+    use curve25519_dalek_ng::ristretto::CompressedRistretto;
+    use curve25519_dalek_ng::scalar::Scalar;
+    use merlin::Transcript;
+
+    use rand::RngCore;
+
+    use super::{
+        tier_range, AggregateOrder, AggregateProof, EqualityProof, HiddenMinimumProof, RangeProof,
+    };
+    use crate::crypto::commitment::compute_commitment_hash;
+    use crate::crypto::types::{CommitmentOpening, OrderID, Salt};
+    use crate::error::{BlackTraceError, Result};
+
+    /// Bit size of each range proof
+    const RANGE_BITS: usize = 64;
+
+    /// Derive the Pedersen blinding factor from the commitment salt
+    fn blinding_from_salt(salt: &Salt) -> Scalar {
+        let mut hasher = Blake2b512::new();
+        hasher.update(b"blacktrace-pedersen-blinding");
+        hasher.update(salt);
+        let mut wide = [0u8; 64];
+        wide.copy_from_slice(&hasher.finalize());
+        Scalar::from_bytes_mod_order_wide(&wide)
+    }
+
+    /// Derive the salt for the hidden minimum's commitment, so its blinding differs from the amount's
+    fn min_salt(salt: &Salt) -> Salt {
+        let mut hasher = Blake2b512::new();
+        hasher.update(b"blacktrace-hidden-min");
+        hasher.update(salt);
+        let mut min_salt = [0u8; 32];
+        min_salt.copy_from_slice(&hasher.finalize()[..32]);
+        min_salt
+    }
+
+    /// What a range proof is bound to: an order ID and the hash commitment published with it
+    pub(super) type Binding<'a> = Option<(&'a str, &'a [u8; 32])>;
+
+    fn transcript(side: &'static [u8], min: u64, max: u64, binding: Binding) -> Transcript {
+        let mut t = Transcript::new(b"blacktrace-range-proof");
+        t.append_message(b"side", side);
+        t.append_u64(b"min", min);
+        t.append_u64(b"max", max);
+        if let Some((order_id, commitment_hash)) = binding {
+            t.append_message(b"order_id", order_id.as_bytes());
+            t.append_message(b"commitment_hash", commitment_hash);
+        }
+        t
+    }
+
+    fn invalid(msg: impl std::fmt::Display) -> BlackTraceError {
+        BlackTraceError::InvalidProof(msg.to_string())
+    }
+
+    /// Prove that `amount` lies in `[min_amount, max_amount]`
+    pub fn generate_range_proof(
+        amount: u64,
+        salt: &Salt,
+        min_amount: u64,
+        max_amount: u64,
+    ) -> Result<RangeProof> {
+        prove_range(amount, salt, min_amount, max_amount, None)
+    }
+
+    /// Prove that `amount` lies in `[min_amount, max_amount]`, with the proof bound to `binding`
+    pub(super) fn prove_range(
+        amount: u64,
+        salt: &Salt,
+        min_amount: u64,
+        max_amount: u64,
+        binding: Binding,
+    ) -> Result<RangeProof> {
+        if min_amount > max_amount || amount < min_amount || amount > max_amount {
+            return Err(invalid("amount outside range"));
+        }
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(RANGE_BITS, 1);
+        let blinding = blinding_from_salt(salt);
+        let commitment = pc_gens.commit(Scalar::from(amount), blinding).compress();
+
+        // amount - min >= 0, committed with the same blinding
+        let (lower, _) = Bulletproof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript(b"lower", min_amount, max_amount, binding),
+            amount - min_amount,
+            &blinding,
+            RANGE_BITS,
+        )
+        .map_err(invalid)?;
+
+        // max - amount >= 0, committed with the negated blinding
+        let (upper, _) = Bulletproof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript(b"upper", min_amount, max_amount, binding),
+            max_amount - amount,
+            &(-blinding),
+            RANGE_BITS,
+        )
+        .map_err(invalid)?;
+
+        Ok(RangeProof {
+            commitment: commitment.to_bytes(),
+            lower: lower.to_bytes(),
+            upper: upper.to_bytes(),
+        })
+    }
+
+    /// Verify that the committed amount lies in `[min_amount, max_amount]`
+    pub fn verify_range_proof(proof: &RangeProof, min_amount: u64, max_amount: u64) -> Result<()> {
+        verify_range(proof, min_amount, max_amount, None)
+    }
+
+    /// Verify a range proof made with `prove_range` for the same binding
+    pub(super) fn verify_range(
+        proof: &RangeProof,
+        min_amount: u64,
+        max_amount: u64,
+        binding: Binding,
+    ) -> Result<()> {
+        if min_amount > max_amount {
+            return Err(invalid("empty range"));
+        }
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(RANGE_BITS, 1);
+
+        let commitment = CompressedRistretto(proof.commitment)
+            .decompress()
+            .ok_or_else(|| invalid("malformed commitment"))?;
+        let lower_commitment = (commitment - pc_gens.B * Scalar::from(min_amount)).compress();
+        let upper_commitment = (pc_gens.B * Scalar::from(max_amount) - commitment).compress();
+
+        let lower = Bulletproof::from_bytes(&proof.lower).map_err(invalid)?;
+        let upper = Bulletproof::from_bytes(&proof.upper).map_err(invalid)?;
+
+        lower
+            .verify_single(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript(b"lower", min_amount, max_amount, binding),
+                &lower_commitment,
+                RANGE_BITS,
+            )
+            .map_err(invalid)?;
+        upper
+            .verify_single(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript(b"upper", min_amount, max_amount, binding),
+                &upper_commitment,
+                RANGE_BITS,
+            )
+            .map_err(invalid)?;
+
+        Ok(())
+    }
+
+    /// Compressed Pedersen commitment to `amount` under the salt's blinding
+    pub fn commit_amount(amount: u64, salt: &Salt) -> [u8; 32] {
+        PedersenGens::default()
+            .commit(Scalar::from(amount), blinding_from_salt(salt))
+            .compress()
+            .to_bytes()
+    }
+
+    /// Prove that `amount >= min_amount` with `min_amount` hidden inside `tier`, bound to `binding`
+    pub(super) fn prove_hidden_minimum(
+        amount: u64,
+        salt: &Salt,
+        min_amount: u64,
+        tier: u8,
+        binding: Binding,
+    ) -> Result<HiddenMinimumProof> {
+        let (tier_min, tier_max) = tier_range(tier).ok_or_else(|| invalid("unknown tier"))?;
+        if amount < min_amount {
+            return Err(invalid("amount below minimum"));
+        }
+
+        let min_salt = min_salt(salt);
+        let min_in_tier = prove_range(min_amount, &min_salt, tier_min, tier_max, binding)?;
+
+        // amount - min >= 0, committed with the difference of the two blindings
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(RANGE_BITS, 1);
+        let (above_min, _) = Bulletproof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript(b"above-min", tier_min, tier_max, binding),
+            amount - min_amount,
+            &(blinding_from_salt(salt) - blinding_from_salt(&min_salt)),
+            RANGE_BITS,
+        )
+        .map_err(invalid)?;
+
+        Ok(HiddenMinimumProof {
+            tier,
+            amount_commitment: commit_amount(amount, salt),
+            min_in_tier,
+            above_min: above_min.to_bytes(),
+        })
+    }
+
+    /// Verify a hidden-minimum proof made with `prove_hidden_minimum` for the same binding
+    pub(super) fn verify_hidden_minimum(
+        proof: &HiddenMinimumProof,
+        binding: Binding,
+    ) -> Result<()> {
+        let (tier_min, tier_max) = tier_range(proof.tier).ok_or_else(|| invalid("unknown tier"))?;
+        verify_range(&proof.min_in_tier, tier_min, tier_max, binding)?;
+
+        let amount = CompressedRistretto(proof.amount_commitment)
+            .decompress()
+            .ok_or_else(|| invalid("malformed amount commitment"))?;
+        let min = CompressedRistretto(proof.min_in_tier.commitment)
+            .decompress()
+            .ok_or_else(|| invalid("malformed minimum commitment"))?;
+
+        let above_min = Bulletproof::from_bytes(&proof.above_min).map_err(invalid)?;
+        above_min
+            .verify_single(
+                &BulletproofGens::new(RANGE_BITS, 1),
+                &PedersenGens::default(),
+                &mut transcript(b"above-min", tier_min, tier_max, binding),
+                &(amount - min).compress(),
+                RANGE_BITS,
+            )
+            .map_err(invalid)
+    }
+
+    fn aggregate_transcript(threshold: u64, orders: &[AggregateOrder]) -> Transcript {
+        let mut t = Transcript::new(b"blacktrace-aggregate-proof");
+        t.append_u64(b"threshold", threshold);
+        t.append_u64(b"orders", orders.len() as u64);
+        for order in orders {
+            t.append_message(b"order_id", order.order_id.as_bytes());
+            t.append_message(b"commitment_hash", &order.commitment_hash);
+            t.append_message(b"amount_commitment", &order.amount_commitment);
+        }
+        t
+    }
+
+    /// Prove that the amounts of `orders` add up to at least `threshold`
+    pub fn aggregate_liquidity_proof(
+        orders: &[(OrderID, CommitmentOpening)],
+        threshold: u64,
+    ) -> Result<AggregateProof> {
+        let total = orders
+            .iter()
+            .try_fold(0u64, |total, (_, opening)| {
+                total.checked_add(opening.amount)
+            })
+            .ok_or_else(|| invalid("total amount overflows"))?;
+        if total < threshold {
+            return Err(invalid("total amount below threshold"));
+        }
+
+        let aggregated: Vec<AggregateOrder> = orders
+            .iter()
+            .map(|(order_id, opening)| AggregateOrder {
+                order_id: order_id.clone(),
+                commitment_hash: *compute_commitment_hash(opening.amount, &opening.salt, order_id)
+                    .as_bytes(),
+                amount_commitment: commit_amount(opening.amount, &opening.salt),
+            })
+            .collect();
+
+        // The commitments add up to a commitment to the total under the summed blindings
+        let blinding: Scalar = orders
+            .iter()
+            .map(|(_, opening)| blinding_from_salt(&opening.salt))
+            .sum();
+        let (above_threshold, _) = Bulletproof::prove_single(
+            &BulletproofGens::new(RANGE_BITS, 1),
+            &PedersenGens::default(),
+            &mut aggregate_transcript(threshold, &aggregated),
+            total - threshold,
+            &blinding,
+            RANGE_BITS,
+        )
+        .map_err(invalid)?;
+
+        Ok(AggregateProof {
+            threshold,
+            orders: aggregated,
+            above_threshold: above_threshold.to_bytes(),
+        })
+    }
+
+    /// Verify that the committed amounts add up to at least the proof's threshold
+    pub fn verify_aggregate_proof(proof: &AggregateProof) -> Result<()> {
+        let pc_gens = PedersenGens::default();
+        let mut total = pc_gens.B * -Scalar::from(proof.threshold);
+        for order in &proof.orders {
+            total += CompressedRistretto(order.amount_commitment)
+                .decompress()
+                .ok_or_else(|| invalid("malformed commitment"))?;
+        }
+
+        let above_threshold = Bulletproof::from_bytes(&proof.above_threshold).map_err(invalid)?;
+        above_threshold
+            .verify_single(
+                &BulletproofGens::new(RANGE_BITS, 1),
+                &pc_gens,
+                &mut aggregate_transcript(proof.threshold, &proof.orders),
+                &total.compress(),
+                RANGE_BITS,
+            )
+            .map_err(invalid)
+    }
+
+    /// Fiat-Shamir challenge binding both commitments and the nonce
+    fn equality_challenge(c1: &[u8; 32], c2: &[u8; 32], nonce: &[u8; 32]) -> Scalar {
+        let mut t = Transcript::new(b"blacktrace-equality-proof");
+        t.append_message(b"c1", c1);
+        t.append_message(b"c2", c2);
+        t.append_message(b"nonce", nonce);
+        let mut wide = [0u8; 64];
+        t.challenge_bytes(b"challenge", &mut wide);
+        Scalar::from_bytes_mod_order_wide(&wide)
+    }
+
+    /// Prove that `c1` and `c2`, made under the two salts, commit to the same amount
+    pub fn prove_equal(
+        c1: &[u8; 32],
+        salt1: &Salt,
+        c2: &[u8; 32],
+        salt2: &Salt,
+    ) -> Result<EqualityProof> {
+        let pc_gens = PedersenGens::default();
+        let p1 = CompressedRistretto(*c1)
+            .decompress()
+            .ok_or_else(|| invalid("malformed first commitment"))?;
+        let p2 = CompressedRistretto(*c2)
+            .decompress()
+            .ok_or_else(|| invalid("malformed second commitment"))?;
+
+        // With equal amounts the difference is a pure blinding term
+        let blinding = blinding_from_salt(salt1) - blinding_from_salt(salt2);
+        if p1 - p2 != pc_gens.B_blinding * blinding {
+            return Err(invalid("commitments hide different amounts"));
+        }
+
+        let mut wide = [0u8; 64];
+        rand::thread_rng().fill_bytes(&mut wide);
+        let k = Scalar::from_bytes_mod_order_wide(&wide);
+        let nonce = (pc_gens.B_blinding * k).compress().to_bytes();
+        let e = equality_challenge(c1, c2, &nonce);
+
+        Ok(EqualityProof {
+            nonce,
+            response: (k + e * blinding).to_bytes(),
+        })
+    }
+
+    /// Verify that `c1` and `c2` commit to the same amount
+    pub fn verify_equal(c1: &[u8; 32], c2: &[u8; 32], proof: &EqualityProof) -> Result<()> {
+        let pc_gens = PedersenGens::default();
+        let p1 = CompressedRistretto(*c1)
+            .decompress()
+            .ok_or_else(|| invalid("malformed first commitment"))?;
+        let p2 = CompressedRistretto(*c2)
+            .decompress()
+            .ok_or_else(|| invalid("malformed second commitment"))?;
+        let nonce = CompressedRistretto(proof.nonce)
+            .decompress()
+            .ok_or_else(|| invalid("malformed nonce"))?;
+        let response = Scalar::from_canonical_bytes(proof.response)
+            .ok_or_else(|| invalid("malformed response"))?;
+
+        let e = equality_challenge(c1, c2, &proof.nonce);
+        if pc_gens.B_blinding * response != nonce + (p1 - p2) * e {
+            return Err(invalid("equality proof does not verify"));
+        }
+        Ok(())
+    }
+}
+
+/// Prove that `amount` lies in `[min_amount, max_amount]`
+#[cfg(feature = "range-proofs")]
+pub fn generate_range_proof(
+    amount: u64,
+    salt: &Salt,
+    min_amount: u64,
+    max_amount: u64,
+) -> Result<RangeProof> {
+    bulletproof::generate_range_proof(amount, salt, min_amount, max_amount)
+}
+
+/// Verify that the committed amount lies in `[min_amount, max_amount]`
+#[cfg(feature = "range-proofs")]
+pub fn verify_range_proof(proof: &RangeProof, min_amount: u64, max_amount: u64) -> Result<()> {
+    bulletproof::verify_range_proof(proof, min_amount, max_amount)
+}
+
+/// Prove that `amount` lies in `[min_amount, max_amount]`, bound to an order and the hash
+/// commitment published with it so the proof cannot be moved to another commitment
+#[cfg(feature = "range-proofs")]
+pub(crate) fn generate_bound_range_proof(
+    amount: u64,
+    salt: &Salt,
+    min_amount: u64,
+    max_amount: u64,
+    order_id: &str,
+    commitment_hash: &[u8; 32],
+) -> Result<RangeProof> {
+    bulletproof::prove_range(
+        amount,
+        salt,
+        min_amount,
+        max_amount,
+        Some((order_id, commitment_hash)),
+    )
+}
+
+/// Verify a range proof made by `generate_bound_range_proof` for this order and commitment
+#[cfg(feature = "range-proofs")]
+pub(crate) fn verify_bound_range_proof(
+    proof: &RangeProof,
+    min_amount: u64,
+    max_amount: u64,
+    order_id: &str,
+    commitment_hash: &[u8; 32],
+) -> Result<()> {
+    bulletproof::verify_range(
+        proof,
+        min_amount,
+        max_amount,
+        Some((order_id, commitment_hash)),
+    )
+}
+
+/// Compressed Pedersen commitment to `amount`, blinded by the commitment salt
+#[cfg(feature = "range-proofs")]
+pub fn commit_amount(amount: u64, salt: &Salt) -> Result<[u8; 32]> {
+    Ok(bulletproof::commit_amount(amount, salt))
+}
+
+/// Prove that `amount >= min_amount` while revealing only the tier `min_amount` is in, bound to
+/// an order and the hash commitment published with it so the proof cannot be moved to another
+#[cfg(feature = "range-proofs")]
+pub fn prove_hidden_minimum(
+    amount: u64,
+    salt: &Salt,
+    min_amount: u64,
+    tier: u8,
+    order_id: &str,
+    commitment_hash: &[u8; 32],
+) -> Result<HiddenMinimumProof> {
+    bulletproof::prove_hidden_minimum(
+        amount,
+        salt,
+        min_amount,
+        tier,
+        Some((order_id, commitment_hash)),
+    )
+}
+
+/// Verify that the committed amount meets a hidden minimum inside the proof's tier, for the
+/// order and commitment the proof was made for
+#[cfg(feature = "range-proofs")]
+pub fn verify_hidden_minimum(
+    proof: &HiddenMinimumProof,
+    order_id: &str,
+    commitment_hash: &[u8; 32],
+) -> Result<()> {
+    bulletproof::verify_hidden_minimum(proof, Some((order_id, commitment_hash)))
+}
+
+/// Prove that the amounts of `orders` add up to at least `threshold`, revealing only the total's
+/// lower bound. The proof is bound to each order's ID and hash commitment.
+#[cfg(feature = "range-proofs")]
+pub fn aggregate_liquidity_proof(
+    orders: &[(OrderID, CommitmentOpening)],
+    threshold: u64,
+) -> Result<AggregateProof> {
+    bulletproof::aggregate_liquidity_proof(orders, threshold)
+}
+
+/// Verify that the committed amounts add up to at least the proof's threshold, for the orders
+/// the proof names
+#[cfg(feature = "range-proofs")]
+pub fn verify_aggregate_proof(proof: &AggregateProof) -> Result<()> {
+    bulletproof::verify_aggregate_proof(proof)
+}
+
+/// Prove that commitments `c1` and `c2`, made with `commit_amount` under the two salts, hide the
+/// same amount
+#[cfg(feature = "range-proofs")]
+pub fn prove_equal(
+    c1: &[u8; 32],
+    salt1: &Salt,
+    c2: &[u8; 32],
+    salt2: &Salt,
+) -> Result<EqualityProof> {
+    bulletproof::prove_equal(c1, salt1, c2, salt2)
+}
+
+/// Verify that commitments `c1` and `c2` hide the same amount
+#[cfg(feature = "range-proofs")]
+pub fn verify_equal(c1: &[u8; 32], c2: &[u8; 32], proof: &EqualityProof) -> Result<()> {
+    bulletproof::verify_equal(c1, c2, proof)
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn generate_range_proof(
+    _amount: u64,
+    _salt: &Salt,
+    _min_amount: u64,
+    _max_amount: u64,
+) -> Result<RangeProof> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn verify_range_proof(_proof: &RangeProof, _min_amount: u64, _max_amount: u64) -> Result<()> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub(crate) fn generate_bound_range_proof(
+    _amount: u64,
+    _salt: &Salt,
+    _min_amount: u64,
+    _max_amount: u64,
+    _order_id: &str,
+    _commitment_hash: &[u8; 32],
+) -> Result<RangeProof> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub(crate) fn verify_bound_range_proof(
+    _proof: &RangeProof,
+    _min_amount: u64,
+    _max_amount: u64,
+    _order_id: &str,
+    _commitment_hash: &[u8; 32],
+) -> Result<()> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn commit_amount(_amount: u64, _salt: &Salt) -> Result<[u8; 32]> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn prove_hidden_minimum(
+    _amount: u64,
+    _salt: &Salt,
+    _min_amount: u64,
+    _tier: u8,
+    _order_id: &str,
+    _commitment_hash: &[u8; 32],
+) -> Result<HiddenMinimumProof> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn verify_hidden_minimum(
+    _proof: &HiddenMinimumProof,
+    _order_id: &str,
+    _commitment_hash: &[u8; 32],
+) -> Result<()> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn aggregate_liquidity_proof(
+    _orders: &[(OrderID, CommitmentOpening)],
+    _threshold: u64,
+) -> Result<AggregateProof> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn verify_aggregate_proof(_proof: &AggregateProof) -> Result<()> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn prove_equal(
+    _c1: &[u8; 32],
+    _salt1: &Salt,
+    _c2: &[u8; 32],
+    _salt2: &Salt,
+) -> Result<EqualityProof> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+/// Stub: range proofs require the `range-proofs` feature
+#[cfg(not(feature = "range-proofs"))]
+pub fn verify_equal(_c1: &[u8; 32], _c2: &[u8; 32], _proof: &EqualityProof) -> Result<()> {
+    Err(crate::error::feature_disabled("range-proofs"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tier_range() {
+        assert_eq!(amount_tier(0), 0);
+        assert_eq!(amount_tier(9), 0);
+        assert_eq!(amount_tier(10), 1);
+        assert_eq!(amount_tier(12_345), 4);
+        assert_eq!(amount_tier(u64::MAX), MAX_TIER);
+
+        assert_eq!(tier_range(0), Some((0, 9)));
+        assert_eq!(tier_range(4), Some((10_000, 99_999)));
+        assert_eq!(tier_range(MAX_TIER), Some((10u64.pow(19), u64::MAX)));
+        assert_eq!(tier_range(MAX_TIER + 1), None);
+    }
+
+    #[cfg(not(feature = "range-proofs"))]
+    #[test]
+    fn test_range_proof_stub_without_feature() {
+        use crate::error::BlackTraceError;
+
+        let expected = BlackTraceError::NotImplemented("feature range-proofs not enabled".into());
+        assert_eq!(generate_range_proof(10, &[0u8; 32], 5, 20), Err(expected.clone()));
+
+        let proof = RangeProof {
+            commitment: [0u8; 32],
+            lower: vec![],
+            upper: vec![],
+        };
+        assert_eq!(verify_range_proof(&proof, 5, 20), Err(expected));
+    }
+
+    #[cfg(feature = "range-proofs")]
+    #[test]
+    fn test_range_proof_roundtrip() {
+        let salt = [7u8; 32];
+        let proof = generate_range_proof(10_000, &salt, 5_000, 20_000).unwrap();
+
+        assert!(verify_range_proof(&proof, 5_000, 20_000).is_ok());
+        // A tighter range the amount is outside of must not verify
+        assert!(verify_range_proof(&proof, 15_000, 20_000).is_err());
+        assert!(generate_range_proof(1_000, &salt, 5_000, 20_000).is_err());
+    }
+
+    #[cfg(feature = "range-proofs")]
+    #[test]
+    fn test_aggregate_proof_of_reserves() {
+        let orders: Vec<(OrderID, CommitmentOpening)> = [4_000, 2_500, 3_500]
+            .iter()
+            .enumerate()
+            .map(|(i, &amount)| {
+                let opening = CommitmentOpening {
+                    amount,
+                    salt: [i as u8 + 1; 32],
+                    merkle_proof: None,
+                };
+                (format!("order_{i}"), opening)
+            })
+            .collect();
+
+        // The real total is 10_000
+        let proof = aggregate_liquidity_proof(&orders, 9_000).unwrap();
+        assert!(verify_aggregate_proof(&proof).is_ok());
+        assert_eq!(
+            proof.orders[0].amount_commitment,
+            commit_amount(4_000, &[1u8; 32]).unwrap()
+        );
+
+        assert!(aggregate_liquidity_proof(&orders, 10_001).is_err());
+
+        // Claiming a higher threshold, or dropping an order, does not verify
+        let mut raised = proof.clone();
+        raised.threshold = 10_001;
+        assert!(verify_aggregate_proof(&raised).is_err());
+        let mut dropped = proof.clone();
+        dropped.orders.pop();
+        assert!(verify_aggregate_proof(&dropped).is_err());
+
+        // Nor does presenting the proof for another order or another commitment
+        let mut renamed = proof.clone();
+        renamed.orders[1].order_id = "order_other".to_string();
+        assert!(verify_aggregate_proof(&renamed).is_err());
+        let mut rehashed = proof;
+        rehashed.orders[1].commitment_hash = [0xab; 32];
+        assert!(verify_aggregate_proof(&rehashed).is_err());
+    }
+
+    #[cfg(feature = "range-proofs")]
+    #[test]
+    fn test_equality_proof() {
+        let (salt1, salt2) = ([1u8; 32], [2u8; 32]);
+        let c1 = commit_amount(10_000, &salt1).unwrap();
+        let c2 = commit_amount(10_000, &salt2).unwrap();
+        let c3 = commit_amount(10_001, &salt2).unwrap();
+
+        let proof = prove_equal(&c1, &salt1, &c2, &salt2).unwrap();
+        assert!(verify_equal(&c1, &c2, &proof).is_ok());
+
+        // Unequal amounts cannot be proven, nor can a proof be reused on other commitments
+        assert!(prove_equal(&c1, &salt1, &c3, &salt2).is_err());
+        assert!(verify_equal(&c1, &c3, &proof).is_err());
+        assert!(verify_equal(&c2, &c1, &proof).is_err());
+
+        let mut forged = proof;
+        forged.response[0] ^= 1;
+        assert!(verify_equal(&c1, &c2, &forged).is_err());
+    }
+}
@@ -1,7 +1,13 @@
 //! Cryptographic types for BlackTrace
 
+use std::fmt;
+
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 
+use super::merkle::MerkleProof;
+use super::range_proof::{HiddenMinimumProof, LiquidityRangeProof};
+
 /// 32-byte hash value (Blake2b-256 output)
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Hash([u8; 32]);
@@ -32,8 +38,11 @@ pub type Salt = [u8; 32];
 /// Viewing key for generating nullifiers
 pub type ViewingKey = Vec<u8>;
 
+/// Order identifier, as used by the Go node
+pub type OrderID = String;
+
 /// Nullifier prevents reuse of the same liquidity proof
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Nullifier(pub Hash);
 
 impl Nullifier {
@@ -48,6 +57,17 @@ impl Nullifier {
     }
 }
 
+/// How much of the minimum amount a liquidity commitment publishes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MinAmountDisclosure {
+    /// Publish the exact minimum
+    #[default]
+    Exact,
+    /// Publish only the minimum's tier; the minimum itself is committed and range-proven
+    Tier,
+}
+
 /// Liquidity commitment proves you have funds without revealing the amount
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LiquidityCommitment {
@@ -55,12 +75,32 @@ pub struct LiquidityCommitment {
     pub commitment_hash: Hash,
     /// Nullifier prevents reuse of this commitment
     pub nullifier: Nullifier,
-    /// Minimum amount being claimed (public)
+    /// Minimum amount being claimed (public); the tier's lower bound when the minimum is hidden
     pub min_amount: u64,
+    /// Proof of the hidden minimum under `MinAmountDisclosure::Tier`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden_min: Option<HiddenMinimumProof>,
+    /// Proof the amount lies in a published range; commitments without one are only checked
+    /// against their hash once opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_proof: Option<LiquidityRangeProof>,
     /// Timestamp of commitment creation
     pub timestamp: u64,
 }
 
+/// One order a market maker wants a commitment for (see `generate_commitments_batch`)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderCommitRequest {
+    /// Order the commitment is bound to
+    pub order_id: OrderID,
+    /// Amount to commit
+    pub amount: u64,
+    /// Minimum amount to claim publicly
+    pub min_amount: u64,
+    /// Viewing key the nullifier is derived from
+    pub viewing_key: ViewingKey,
+}
+
 /// Commitment opening reveals the committed values
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CommitmentOpening {
@@ -68,4 +108,110 @@ pub struct CommitmentOpening {
     pub amount: u64,
     /// Random salt used in commitment
     pub salt: [u8; 32],
+    /// Proof that the commitment is in the maker's published set (see `CommitmentTree`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merkle_proof: Option<MerkleProof>,
+}
+
+/// 32-byte HTLC secret pre-image
+///
+/// Serializes as its raw bytes so it can be stored, but must never be part of a public
+/// wire struct; `Debug` is redacted so it cannot leak through logs or error messages.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretPreimage([u8; 32]);
+
+impl SecretPreimage {
+    /// Wrap existing secret bytes
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        SecretPreimage(bytes)
+    }
+
+    /// Generate a fresh random secret
+    pub fn random() -> Self {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        SecretPreimage(bytes)
+    }
+
+    /// Read the secret bytes (only when revealing or hashing it)
+    pub fn expose(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretPreimage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretPreimage([redacted])")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_preimage_debug_redacted() {
+        let secret = SecretPreimage::from_bytes([0xab; 32]);
+        let debug = format!("{:?}", secret);
+
+        assert!(!debug.contains(&hex::encode(secret.expose())));
+        assert!(!debug.contains("171")); // 0xab as a decimal array element
+        assert_eq!(debug, "SecretPreimage([redacted])");
+    }
+
+    #[test]
+    fn test_secret_preimage_serializes_as_bytes() {
+        let secret = SecretPreimage::from_bytes([7; 32]);
+        let json = serde_json::to_string(&secret).unwrap();
+
+        assert_eq!(json, serde_json::to_string(&[7u8; 32]).unwrap());
+        assert_eq!(
+            serde_json::from_str::<SecretPreimage>(&json).unwrap(),
+            secret
+        );
+    }
+
+    /// No public wire type in the crypto modules may carry a secret pre-image
+    #[test]
+    fn test_wire_structs_do_not_contain_secret_preimage() {
+        let modules = [
+            ("types.rs", include_str!("types.rs")),
+            ("commitment.rs", include_str!("commitment.rs")),
+            ("merkle.rs", include_str!("merkle.rs")),
+            ("nullifier.rs", include_str!("nullifier.rs")),
+            ("range_proof.rs", include_str!("range_proof.rs")),
+        ];
+
+        for (module, source) in modules {
+            let source = &source[..source.find("#[cfg(test)]").unwrap_or(source.len())];
+
+            let mut scanned = 0;
+            for keyword in ["pub struct ", "pub enum "] {
+                for (start, _) in source.match_indices(keyword) {
+                    let decl = &source[start..];
+                    let name: String = decl[keyword.len()..]
+                        .chars()
+                        .take_while(|c| c.is_alphanumeric() || *c == '_')
+                        .collect();
+                    if name == "SecretPreimage" {
+                        continue;
+                    }
+                    let end = match decl.find('{') {
+                        Some(brace) if brace < decl.find(';').unwrap_or(usize::MAX) => {
+                            decl.find("\n}").unwrap()
+                        }
+                        _ => decl.find(';').unwrap(),
+                    };
+                    assert!(
+                        !decl[..end].contains("SecretPreimage"),
+                        "public type {} in {} must not contain a SecretPreimage",
+                        name,
+                        module
+                    );
+                    scanned += 1;
+                }
+            }
+            assert!(scanned > 0, "no public types found in {}", module);
+        }
+    }
 }
@@ -0,0 +1,32 @@
+//! Error types for BlackTrace cryptography
+
+use thiserror::Error;
+
+/// Errors returned by BlackTrace crypto operations
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BlackTraceError {
+    /// Functionality compiled out or not yet available
+    #[error("Not implemented: {0}")]
+    NotImplemented(String),
+
+    /// A proof failed to generate or verify
+    #[error("Invalid proof: {0}")]
+    InvalidProof(String),
+
+    /// A commitment request was rejected before anything was committed
+    #[error("Invalid commitment request: {0}")]
+    InvalidCommitmentRequest(String),
+
+    /// Persisted state failed its integrity check and must not be trusted
+    #[error("State corruption: {0}")]
+    StateCorruption(String),
+}
+
+/// Result type for BlackTrace crypto operations
+pub type Result<T> = std::result::Result<T, BlackTraceError>;
+
+/// Error for a code path whose cargo feature is disabled
+#[cfg_attr(feature = "range-proofs", allow(dead_code))]
+pub(crate) fn feature_disabled(feature: &str) -> BlackTraceError {
+    BlackTraceError::NotImplemented(format!("feature {} not enabled", feature))
+}
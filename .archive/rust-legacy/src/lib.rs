@@ -4,16 +4,25 @@
 //!
 //! This library provides cryptographic functions called by the Go application
 //! via FFI/cgo for:
-//! - Blake2b-based commitments for liquidity proofs
+//! - Blake2b-based commitments for liquidity proofs, with Merkle membership proofs
 //! - Nullifier generation for double-spend prevention
-//! - ZK proof verification (future)
+//! - Bulletproof range proofs (`range-proofs` feature)
 //! - Zcash Orchard HTLC creation (future)
 
 pub mod crypto;
+pub mod error;
 
 // Re-export commonly used types and functions
 pub use crypto::{
-    CommitmentScheme, CommitmentOpening, Hash, LiquidityCommitment, Nullifier, Salt, ViewingKey,
-    compute_commitment_hash, generate_commitment, generate_nullifier, generate_random_salt,
-    verify_commitment,
+    AggregateOrder, AggregateProof, CommitmentScheme, CommitmentOpening, CommitmentTree,
+    EqualityProof, Hash, HiddenMinimumProof, LiquidityCommitment, LiquidityRangeProof, MerkleProof,
+    MinAmountDisclosure, Nullifier, NullifierSet, OrderCommitRequest, OrderID, RangeProof, Salt,
+    SecretPreimage, ViewingKey, aggregate_liquidity_proof, commit_amount,
+    compute_commitment_hash, generate_commitment, generate_commitment_with_disclosure,
+    generate_commitment_with_range_proof, generate_commitments_batch, generate_nullifier,
+    generate_random_salt, generate_range_proof, prove_equal, verify_aggregate_proof,
+    verify_commitment, verify_commitment_full, verify_commitment_membership,
+    verify_commitment_published, verify_commitment_range, verify_commitment_raw, verify_equal,
+    verify_min_amount, verify_nullifier, verify_range_proof,
 };
+pub use error::{BlackTraceError, Result};
@@ -17,6 +17,8 @@ var (
 	nodePort int
 	apiPort  int
 	connectAddr string
+	interests   []string
+	wireFormat  string
 )
 
 var nodeCmd = &cobra.Command{
@@ -53,6 +55,8 @@ func init() {
 	nodeCmd.Flags().IntVarP(&nodePort, "port", "p", 9000, "Port to listen on for P2P")
 	nodeCmd.Flags().IntVar(&apiPort, "api-port", 8080, "Port for HTTP API server")
 	nodeCmd.Flags().StringVarP(&connectAddr, "connect", "c", "", "Multiaddr of peer to connect to (optional)")
+	nodeCmd.Flags().StringSliceVar(&interests, "interests", nil, "Stablecoins to receive orders for, e.g. USDC,DAI (default: all)")
+	nodeCmd.Flags().StringVar(&wireFormat, "wire-format", "json", "Preferred encoding for direct messages: json or bincode (bincode is used only with peers that prefer it too)")
 }
 
 func runNode(cmd *cobra.Command, args []string) {
@@ -69,6 +73,19 @@ func runNode(cmd *cobra.Command, args []string) {
 	if err != nil {
 		log.Fatalf("Failed to create app: %v", err)
 	}
+	format, err := node.ParseWireFormat(wireFormat)
+	if err != nil {
+		log.Fatalf("Invalid --wire-format: %v", err)
+	}
+	app.SetWireFormat(format)
+	if len(interests) > 0 {
+		coins := make([]node.StablecoinType, len(interests))
+		for i, coin := range interests {
+			coins[i] = node.StablecoinType(strings.ToUpper(coin))
+		}
+		app.SetInterests(coins)
+		fmt.Printf("   Interests: %v\n\n", coins)
+	}
 	app.Run()
 
 	// Start API server
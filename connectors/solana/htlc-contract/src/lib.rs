@@ -5,6 +5,9 @@ use ripemd::Ripemd160;
 
 declare_id!("CUxqXa849pvw3TLEWRrA2RyA3vm5SXXwb181BFnRSvej");
 
+/// Length of the secret pre-image in bytes (protocol-wide 32-byte secrets)
+pub const SECRET_LENGTH: usize = 32;
+
 /// Compute HASH160 = RIPEMD160(SHA256(data)) - Bitcoin/Zcash standard
 /// Returns a 20-byte hash
 fn hash160(data: &[u8]) -> [u8; 20] {
@@ -15,6 +18,110 @@ fn hash160(data: &[u8]) -> [u8; 20] {
     result
 }
 
+/// Checks a lock amount against the configured cap, if the program config sets one
+fn check_lock_amount(amount: u64, max_lock_lamports: Option<u64>) -> Result<()> {
+    require!(amount > 0, HTLCError::InvalidAmount);
+    if let Some(max) = max_lock_lamports {
+        require!(amount <= max, HTLCError::AmountAboveMax);
+    }
+    Ok(())
+}
+
+/// Cap set by the program config, or `None` while the config has not been initialized
+fn configured_max_lock(config: &AccountInfo) -> Result<Option<u64>> {
+    if config.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(
+        *config.owner,
+        crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    let config = HTLCConfig::try_deserialize(&mut &config.try_borrow_data()?[..])?;
+    Ok(Some(config.max_lock_lamports))
+}
+
+/// Shared body of `lock` and `lock_relative`: validates the absolute timeout and amount,
+/// initializes the HTLC account and moves the lamports into it
+fn lock_htlc(
+    ctx: Context<Lock>,
+    hash_lock: [u8; 20],
+    receiver: Pubkey,
+    amount: u64,
+    timeout: i64,
+) -> Result<()> {
+    let htlc = &mut ctx.accounts.htlc;
+    let clock = Clock::get()?;
+
+    // Validate timeout is in the future
+    require!(timeout > clock.unix_timestamp, HTLCError::InvalidTimeout);
+    // A closed HTLC's secret may be public, so its hash_lock cannot guard new funds
+    require!(ctx.accounts.spent.data_is_empty(), HTLCError::HashLockSpent);
+    check_lock_amount(amount, configured_max_lock(&ctx.accounts.config)?)?;
+
+    // Initialize HTLC account
+    htlc.hash_lock = hash_lock;
+    htlc.sender = ctx.accounts.sender.key();
+    htlc.receiver = receiver;
+    htlc.amount = amount;
+    htlc.timeout = timeout;
+    htlc.claimed = false;
+    htlc.refunded = false;
+    htlc.bump = ctx.bumps.htlc;
+
+    // Transfer SOL from sender to HTLC PDA account
+    let cpi_context = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        system_program::Transfer {
+            from: ctx.accounts.sender.to_account_info(),
+            to: htlc.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_context, amount)?;
+
+    emit!(Locked {
+        hash_lock,
+        sender: ctx.accounts.sender.key(),
+        receiver,
+        amount,
+        timeout,
+    });
+
+    msg!("HTLC locked: {} lamports for receiver {}", amount, receiver);
+    Ok(())
+}
+
+/// Balance an HTLC account keeps after releasing `amount`, or `RentExemptionViolated` if that
+/// would take it below `rent_minimum`. The `lock` payer funds the rent-exempt reserve on top of
+/// the amount, so only an amount larger than the balance actually locked can trip this.
+fn balance_after_release(balance: u64, rent_minimum: u64, amount: u64) -> Result<u64> {
+    let releasable = balance.saturating_sub(rent_minimum);
+    require!(amount <= releasable, HTLCError::RentExemptionViolated);
+    Ok(balance - amount)
+}
+
+/// Moves an HTLC's locked lamports to `to` after a claim or refund has settled it
+///
+/// Invariant: the locked amount is credited at most once. Callers write `claimed` or `refunded`
+/// before calling, so the flag is set before any lamports move. Both happen in one instruction:
+/// if the transfer fails the flag rolls back with it and a retry finds the HTLC still open, and
+/// once it succeeds every later claim or refund is rejected by the flag.
+fn release_locked<'info>(htlc: &Account<'info, HTLCAccount>, to: &AccountInfo<'info>) -> Result<u64> {
+    require!(htlc.claimed != htlc.refunded, HTLCError::NotTerminal);
+
+    let amount = htlc.amount;
+    let from = htlc.to_account_info();
+    let rent_minimum = Rent::get()?.minimum_balance(from.data_len());
+    let remaining = balance_after_release(from.lamports(), rent_minimum, amount)?;
+    let credited = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? = credited;
+    Ok(amount)
+}
+
 /// BlackTrace HTLC Program for Solana
 ///
 /// This contract implements Hash Time-Locked Contracts (HTLC) for atomic swaps.
@@ -25,16 +132,52 @@ fn hash160(data: &[u8]) -> [u8; 20] {
 /// 1. Sender locks SOL with a hash_lock (HASH160 of secret = 20 bytes)
 /// 2. Receiver claims SOL by revealing the secret (pre-image)
 /// 3. If timeout expires, sender can refund the SOL
+///    (or earlier, with `mutual_cancel`, if sender and receiver both sign)
 #[program]
 pub mod blacktrace_htlc {
     use super::*;
 
+    /// Create the program config, capping how many lamports a single `lock` may move
+    ///
+    /// Only the program's upgrade authority may call this; it becomes the config authority.
+    /// Until this runs, locks are unbounded.
+    ///
+    /// # Arguments
+    /// * `max_lock_lamports` - Largest amount `lock` and `lock_relative` accept
+    pub fn initialize_config(ctx: Context<InitializeConfig>, max_lock_lamports: u64) -> Result<()> {
+        require!(max_lock_lamports > 0, HTLCError::InvalidAmount);
+
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.max_lock_lamports = max_lock_lamports;
+        config.bump = ctx.bumps.config;
+
+        msg!(
+            "HTLC config created: locks capped at {} lamports",
+            max_lock_lamports
+        );
+        Ok(())
+    }
+
+    /// Change the lock cap (config authority only)
+    ///
+    /// # Arguments
+    /// * `max_lock_lamports` - Largest amount `lock` and `lock_relative` accept
+    pub fn set_max_lock_lamports(ctx: Context<UpdateConfig>, max_lock_lamports: u64) -> Result<()> {
+        require!(max_lock_lamports > 0, HTLCError::InvalidAmount);
+
+        ctx.accounts.config.max_lock_lamports = max_lock_lamports;
+
+        msg!("HTLC locks capped at {} lamports", max_lock_lamports);
+        Ok(())
+    }
+
     /// Lock native SOL in an HTLC
     ///
     /// # Arguments
     /// * `hash_lock` - HASH160 of the secret (20 bytes) = RIPEMD160(SHA256(secret))
     /// * `receiver` - Public key of the receiver who can claim with the secret
-    /// * `amount` - Amount of lamports to lock
+    /// * `amount` - Amount of lamports to lock, at most the config's `max_lock_lamports`
     /// * `timeout` - Unix timestamp after which sender can refund
     pub fn lock(
         ctx: Context<Lock>,
@@ -43,50 +186,36 @@ pub mod blacktrace_htlc {
         amount: u64,
         timeout: i64,
     ) -> Result<()> {
-        let htlc = &mut ctx.accounts.htlc;
-        let clock = Clock::get()?;
-
-        // Validate timeout is in the future
-        require!(timeout > clock.unix_timestamp, HTLCError::InvalidTimeout);
-        require!(amount > 0, HTLCError::InvalidAmount);
-
-        // Initialize HTLC account
-        htlc.hash_lock = hash_lock;
-        htlc.sender = ctx.accounts.sender.key();
-        htlc.receiver = receiver;
-        htlc.amount = amount;
-        htlc.timeout = timeout;
-        htlc.claimed = false;
-        htlc.refunded = false;
-        htlc.bump = ctx.bumps.htlc;
-
-        // Transfer SOL from sender to HTLC PDA account
-        let cpi_context = CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.sender.to_account_info(),
-                to: htlc.to_account_info(),
-            },
-        );
-        system_program::transfer(cpi_context, amount)?;
-
-        emit!(Locked {
-            hash_lock,
-            sender: ctx.accounts.sender.key(),
-            receiver,
-            amount,
-            timeout,
-        });
+        lock_htlc(ctx, hash_lock, receiver, amount, timeout)
+    }
 
-        msg!("HTLC locked: {} lamports for receiver {}", amount, receiver);
-        Ok(())
+    /// Lock native SOL in an HTLC with a timeout relative to the validator clock
+    ///
+    /// Avoids clock skew between the client and the validator: the refund time is
+    /// `Clock::get()?.unix_timestamp + timeout_seconds`, subject to the same checks as `lock`.
+    ///
+    /// # Arguments
+    /// * `hash_lock` - HASH160 of the secret (20 bytes) = RIPEMD160(SHA256(secret))
+    /// * `receiver` - Public key of the receiver who can claim with the secret
+    /// * `amount` - Amount of lamports to lock
+    /// * `timeout_seconds` - Seconds from now after which sender can refund
+    pub fn lock_relative(
+        ctx: Context<Lock>,
+        hash_lock: [u8; 20],
+        receiver: Pubkey,
+        amount: u64,
+        timeout_seconds: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let timeout = clock.unix_timestamp + i64::from(timeout_seconds);
+        lock_htlc(ctx, hash_lock, receiver, amount, timeout)
     }
 
     /// Claim SOL by revealing the secret
     ///
     /// # Arguments
     /// * `hash_lock` - The hash_lock identifying the HTLC (20 bytes)
-    /// * `secret` - The pre-image that hashes to hash_lock (HASH160)
+    /// * `secret` - The pre-image that hashes to hash_lock (HASH160), exactly `SECRET_LENGTH` bytes
     pub fn claim(
         ctx: Context<Claim>,
         hash_lock: [u8; 20],
@@ -99,6 +228,12 @@ pub mod blacktrace_htlc {
         require!(!htlc.refunded, HTLCError::AlreadyRefunded);
         require!(htlc.hash_lock == hash_lock, HTLCError::HashMismatch);
 
+        // Reject wrongly-sized secrets before spending compute on hashing them
+        require!(
+            secret.len() == SECRET_LENGTH,
+            HTLCError::InvalidSecretLength
+        );
+
         // Verify the secret: HASH160(secret) = RIPEMD160(SHA256(secret)) must equal hash_lock
         let computed_hash = hash160(&secret);
         require!(
@@ -112,13 +247,11 @@ pub mod blacktrace_htlc {
             HTLCError::NotReceiver
         );
 
-        // Mark as claimed
+        // Mark as claimed before any lamports move (see release_locked)
         htlc.claimed = true;
 
         // Transfer SOL from HTLC PDA to receiver
-        let amount = htlc.amount;
-        **htlc.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.receiver.to_account_info().try_borrow_mut_lamports()? += amount;
+        let amount = release_locked(htlc, &ctx.accounts.receiver.to_account_info())?;
 
         emit!(Claimed {
             hash_lock,
@@ -159,13 +292,11 @@ pub mod blacktrace_htlc {
             HTLCError::NotSender
         );
 
-        // Mark as refunded
+        // Mark as refunded before any lamports move (see release_locked)
         htlc.refunded = true;
 
         // Transfer SOL from HTLC PDA back to sender
-        let amount = htlc.amount;
-        **htlc.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.sender.to_account_info().try_borrow_mut_lamports()? += amount;
+        let amount = release_locked(htlc, &ctx.accounts.sender.to_account_info())?;
 
         emit!(Refunded {
             hash_lock,
@@ -177,6 +308,76 @@ pub mod blacktrace_htlc {
         Ok(())
     }
 
+    /// Refund SOL to the sender before the timeout, with the consent of both parties
+    ///
+    /// Lets a swap both sides agree to abort release the sender's capital immediately instead
+    /// of waiting for the timeout. Requires signatures from the sender and the receiver, and
+    /// leaves the HTLC refunded, so it can be closed afterwards.
+    ///
+    /// # Arguments
+    /// * `hash_lock` - The hash_lock identifying the HTLC (20 bytes)
+    pub fn mutual_cancel(
+        ctx: Context<MutualCancel>,
+        hash_lock: [u8; 20],
+    ) -> Result<()> {
+        let htlc = &mut ctx.accounts.htlc;
+
+        // Verify HTLC state
+        require!(!htlc.claimed, HTLCError::AlreadyClaimed);
+        require!(!htlc.refunded, HTLCError::AlreadyRefunded);
+        require!(htlc.hash_lock == hash_lock, HTLCError::HashMismatch);
+
+        // Verify both parties signed
+        require!(
+            ctx.accounts.sender.key() == htlc.sender,
+            HTLCError::NotSender
+        );
+        require!(
+            ctx.accounts.receiver.key() == htlc.receiver,
+            HTLCError::NotReceiver
+        );
+
+        // Mark as refunded before any lamports move (see release_locked)
+        htlc.refunded = true;
+
+        // Transfer SOL from HTLC PDA back to sender
+        let amount = release_locked(htlc, &ctx.accounts.sender.to_account_info())?;
+
+        emit!(Cancelled {
+            hash_lock,
+            sender: ctx.accounts.sender.key(),
+            receiver: ctx.accounts.receiver.key(),
+            amount,
+        });
+
+        msg!("HTLC cancelled by both parties: {} lamports returned to sender", amount);
+        Ok(())
+    }
+
+    /// Close a claimed or refunded HTLC and return its rent to the sender
+    ///
+    /// Leaves a `["spent", hash_lock]` tombstone in its place, paid for by the sender, so the
+    /// freed `["htlc", hash_lock]` address cannot be locked again under a hash_lock whose secret
+    /// may already be public.
+    ///
+    /// # Arguments
+    /// * `hash_lock` - The hash_lock identifying the HTLC (20 bytes)
+    pub fn close_htlc(
+        ctx: Context<CloseHTLC>,
+        hash_lock: [u8; 20],
+    ) -> Result<()> {
+        let htlc = &ctx.accounts.htlc;
+
+        require!(htlc.claimed || htlc.refunded, HTLCError::NotTerminal);
+        require!(htlc.hash_lock == hash_lock, HTLCError::HashMismatch);
+
+        ctx.accounts.spent.bump = ctx.bumps.spent;
+
+        // Anchor's `close = sender` moves the remaining (rent) lamports and zeroes the account
+        msg!("HTLC closed: rent returned to sender {}", htlc.sender);
+        Ok(())
+    }
+
     /// Get HTLC details (view function)
     pub fn get_htlc_details(ctx: Context<GetHTLCDetails>) -> Result<HTLCDetailsResponse> {
         let htlc = &ctx.accounts.htlc;
@@ -231,6 +432,38 @@ impl HTLCAccount {
         1;   // bump
 }
 
+/// Tombstone left by `close_htlc` at `["spent", hash_lock]`; `lock` refuses its hash_lock
+#[account]
+#[derive(Default)]
+pub struct SpentHashLock {
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SpentHashLock {
+    pub const SIZE: usize = 8 + // discriminator
+        1;   // bump
+}
+
+/// Program-wide settings, at the `["config"]` PDA
+#[account]
+#[derive(Default)]
+pub struct HTLCConfig {
+    /// Who may change the settings
+    pub authority: Pubkey,
+    /// Largest amount of lamports a single lock may move
+    pub max_lock_lamports: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl HTLCConfig {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // authority
+        8 +  // max_lock_lamports
+        1;   // bump
+}
+
 // ============================================================================
 // Instruction Contexts
 // ============================================================================
@@ -250,9 +483,58 @@ pub struct Lock<'info> {
     #[account(mut)]
     pub sender: Signer<'info>,
 
+    /// CHECK: the program config PDA, read in `lock_htlc` only once it has been initialized
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: the hash_lock's tombstone PDA, which `lock_htlc` requires not to exist
+    #[account(seeds = [b"spent", hash_lock.as_ref()], bump)]
+    pub spent: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = HTLCConfig::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, HTLCConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// This program, to find its program data account
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::BlacktraceHtlc>,
+
+    /// Whoever can upgrade the program is the only one trusted to create its config
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ HTLCError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ HTLCError::NotConfigAuthority
+    )]
+    pub config: Account<'info, HTLCConfig>,
+
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(hash_lock: [u8; 20])]
 pub struct Claim<'info> {
@@ -281,6 +563,49 @@ pub struct Refund<'info> {
     pub sender: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(hash_lock: [u8; 20])]
+pub struct MutualCancel<'info> {
+    #[account(
+        mut,
+        seeds = [b"htlc", hash_lock.as_ref()],
+        bump = htlc.bump
+    )]
+    pub htlc: Account<'info, HTLCAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub receiver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(hash_lock: [u8; 20])]
+pub struct CloseHTLC<'info> {
+    #[account(
+        mut,
+        seeds = [b"htlc", hash_lock.as_ref()],
+        bump = htlc.bump,
+        has_one = sender @ HTLCError::NotSender,
+        close = sender
+    )]
+    pub htlc: Account<'info, HTLCAccount>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = SpentHashLock::SIZE,
+        seeds = [b"spent", hash_lock.as_ref()],
+        bump
+    )]
+    pub spent: Account<'info, SpentHashLock>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct GetHTLCDetails<'info> {
     pub htlc: Account<'info, HTLCAccount>,
@@ -314,6 +639,14 @@ pub struct Refunded {
     pub amount: u64,
 }
 
+#[event]
+pub struct Cancelled {
+    pub hash_lock: [u8; 20],
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    pub amount: u64,
+}
+
 // ============================================================================
 // Response Types
 // ============================================================================
@@ -361,4 +694,71 @@ pub enum HTLCError {
 
     #[msg("Hash lock mismatch")]
     HashMismatch,
+
+    #[msg("Invalid secret length: must be exactly 32 bytes")]
+    InvalidSecretLength,
+
+    #[msg("HTLC is still active: it must be claimed or refunded before closing")]
+    NotTerminal,
+
+    #[msg("Releasing the amount would leave the HTLC account below its rent-exempt minimum")]
+    RentExemptionViolated,
+
+    #[msg("Amount exceeds the configured max_lock_lamports")]
+    AmountAboveMax,
+
+    #[msg("Only the config authority can change the config")]
+    NotConfigAuthority,
+
+    #[msg("Only the program's upgrade authority can create the config")]
+    NotUpgradeAuthority,
+
+    #[msg("Hash lock belongs to a closed HTLC and cannot be locked again")]
+    HashLockSpent,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RENT_MINIMUM: u64 = 1_559_040;
+    const AMOUNT: u64 = 1_000_000;
+
+    fn rent_violation() -> Error {
+        HTLCError::RentExemptionViolated.into()
+    }
+
+    #[test]
+    fn releases_down_to_the_rent_exempt_minimum() {
+        // As funded by `lock`: the amount plus the reserve
+        let remaining = balance_after_release(RENT_MINIMUM + AMOUNT, RENT_MINIMUM, AMOUNT).unwrap();
+        assert_eq!(remaining, RENT_MINIMUM);
+    }
+
+    #[test]
+    fn rejects_a_release_that_would_break_rent_exemption() {
+        // One lamport short of the amount plus the reserve
+        let result = balance_after_release(RENT_MINIMUM + AMOUNT - 1, RENT_MINIMUM, AMOUNT);
+        assert_eq!(result.unwrap_err(), rent_violation());
+
+        // Nor is a balance already below the reserve drained further
+        let result = balance_after_release(RENT_MINIMUM - 1, RENT_MINIMUM, 1);
+        assert_eq!(result.unwrap_err(), rent_violation());
+    }
+
+    #[test]
+    fn caps_a_lock_at_the_configured_max() {
+        assert!(check_lock_amount(AMOUNT, Some(AMOUNT)).is_ok());
+        assert_eq!(
+            check_lock_amount(AMOUNT + 1, Some(AMOUNT)).unwrap_err(),
+            HTLCError::AmountAboveMax.into()
+        );
+
+        // Without a config only zero is rejected
+        assert!(check_lock_amount(u64::MAX, None).is_ok());
+        assert_eq!(
+            check_lock_amount(0, None).unwrap_err(),
+            HTLCError::InvalidAmount.into()
+        );
+    }
 }
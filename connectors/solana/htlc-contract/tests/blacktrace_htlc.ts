@@ -0,0 +1,306 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { assert } from "chai";
+import { createHash, randomBytes } from "crypto";
+import { BlacktraceHtlc } from "../target/types/blacktrace_htlc";
+
+describe("blacktrace_htlc", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.BlacktraceHtlc as Program<BlacktraceHtlc>;
+  const sender = provider.wallet.publicKey;
+
+  // HASH160 = RIPEMD160(SHA256(data)), matching the on-chain hash160()
+  const hash160 = (data: Buffer): Buffer => {
+    const sha = createHash("sha256").update(data).digest();
+    return createHash("ripemd160").update(sha).digest();
+  };
+
+  const htlcPda = (hashLock: Buffer): PublicKey =>
+    PublicKey.findProgramAddressSync([Buffer.from("htlc"), hashLock], program.programId)[0];
+
+  const spentPda = (hashLock: Buffer): PublicKey =>
+    PublicKey.findProgramAddressSync([Buffer.from("spent"), hashLock], program.programId)[0];
+
+  const fundedKeypair = async (): Promise<Keypair> => {
+    const kp = Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(kp.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig, "confirmed");
+    return kp;
+  };
+
+  const lock = async (secret: Buffer, receiver: PublicKey, amount: number, timeoutSecs = 3600) => {
+    const hashLock = hash160(secret);
+    const timeout = new BN(Math.floor(Date.now() / 1000) + timeoutSecs);
+    await program.methods
+      .lock([...hashLock], receiver, new BN(amount), timeout)
+      .accountsPartial({ htlc: htlcPda(hashLock), sender })
+      .rpc();
+    return hashLock;
+  };
+
+  const claim = (hashLock: Buffer, secret: Buffer, receiver: Keypair) =>
+    program.methods
+      .claim([...hashLock], secret)
+      .accountsPartial({ htlc: htlcPda(hashLock), receiver: receiver.publicKey })
+      .signers([receiver])
+      .rpc();
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+      assert.fail(`expected ${code}`);
+    } catch (err) {
+      assert.instanceOf(err, anchor.AnchorError);
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, code);
+    }
+  };
+
+  describe("lock_relative", () => {
+    const lockRelative = (hashLock: Buffer, receiver: PublicKey, timeoutSeconds: number) =>
+      program.methods
+        .lockRelative([...hashLock], receiver, new BN(1_000_000), timeoutSeconds)
+        .accountsPartial({ htlc: htlcPda(hashLock), sender })
+        .rpc({ commitment: "confirmed" });
+
+    it("stores the validator clock plus the duration as the timeout", async () => {
+      const receiver = await fundedKeypair();
+      const hashLock = hash160(randomBytes(32));
+
+      const sig = await lockRelative(hashLock, receiver.publicKey, 600);
+
+      // The block time of the lock's slot is the Clock the program read
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const htlc = await program.account.htlcAccount.fetch(htlcPda(hashLock));
+      assert.equal(htlc.timeout.toNumber(), tx!.blockTime! + 600);
+    });
+
+    it("rejects a zero duration like a past absolute timeout", async () => {
+      const receiver = await fundedKeypair();
+      const hashLock = hash160(randomBytes(32));
+
+      await expectError(lockRelative(hashLock, receiver.publicKey, 0), "InvalidTimeout");
+    });
+  });
+
+  describe("mutual_cancel", () => {
+    const mutualCancel = (hashLock: Buffer, receiver: PublicKey, signers: Keypair[]) =>
+      program.methods
+        .mutualCancel([...hashLock])
+        .accountsPartial({ htlc: htlcPda(hashLock), sender, receiver })
+        .signers(signers)
+        .rpc({ commitment: "confirmed" });
+
+    it("refunds the sender before the timeout when both parties sign", async () => {
+      const receiver = await fundedKeypair();
+      const amount = 1_000_000;
+      const hashLock = await lock(randomBytes(32), receiver.publicKey, amount);
+
+      const sig = await mutualCancel(hashLock, receiver.publicKey, [receiver]);
+
+      // The sender paid the fee for this transaction and got the locked amount back
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const senderIndex = tx!.transaction.message.staticAccountKeys.findIndex((k) => k.equals(sender));
+      const delta = tx!.meta!.postBalances[senderIndex] - tx!.meta!.preBalances[senderIndex];
+      assert.equal(delta, amount - tx!.meta!.fee);
+
+      const htlc = await program.account.htlcAccount.fetch(htlcPda(hashLock));
+      assert.isTrue(htlc.refunded);
+      assert.isFalse(htlc.claimed);
+    });
+
+    it("rejects a cancel signed by the sender alone", async () => {
+      const receiver = await fundedKeypair();
+      const hashLock = await lock(randomBytes(32), receiver.publicKey, 1_000_000);
+
+      // Without the receiver's key the sender can only stand in as the receiver itself
+      await expectError(mutualCancel(hashLock, sender, []), "NotReceiver");
+
+      const htlc = await program.account.htlcAccount.fetch(htlcPda(hashLock));
+      assert.isFalse(htlc.refunded);
+    });
+  });
+
+  describe("close_htlc", () => {
+    const closeHtlc = (hashLock: Buffer) =>
+      program.methods
+        .closeHtlc([...hashLock])
+        .accountsPartial({ htlc: htlcPda(hashLock), sender })
+        .rpc({ commitment: "confirmed" });
+
+    it("closes a claimed HTLC and returns the rent to the sender", async () => {
+      const receiver = await fundedKeypair();
+      const secret = randomBytes(32);
+      const hashLock = await lock(secret, receiver.publicKey, 1_000_000);
+      await claim(hashLock, secret, receiver);
+
+      const rent = await provider.connection.getBalance(htlcPda(hashLock), "confirmed");
+      assert.isAbove(rent, 0);
+
+      const sig = await closeHtlc(hashLock);
+
+      // The sender paid the fee and the tombstone's rent for this transaction and got the rent back
+      const tombstoneRent = await provider.connection.getBalance(spentPda(hashLock), "confirmed");
+      assert.isAbove(tombstoneRent, 0);
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const senderIndex = tx!.transaction.message.staticAccountKeys.findIndex((k) => k.equals(sender));
+      const delta = tx!.meta!.postBalances[senderIndex] - tx!.meta!.preBalances[senderIndex];
+      assert.equal(delta, rent - tombstoneRent - tx!.meta!.fee);
+
+      assert.isNull(await provider.connection.getAccountInfo(htlcPda(hashLock), "confirmed"));
+    });
+
+    it("rejects closing an active HTLC", async () => {
+      const receiver = await fundedKeypair();
+      const hashLock = await lock(randomBytes(32), receiver.publicKey, 1_000_000);
+
+      await expectError(closeHtlc(hashLock), "NotTerminal");
+
+      const htlc = await program.account.htlcAccount.fetch(htlcPda(hashLock));
+      assert.isFalse(htlc.claimed);
+      assert.isNull(await provider.connection.getAccountInfo(spentPda(hashLock)));
+    });
+
+    it("refuses to reopen a closed HTLC under its revealed hash_lock", async () => {
+      const receiver = await fundedKeypair();
+      const secret = randomBytes(32);
+      const hashLock = await lock(secret, receiver.publicKey, 1_000_000);
+      await claim(hashLock, secret, receiver);
+      await closeHtlc(hashLock);
+
+      // Anyone who saw the Claimed event could otherwise take these funds
+      await expectError(lock(secret, receiver.publicKey, 1_000_000), "HashLockSpent");
+      assert.isNull(await provider.connection.getAccountInfo(htlcPda(hashLock)));
+    });
+  });
+
+  describe("claim", () => {
+    it("accepts a correctly-sized secret", async () => {
+      const receiver = await fundedKeypair();
+      const secret = randomBytes(32);
+      const hashLock = await lock(secret, receiver.publicKey, 1_000_000);
+
+      await claim(hashLock, secret, receiver);
+
+      const htlc = await program.account.htlcAccount.fetch(htlcPda(hashLock));
+      assert.isTrue(htlc.claimed);
+    });
+
+    it("credits the receiver exactly once and rejects a second claim", async () => {
+      const receiver = await fundedKeypair();
+      const secret = randomBytes(32);
+      const amount = 1_000_000;
+      const hashLock = await lock(secret, receiver.publicKey, amount);
+      const before = await provider.connection.getBalance(receiver.publicKey);
+
+      // The provider wallet pays the fees, so the receiver's balance moves by the amount alone
+      await claim(hashLock, secret, receiver);
+      const afterClaim = await provider.connection.getBalance(receiver.publicKey);
+      assert.equal(afterClaim - before, amount);
+
+      // A retried claim hits the flag set by the first one and moves nothing
+      await expectError(claim(hashLock, secret, receiver), "AlreadyClaimed");
+      const afterRetry = await provider.connection.getBalance(receiver.publicKey);
+      assert.equal(afterRetry, afterClaim);
+
+      const htlc = await program.account.htlcAccount.fetch(htlcPda(hashLock));
+      assert.isTrue(htlc.claimed);
+      assert.isFalse(htlc.refunded);
+    });
+
+    it("leaves the HTLC account exactly rent-exempt", async () => {
+      const receiver = await fundedKeypair();
+      const secret = randomBytes(32);
+      const hashLock = await lock(secret, receiver.publicKey, 1_000_000);
+
+      await claim(hashLock, secret, receiver);
+
+      // Only the amount moves; the reserve the lock payer funded stays behind
+      const account = await provider.connection.getAccountInfo(htlcPda(hashLock), "confirmed");
+      const rentMinimum = await provider.connection.getMinimumBalanceForRentExemption(account!.data.length);
+      assert.equal(account!.lamports, rentMinimum);
+    });
+
+    it("rejects an over-long secret before the hash check", async () => {
+      const receiver = await fundedKeypair();
+      // The hash lock matches this secret, so only the length check can reject it
+      const secret = randomBytes(64);
+      const hashLock = await lock(secret, receiver.publicKey, 1_000_000);
+
+      await expectError(claim(hashLock, secret, receiver), "InvalidSecretLength");
+
+      const htlc = await program.account.htlcAccount.fetch(htlcPda(hashLock));
+      assert.isFalse(htlc.claimed);
+    });
+  });
+
+  describe("max_lock_lamports", () => {
+    const maxLock = 5_000_000;
+    const configPda = PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId)[0];
+    const programData = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    )[0];
+
+    const initializeConfig = (authority: PublicKey) =>
+      program.methods.initializeConfig(new BN(maxLock)).accountsPartial({ authority, programData });
+
+    before(async () => {
+      // The config is program-wide, so reuse it if an earlier run created it
+      if (await provider.connection.getAccountInfo(configPda)) {
+        await program.methods.setMaxLockLamports(new BN(maxLock)).accountsPartial({ authority: sender }).rpc();
+        return;
+      }
+
+      // Anyone but the upgrade authority (the provider wallet that deployed) is turned away
+      const intruder = await fundedKeypair();
+      await expectError(initializeConfig(intruder.publicKey).signers([intruder]).rpc(), "NotUpgradeAuthority");
+      assert.isNull(await provider.connection.getAccountInfo(configPda));
+
+      await initializeConfig(sender).rpc();
+    });
+
+    it("accepts a lock of exactly the max", async () => {
+      const receiver = await fundedKeypair();
+      const hashLock = await lock(randomBytes(32), receiver.publicKey, maxLock);
+
+      const htlc = await program.account.htlcAccount.fetch(htlcPda(hashLock));
+      assert.equal(htlc.amount.toNumber(), maxLock);
+    });
+
+    it("rejects a lock above the max", async () => {
+      const receiver = await fundedKeypair();
+      const secret = randomBytes(32);
+
+      await expectError(lock(secret, receiver.publicKey, maxLock + 1), "AmountAboveMax");
+      assert.isNull(await provider.connection.getAccountInfo(htlcPda(hash160(secret))));
+    });
+
+    it("only lets the config authority change the max", async () => {
+      const intruder = await fundedKeypair();
+
+      await expectError(
+        program.methods
+          .setMaxLockLamports(new BN(Number.MAX_SAFE_INTEGER))
+          .accountsPartial({ authority: intruder.publicKey })
+          .signers([intruder])
+          .rpc(),
+        "NotConfigAuthority"
+      );
+
+      const config = await program.account.htlcConfig.fetch(configPda);
+      assert.equal(config.maxLockLamports.toNumber(), maxLock);
+    });
+  });
+});
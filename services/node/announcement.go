@@ -0,0 +1,169 @@
+package node
+
+import (
+	"bytes"
+	"encoding/binary"
+	"encoding/hex"
+	"fmt"
+
+	"golang.org/x/crypto/blake2b"
+)
+
+// OrderSigningVersion is the signing-bytes layout used for new announcements
+const OrderSigningVersion uint8 = 1
+
+// orderSigningTag domain-separates announcement signatures from other signed data
+const orderSigningTag = "blacktrace/order-announcement"
+
+// orderRotationTag domain-separates key rotation endorsements from announcement signatures
+const orderRotationTag = "blacktrace/order-key-rotation"
+
+// orderContentTag domain-separates content-derived order IDs
+const orderContentTag = "blacktrace/order-content"
+
+// OrderIDFromContent derives an order ID from the maker's proof commitment, public key and
+// announcement timestamp, so every node that learns of the same order derives the same ID
+func OrderIDFromContent(commitment, makerPubKey []byte, timestamp int64) OrderID {
+	h, _ := blake2b.New256(nil)
+	h.Write([]byte(orderContentTag))
+	for _, field := range [][]byte{commitment, makerPubKey} {
+		var length [4]byte
+		binary.BigEndian.PutUint32(length[:], uint32(len(field)))
+		h.Write(length[:])
+		h.Write(field)
+	}
+	var ts [8]byte
+	binary.BigEndian.PutUint64(ts[:], uint64(timestamp))
+	h.Write(ts[:])
+
+	return OrderID("order_c_" + hex.EncodeToString(h.Sum(nil)[:16]))
+}
+
+// ContentID returns the announcement's content-derived order ID. Only signed announcements have
+// one: until then the maker key is unset and the locally generated OrderID is all there is.
+func (a *OrderAnnouncement) ContentID() (OrderID, bool) {
+	if len(a.Signature) == 0 || len(a.MakerPubKey) == 0 {
+		return "", false
+	}
+	return OrderIDFromContent(a.ProofCommitment, a.MakerPubKey, a.Timestamp), true
+}
+
+// SigningBytes returns the bytes a maker signs for this announcement.
+//
+// Version 1 covers exactly, in order: order_id, order_type, stablecoin,
+// proof_commitment, timestamp, expiry, maker_pubkey. Any other field
+// (including ones added later) is outside the signature, so adding fields
+// never invalidates existing signatures.
+func (a *OrderAnnouncement) SigningBytes(version uint8) ([]byte, error) {
+	if version != 1 {
+		return nil, fmt.Errorf("unsupported order signing version %d", version)
+	}
+
+	var buf bytes.Buffer
+	writeField := func(b []byte) {
+		var length [4]byte
+		binary.BigEndian.PutUint32(length[:], uint32(len(b)))
+		buf.Write(length[:])
+		buf.Write(b)
+	}
+	writeInt := func(v int64) {
+		var b [8]byte
+		binary.BigEndian.PutUint64(b[:], uint64(v))
+		buf.Write(b[:])
+	}
+
+	buf.WriteString(orderSigningTag)
+	buf.WriteByte(version)
+	writeField([]byte(a.OrderID))
+	writeField([]byte(a.OrderType))
+	writeField([]byte(a.Stablecoin))
+	writeField(a.ProofCommitment)
+	writeInt(a.Timestamp)
+	writeInt(a.Expiry)
+	writeField(a.MakerPubKey)
+
+	return buf.Bytes(), nil
+}
+
+// Sign sets the maker's public key and signs the announcement with the current signing version
+func (a *OrderAnnouncement) Sign(cm *CryptoManager) error {
+	a.MakerPubKey = cm.GetPublicKey()
+	a.SignatureVersion = OrderSigningVersion
+
+	message, err := a.SigningBytes(a.SignatureVersion)
+	if err != nil {
+		return err
+	}
+
+	signature, err := cm.SignMessage(message)
+	if err != nil {
+		return fmt.Errorf("failed to sign order announcement: %w", err)
+	}
+	a.Signature = signature
+	return nil
+}
+
+// Verify checks the maker's signature over the announcement's signed fields
+func (a *OrderAnnouncement) Verify() error {
+	if len(a.Signature) == 0 {
+		return fmt.Errorf("order announcement %s is not signed", a.OrderID)
+	}
+
+	makerPubKey, err := ParsePublicKey(a.MakerPubKey)
+	if err != nil {
+		return fmt.Errorf("invalid maker public key: %w", err)
+	}
+
+	message, err := a.SigningBytes(a.SignatureVersion)
+	if err != nil {
+		return err
+	}
+
+	if err := VerifySignature(makerPubKey, message, a.Signature); err != nil {
+		return fmt.Errorf("order announcement %s: %w", a.OrderID, err)
+	}
+	return nil
+}
+
+// rotationSigningBytes is what the previous maker key signs to hand an order to MakerPubKey:
+// the order ID and the new key, so the endorsement cannot be moved to another order or key
+func (a *OrderAnnouncement) rotationSigningBytes() []byte {
+	var buf bytes.Buffer
+	buf.WriteString(orderRotationTag)
+	for _, field := range [][]byte{[]byte(a.OrderID), a.MakerPubKey} {
+		var length [4]byte
+		binary.BigEndian.PutUint32(length[:], uint32(len(field)))
+		buf.Write(length[:])
+		buf.Write(field)
+	}
+	return buf.Bytes()
+}
+
+// EndorseRotation signs the announcement over to its current MakerPubKey with the key it was
+// signed with before, so nodes holding the old announcement accept the re-signed one.
+// Call it after Sign with the new key.
+func (a *OrderAnnouncement) EndorseRotation(previous *CryptoManager) error {
+	signature, err := previous.SignMessage(a.rotationSigningBytes())
+	if err != nil {
+		return fmt.Errorf("failed to endorse key rotation: %w", err)
+	}
+	a.RotatedFromKey = previous.GetPublicKey()
+	a.RotationSig = signature
+	return nil
+}
+
+// VerifyRotation checks that RotatedFromKey handed the order to MakerPubKey
+func (a *OrderAnnouncement) VerifyRotation() error {
+	if len(a.RotatedFromKey) == 0 || len(a.RotationSig) == 0 {
+		return fmt.Errorf("order announcement %s carries no key rotation", a.OrderID)
+	}
+
+	previous, err := ParsePublicKey(a.RotatedFromKey)
+	if err != nil {
+		return fmt.Errorf("invalid rotated-from key: %w", err)
+	}
+	if err := VerifySignature(previous, a.rotationSigningBytes(), a.RotationSig); err != nil {
+		return fmt.Errorf("order announcement %s key rotation: %w", a.OrderID, err)
+	}
+	return nil
+}
@@ -2,6 +2,8 @@ package node
 
 import (
 	"bytes"
+	"crypto/rand"
+	"encoding/hex"
 	"encoding/json"
 	"fmt"
 	"io"
@@ -73,6 +75,7 @@ func (api *APIServer) Start() error {
 	// Order and negotiation endpoints
 	mux.HandleFunc("/orders", api.handleOrders)
 	mux.HandleFunc("/orders/create", api.handleCreateOrder)
+	mux.HandleFunc("/market", api.handleMarket)
 	mux.HandleFunc("/negotiate/request", api.handleNegotiateRequest)
 	mux.HandleFunc("/negotiate/propose", api.handleNegotiatePropose)
 	mux.HandleFunc("/negotiate/proposals", api.handleListProposals)
@@ -85,6 +88,9 @@ func (api *APIServer) Start() error {
 	mux.HandleFunc("/settlement/claim-zec", api.handleClaimZEC)
 	mux.HandleFunc("/settlement/update-status", api.handleUpdateSettlementStatus)
 
+	// Node key endpoints
+	mux.HandleFunc("/node/rotate-key", api.handleRotateKey)
+
 	// Network endpoints
 	mux.HandleFunc("/peers", api.handlePeers)
 	mux.HandleFunc("/status", api.handleStatus)
@@ -143,6 +149,10 @@ type OrderWithDetails struct {
 	MaxPrice   uint64 `json:"max_price"`
 	Timestamp  int64  `json:"timestamp"`  // Unix seconds
 	Expiry     int64  `json:"expiry"`
+
+	LiquidityVerified bool   `json:"liquidity_verified"`      // Maker's commitment opening checked
+	Owned             bool   `json:"owned"`                   // Created by this node
+	DetailsError      string `json:"details_error,omitempty"` // Why received details could not be decrypted
 }
 
 type ListOrdersResponse struct {
@@ -216,10 +226,11 @@ type PeerInfo struct {
 }
 
 type StatusResponse struct {
-	PeerID     string `json:"peer_id"`
-	ListenAddr string `json:"listen_addr"`
-	PeerCount  int    `json:"peer_count"`
-	OrderCount int    `json:"order_count"`
+	PeerID       string `json:"peer_id"`
+	ListenAddr   string `json:"listen_addr"`
+	PeerCount    int    `json:"peer_count"`
+	OrderCount   int    `json:"order_count"`
+	SequenceGaps uint64 `json:"sequence_gaps"`
 }
 
 type ErrorResponse struct {
@@ -272,6 +283,14 @@ type AuthWhoamiResponse struct {
 	ExpiresAt  string `json:"expires_at"`
 }
 
+type RotateKeyRequest struct {
+	SessionID string `json:"session_id"`
+}
+
+type RotateKeyResponse struct {
+	PublicKey string `json:"public_key"` // New node public key (hex, uncompressed)
+}
+
 type ListUsersResponse struct {
 	Users []UserInfo `json:"users"`
 }
@@ -408,7 +427,7 @@ func (api *APIServer) handleAuthLogin(w http.ResponseWriter, r *http.Request) {
 	}
 
 	// Initialize CryptoManager with user's private key (ONE TIME per node)
-	if api.app.cryptoMgr == nil {
+	if api.app.crypto() == nil {
 		cryptoMgr := NewCryptoManager(session.PrivateKey)
 		api.app.SetCryptoManager(cryptoMgr)
 		log.Printf("Auth: Initialized CryptoManager for user: %s", session.Username)
@@ -775,8 +794,14 @@ func (api *APIServer) handleOrders(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
+	sortBy, err := parseOrderSort(r.URL.Query().Get("sort"))
+	if err != nil {
+		api.sendError(w, err.Error(), http.StatusBadRequest)
+		return
+	}
+
 	// Get all orders
-	announcements := api.app.ListOrders()
+	announcements := api.app.ListOrdersBy(sortBy)
 
 	// Enrich with details for UI
 	enrichedOrders := make([]*OrderWithDetails, 0)
@@ -796,11 +821,14 @@ func (api *APIServer) handleOrders(w http.ResponseWriter, r *http.Request) {
 				MaxPrice:   details.MaxPrice,
 				Timestamp:  ann.Timestamp,
 				Expiry:     ann.Expiry,
+
+				LiquidityVerified: api.app.IsLiquidityVerified(ann.OrderID),
+				Owned:             api.app.IsOwnedOrder(ann.OrderID),
 			})
 		} else {
 			// For demo: show announcements even without details (Bob can request details on click)
 			// Use placeholder values for amount/price
-			enrichedOrders = append(enrichedOrders, &OrderWithDetails{
+			order := &OrderWithDetails{
 				OrderID:    string(ann.OrderID),
 				OrderType:  string(ann.OrderType),
 				Stablecoin: string(ann.Stablecoin),
@@ -809,7 +837,13 @@ func (api *APIServer) handleOrders(w http.ResponseWriter, r *http.Request) {
 				MaxPrice:   999999, // Placeholder
 				Timestamp:  ann.Timestamp,
 				Expiry:     ann.Expiry,
-			})
+
+				Owned: api.app.IsOwnedOrder(ann.OrderID),
+			}
+			if err := api.app.OrderDetailsError(ann.OrderID); err != nil {
+				order.DetailsError = err.Error()
+			}
+			enrichedOrders = append(enrichedOrders, order)
 		}
 	}
 
@@ -855,8 +889,9 @@ func (api *APIServer) handleNegotiatePropose(w http.ResponseWriter, r *http.Requ
 		return
 	}
 
-	if req.Price == 0 || req.Amount == 0 {
-		api.sendError(w, "Price and amount must be greater than 0", http.StatusBadRequest)
+	// An amount of 0 proposes for the whole order, as revealed in its details
+	if req.Price == 0 {
+		api.sendError(w, "Price must be greater than 0", http.StatusBadRequest)
 		return
 	}
 
@@ -879,8 +914,16 @@ func (api *APIServer) handleNegotiatePropose(w http.ResponseWriter, r *http.Requ
 		return
 	}
 
+	// Details must be revealed and the maker must have proven their committed liquidity
+	// before a proposal is allowed
+	amount, err := api.app.checkCanPropose(req.OrderID, req.Amount)
+	if err != nil {
+		api.sendError(w, err.Error(), http.StatusPreconditionFailed)
+		return
+	}
+
 	// Propose price with proposer's info
-	api.app.ProposePrice(req.OrderID, req.Price, req.Amount, identity.Username, pubKeyHash)
+	api.app.ProposePrice(req.OrderID, req.Price, amount, identity.Username, pubKeyHash)
 
 	log.Printf("Proposal for order %s created by user: %s (pubkey_hash: %s)", req.OrderID, identity.Username, pubKeyHash)
 
@@ -1170,6 +1213,7 @@ func (api *APIServer) handleClaimZEC(w http.ResponseWriter, r *http.Request) {
 	if proposal, exists := api.app.proposals[ProposalID(req.ProposalID)]; exists {
 		completedStatus := SettlementStatusComplete
 		proposal.SettlementStatus = &completedStatus
+		api.app.persistProposal(proposal)
 	}
 	api.app.proposalsMux.Unlock()
 
@@ -1214,6 +1258,7 @@ func (api *APIServer) handleUpdateSettlementStatus(w http.ResponseWriter, r *htt
 	// Update settlement status
 	newStatus := SettlementStatus(req.SettlementStatus)
 	proposal.SettlementStatus = &newStatus
+	api.app.persistProposal(proposal)
 
 	log.Printf("✅ [ADMIN] Updated proposal %s settlement status to: %s", req.ProposalID, req.SettlementStatus)
 
@@ -1224,6 +1269,21 @@ func (api *APIServer) handleUpdateSettlementStatus(w http.ResponseWriter, r *htt
 	})
 }
 
+func (api *APIServer) handleMarket(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	coin := StablecoinType(r.URL.Query().Get("stablecoin"))
+	if coin == "" {
+		http.Error(w, "Missing stablecoin parameter", http.StatusBadRequest)
+		return
+	}
+
+	api.sendJSON(w, api.app.MarketSnapshot(coin))
+}
+
 func (api *APIServer) handlePeers(w http.ResponseWriter, r *http.Request) {
 	if r.Method != http.MethodGet {
 		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
@@ -1244,6 +1304,40 @@ func (api *APIServer) handlePeers(w http.ResponseWriter, r *http.Request) {
 	api.sendJSON(w, PeersResponse{Peers: peerInfos})
 }
 
+func (api *APIServer) handleRotateKey(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	var req RotateKeyRequest
+	if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		api.sendError(w, "Invalid request body", http.StatusBadRequest)
+		return
+	}
+
+	// Authenticate user
+	authMgr := api.app.GetAuthManager()
+	identity, _, err := authMgr.RequireAuth(req.SessionID)
+	if err != nil {
+		api.sendError(w, "Authentication required: "+err.Error(), http.StatusUnauthorized)
+		return
+	}
+
+	seed := make([]byte, minSeedSize)
+	if _, err := rand.Read(seed); err != nil {
+		api.sendError(w, "Failed to generate key seed", http.StatusInternalServerError)
+		return
+	}
+	if err := api.app.RotateKeys(seed); err != nil {
+		api.sendError(w, "Key rotation failed: "+err.Error(), http.StatusInternalServerError)
+		return
+	}
+
+	log.Printf("Node key rotated by user: %s", identity.Username)
+	api.sendJSON(w, RotateKeyResponse{PublicKey: hex.EncodeToString(api.app.crypto().GetPublicKey())})
+}
+
 func (api *APIServer) handleStatus(w http.ResponseWriter, r *http.Request) {
 	if r.Method != http.MethodGet {
 		http.Error(w, "Method not allowed", http.StatusMethodNotAllowed)
@@ -1255,10 +1349,11 @@ func (api *APIServer) handleStatus(w http.ResponseWriter, r *http.Request) {
 	orders := api.app.ListOrders()
 
 	response := StatusResponse{
-		PeerID:     status.PeerID,
-		ListenAddr: status.ListenAddr,
-		PeerCount:  status.PeerCount,
-		OrderCount: len(orders),
+		PeerID:       status.PeerID,
+		ListenAddr:   status.ListenAddr,
+		PeerCount:    status.PeerCount,
+		OrderCount:   len(orders),
+		SequenceGaps: status.SequenceGaps,
 	}
 
 	api.sendJSON(w, response)
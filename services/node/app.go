@@ -4,10 +4,15 @@ import (
 	"bytes"
 	"crypto/ecdsa"
 	"crypto/elliptic"
+	"crypto/rand"
 	"encoding/json"
+	"errors"
 	"fmt"
 	"log"
 	"math/big"
+	"os"
+	"path/filepath"
+	"sort"
 	"sync"
 	"time"
 )
@@ -15,28 +20,131 @@ import (
 // DataDir is the default data directory for storing user identities and wallets
 const DataDir = "/root/.blacktrace"
 
+// commitmentSeedFile holds the node's commitment seed under the app's data directory
+const commitmentSeedFile = "commitment_seed"
+
+// commitmentSeedSize is the length of the commitment seed
+const commitmentSeedSize = 32
+
 // BlackTraceApp is the main application
 type BlackTraceApp struct {
 	network      *NetworkManager
 	authMgr      *AuthManager
 	walletMgr    *WalletManager // Manages user Zcash wallets
-	cryptoMgr    *CryptoManager // Initialized when first user logs in (one node = one user)
+	cryptoMgr    *CryptoManager // Initialized when first user logs in (one node = one user); read through crypto()
+	cryptoMux    sync.RWMutex   // Guards cryptoMgr, which key rotation swaps while handlers use it
+	dataDir      string         // Holds node secrets that outlive logins: rotated key seed, commitment seed (DataDir; empty keeps them in memory)
 	settlementMgr *SettlementManager // Phase 3: NATS-based settlement coordination
 	orders       map[OrderID]*OrderAnnouncement
 	ordersMux    sync.RWMutex
 
+	// Orders and proposals are persisted through store so they survive restarts
+	store Storage
+
+	// Secondary index from hex proof commitment to order, guarded by ordersMux
+	ordersByCommitment map[string]OrderID
+
+	// Secondary index from content-derived order ID to the ID the order is held under, guarded by ordersMux
+	ordersByContent map[OrderID]OrderID
+
+	// Orders this node created (ours to cancel and re-broadcast), persisted with their details
+	ownedOrders    map[OrderID]bool
+	ownedOrdersMux sync.RWMutex
+
+	// Peer each order announcement was received from (used to route detail requests)
+	orderSources    map[OrderID]PeerID
+	orderSourcesMux sync.RWMutex
+
 	// Order details cache (for own orders and received details) - DEMO/UI only
 	orderDetails    map[OrderID]*OrderDetails
 	orderDetailsMux sync.RWMutex
 
+	// Last decryption failure per order whose details could not be opened, guarded by orderDetailsMux
+	orderDetailsErrors map[OrderID]error
+
+	// Whether each of our orders has reached peers yet
+	orderBroadcasts    map[OrderID]OrderBroadcastState
+	orderBroadcastsMux sync.RWMutex
+
 	// Proposal tracking - maps ProposalID to Proposal
 	proposals    map[ProposalID]*Proposal
 	proposalsMux sync.RWMutex
 
+	// The same proposals grouped by order, guarded by proposalsMux, so work on one
+	// negotiation never walks every other order's proposals
+	proposalsByOrder map[OrderID]map[ProposalID]*Proposal
+
+	// Negotiation histories, hashed into the settlement terms both parties sign
+	transcripts    map[transcriptKey]*negotiationTranscript
+	transcriptsMux sync.Mutex
+
+	// Co-signed settlements received over the wire, stored only after full verification
+	signedSettlements    map[ProposalID]*SignedSettlement
+	signedSettlementsMux sync.RWMutex
+
+	// Node secret the commitment salts of own orders are derived from, so an opening is
+	// recomputed when a taker asks for it instead of being stored. Created once and saved to
+	// the data directory apart from the signing key, so earlier commitments still open after
+	// key rotations and restarts.
+	commitmentSeed    Secret[[]byte]
+	commitmentSeedMux sync.Mutex
+
+	// Orders whose maker liquidity commitment has been verified (taker side)
+	liquidityVerified    map[OrderID]bool
+	liquidityVerifiedMux sync.RWMutex
+
+	// Outstanding liquidity challenge nonces, one per order (taker side)
+	liquidityChallenges    map[OrderID][]byte
+	liquidityChallengesMux sync.Mutex
+
 	// Peer public key cache for signature verification
 	peerKeys    map[PeerID][]byte // Maps peer ID to their public key
 	peerKeysMux sync.RWMutex
 
+	// Peers we dialed, persisted so they are redialed after a restart
+	peerBook    map[PeerID]*PeerRecord
+	peerBookMux sync.RWMutex
+
+	// Format we prefer for direct messages, and the format agreed with each connected peer
+	wireFormat         WireFormat
+	peerWireFormats    map[PeerID]WireFormat
+	peerWireFormatsMux sync.RWMutex
+
+	// Stablecoins this node wants order announcements for (empty = all)
+	interests []StablecoinType
+
+	// How often own live orders are re-announced (zero = default)
+	rebroadcastInterval time.Duration
+
+	// Per-peer round-trip times measured with ping/pong
+	rtt *rttTracker
+
+	// Per-negotiation session keys from an ephemeral ECDH exchange, keyed by peer and order
+	negotiationSessions    map[proposalSession]*negotiationSession
+	negotiationSessionsMux sync.RWMutex
+
+	// Takers we revealed own order details to and are waiting on for a first proposal
+	detailReveals    map[proposalSession]*detailsReveal
+	detailRevealsMux sync.RWMutex
+
+	// Negotiation session deadlines, keyed by peer and order, and the cap on one extension
+	sessionDeadlines    map[proposalSession]time.Time
+	maxExtension        time.Duration // Zero means DefaultMaxDeadlineExtension
+	sessionDeadlinesMux sync.Mutex
+
+	// Per-peer, per-order proposal rate limit
+	proposalLimit *proposalLimiter
+
+	// Recent announcement signature checks (nil = verify every time)
+	verifyCache *verifyCache
+
+	// Workers handling received messages off the event loop (nil = handle inline)
+	messages *messagePool
+
+	// Optional oracle check on proposed and accepted prices (nil = no check)
+	priceCheck    *PriceCheck
+	priceCheckMux sync.RWMutex
+
 	// Channels for inter-component communication
 	appCommandCh chan AppCommand
 	shutdownCh   chan struct{}
@@ -59,6 +167,9 @@ type AppCommand struct {
 	ProposerUsername   string // Username of proposer (for proposals)
 	ProposerPubKeyHash string // Proposer's pubkey hash for HTLC (for proposals)
 
+	// Order listing
+	SortBy OrderSort
+
 	// Response channel for synchronous operations
 	ResponseCh chan interface{}
 }
@@ -81,17 +192,38 @@ func NewBlackTraceApp(port int) (*BlackTraceApp, error) {
 	}
 
 	app := &BlackTraceApp{
-		network:       nm,
-		authMgr:       authMgr,
-		walletMgr:     walletMgr,
-		cryptoMgr:     nil, // Initialized on first user login
-		settlementMgr: nil, // Initialized below after app is created
-		orders:        make(map[OrderID]*OrderAnnouncement),
-		orderDetails:  make(map[OrderID]*OrderDetails),
-		proposals:     make(map[ProposalID]*Proposal),
-		peerKeys:      make(map[PeerID][]byte),
-		appCommandCh:  make(chan AppCommand, 100),
-		shutdownCh:    make(chan struct{}),
+		network:             nm,
+		authMgr:             authMgr,
+		walletMgr:           walletMgr,
+		cryptoMgr:           nil, // Initialized on first user login
+		dataDir:             DataDir,
+		settlementMgr:       nil, // Initialized below after app is created
+		orders:              make(map[OrderID]*OrderAnnouncement),
+		ordersByCommitment:  make(map[string]OrderID),
+		ordersByContent:     make(map[OrderID]OrderID),
+		ownedOrders:         make(map[OrderID]bool),
+		orderSources:        make(map[OrderID]PeerID),
+		orderDetails:        make(map[OrderID]*OrderDetails),
+		orderDetailsErrors:  make(map[OrderID]error),
+		orderBroadcasts:     make(map[OrderID]OrderBroadcastState),
+		proposals:           make(map[ProposalID]*Proposal),
+		proposalsByOrder:    make(map[OrderID]map[ProposalID]*Proposal),
+		signedSettlements:   make(map[ProposalID]*SignedSettlement),
+		transcripts:         make(map[transcriptKey]*negotiationTranscript),
+		liquidityVerified:   make(map[OrderID]bool),
+		liquidityChallenges: make(map[OrderID][]byte),
+		peerKeys:            make(map[PeerID][]byte),
+		peerBook:            make(map[PeerID]*PeerRecord),
+		wireFormat:          WireFormatJSON,
+		peerWireFormats:     make(map[PeerID]WireFormat),
+		rtt:                 newRTTTracker(),
+		proposalLimit:       newProposalLimiter(),
+		verifyCache:         newVerifyCache(DefaultVerifyCacheSize),
+		detailReveals:       make(map[proposalSession]*detailsReveal),
+		negotiationSessions: make(map[proposalSession]*negotiationSession),
+		sessionDeadlines:    make(map[proposalSession]time.Time),
+		appCommandCh:        make(chan AppCommand, 100),
+		shutdownCh:          make(chan struct{}),
 	}
 
 	// Initialize settlement manager after app is created (needs app reference for subscriptions)
@@ -99,29 +231,94 @@ func NewBlackTraceApp(port int) (*BlackTraceApp, error) {
 	if err != nil {
 		log.Printf("Warning: Failed to initialize settlement manager: %v", err)
 		// Continue without settlement service
-		settlementMgr = &SettlementManager{enabled: false, app: app}
+		settlementMgr = &SettlementManager{enabled: false, nonCustodial: settlementNonCustodial(), app: app}
 	}
 	app.settlementMgr = settlementMgr
 
+	// A rotated node key outlives logins: restore it before the first login would install the session's key
+	if rotated, err := app.LoadRotatedKey(); err != nil {
+		log.Printf("Warning: Failed to restore rotated node key: %v", err)
+	} else if rotated {
+		log.Printf("App: Restored rotated node key from %s", keySeedFile)
+	}
+
+	// Restore persisted orders and proposals; fall back to memory if the state directory is unusable
+	store, err := NewFileStorage(filepath.Join(DataDir, "state"))
+	if err != nil {
+		log.Printf("Warning: Failed to open state storage, keeping state in memory: %v", err)
+		app.store = NewMemStorage()
+	} else {
+		app.store = store
+	}
+	if err := app.loadState(); err != nil {
+		log.Printf("Warning: Failed to restore state: %v", err)
+	}
+
 	return app, nil
 }
 
 // SetCryptoManager sets the crypto manager (called after user login)
 func (app *BlackTraceApp) SetCryptoManager(cm *CryptoManager) {
+	app.cryptoMux.Lock()
 	app.cryptoMgr = cm
+	app.cryptoMux.Unlock()
 	log.Printf("App: CryptoManager initialized for message signing and encryption")
 }
 
+// crypto returns the node's current crypto manager (nil before login)
+func (app *BlackTraceApp) crypto() *CryptoManager {
+	app.cryptoMux.RLock()
+	defer app.cryptoMux.RUnlock()
+	return app.cryptoMgr
+}
+
+// SetInterests restricts which order announcements this node keeps, and which peers send to
+// it directly. Must be called before Run; an empty list keeps the default of receiving everything.
+func (app *BlackTraceApp) SetInterests(coins []StablecoinType) {
+	app.interests = coins
+}
+
+// interestedIn reports whether this node wants orders for a coin (all coins if it set no interests)
+func (app *BlackTraceApp) interestedIn(coin StablecoinType) bool {
+	if len(app.interests) == 0 {
+		return true
+	}
+	for _, c := range app.interests {
+		if c == coin {
+			return true
+		}
+	}
+	return false
+}
+
 // Run starts the application (non-blocking)
 func (app *BlackTraceApp) Run() {
 	// Start network manager
 	app.network.Run()
 
+	// Received messages are handled on per-peer workers; lifecycle events stay on the event loop
+	app.messages = newMessagePool(messageWorkers, messageQueueDepth, app.handleMessage)
+
 	// Start event processor
 	go app.processEvents()
 
 	// Start command processor
 	go app.processCommands()
+
+	// Periodically measure peer latency
+	go app.pingLoop()
+
+	// Sweep expired orders and takers that never proposed
+	go app.orderExpiryLoop()
+
+	// Keep own live orders discoverable by late-joining peers
+	go app.orderRebroadcastLoop()
+
+	// Announce own orders a previous run stored but never got to peers
+	go app.retryPendingBroadcasts()
+
+	// Rejoin the network through peers a previous run dialed
+	go app.dialKnownPeers()
 }
 
 // processEvents handles network events
@@ -130,6 +327,9 @@ func (app *BlackTraceApp) processEvents() {
 	for {
 		select {
 		case <-app.shutdownCh:
+			if app.messages != nil {
+				app.messages.stop()
+			}
 			return
 		case event := <-app.network.EventChan():
 			app.handleNetworkEvent(event)
@@ -154,12 +354,32 @@ func (app *BlackTraceApp) handleNetworkEvent(event NetworkEvent) {
 	switch event.Type {
 	case "peer_connected":
 		log.Printf("App: Peer connected: %s", event.From)
+		app.touchKnownPeer(event.From)
+		app.sendWireFormat(event.From)
+		app.sendInterests(event.From)
+		app.pingPeer(event.From)
+
+	case "peer_dialed":
+		app.rememberPeer(event.From, string(event.Data))
 
 	case "peer_disconnected":
 		log.Printf("App: Peer disconnected: %s", event.From)
+		app.touchKnownPeer(event.From)
+		app.forgetWireFormat(event.From)
+		app.rtt.forget(event.From)
+
+	case "send_failed":
+		app.handleSendFailure(event.From, event.Data)
+
+	case "sequence_gap":
+		log.Printf("App: Frame sequence gap detected from %s", event.From)
 
 	case "message_received":
-		app.handleMessage(event.From, event.Data)
+		if app.messages != nil {
+			app.messages.submit(event.From, event.Data)
+		} else {
+			app.handleMessage(event.From, event.Data)
+		}
 	}
 }
 
@@ -199,11 +419,128 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 			return
 		}
 
+		// Gossip, used until peers' interests are known, reaches every node;
+		// orders for coins we did not ask for are dropped here
+		if !app.interestedIn(announcement.Stablecoin) {
+			return
+		}
+
+		if err := app.verifyOrderAnnouncement(&announcement); err != nil {
+			log.Printf("App: Dropping order announcement %s from %s: %v", announcement.OrderID, from, err)
+			return
+		}
+
+		// A periodic rebroadcast of an order we already hold only refreshes its source
+		if app.isRepeatAnnouncement(&announcement) {
+			app.orderSourcesMux.Lock()
+			app.orderSources[announcement.OrderID] = from
+			app.orderSourcesMux.Unlock()
+			return
+		}
+
 		log.Printf("App: Received signed order announcement: %s from %s", announcement.OrderID, from)
 
 		app.ordersMux.Lock()
-		app.orders[announcement.OrderID] = &announcement
+		app.storeOrderLocked(&announcement)
 		app.ordersMux.Unlock()
+		app.persistOrder(&announcement)
+
+		app.orderSourcesMux.Lock()
+		app.orderSources[announcement.OrderID] = from
+		app.orderSourcesMux.Unlock()
+
+	case "negotiation_key":
+		var keyMsg NegotiationKeyMessage
+		if err := json.Unmarshal(payload, &keyMsg); err != nil {
+			log.Printf("Failed to unmarshal negotiation key: %v", err)
+			return
+		}
+
+		if err := app.handleNegotiationKey(from, &keyMsg); err != nil {
+			log.Printf("App: Negotiation key exchange with %s for %s failed: %v", from, keyMsg.OrderID, err)
+		}
+
+	case "negotiation_message":
+		var negMsg NegotiationMessage
+		if err := json.Unmarshal(payload, &negMsg); err != nil {
+			log.Printf("Failed to unmarshal negotiation message: %v", err)
+			return
+		}
+
+		inner, err := app.openNegotiationMessage(from, &negMsg)
+		if err != nil {
+			log.Printf("App: Dropping negotiation message from %s: %v", from, err)
+			return
+		}
+		app.handleMessagePayload(from, inner.Type, inner.Payload, signerPubKey)
+
+	case "extend_deadline":
+		var extend ExtendDeadlineMessage
+		if err := json.Unmarshal(payload, &extend); err != nil {
+			log.Printf("Failed to unmarshal deadline extension: %v", err)
+			return
+		}
+
+		app.handleExtendDeadline(from, &extend, time.Now())
+
+	case "negotiation_cancelled":
+		var cancelled NegotiationCancelledMessage
+		if err := json.Unmarshal(payload, &cancelled); err != nil {
+			log.Printf("Failed to unmarshal negotiation cancellation: %v", err)
+			return
+		}
+
+		app.handleNegotiationCancelled(from, &cancelled)
+
+	case "deadline_extended":
+		var reply DeadlineExtendedMessage
+		if err := json.Unmarshal(payload, &reply); err != nil {
+			log.Printf("Failed to unmarshal deadline extension reply: %v", err)
+			return
+		}
+
+		app.handleDeadlineExtended(from, &reply)
+
+	case "wire_format":
+		var wireFormat WireFormatMessage
+		if err := json.Unmarshal(payload, &wireFormat); err != nil {
+			log.Printf("Failed to unmarshal wire format: %v", err)
+			return
+		}
+
+		app.handleWireFormat(from, &wireFormat)
+
+	case "interests":
+		var interests InterestsMessage
+		if err := json.Unmarshal(payload, &interests); err != nil {
+			log.Printf("Failed to unmarshal interests: %v", err)
+			return
+		}
+
+		app.network.SetPeerInterests(from, interests.Stablecoins)
+
+	case "ping":
+		var ping PingMessage
+		if err := json.Unmarshal(payload, &ping); err != nil {
+			log.Printf("Failed to unmarshal ping: %v", err)
+			return
+		}
+
+		if err := app.sendSignedMessage(from, "pong", ping); err != nil {
+			log.Printf("Failed to send pong to %s: %v", from, err)
+		}
+
+	case "pong":
+		var pong PingMessage
+		if err := json.Unmarshal(payload, &pong); err != nil {
+			log.Printf("Failed to unmarshal pong: %v", err)
+			return
+		}
+
+		if rtt, ok := app.rtt.recordPong(from, pong.Nonce, time.Now()); ok {
+			log.Printf("App: RTT to %s: %v", from, rtt)
+			app.network.RecordPeerRTT(from, rtt)
+		}
 
 	case "order_request":
 		var orderID OrderID
@@ -215,12 +552,7 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 		log.Printf("App: Received order request: %s from %s", orderID, from)
 
 		// Send ENCRYPTED order details back (Phase 2B - ECIES encryption)
-		if err := app.sendEncryptedOrderDetails(from, orderID); err != nil {
-			log.Printf("Failed to send encrypted order details: %v", err)
-			// Fallback to unencrypted if encryption fails
-			log.Printf("Falling back to unencrypted order details")
-			app.sendOrderDetails(from, orderID)
-		}
+		app.revealOrderDetails(from, orderID, time.Now())
 
 	case "order_details":
 		var details OrderDetails
@@ -229,6 +561,12 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 			return
 		}
 
+		if err := app.verifyDetailsSender(from, signerPubKey, details.OrderID); err != nil {
+			log.Printf("App: Rejecting order details from %s: %v", from, err)
+			app.recordOrderDetailsError(details.OrderID, err)
+			return
+		}
+
 		log.Printf("App: Received order details: %s (Amount: %d, Min: %d, Max: %d)",
 			details.OrderID, details.Amount, details.MinPrice, details.MaxPrice)
 
@@ -237,19 +575,32 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 		app.orderDetails[details.OrderID] = &details
 		app.orderDetailsMux.Unlock()
 
+		// Ask the maker to prove the committed liquidity before we can propose
+		app.requestLiquidityOpening(from, details.OrderID)
+
 	case "proposal":
 		var proposal Proposal
 		if err := json.Unmarshal(payload, &proposal); err != nil {
 			log.Printf("Failed to unmarshal proposal: %v", err)
 			return
 		}
+		if !app.admitProposal(from, proposal.OrderID) {
+			return
+		}
+		if err := proposal.VerifyReceived(from, signerPubKey); err != nil {
+			log.Printf("App: Rejecting proposal from %s: %v", from, err)
+			return
+		}
 
 		log.Printf("App: Received signed proposal: %s from %s", proposal.ProposalID, from)
 
 		// Store the proposal
-		app.proposalsMux.Lock()
-		app.proposals[proposal.ProposalID] = &proposal
-		app.proposalsMux.Unlock()
+		if err := app.storeReceivedProposal(&proposal); err != nil {
+			log.Printf("App: Rejecting proposal from %s: %v", from, err)
+			return
+		}
+		app.proposalReceived(from, proposal.OrderID)
+		app.recordProposal(&proposal)
 
 	case "encrypted_order_details":
 		var encMsg EncryptedOrderDetailsMessage
@@ -258,30 +609,17 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 			return
 		}
 
-		// Decrypt if we have crypto manager
-		if app.cryptoMgr == nil {
-			log.Printf("Cannot decrypt order details: CryptoManager not initialized")
-			return
-		}
-
-		// Deserialize ECIES message
-		eciesMsg, err := DeserializeECIESMessage(encMsg.EncryptedPayload)
-		if err != nil {
-			log.Printf("Failed to deserialize ECIES message: %v", err)
-			return
-		}
-
-		// Decrypt
-		decrypted, err := app.cryptoMgr.ECIESDecrypt(eciesMsg)
+		details, err := app.decryptOrderDetails(&encMsg)
 		if err != nil {
-			log.Printf("Failed to decrypt order details: %v", err)
+			log.Printf("App: Failed to open order details for %s from %s: %v", encMsg.OrderID, from, err)
+			if errors.Is(err, ErrDecryption) {
+				app.recordOrderDetailsError(encMsg.OrderID, err)
+			}
 			return
 		}
-
-		// Parse decrypted details
-		var details OrderDetails
-		if err := json.Unmarshal(decrypted, &details); err != nil {
-			log.Printf("Failed to unmarshal decrypted details: %v", err)
+		if err := app.verifyDetailsSender(from, signerPubKey, details.OrderID); err != nil {
+			log.Printf("App: Rejecting order details from %s: %v", from, err)
+			app.recordOrderDetailsError(details.OrderID, err)
 			return
 		}
 
@@ -290,18 +628,51 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 
 		// Store the decrypted order details
 		app.orderDetailsMux.Lock()
-		app.orderDetails[details.OrderID] = &details
+		app.orderDetails[details.OrderID] = details
+		delete(app.orderDetailsErrors, details.OrderID)
 		app.orderDetailsMux.Unlock()
 
+		// Ask the maker to prove the committed liquidity before we can propose
+		app.requestLiquidityOpening(from, details.OrderID)
+
+	case "liquidity_request":
+		var challenge LiquidityChallengeMessage
+		if err := json.Unmarshal(payload, &challenge); err != nil {
+			log.Printf("Failed to unmarshal liquidity request: %v", err)
+			return
+		}
+
+		log.Printf("App: Received liquidity request: %s from %s", challenge.OrderID, from)
+		app.sendLiquidityOpening(from, challenge.OrderID, challenge.Nonce)
+
+	case "liquidity_opening":
+		var msg LiquidityOpeningMessage
+		if err := json.Unmarshal(payload, &msg); err != nil {
+			log.Printf("Failed to unmarshal liquidity opening: %v", err)
+			return
+		}
+
+		if err := app.verifyLiquidityOpening(msg.OrderID, &msg.Opening, msg.Response); err != nil {
+			log.Printf("App: Liquidity verification FAILED for order %s from %s: %v", msg.OrderID, from, err)
+			return
+		}
+
+		log.Printf("App: Liquidity verified for order %s (maker: %s)", msg.OrderID, from)
+
 	case "encrypted_proposal":
 		var encMsg EncryptedProposalMessage
 		if err := json.Unmarshal(payload, &encMsg); err != nil {
 			log.Printf("Failed to unmarshal encrypted proposal: %v", err)
 			return
 		}
+		// Throttle before paying for decryption
+		if !app.admitProposal(from, encMsg.OrderID) {
+			return
+		}
 
 		// Decrypt if we have crypto manager
-		if app.cryptoMgr == nil {
+		cryptoMgr := app.crypto()
+		if cryptoMgr == nil {
 			log.Printf("Cannot decrypt proposal: CryptoManager not initialized")
 			return
 		}
@@ -314,7 +685,7 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 		}
 
 		// Decrypt
-		decrypted, err := app.cryptoMgr.ECIESDecrypt(eciesMsg)
+		decrypted, err := cryptoMgr.ECIESDecrypt(eciesMsg)
 		if err != nil {
 			log.Printf("Failed to decrypt proposal: %v", err)
 			return
@@ -326,14 +697,21 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 			log.Printf("Failed to unmarshal decrypted proposal: %v", err)
 			return
 		}
+		if err := proposal.VerifyReceived(from, signerPubKey); err != nil {
+			log.Printf("App: Rejecting proposal from %s: %v", from, err)
+			return
+		}
 
 		log.Printf("App: Decrypted proposal %s from %s: Price=$%d, Amount=%d (frontrunning prevented)",
 			proposal.ProposalID, from, proposal.Price, proposal.Amount)
 
 		// Store the proposal
-		app.proposalsMux.Lock()
-		app.proposals[proposal.ProposalID] = &proposal
-		app.proposalsMux.Unlock()
+		if err := app.storeReceivedProposal(&proposal); err != nil {
+			log.Printf("App: Rejecting proposal from %s: %v", from, err)
+			return
+		}
+		app.proposalReceived(from, proposal.OrderID)
+		app.recordProposal(&proposal)
 
 	case "encrypted_acceptance":
 		var encMsg EncryptedAcceptanceMessage
@@ -343,7 +721,8 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 		}
 
 		// Decrypt if we have crypto manager
-		if app.cryptoMgr == nil {
+		cryptoMgr := app.crypto()
+		if cryptoMgr == nil {
 			log.Printf("Cannot decrypt acceptance: CryptoManager not initialized")
 			return
 		}
@@ -356,7 +735,7 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 		}
 
 		// Decrypt
-		decrypted, err := app.cryptoMgr.ECIESDecrypt(eciesMsg)
+		decrypted, err := cryptoMgr.ECIESDecrypt(eciesMsg)
 		if err != nil {
 			log.Printf("Failed to decrypt acceptance: %v", err)
 			return
@@ -380,13 +759,49 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 		}
 		proposalID := ProposalID(proposalIDStr)
 
+		// Countersigned terms: verify both signatures against our own proposal before agreeing
+		var agreed struct {
+			Settlement *SignedSettlement `json:"settlement"`
+		}
+		if err := json.Unmarshal(decrypted, &agreed); err != nil {
+			log.Printf("Failed to unmarshal settlement in acceptance: %v", err)
+			return
+		}
+		if agreed.Settlement != nil {
+			if agreed.Settlement.Terms.ProposalID != proposalID {
+				log.Printf("App: Rejecting acceptance for %s: settlement is for %s", proposalID, agreed.Settlement.Terms.ProposalID)
+				return
+			}
+			if err := app.applyAgreedSettlement(agreed.Settlement); err != nil {
+				log.Printf("App: Rejecting settlement for proposal %s: %v", proposalID, err)
+				return
+			}
+			log.Printf("App: Terms agreed for proposal %s (both signatures verified)", proposalID)
+			return
+		}
+
 		app.proposalsMux.Lock()
 		if proposal, exists := app.proposals[proposalID]; exists {
 			proposal.Status = ProposalStatusAccepted
+			app.persistProposal(proposal)
+			app.recordProposalState(proposal, ProposalStatusAccepted)
 			log.Printf("App: Updated proposal %s status to Accepted", proposalID)
 		}
 		app.proposalsMux.Unlock()
 
+	case "signed_settlement":
+		var settlement SignedSettlement
+		if err := json.Unmarshal(payload, &settlement); err != nil {
+			log.Printf("Failed to unmarshal signed settlement: %v", err)
+			return
+		}
+
+		if err := app.storeSignedSettlement(&settlement); err != nil {
+			log.Printf("App: Rejecting signed settlement for %s from %s: %v", settlement.Terms.ProposalID, from, err)
+			return
+		}
+		log.Printf("App: Stored signed settlement for proposal %s (both signatures verified)", settlement.Terms.ProposalID)
+
 	case "rejection":
 		var rejection map[string]interface{}
 		if err := json.Unmarshal(payload, &rejection); err != nil {
@@ -407,6 +822,8 @@ func (app *BlackTraceApp) handleMessagePayload(from PeerID, msgType string, payl
 		app.proposalsMux.Lock()
 		if proposal, exists := app.proposals[proposalID]; exists {
 			proposal.Status = ProposalStatusRejected
+			app.persistProposal(proposal)
+			app.recordProposalState(proposal, ProposalStatusRejected)
 			log.Printf("App: Updated proposal %s status to Rejected", proposalID)
 		}
 		app.proposalsMux.Unlock()
@@ -450,6 +867,7 @@ func (app *BlackTraceApp) handleAppCommand(cmd AppCommand) {
 			orders = append(orders, order)
 		}
 		app.ordersMux.RUnlock()
+		app.sortOrders(orders, cmd.SortBy)
 
 		if cmd.ResponseCh != nil {
 			cmd.ResponseCh <- orders
@@ -482,6 +900,9 @@ func (app *BlackTraceApp) createOrder(amount uint64, stablecoin StablecoinType,
 	app.orderDetails[orderID] = details
 	app.orderDetailsMux.Unlock()
 
+	// Commit to the order amount so takers can verify our liquidity later
+	commitment := app.commitToOrder(orderID, amount)
+
 	// Prepare announcement
 	announcement := &OrderAnnouncement{
 		OrderID:          orderID,
@@ -489,7 +910,7 @@ func (app *BlackTraceApp) createOrder(amount uint64, stablecoin StablecoinType,
 		Stablecoin:       stablecoin,
 		MakerID:          app.GetPeerID(), // Include maker ID for encrypted proposals
 		EncryptedDetails: []byte{},        // Will be populated if taker is specified
-		ProofCommitment:  []byte{},        // Would call Rust crypto here
+		ProofCommitment:  commitment,      // Blake2b commitment to the amount
 		Timestamp:        time.Now().Unix(),
 		Expiry:           time.Now().Add(1 * time.Hour).Unix(),
 	}
@@ -524,39 +945,286 @@ func (app *BlackTraceApp) createOrder(amount uint64, stablecoin StablecoinType,
 		}
 	}
 
+	// Maker signature over the announcement's versioned signed fields
+	if cryptoMgr := app.crypto(); cryptoMgr != nil {
+		if err := announcement.Sign(cryptoMgr); err != nil {
+			log.Printf("Warning: Failed to sign order announcement: %v", err)
+		}
+	}
+
+	// The order is pending until peers have it; the marker survives a restart mid-broadcast
 	app.ordersMux.Lock()
-	app.orders[orderID] = announcement
+	app.storeOrderLocked(announcement)
 	app.ordersMux.Unlock()
-
-	// Broadcast SIGNED announcement
-	if err := app.broadcastSignedMessage("order_announcement", announcement); err != nil {
-		log.Printf("Failed to broadcast order announcement: %v", err)
-		return orderID
-	}
+	app.persistOrder(announcement)
+	app.markOwnedOrder(details)
+	app.markBroadcastPending(orderID)
 
 	if takerUsername != "" {
-		log.Printf("App: Created and broadcast order %s encrypted for taker: %s", orderID, takerUsername)
+		log.Printf("App: Created order %s encrypted for taker: %s", orderID, takerUsername)
 	} else {
-		log.Printf("App: Created and broadcast signed order: %s", orderID)
+		log.Printf("App: Created signed order: %s", orderID)
 	}
 
+	// Broadcast SIGNED announcement to peers interested in this stablecoin, with retries
+	go app.announceOrder(orderID, orderBroadcastAttempts, orderBroadcastBackoff)
+
 	return orderID
 }
 
+// nodeCommitmentSeed returns the secret commitment salts are derived from, loading it from the
+// data directory or creating it on first use. A new seed is taken from the current node key when
+// there is one, so commitments made before the seed was saved on its own still open.
+func (app *BlackTraceApp) nodeCommitmentSeed() ([]byte, error) {
+	app.commitmentSeedMux.Lock()
+	defer app.commitmentSeedMux.Unlock()
+
+	if len(app.commitmentSeed.Expose()) > 0 {
+		return app.commitmentSeed.Expose(), nil
+	}
+
+	path := filepath.Join(app.dataDir, commitmentSeedFile)
+	if app.dataDir != "" {
+		seed, err := os.ReadFile(path)
+		if err == nil && len(seed) == commitmentSeedSize {
+			app.commitmentSeed = NewSecret(seed)
+			return seed, nil
+		}
+		if err == nil {
+			return nil, fmt.Errorf("commitment seed %s is %d bytes, expected %d", path, len(seed), commitmentSeedSize)
+		}
+		if !os.IsNotExist(err) {
+			return nil, fmt.Errorf("failed to read commitment seed: %w", err)
+		}
+	}
+
+	var seed []byte
+	if cryptoMgr := app.crypto(); cryptoMgr != nil {
+		seed = cryptoMgr.CommitmentSeed()
+	} else {
+		seed = make([]byte, commitmentSeedSize)
+		if _, err := rand.Read(seed); err != nil {
+			return nil, fmt.Errorf("failed to generate commitment seed: %w", err)
+		}
+	}
+	if app.dataDir != "" {
+		if err := writeFileAtomic(path, seed, 0600); err != nil {
+			return nil, fmt.Errorf("failed to save commitment seed: %w", err)
+		}
+	}
+	app.commitmentSeed = NewSecret(seed)
+	return seed, nil
+}
+
+// commitToOrder commits to an order's amount under the salt derived for it, returning the
+// commitment (empty if no node key is available). Nothing is stored: the opening is
+// recomputed by CommitmentOpening.
+func (app *BlackTraceApp) commitToOrder(orderID OrderID, amount uint64) []byte {
+	seed, err := app.nodeCommitmentSeed()
+	if err != nil {
+		log.Printf("Warning: Failed to generate liquidity commitment: %v", err)
+		return []byte{}
+	}
+
+	commitment, _ := DeriveCommitment(seed, orderID, amount)
+	return commitment
+}
+
+// CommitmentOpening recomputes the opening for one of our live orders from its details and the
+// derived salt. Cancelled and expired orders have no opening.
+func (app *BlackTraceApp) CommitmentOpening(orderID OrderID) (*CommitmentOpening, bool) {
+	app.ordersMux.RLock()
+	order, exists := app.orders[orderID]
+	live := exists && (order.Expiry == 0 || time.Now().Unix() < order.Expiry)
+	app.ordersMux.RUnlock()
+	if !live || !app.IsOwnedOrder(orderID) {
+		return nil, false
+	}
+
+	app.orderDetailsMux.RLock()
+	details, ok := app.orderDetails[orderID]
+	var amount uint64
+	if ok {
+		amount = details.Amount
+	}
+	app.orderDetailsMux.RUnlock()
+	if !ok {
+		return nil, false
+	}
+
+	seed, err := app.nodeCommitmentSeed()
+	if err != nil {
+		return nil, false
+	}
+	_, opening := DeriveCommitment(seed, orderID, amount)
+	return opening, true
+}
+
+// CancelOrder removes an order locally; once removed, an order of ours can no longer be opened
+func (app *BlackTraceApp) CancelOrder(orderID OrderID) error {
+	app.ordersMux.Lock()
+	if _, exists := app.orders[orderID]; !exists {
+		app.ordersMux.Unlock()
+		return fmt.Errorf("order %s not found", orderID)
+	}
+	app.removeOrderLocked(orderID)
+	app.ordersMux.Unlock()
+	app.forgetOrder(orderID)
+	app.unmarkOwnedOrder(orderID)
+
+	app.orderDetailsMux.Lock()
+	delete(app.orderDetails, orderID)
+	app.orderDetailsMux.Unlock()
+
+	log.Printf("App: Cancelled order %s", orderID)
+	return nil
+}
+
+// markOwnedOrder records an order as created by this node, persisting its details with the marker
+func (app *BlackTraceApp) markOwnedOrder(details *OrderDetails) {
+	app.ownedOrdersMux.Lock()
+	app.ownedOrders[details.OrderID] = true
+	app.ownedOrdersMux.Unlock()
+
+	app.persist(storageNamespaceOwnedOrders, string(details.OrderID), details)
+}
+
+// unmarkOwnedOrder forgets that an order was ours
+func (app *BlackTraceApp) unmarkOwnedOrder(orderID OrderID) {
+	app.ownedOrdersMux.Lock()
+	owned := app.ownedOrders[orderID]
+	delete(app.ownedOrders, orderID)
+	app.ownedOrdersMux.Unlock()
+
+	if owned && app.store != nil {
+		if err := app.store.Delete(storageNamespaceOwnedOrders, string(orderID)); err != nil {
+			log.Printf("Warning: Failed to delete ownership of order %s: %v", orderID, err)
+		}
+	}
+
+	app.orderBroadcastsMux.Lock()
+	_, tracked := app.orderBroadcasts[orderID]
+	delete(app.orderBroadcasts, orderID)
+	app.orderBroadcastsMux.Unlock()
+	if tracked {
+		app.forgetPendingBroadcast(orderID)
+	}
+}
+
+// IsOwnedOrder reports whether this node created the order
+func (app *BlackTraceApp) IsOwnedOrder(orderID OrderID) bool {
+	app.ownedOrdersMux.RLock()
+	defer app.ownedOrdersMux.RUnlock()
+	return app.ownedOrders[orderID]
+}
+
+// expireOrders ends negotiations on orders past their expiry and evicts expired discovered orders
+func (app *BlackTraceApp) expireOrders(now time.Time) {
+	app.ordersMux.RLock()
+	expired := make([]OrderID, 0)
+	for orderID, order := range app.orders {
+		if order.Expiry > 0 && now.Unix() >= order.Expiry {
+			expired = append(expired, orderID)
+		}
+	}
+	app.ordersMux.RUnlock()
+
+	for _, orderID := range expired {
+		app.forgetNegotiationSessions(orderID)
+
+		// Discovered orders are evicted once expired; our own stay listed for the maker
+		if !app.IsOwnedOrder(orderID) {
+			app.ordersMux.Lock()
+			app.removeOrderLocked(orderID)
+			app.ordersMux.Unlock()
+			app.forgetOrder(orderID)
+		}
+	}
+}
+
+// orderExpiryLoop periodically sweeps expired orders and takers that never proposed
+func (app *BlackTraceApp) orderExpiryLoop() {
+	ticker := time.NewTicker(time.Minute)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-app.shutdownCh:
+			return
+		case now := <-ticker.C:
+			app.expireOrders(now)
+			app.sweepDetailReveals(now)
+			app.sweepSessionDeadlines(now)
+		}
+	}
+}
+
 // requestOrderDetails requests details for an order
 func (app *BlackTraceApp) requestOrderDetails(orderID OrderID) {
-	data, _ := MarshalMessage("order_request", orderID)
+	to, ok := app.selectDetailsPeer(orderID, app.network.PeerIDs())
+	if !ok {
+		log.Printf("App: Cannot request details for %s: no connected peers", orderID)
+		return
+	}
 
-	// Send to first peer (simplified - should route to order owner)
+	// Exchange ephemeral keys first so the rest of the negotiation is encrypted
+	if err := app.startNegotiationSession(to, orderID); err != nil {
+		log.Printf("App: Failed to start negotiation session for %s: %v", orderID, err)
+	}
+	app.startSessionDeadline(to, orderID, time.Now())
+
+	data, _ := MarshalMessage("order_request", orderID)
 	app.network.CommandChan() <- NetworkCommand{
-		Type: "broadcast",
+		Type: "send",
+		To:   to,
 		Data: data,
 	}
 
-	log.Printf("App: Requested details for order: %s", orderID)
+	log.Printf("App: Requested details for order %s from %s", orderID, to)
+}
+
+// selectDetailsPeer picks the peer to ask for an order's details: the order's maker, then the
+// peer the announcement came from, then the lowest connected peer ID. Only the maker can answer;
+// the relay and fallback choices cover a maker we are not connected to yet.
+// LIMITATION: the fallback is deterministic but not maker-aware; if neither the maker nor the
+// source is connected the request may go to a peer that cannot answer it.
+func (app *BlackTraceApp) selectDetailsPeer(orderID OrderID, connected []PeerID) (PeerID, bool) {
+	isConnected := func(id PeerID) bool {
+		for _, p := range connected {
+			if p == id {
+				return true
+			}
+		}
+		return false
+	}
+
+	app.ordersMux.RLock()
+	order, exists := app.orders[orderID]
+	app.ordersMux.RUnlock()
+
+	if exists && isConnected(order.MakerID) {
+		return order.MakerID, true
+	}
+
+	app.orderSourcesMux.RLock()
+	source, hasSource := app.orderSources[orderID]
+	app.orderSourcesMux.RUnlock()
+
+	if hasSource && isConnected(source) {
+		return source, true
+	}
+
+	if len(connected) == 0 {
+		return "", false
+	}
+
+	sorted := append([]PeerID(nil), connected...)
+	sort.Slice(sorted, func(i, j int) bool { return sorted[i] < sorted[j] })
+	return sorted[0], true
 }
 
-// sendOrderDetails sends order details to a peer
+// sendOrderDetails sends order details to a peer unencrypted, signed and inside the negotiation
+// session if one is established. It is the fallback when the peer's key is not cached yet.
 func (app *BlackTraceApp) sendOrderDetails(to PeerID, orderID OrderID) {
 	app.ordersMux.RLock()
 	order, ok := app.orders[orderID]
@@ -586,16 +1254,59 @@ func (app *BlackTraceApp) sendOrderDetails(to PeerID, orderID OrderID) {
 		Stablecoin: order.Stablecoin,
 	}
 
-	data, _ := MarshalMessage("order_details", details)
-	app.network.CommandChan() <- NetworkCommand{
-		Type: "send",
-		To:   to,
-		Data: data,
+	// Signed so the taker can check the details come from the order's maker
+	msgType, wrapped, err := app.sealForSession(to, orderID, "order_details", details)
+	if err != nil {
+		log.Printf("App: Failed to seal order details for %s: %v", to, err)
+		return
+	}
+	if err := app.sendSignedMessage(to, msgType, wrapped); err != nil {
+		log.Printf("App: Failed to send order details to %s: %v", to, err)
+		return
 	}
 
 	log.Printf("App: Sent order details to %s", to)
 }
 
+// decryptOrderDetails opens order details encrypted to our key. Ciphertext that cannot be
+// decrypted (encrypted to another key, truncated or tampered with) yields an ErrDecryption error.
+func (app *BlackTraceApp) decryptOrderDetails(encMsg *EncryptedOrderDetailsMessage) (*OrderDetails, error) {
+	cryptoMgr := app.crypto()
+	if cryptoMgr == nil {
+		return nil, fmt.Errorf("CryptoManager not initialized")
+	}
+
+	eciesMsg, err := DeserializeECIESMessage(encMsg.EncryptedPayload)
+	if err != nil {
+		return nil, fmt.Errorf("%w: malformed ECIES message: %v", ErrDecryption, err)
+	}
+
+	decrypted, err := cryptoMgr.ECIESDecrypt(eciesMsg)
+	if err != nil {
+		return nil, err
+	}
+
+	var details OrderDetails
+	if err := json.Unmarshal(decrypted, &details); err != nil {
+		return nil, fmt.Errorf("failed to unmarshal decrypted details: %w", err)
+	}
+	return &details, nil
+}
+
+// recordOrderDetailsError keeps why an order's details were rejected so the UI can show it
+func (app *BlackTraceApp) recordOrderDetailsError(orderID OrderID, err error) {
+	app.orderDetailsMux.Lock()
+	app.orderDetailsErrors[orderID] = err
+	app.orderDetailsMux.Unlock()
+}
+
+// OrderDetailsError returns why an order's details were rejected, or nil if they were not
+func (app *BlackTraceApp) OrderDetailsError(orderID OrderID) error {
+	app.orderDetailsMux.RLock()
+	defer app.orderDetailsMux.RUnlock()
+	return app.orderDetailsErrors[orderID]
+}
+
 // sendEncryptedOrderDetails encrypts and sends order details to a specific peer
 func (app *BlackTraceApp) sendEncryptedOrderDetails(to PeerID, orderID OrderID) error {
 	// Get order details
@@ -662,8 +1373,12 @@ func (app *BlackTraceApp) sendEncryptedOrderDetails(to PeerID, orderID OrderID)
 		EncryptedPayload: encryptedPayload,
 	}
 
-	// Send as signed message
-	if err := app.sendSignedMessage(to, "encrypted_order_details", encryptedMsg); err != nil {
+	// Send as signed message, inside the negotiation session if one is established
+	msgType, wrapped, err := app.sealForSession(to, orderID, "encrypted_order_details", encryptedMsg)
+	if err != nil {
+		return err
+	}
+	if err := app.sendSignedMessage(to, msgType, wrapped); err != nil {
 		return fmt.Errorf("failed to send encrypted details: %w", err)
 	}
 
@@ -712,7 +1427,11 @@ func (app *BlackTraceApp) sendEncryptedProposal(makerID PeerID, proposal Proposa
 	}
 
 	// Send as signed message to maker only (not broadcast)
-	if err := app.sendSignedMessage(makerID, "encrypted_proposal", encryptedMsg); err != nil {
+	msgType, wrapped, err := app.sealForSession(makerID, proposal.OrderID, "encrypted_proposal", encryptedMsg)
+	if err != nil {
+		return err
+	}
+	if err := app.sendReliableMessage(makerID, msgType, wrapped); err != nil {
 		return fmt.Errorf("failed to send encrypted proposal: %w", err)
 	}
 
@@ -748,6 +1467,9 @@ func (app *BlackTraceApp) sendEncryptedAcceptance(proposerID PeerID, proposal *P
 		"status":      "accepted",
 		"timestamp":   time.Now().Unix(),
 	}
+	if proposal.Settlement != nil {
+		acceptanceDetails["settlement"] = proposal.Settlement
+	}
 
 	// Marshal acceptance to JSON
 	acceptanceJSON, err := json.Marshal(acceptanceDetails)
@@ -771,7 +1493,11 @@ func (app *BlackTraceApp) sendEncryptedAcceptance(proposerID PeerID, proposal *P
 	}
 
 	// Send as signed message to proposer only (not broadcast)
-	if err := app.sendSignedMessage(proposerID, "encrypted_acceptance", encryptedMsg); err != nil {
+	msgType, wrapped, err := app.sealForSession(proposerID, proposal.OrderID, "encrypted_acceptance", encryptedMsg)
+	if err != nil {
+		return err
+	}
+	if err := app.sendReliableMessage(proposerID, msgType, wrapped); err != nil {
 		return fmt.Errorf("failed to send encrypted acceptance: %w", err)
 	}
 
@@ -781,27 +1507,27 @@ func (app *BlackTraceApp) sendEncryptedAcceptance(proposerID PeerID, proposal *P
 	return nil
 }
 
-// broadcastSignedMessage signs and broadcasts a message via gossipsub
-func (app *BlackTraceApp) broadcastSignedMessage(msgType string, payload interface{}) error {
-	// Check if crypto manager is initialized
-	if app.cryptoMgr == nil {
+// marshalOutbound signs a message, falling back to an unsigned message if no CryptoManager is set
+func (app *BlackTraceApp) marshalOutbound(msgType string, payload interface{}) ([]byte, error) {
+	cryptoMgr := app.crypto()
+	if cryptoMgr == nil {
 		// Graceful degradation: send unsigned message
 		log.Printf("Warning: CryptoManager not initialized, sending unsigned message")
-		data, err := MarshalMessage(msgType, payload)
-		if err != nil {
-			return err
-		}
-		app.network.CommandChan() <- NetworkCommand{
-			Type: "broadcast",
-			Data: data,
-		}
-		return nil
+		return MarshalMessage(msgType, payload)
+	}
+
+	data, err := MarshalSignedMessage(msgType, payload, cryptoMgr)
+	if err != nil {
+		return nil, fmt.Errorf("failed to sign message: %w", err)
 	}
+	return data, nil
+}
 
-	// Sign the message
-	data, err := MarshalSignedMessage(msgType, payload, app.cryptoMgr)
+// broadcastSignedMessage signs and broadcasts a message via gossipsub
+func (app *BlackTraceApp) broadcastSignedMessage(msgType string, payload interface{}) error {
+	data, err := app.marshalOutbound(msgType, payload)
 	if err != nil {
-		return fmt.Errorf("failed to sign message: %w", err)
+		return err
 	}
 
 	log.Printf("App: Broadcasting signed message (type: %s, size: %d bytes)", msgType, len(data))
@@ -812,38 +1538,317 @@ func (app *BlackTraceApp) broadcastSignedMessage(msgType string, payload interfa
 	return nil
 }
 
+// broadcastSignedMessageForCoin signs a message and routes it only to peers interested in the coin
+func (app *BlackTraceApp) broadcastSignedMessageForCoin(coin StablecoinType, msgType string, payload interface{}) error {
+	data, err := app.marshalOutbound(msgType, payload)
+	if err != nil {
+		return err
+	}
+
+	log.Printf("App: Routing signed message to %s peers (type: %s, size: %d bytes)", coin, msgType, len(data))
+	app.network.CommandChan() <- NetworkCommand{
+		Type: "broadcast_coin",
+		Coin: coin,
+		Data: data,
+	}
+	return nil
+}
+
 // sendSignedMessage signs and sends a message to a specific peer
 func (app *BlackTraceApp) sendSignedMessage(to PeerID, msgType string, payload interface{}) error {
-	if app.cryptoMgr == nil {
-		log.Printf("Warning: CryptoManager not initialized, sending unsigned message")
-		data, err := MarshalMessage(msgType, payload)
+	data, err := app.marshalOutbound(msgType, payload)
+	if err != nil {
+		return err
+	}
+
+	log.Printf("App: Sending signed message to %s (type: %s)", to, msgType)
+	app.network.CommandChan() <- NetworkCommand{
+		Type: "send",
+		To:   to,
+		Data: app.encodeForPeer(to, data),
+	}
+	return nil
+}
+
+// sendReliableMessage signs and sends a negotiation-critical message to a peer.
+// The network layer retries it and reports "send_failed" if the peer stays unreachable.
+func (app *BlackTraceApp) sendReliableMessage(to PeerID, msgType string, payload interface{}) error {
+	data, err := app.marshalOutbound(msgType, payload)
+	if err != nil {
+		return err
+	}
+
+	log.Printf("App: Sending signed message to %s with retry (type: %s)", to, msgType)
+	app.network.CommandChan() <- NetworkCommand{
+		Type: "send_reliable",
+		To:   to,
+		Data: app.encodeForPeer(to, data),
+	}
+	return nil
+}
+
+// handleSendFailure cancels the negotiation a dead-lettered message belonged to
+func (app *BlackTraceApp) handleSendFailure(to PeerID, data []byte) {
+	// Signed and unsigned envelopes share the type and payload fields
+	msg, err := UnmarshalMessage(data)
+	if err != nil {
+		log.Printf("App: Undeliverable message to %s could not be decoded: %v", to, err)
+		return
+	}
+
+	switch msg.Type {
+	case "negotiation_message":
+		// We sealed it, so the session key opens it to find the negotiation it belonged to
+		var negMsg NegotiationMessage
+		if err := json.Unmarshal(msg.Payload, &negMsg); err != nil {
+			log.Printf("App: Failed to decode undeliverable negotiation message: %v", err)
+			return
+		}
+		inner, err := app.openNegotiationMessage(to, &negMsg)
 		if err != nil {
-			return err
+			log.Printf("App: Failed to open undeliverable negotiation message: %v", err)
+			return
 		}
-		app.network.CommandChan() <- NetworkCommand{
-			Type: "send",
-			To:   to,
-			Data: data,
+		innerData, _ := json.Marshal(inner)
+		app.handleSendFailure(to, innerData)
+
+	case "encrypted_proposal":
+		var proposalMsg EncryptedProposalMessage
+		if err := json.Unmarshal(msg.Payload, &proposalMsg); err != nil {
+			log.Printf("App: Failed to decode undeliverable proposal: %v", err)
+			return
 		}
-		return nil
+		app.cancelPendingProposals(proposalMsg.OrderID, CancelReasonCounterpartyDisconnected)
+
+	case "encrypted_acceptance":
+		var acceptanceMsg EncryptedAcceptanceMessage
+		if err := json.Unmarshal(msg.Payload, &acceptanceMsg); err != nil {
+			log.Printf("App: Failed to decode undeliverable acceptance: %v", err)
+			return
+		}
+		app.cancelProposal(acceptanceMsg.ProposalID, CancelReasonCounterpartyDisconnected)
+
+	default:
+		log.Printf("App: Dropped undeliverable %s message to %s", msg.Type, to)
 	}
+}
+
+// cancelProposal marks a proposal cancelled with the given reason
+func (app *BlackTraceApp) cancelProposal(proposalID ProposalID, reason CancelReason) {
+	app.proposalsMux.Lock()
+	defer app.proposalsMux.Unlock()
+
+	proposal, ok := app.proposals[proposalID]
+	if !ok {
+		return
+	}
+	proposal.Status = ProposalStatusCancelled
+	proposal.CancelReason = reason
+	app.persistProposal(proposal)
+
+	log.Printf("App: Cancelled proposal %s (%s)", proposalID, reason)
+}
 
-	data, err := MarshalSignedMessage(msgType, payload, app.cryptoMgr)
+// cancelPendingProposals cancels every pending proposal on an order
+func (app *BlackTraceApp) cancelPendingProposals(orderID OrderID, reason CancelReason) {
+	app.proposalsMux.Lock()
+	defer app.proposalsMux.Unlock()
+
+	for id, proposal := range app.proposalsByOrder[orderID] {
+		if proposal.Status != ProposalStatusPending {
+			continue
+		}
+		proposal.Status = ProposalStatusCancelled
+		proposal.CancelReason = reason
+		app.persistProposal(proposal)
+
+		log.Printf("App: Cancelled proposal %s (%s)", id, reason)
+	}
+}
+
+// sendInterests advertises this node's stablecoin interests to a newly connected peer
+func (app *BlackTraceApp) sendInterests(to PeerID) {
+	if len(app.interests) == 0 {
+		return
+	}
+
+	if err := app.sendSignedMessage(to, "interests", InterestsMessage{Stablecoins: app.interests}); err != nil {
+		log.Printf("Failed to send interests to %s: %v", to, err)
+	}
+}
+
+// requestLiquidityOpening challenges the maker to open the commitment in the order announcement.
+// The fresh nonce replaces any earlier one, so only a response to the latest challenge verifies.
+func (app *BlackTraceApp) requestLiquidityOpening(maker PeerID, orderID OrderID) {
+	nonce, err := NewChallengeNonce()
 	if err != nil {
-		return fmt.Errorf("failed to sign message: %w", err)
+		log.Printf("Failed to request liquidity opening for %s: %v", orderID, err)
+		return
 	}
+	app.liquidityChallengesMux.Lock()
+	app.liquidityChallenges[orderID] = nonce
+	app.liquidityChallengesMux.Unlock()
 
-	log.Printf("App: Sending signed message to %s (type: %s)", to, msgType)
-	app.network.CommandChan() <- NetworkCommand{
-		Type: "send",
-		To:   to,
-		Data: data,
+	challenge := LiquidityChallengeMessage{OrderID: orderID, Nonce: nonce}
+	msgType, wrapped, err := app.sealForSession(maker, orderID, "liquidity_request", challenge)
+	if err == nil {
+		err = app.sendSignedMessage(maker, msgType, wrapped)
+	}
+	if err != nil {
+		log.Printf("Failed to request liquidity opening for %s: %v", orderID, err)
+		return
+	}
+
+	log.Printf("App: Requested liquidity opening for order %s from %s", orderID, maker)
+}
+
+// sendLiquidityOpening reveals the commitment opening for one of our orders to a taker,
+// answering the taker's challenge nonce
+func (app *BlackTraceApp) sendLiquidityOpening(to PeerID, orderID OrderID, nonce []byte) {
+	if len(nonce) != ChallengeNonceSize {
+		log.Printf("App: Ignoring liquidity request for %s from %s: bad challenge nonce", orderID, to)
+		return
+	}
+
+	opening, ok := app.CommitmentOpening(orderID)
+	if !ok {
+		log.Printf("App: No commitment opening for order %s", orderID)
+		return
 	}
+	defer opening.Zeroize()
+
+	msg := LiquidityOpeningMessage{
+		OrderID:  orderID,
+		Opening:  *opening,
+		Response: ComputeChallengeResponse(orderID, opening, nonce),
+	}
+	msgType, wrapped, err := app.sealForSession(to, orderID, "liquidity_opening", msg)
+	if err == nil {
+		err = app.sendSignedMessage(to, msgType, wrapped)
+	}
+	if err != nil {
+		log.Printf("Failed to send liquidity opening: %v", err)
+		return
+	}
+
+	log.Printf("App: Sent liquidity opening for %s to %s", orderID, to)
+}
+
+// verifyLiquidityOpening checks a maker's opening against the announced commitment, the revealed
+// order amount and our outstanding challenge nonce, marking the order as verified on success.
+// The nonce is consumed either way, so a response can be checked at most once.
+func (app *BlackTraceApp) verifyLiquidityOpening(orderID OrderID, opening *CommitmentOpening, response []byte) error {
+	app.liquidityChallengesMux.Lock()
+	nonce, challenged := app.liquidityChallenges[orderID]
+	delete(app.liquidityChallenges, orderID)
+	app.liquidityChallengesMux.Unlock()
+
+	if !challenged {
+		return fmt.Errorf("no outstanding liquidity challenge for order %s", orderID)
+	}
+
+	app.ordersMux.RLock()
+	order, exists := app.orders[orderID]
+	app.ordersMux.RUnlock()
+
+	if !exists {
+		return fmt.Errorf("order not found: %s", orderID)
+	}
+
+	app.orderDetailsMux.RLock()
+	details, hasDetails := app.orderDetails[orderID]
+	app.orderDetailsMux.RUnlock()
+
+	if !hasDetails {
+		return fmt.Errorf("order details not revealed yet: %s", orderID)
+	}
+
+	if err := order.VerifyOpening(opening, details.Amount, nonce, response); err != nil {
+		return err
+	}
+
+	app.liquidityVerifiedMux.Lock()
+	app.liquidityVerified[orderID] = true
+	app.liquidityVerifiedMux.Unlock()
+
 	return nil
 }
 
+// IsLiquidityVerified reports whether the maker's liquidity for an order has been verified
+func (app *BlackTraceApp) IsLiquidityVerified(orderID OrderID) bool {
+	app.liquidityVerifiedMux.RLock()
+	defer app.liquidityVerifiedMux.RUnlock()
+	return app.liquidityVerified[orderID]
+}
+
+// ErrInvalidOrderState is returned when an order has not reached the stage an action needs
+var ErrInvalidOrderState = errors.New("invalid order state")
+
+// checkCanPropose returns the ZEC amount to propose for an order, taken from its revealed
+// details (amount 0 takes the whole order). It returns an error unless the details have been
+// revealed, the maker's liquidity has been verified and amount is within the order.
+func (app *BlackTraceApp) checkCanPropose(orderID OrderID, amount uint64) (uint64, error) {
+	app.orderDetailsMux.RLock()
+	details, hasDetails := app.orderDetails[orderID]
+	var orderAmount uint64
+	if hasDetails {
+		orderAmount = details.Amount
+	}
+	app.orderDetailsMux.RUnlock()
+
+	if !hasDetails {
+		return 0, fmt.Errorf("%w: details for order %s not revealed yet", ErrInvalidOrderState, orderID)
+	}
+	if !app.IsLiquidityVerified(orderID) {
+		return 0, fmt.Errorf("maker liquidity not verified for order %s", orderID)
+	}
+	if amount == 0 {
+		return orderAmount, nil
+	}
+	if amount > orderAmount {
+		return 0, fmt.Errorf("%w: amount %d exceeds the %d revealed for order %s", ErrInvalidOrderState, amount, orderAmount, orderID)
+	}
+	return amount, nil
+}
+
+// pingPeer sends a ping to measure round-trip time to a peer
+func (app *BlackTraceApp) pingPeer(to PeerID) {
+	nonce := app.rtt.startPing(to, time.Now())
+	if err := app.sendSignedMessage(to, "ping", PingMessage{Nonce: nonce}); err != nil {
+		log.Printf("Failed to ping %s: %v", to, err)
+	}
+}
+
+// pingLoop pings every connected peer at a fixed interval
+func (app *BlackTraceApp) pingLoop() {
+	ticker := time.NewTicker(pingInterval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-app.shutdownCh:
+			return
+		case <-ticker.C:
+			for _, peerID := range app.network.PeerIDs() {
+				app.pingPeer(peerID)
+			}
+		}
+	}
+}
+
+// PeerRTT returns the smoothed round-trip time to a peer, if it has been measured
+func (app *BlackTraceApp) PeerRTT(peerID PeerID) (time.Duration, bool) {
+	return app.rtt.get(peerID)
+}
+
 // proposePrice proposes a price for an order
 func (app *BlackTraceApp) proposePrice(orderID OrderID, price, amount uint64, proposerUsername, proposerPubKeyHash string) {
+	amount, err := app.checkCanPropose(orderID, amount)
+	if err != nil {
+		log.Printf("App: Refusing to propose: %v", err)
+		return
+	}
+
 	proposalID := NewProposalID(orderID)
 
 	proposal := Proposal{
@@ -860,7 +1865,7 @@ func (app *BlackTraceApp) proposePrice(orderID OrderID, price, amount uint64, pr
 
 	// Store the proposal locally
 	app.proposalsMux.Lock()
-	app.proposals[proposalID] = &proposal
+	app.storeProposalLocked(&proposal)
 	app.proposalsMux.Unlock()
 
 	// Get maker ID from the order
@@ -873,6 +1878,33 @@ func (app *BlackTraceApp) proposePrice(orderID OrderID, price, amount uint64, pr
 		return
 	}
 
+	// Taker signs the terms; the maker countersigns them on acceptance
+	app.proposalsMux.Lock()
+	if err := app.signTermsAsTaker(&proposal, order); errors.Is(err, ErrPriceDeviation) {
+		app.removeProposalLocked(proposalID)
+		app.proposalsMux.Unlock()
+		log.Printf("App: Refusing to propose: %v", err)
+		return
+	} else if err != nil {
+		log.Printf("Warning: Sending proposal %s without signed terms: %v", proposalID, err)
+	}
+	cryptoMgr := app.crypto()
+	if cryptoMgr == nil {
+		app.removeProposalLocked(proposalID)
+		app.proposalsMux.Unlock()
+		log.Printf("App: Refusing to propose: CryptoManager not initialized, cannot sign proposal %s", proposalID)
+		return
+	}
+	if err := proposal.Sign(cryptoMgr); err != nil {
+		app.removeProposalLocked(proposalID)
+		app.proposalsMux.Unlock()
+		log.Printf("App: Refusing to propose: %v", err)
+		return
+	}
+	app.persistProposal(&proposal)
+	app.recordProposal(&proposal)
+	app.proposalsMux.Unlock()
+
 	// Send ENCRYPTED proposal to maker only (prevents frontrunning)
 	if err := app.sendEncryptedProposal(order.MakerID, proposal); err != nil {
 		log.Printf("Failed to send encrypted proposal: %v", err)
@@ -905,12 +1937,18 @@ func (app *BlackTraceApp) CreateOrder(amount uint64, stablecoin StablecoinType,
 	return result.(OrderID)
 }
 
-// ListOrders returns all known orders (synchronous API)
+// ListOrders returns all known orders, oldest first (synchronous API)
 func (app *BlackTraceApp) ListOrders() []*OrderAnnouncement {
+	return app.ListOrdersBy(OrderSortTimestamp)
+}
+
+// ListOrdersBy returns all known orders sorted by the given key (synchronous API)
+func (app *BlackTraceApp) ListOrdersBy(by OrderSort) []*OrderAnnouncement {
 	responseCh := make(chan interface{})
 
 	app.appCommandCh <- AppCommand{
 		Type:       "list_orders",
+		SortBy:     by,
 		ResponseCh: responseCh,
 	}
 
@@ -943,11 +1981,9 @@ func (app *BlackTraceApp) ListProposals(orderID OrderID) []*Proposal {
 	app.proposalsMux.RLock()
 	defer app.proposalsMux.RUnlock()
 
-	proposals := make([]*Proposal, 0)
-	for _, proposal := range app.proposals {
-		if proposal.OrderID == orderID {
-			proposals = append(proposals, proposal)
-		}
+	proposals := make([]*Proposal, 0, len(app.proposalsByOrder[orderID]))
+	for _, proposal := range app.proposalsByOrder[orderID] {
+		proposals = append(proposals, proposal)
 	}
 
 	return proposals
@@ -962,11 +1998,37 @@ func (app *BlackTraceApp) AcceptProposal(proposalID ProposalID, secret string) e
 		return fmt.Errorf("proposal %s not found", proposalID)
 	}
 
+	signedTerms := proposal.Settlement
+	app.proposalsMux.Unlock()
+
+	// Countersign the taker's terms so both sides hold the same signed settlement
+	var countersigned *SignedSettlement
+	if signedTerms != nil {
+		app.ordersMux.RLock()
+		order, exists := app.orders[proposal.OrderID]
+		app.ordersMux.RUnlock()
+		if !exists {
+			return fmt.Errorf("order %s not found", proposal.OrderID)
+		}
+
+		var err error
+		countersigned, err = app.countersignSettlement(proposal, order)
+		if err != nil {
+			return fmt.Errorf("cannot accept proposal %s: %w", proposalID, err)
+		}
+	}
+
 	// Update status to accepted and initialize settlement status
+	app.proposalsMux.Lock()
 	proposal.Status = ProposalStatusAccepted
 	readyStatus := SettlementStatusReady
 	proposal.SettlementStatus = &readyStatus
+	if countersigned != nil {
+		proposal.Settlement = countersigned
+	}
+	app.persistProposal(proposal)
 	app.proposalsMux.Unlock()
+	app.recordProposalState(proposal, ProposalStatusAccepted)
 
 	log.Printf("App: Accepted proposal %s (Price: $%d, Amount: %d) with secret", proposalID, proposal.Price, proposal.Amount)
 
@@ -995,26 +2057,15 @@ func (app *BlackTraceApp) AcceptProposal(proposalID ProposalID, secret string) e
 			return nil
 		}
 
-		// Create settlement request with Alice's secret
-		settlementReq := SettlementRequest{
-			ProposalID:      string(proposalID),
-			OrderID:         string(proposal.OrderID),
-			MakerID:         string(app.GetPeerID()),
-			TakerID:         string(proposal.ProposerID),
-			Amount:          proposal.Amount,
-			Price:           proposal.Price,
-			Stablecoin:      string(order.Stablecoin),
-			SettlementChain: "ztarknet", // Default for now, will be from proposal in future
-			Secret:          secret,     // Alice's secret for HTLC
-			Timestamp:       time.Now(),
-		}
+		// Create settlement request with Alice's secret (or only its hash in non-custodial mode)
+		settlementReq := app.settlementMgr.NewSettlementRequest(app.GetPeerID(), proposal, order.Stablecoin, secret)
 
 		// Publish to NATS for Rust settlement service
 		if err := app.settlementMgr.PublishSettlementRequest(settlementReq); err != nil {
 			log.Printf("Warning: Failed to publish settlement request: %v", err)
 			// Continue anyway - this is not critical for acceptance
 		} else {
-			log.Printf("Settlement: Request published to NATS with Alice's secret")
+			log.Printf("Settlement: Request published to NATS")
 		}
 	}
 
@@ -1032,7 +2083,9 @@ func (app *BlackTraceApp) RejectProposal(proposalID ProposalID) error {
 
 	// Update status to rejected
 	proposal.Status = ProposalStatusRejected
+	app.persistProposal(proposal)
 	app.proposalsMux.Unlock()
+	app.recordProposalState(proposal, ProposalStatusRejected)
 
 	log.Printf("App: Rejected proposal %s (Price: $%d, Amount: %d)", proposalID, proposal.Price, proposal.Amount)
 
@@ -1066,6 +2119,7 @@ func (app *BlackTraceApp) LockZEC(proposalID ProposalID, username string, zcashA
 	// Set settlement status to alice_locked
 	status := SettlementStatusAliceLocked
 	proposal.SettlementStatus = &status
+	app.persistProposal(proposal)
 	app.proposalsMux.Unlock()
 
 	log.Printf("Settlement: %s locked %d ZEC for proposal %s from address %s with secret", username, proposal.Amount, proposalID, zcashAddress)
@@ -1074,18 +2128,22 @@ func (app *BlackTraceApp) LockZEC(proposalID ProposalID, username string, zcashA
 	// Publish settlement status update to NATS
 	if app.settlementMgr.IsEnabled() {
 		statusUpdate := map[string]interface{}{
+			"version":            settlementSchemaVersion,
 			"proposal_id":        string(proposalID),
 			"order_id":           string(proposal.OrderID),
 			"settlement_status":  string(status),
 			"action":             "alice_lock_zec",
-			"amount":             proposal.Amount,
+			"zec_zatoshi":        proposal.Amount,
 			"username":           username,
 			"zcash_address":      zcashAddress,
-			"secret":             secret, // Alice's secret for HTLC
 			"alice_pubkey_hash":  alicePubKeyHash, // Alice's pubkey hash for HTLC claim (Bob needs to provide sig)
 			"bob_pubkey_hash":    bobPubKeyHash,   // Bob's pubkey hash for HTLC refund
 			"timestamp":          time.Now(),
 		}
+		// The preimage only goes to a custodial settlement service
+		if !app.settlementMgr.nonCustodial {
+			statusUpdate["secret"] = secret // Alice's secret for HTLC
+		}
 
 		if err := app.settlementMgr.PublishSettlementStatusUpdate(statusUpdate); err != nil {
 			log.Printf("Warning: Failed to publish settlement status update: %v", err)
@@ -1124,6 +2182,7 @@ func (app *BlackTraceApp) LockUSDC(proposalID ProposalID) (SettlementStatus, err
 	// Set settlement status to both_locked
 	status := SettlementStatusBothLocked
 	proposal.SettlementStatus = &status
+	app.persistProposal(proposal)
 	app.proposalsMux.Unlock()
 
 	totalUSDC := proposal.Amount * proposal.Price
@@ -1133,11 +2192,12 @@ func (app *BlackTraceApp) LockUSDC(proposalID ProposalID) (SettlementStatus, err
 	// Publish settlement status update to NATS
 	if app.settlementMgr.IsEnabled() {
 		statusUpdate := map[string]interface{}{
+			"version":           settlementSchemaVersion,
 			"proposal_id":       string(proposalID),
 			"order_id":          string(proposal.OrderID),
 			"settlement_status": string(status),
 			"action":            "bob_lock_usdc",
-			"amount_usdc":       totalUSDC,
+			"usdc_minor":        totalUSDC,
 			"timestamp":         time.Now(),
 		}
 
@@ -1158,7 +2218,7 @@ func (app *BlackTraceApp) GetStatus() NodeStatus {
 
 // GetPeerID returns this node's peer ID
 func (app *BlackTraceApp) GetPeerID() PeerID {
-	return PeerID(app.network.host.ID().String())
+	return PeerID(app.network.self.String())
 }
 
 // ConnectToPeer connects to a peer by multiaddr
@@ -1169,14 +2229,19 @@ func (app *BlackTraceApp) ConnectToPeer(addr string) {
 	}
 }
 
-// Shutdown gracefully shuts down the application
+// Shutdown gracefully shuts down the application. Counterparties of active negotiations are
+// told we are going offline before the network closes.
 func (app *BlackTraceApp) Shutdown() {
+	app.drainNegotiations(DefaultDrainTimeout)
+
 	close(app.shutdownCh)
 	app.network.CommandChan() <- NetworkCommand{
 		Type: "shutdown",
 	}
 	// Close NATS connection
-	app.settlementMgr.Close()
+	if app.settlementMgr != nil {
+		app.settlementMgr.Close()
+	}
 }
 
 // GetAuthManager returns the authentication manager
@@ -0,0 +1,663 @@
+package node
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"log"
+	"os"
+	"testing"
+	"time"
+)
+
+// newTestApp builds a BlackTraceApp with empty state and no network
+func newTestApp() *BlackTraceApp {
+	return &BlackTraceApp{
+		orders:              make(map[OrderID]*OrderAnnouncement),
+		ordersByCommitment:  make(map[string]OrderID),
+		ordersByContent:     make(map[OrderID]OrderID),
+		ownedOrders:         make(map[OrderID]bool),
+		orderSources:        make(map[OrderID]PeerID),
+		orderDetails:        make(map[OrderID]*OrderDetails),
+		orderDetailsErrors:  make(map[OrderID]error),
+		orderBroadcasts:     make(map[OrderID]OrderBroadcastState),
+		proposals:           make(map[ProposalID]*Proposal),
+		proposalsByOrder:    make(map[OrderID]map[ProposalID]*Proposal),
+		signedSettlements:   make(map[ProposalID]*SignedSettlement),
+		transcripts:         make(map[transcriptKey]*negotiationTranscript),
+		liquidityVerified:   make(map[OrderID]bool),
+		liquidityChallenges: make(map[OrderID][]byte),
+		peerKeys:            make(map[PeerID][]byte),
+		peerBook:            make(map[PeerID]*PeerRecord),
+		peerWireFormats:     make(map[PeerID]WireFormat),
+		proposalLimit:       newProposalLimiter(),
+		verifyCache:         newVerifyCache(DefaultVerifyCacheSize),
+		detailReveals:       make(map[proposalSession]*detailsReveal),
+		negotiationSessions: make(map[proposalSession]*negotiationSession),
+		sessionDeadlines:    make(map[proposalSession]time.Time),
+	}
+}
+
+// challengeLiquidity records a fresh outstanding challenge for an order, as requestLiquidityOpening does
+func challengeLiquidity(t *testing.T, app *BlackTraceApp, orderID OrderID) []byte {
+	nonce, err := NewChallengeNonce()
+	if err != nil {
+		t.Fatalf("Failed to generate nonce: %v", err)
+	}
+	app.liquidityChallenges[orderID] = nonce
+	return nonce
+}
+
+func TestProposalBlockedUntilLiquidityVerified(t *testing.T) {
+	app := newTestApp()
+	orderID := OrderID("order_1")
+
+	commitment, opening, err := GenerateCommitment(orderID, 10000)
+	if err != nil {
+		t.Fatalf("Failed to generate commitment: %v", err)
+	}
+
+	// Taker has the announcement and the revealed details
+	app.orders[orderID] = &OrderAnnouncement{OrderID: orderID, ProofCommitment: commitment}
+	app.orderDetails[orderID] = &OrderDetails{OrderID: orderID, Amount: 10000}
+
+	if _, err := app.checkCanPropose(orderID, 0); err == nil {
+		t.Fatal("Proposal should be blocked before liquidity is verified")
+	}
+
+	// A forged opening must not unlock proposals
+	forged := &CommitmentOpening{Amount: 20000, Salt: opening.Salt}
+	nonce := challengeLiquidity(t, app, orderID)
+	if err := app.verifyLiquidityOpening(orderID, forged, ComputeChallengeResponse(orderID, forged, nonce)); err == nil {
+		t.Fatal("Forged opening should fail verification")
+	}
+	if _, err := app.checkCanPropose(orderID, 0); err == nil {
+		t.Fatal("Proposal should still be blocked after failed verification")
+	}
+
+	nonce = challengeLiquidity(t, app, orderID)
+	if err := app.verifyLiquidityOpening(orderID, opening, ComputeChallengeResponse(orderID, opening, nonce)); err != nil {
+		t.Fatalf("Valid opening failed verification: %v", err)
+	}
+	if _, err := app.checkCanPropose(orderID, 0); err != nil {
+		t.Errorf("Proposal should be allowed after verification: %v", err)
+	}
+}
+
+func TestLiquidityOpeningBelowOrderAmountRejected(t *testing.T) {
+	app := newTestApp()
+	orderID := OrderID("order_2")
+
+	// Maker committed to less than the amount revealed in the details
+	commitment, opening, err := GenerateCommitment(orderID, 500)
+	if err != nil {
+		t.Fatalf("Failed to generate commitment: %v", err)
+	}
+	app.orders[orderID] = &OrderAnnouncement{OrderID: orderID, ProofCommitment: commitment}
+	app.orderDetails[orderID] = &OrderDetails{OrderID: orderID, Amount: 10000}
+
+	nonce := challengeLiquidity(t, app, orderID)
+	if err := app.verifyLiquidityOpening(orderID, opening, ComputeChallengeResponse(orderID, opening, nonce)); err == nil {
+		t.Fatal("Opening below the order amount should fail verification")
+	}
+	if app.IsLiquidityVerified(orderID) {
+		t.Error("Order should not be marked verified")
+	}
+}
+
+func TestProposalRequiresRevealedDetails(t *testing.T) {
+	app := newTestApp()
+	orderID := OrderID("order_3")
+	app.orders[orderID] = &OrderAnnouncement{OrderID: orderID}
+	app.liquidityVerified[orderID] = true
+
+	// The taker never saw what it would be agreeing to
+	if _, err := app.checkCanPropose(orderID, 10000); !errors.Is(err, ErrInvalidOrderState) {
+		t.Fatalf("Expected ErrInvalidOrderState before details are revealed, got %v", err)
+	}
+
+	app.orderDetails[orderID] = &OrderDetails{OrderID: orderID, Amount: 10000}
+
+	// The amount comes from the revealed details
+	if amount, err := app.checkCanPropose(orderID, 0); err != nil || amount != 10000 {
+		t.Errorf("Expected the revealed amount 10000, got %d (%v)", amount, err)
+	}
+	if amount, err := app.checkCanPropose(orderID, 4000); err != nil || amount != 4000 {
+		t.Errorf("Expected a partial amount of 4000, got %d (%v)", amount, err)
+	}
+	if _, err := app.checkCanPropose(orderID, 20000); !errors.Is(err, ErrInvalidOrderState) {
+		t.Errorf("Expected ErrInvalidOrderState for more than the order holds, got %v", err)
+	}
+}
+
+// encryptedDetailsPayload encrypts order details to the recipient as a maker would send them
+func encryptedDetailsPayload(t *testing.T, recipient *BlackTraceApp, details *OrderDetails, tamper bool) []byte {
+	detailsJSON, _ := json.Marshal(details)
+	encrypted, err := ECIESEncrypt(recipient.cryptoMgr.publicKey, detailsJSON)
+	if err != nil {
+		t.Fatalf("Failed to encrypt details: %v", err)
+	}
+	if tamper {
+		encrypted.Ciphertext[0] ^= 0xff
+	}
+	payload, _ := json.Marshal(EncryptedOrderDetailsMessage{
+		OrderID:          details.OrderID,
+		EncryptedPayload: SerializeECIESMessage(encrypted),
+	})
+	return payload
+}
+
+func TestEncryptedOrderDetailsDecrypted(t *testing.T) {
+	taker := newTestAppWithKey(t)
+	taker.network = newTestNetworkManager()
+	taker.network.commandCh = make(chan NetworkCommand, 10)
+	details := &OrderDetails{OrderID: "order_1", Amount: 10000, MinPrice: 450, MaxPrice: 470, Stablecoin: StablecoinUSDC}
+
+	taker.handleMessagePayload("maker-peer", "encrypted_order_details", encryptedDetailsPayload(t, taker, details, false), nil)
+
+	got, ok := taker.orderDetails["order_1"]
+	if !ok || got.Amount != 10000 || got.MaxPrice != 470 {
+		t.Fatalf("Expected decrypted details stored, got %+v", got)
+	}
+	if err := taker.OrderDetailsError("order_1"); err != nil {
+		t.Errorf("Expected no details error, got %v", err)
+	}
+}
+
+func TestTamperedOrderDetailsReportDecryptionError(t *testing.T) {
+	taker := newTestAppWithKey(t)
+	details := &OrderDetails{OrderID: "order_1", Amount: 10000}
+
+	var encMsg EncryptedOrderDetailsMessage
+	if err := json.Unmarshal(encryptedDetailsPayload(t, taker, details, true), &encMsg); err != nil {
+		t.Fatalf("Failed to decode payload: %v", err)
+	}
+	if _, err := taker.decryptOrderDetails(&encMsg); !errors.Is(err, ErrDecryption) {
+		t.Fatalf("Expected ErrDecryption for tampered ciphertext, got %v", err)
+	}
+
+	// Details encrypted to someone else fail the same way, and the failure is kept for the UI
+	other := newTestAppWithKey(t)
+	taker.handleMessagePayload("maker-peer", "encrypted_order_details", encryptedDetailsPayload(t, other, details, false), nil)
+	if _, ok := taker.orderDetails["order_1"]; ok {
+		t.Fatal("Undecryptable details must not be stored")
+	}
+	if err := taker.OrderDetailsError("order_1"); !errors.Is(err, ErrDecryption) {
+		t.Errorf("Expected ErrDecryption recorded for order_1, got %v", err)
+	}
+}
+
+func TestSelectDetailsPeerDeterministic(t *testing.T) {
+	app := newTestApp()
+	orderID := OrderID("order_3")
+
+	first, ok := app.selectDetailsPeer(orderID, []PeerID{"peer-c", "peer-a", "peer-b"})
+	if !ok {
+		t.Fatal("Expected a peer to be selected")
+	}
+	for i := 0; i < 10; i++ {
+		// Same peer set in a different order must yield the same choice
+		got, _ := app.selectDetailsPeer(orderID, []PeerID{"peer-b", "peer-c", "peer-a"})
+		if got != first {
+			t.Fatalf("Selection not deterministic: got %s, previously %s", got, first)
+		}
+	}
+	if first != "peer-a" {
+		t.Errorf("Expected lowest peer ID, got %s", first)
+	}
+
+	// Once the announcement source is known it is preferred
+	app.orderSources[orderID] = "peer-c"
+	if got, _ := app.selectDetailsPeer(orderID, []PeerID{"peer-a", "peer-b", "peer-c"}); got != "peer-c" {
+		t.Errorf("Expected announcement source peer-c, got %s", got)
+	}
+
+	// The maker beats a relay that only forwarded the announcement
+	app.orders[orderID] = &OrderAnnouncement{OrderID: orderID, MakerID: "peer-b"}
+	if got, _ := app.selectDetailsPeer(orderID, []PeerID{"peer-a", "peer-b", "peer-c"}); got != "peer-b" {
+		t.Errorf("Expected maker peer-b over relay peer-c, got %s", got)
+	}
+	if got, _ := app.selectDetailsPeer(orderID, []PeerID{"peer-a", "peer-c"}); got != "peer-c" {
+		t.Errorf("Expected relay peer-c while the maker is not connected, got %s", got)
+	}
+}
+
+func TestPlaintextOrderDetailsFallbackIsSigned(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	taker := newTestAppWithKey(t)
+	for _, app := range []*BlackTraceApp{maker, taker} {
+		app.network = newTestNetworkManager()
+		app.network.commandCh = make(chan NetworkCommand, 10)
+	}
+
+	order := &OrderAnnouncement{OrderID: "order_1", Stablecoin: StablecoinUSDC, MakerID: "maker-peer"}
+	if err := order.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign order: %v", err)
+	}
+	maker.orders["order_1"] = order
+	maker.orderDetails["order_1"] = &OrderDetails{OrderID: "order_1", Amount: 10000, MinPrice: 450, MaxPrice: 470}
+	taker.orders["order_1"] = order
+
+	// The maker has no cached key for the taker, so the details go out unencrypted
+	maker.sendDetailsTo("taker-peer", "order_1")
+	var cmd NetworkCommand
+	select {
+	case cmd = <-maker.network.commandCh:
+	default:
+		t.Fatal("Expected the details to be sent")
+	}
+	if _, err := UnmarshalSignedMessage(cmd.Data); err != nil {
+		t.Fatalf("Plaintext details must be signed: %v", err)
+	}
+
+	taker.handleMessage("maker-peer", cmd.Data)
+	got, ok := taker.orderDetails["order_1"]
+	if !ok || got.Amount != 10000 || got.MaxPrice != 470 {
+		t.Fatalf("Expected the taker to accept the maker's signed details, got %+v", got)
+	}
+	if err := taker.OrderDetailsError("order_1"); err != nil {
+		t.Errorf("Expected no details error, got %v", err)
+	}
+}
+
+// commitOwnOrder creates one of our orders committed to amount, as createOrder does
+func commitOwnOrder(app *BlackTraceApp, orderID OrderID, amount uint64, expiry int64) *OrderAnnouncement {
+	announcement := &OrderAnnouncement{
+		OrderID:         orderID,
+		ProofCommitment: app.commitToOrder(orderID, amount),
+		Expiry:          expiry,
+	}
+	app.orders[orderID] = announcement
+	app.orderDetails[orderID] = &OrderDetails{OrderID: orderID, Amount: amount}
+	app.markOwnedOrder(app.orderDetails[orderID])
+	return announcement
+}
+
+func TestCommitmentOpeningRecomputedFromDerivedSalt(t *testing.T) {
+	app := newTestAppWithKey(t)
+	orderID := OrderID("order_4")
+	announcement := commitOwnOrder(app, orderID, 25000, time.Now().Add(time.Hour).Unix())
+
+	opening, ok := app.CommitmentOpening(orderID)
+	if !ok {
+		t.Fatal("Opening should be recomputable for a live order")
+	}
+	if err := VerifyCommitment(announcement.ProofCommitment, orderID, opening, 25000); err != nil {
+		t.Errorf("Recomputed opening does not verify against announcement: %v", err)
+	}
+
+	// Recomputed the same way every time, and still after a key rotation
+	again, _ := app.CommitmentOpening(orderID)
+	if !bytes.Equal(again.Salt, opening.Salt) {
+		t.Error("Derived salt should be reproducible")
+	}
+	rotated := newTestAppWithKey(t)
+	if _, err := app.rotateTo(rotated.cryptoMgr, time.Now()); err != nil {
+		t.Fatalf("Failed to rotate keys: %v", err)
+	}
+	if afterRotation, ok := app.CommitmentOpening(orderID); !ok || !bytes.Equal(afterRotation.Salt, opening.Salt) {
+		t.Error("Opening should still recompute after a key rotation")
+	}
+}
+
+func TestCommitmentOpeningUnavailableAfterExpiry(t *testing.T) {
+	app := newTestAppWithKey(t)
+	orderID := OrderID("order_5")
+	commitOwnOrder(app, orderID, 25000, time.Now().Add(-time.Minute).Unix())
+
+	app.expireOrders(time.Now())
+
+	if _, ok := app.CommitmentOpening(orderID); ok {
+		t.Error("Opening should not be available after expiry")
+	}
+}
+
+func TestCommitmentOpeningUnavailableAfterCancel(t *testing.T) {
+	app := newTestAppWithKey(t)
+	orderID := OrderID("order_6")
+	commitOwnOrder(app, orderID, 25000, 0)
+
+	if err := app.CancelOrder(orderID); err != nil {
+		t.Fatalf("Failed to cancel order: %v", err)
+	}
+	if _, ok := app.CommitmentOpening(orderID); ok {
+		t.Error("Opening should not be available after cancellation")
+	}
+}
+
+func TestProposalCancelledWhenCounterpartyUnreachable(t *testing.T) {
+	app := newTestApp()
+	orderID := OrderID("order_7")
+	proposalID := NewProposalID(orderID)
+	app.storeProposalLocked(&Proposal{ProposalID: proposalID, OrderID: orderID, Status: ProposalStatusPending})
+
+	// The maker dropped while the proposal was in flight and every retry failed
+	data, err := MarshalMessage("encrypted_proposal", EncryptedProposalMessage{OrderID: orderID})
+	if err != nil {
+		t.Fatalf("Failed to marshal message: %v", err)
+	}
+	app.handleNetworkEvent(NetworkEvent{Type: "send_failed", From: "maker", Data: data})
+
+	proposal := app.proposals[proposalID]
+	if proposal.Status != ProposalStatusCancelled {
+		t.Errorf("Expected proposal to be cancelled, got %s", proposal.Status)
+	}
+	if proposal.CancelReason != CancelReasonCounterpartyDisconnected {
+		t.Errorf("Expected reason %s, got %q", CancelReasonCounterpartyDisconnected, proposal.CancelReason)
+	}
+}
+
+func TestProposalStorageAllocationsStayFlat(t *testing.T) {
+	log.SetOutput(io.Discard)
+	defer log.SetOutput(os.Stderr)
+
+	app := newTestApp()
+	taker := newTestAppWithKey(t)
+	next := 0
+	receive := func() {
+		// The limit is per peer and order, so thousands of proposals on one order come from
+		// distinct takers; each still passes the signature check and the rate limiter
+		from := PeerID(fmt.Sprintf("taker-%d", next))
+		proposal := Proposal{
+			ProposalID: ProposalID(fmt.Sprintf("order_1_proposal_%d", next)),
+			OrderID:    "order_1",
+			Price:      45,
+			Amount:     100000000,
+			ProposerID: from,
+			Status:     ProposalStatusPending,
+		}
+		if err := proposal.Sign(taker.cryptoMgr); err != nil {
+			t.Fatalf("Failed to sign proposal: %v", err)
+		}
+		payload, _ := json.Marshal(proposal)
+		app.handleMessagePayload(from, "proposal", payload, taker.cryptoMgr.GetPublicKey())
+		next++
+	}
+
+	// Another order's negotiation, kept apart in the per-order index
+	app.proposalsMux.Lock()
+	app.storeProposalLocked(&Proposal{ProposalID: "order_2_proposal", OrderID: "order_2", ProposerID: "taker-x", Status: ProposalStatusPending})
+	app.proposalsMux.Unlock()
+
+	// Proposals are stored once by ID, so each new one costs the same however many came before
+	early := testing.AllocsPerRun(200, receive)
+	for i := 0; i < 5000; i++ {
+		receive()
+	}
+	late := testing.AllocsPerRun(200, receive)
+
+	if late > 2*early {
+		t.Errorf("Allocations per proposal grew with negotiation size: %.1f early, %.1f after 5000", early, late)
+	}
+	if got := len(app.ListProposals("order_1")); got != next {
+		t.Errorf("Expected %d stored proposals, got %d", next, got)
+	}
+	if got := len(app.ListProposals("order_2")); got != 1 {
+		t.Errorf("Expected 1 proposal on the quiet order, got %d", got)
+	}
+}
+
+func TestProposalBurstThrottled(t *testing.T) {
+	limiter := newProposalLimiter()
+	start := time.Now()
+
+	// A burst is admitted up to the bucket size, then throttled
+	admitted := 0
+	for i := 0; i < 10; i++ {
+		if ok, _ := limiter.allow("taker-peer", "order_1", start); ok {
+			admitted++
+		}
+	}
+	if admitted != int(proposalBurst) {
+		t.Errorf("Expected %d proposals admitted from a burst, got %d", int(proposalBurst), admitted)
+	}
+
+	// Other orders and other peers have their own budget
+	if ok, _ := limiter.allow("taker-peer", "order_2", start); !ok {
+		t.Error("Proposal on another order should not be throttled")
+	}
+	if ok, _ := limiter.allow("other-peer", "order_1", start); !ok {
+		t.Error("Proposal from another peer should not be throttled")
+	}
+
+	// Tokens refill over time
+	if ok, _ := limiter.allow("taker-peer", "order_1", start.Add(time.Second)); !ok {
+		t.Error("Proposal should be admitted after the bucket refills")
+	}
+
+	// Sustained abuse cancels the peer's pending negotiation on the order
+	app := newTestApp()
+	app.storeProposalLocked(&Proposal{ProposalID: "p1", OrderID: "order_1", ProposerID: "taker-peer", Status: ProposalStatusPending})
+	for i := 0; i < int(proposalBurst)+proposalAbuseThreshold; i++ {
+		app.admitProposal("taker-peer", "order_1")
+	}
+	if proposal := app.proposals["p1"]; proposal.Status != ProposalStatusCancelled || proposal.CancelReason != CancelReasonRateLimited {
+		t.Errorf("Expected negotiation cancelled for rate limiting, got %s (%q)", proposal.Status, proposal.CancelReason)
+	}
+}
+
+func TestLateJoiningPeerDiscoversRebroadcastOrder(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	maker.network = newTestNetworkManager()
+	maker.network.commandCh = make(chan NetworkCommand, 10)
+
+	now := time.Now()
+	order := &OrderAnnouncement{OrderID: "order_live", Stablecoin: StablecoinUSDC, Timestamp: now.Unix(), Expiry: now.Add(time.Hour).Unix()}
+	if err := order.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign order: %v", err)
+	}
+	expired := &OrderAnnouncement{OrderID: "order_expired", Stablecoin: StablecoinUSDC, Expiry: now.Add(-time.Minute).Unix()}
+	for _, o := range []*OrderAnnouncement{order, expired} {
+		maker.orders[o.OrderID] = o
+		maker.markOwnedOrder(&OrderDetails{OrderID: o.OrderID})
+	}
+	// Someone else's order is not ours to re-announce
+	maker.orders["order_foreign"] = &OrderAnnouncement{OrderID: "order_foreign", Stablecoin: StablecoinUSDC}
+
+	if n := maker.rebroadcastOwnedOrders(now); n != 1 {
+		t.Fatalf("Expected 1 order re-broadcast, got %d", n)
+	}
+	cmd := <-maker.network.commandCh
+	if cmd.Type != "broadcast_coin" || cmd.Coin != StablecoinUSDC {
+		t.Fatalf("Unexpected command: %s/%s", cmd.Type, cmd.Coin)
+	}
+
+	// The taker connected after the original announcement and never synced
+	taker := newTestApp()
+	taker.handleMessage("maker-peer", cmd.Data)
+	received, ok := taker.orders[order.OrderID]
+	if !ok {
+		t.Fatal("Late-joining peer should learn the order from the rebroadcast")
+	}
+
+	// Hearing the same announcement again via another peer is a no-op apart from its source
+	taker.handleMessage("relay-peer", cmd.Data)
+	if taker.orders[order.OrderID] != received {
+		t.Error("Repeated announcement should not replace the stored order")
+	}
+	if taker.orderSources[order.OrderID] != "relay-peer" {
+		t.Errorf("Order source should be refreshed, got %s", taker.orderSources[order.OrderID])
+	}
+}
+
+func TestMakerStuckInDetailsRevealedTimesOut(t *testing.T) {
+	app := newTestApp()
+	app.network = newTestNetworkManager()
+	app.network.commandCh = make(chan NetworkCommand, 10)
+
+	orderID := OrderID("order_1")
+	app.orders[orderID] = &OrderAnnouncement{OrderID: orderID, Stablecoin: StablecoinUSDC}
+	details := &OrderDetails{OrderID: orderID, Amount: 10000, MinPrice: 450, MaxPrice: 470}
+	app.orderDetails[orderID] = details
+	app.markOwnedOrder(details)
+
+	requestDetails := func(from PeerID) {
+		payload, _ := json.Marshal(orderID)
+		app.handleMessagePayload(from, "order_request", payload, nil)
+		if cmd := <-app.network.commandCh; cmd.Type != "send" || cmd.To != from {
+			t.Fatalf("Expected details sent to %s, got %s to %s", from, cmd.Type, cmd.To)
+		}
+	}
+	requestDetails("silent-taker")
+	requestDetails("active-taker")
+
+	// The active taker's proposal moves it on to price discovery
+	signed := &Proposal{ProposalID: NewProposalID(orderID), OrderID: orderID, Price: 460, ProposerID: "active-taker", Status: ProposalStatusPending}
+	activeTaker := newTestAppWithKey(t)
+	if err := signed.Sign(activeTaker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign proposal: %v", err)
+	}
+	proposal, _ := json.Marshal(signed)
+	app.handleMessagePayload("active-taker", "proposal", proposal, activeTaker.cryptoMgr.GetPublicKey())
+
+	now := time.Now()
+	reoffered, timedOut := app.sweepDetailReveals(now.Add(detailsRevealTimeout / 2))
+	if len(reoffered) != 0 || len(timedOut) != 0 {
+		t.Fatal("Nothing should happen inside the window")
+	}
+
+	// First window passes: details are re-offered to the silent taker only
+	reoffered, timedOut = app.sweepDetailReveals(now.Add(detailsRevealTimeout + time.Second))
+	if len(reoffered) != 1 || reoffered[0].peer != "silent-taker" || len(timedOut) != 0 {
+		t.Fatalf("Expected a re-offer to the silent taker, got %v / %v", reoffered, timedOut)
+	}
+	if cmd := <-app.network.commandCh; cmd.Type != "send" || cmd.To != "silent-taker" {
+		t.Fatalf("Expected details re-sent, got %s to %s", cmd.Type, cmd.To)
+	}
+
+	// Still no proposal after the re-offer: the negotiation times out
+	reoffered, timedOut = app.sweepDetailReveals(now.Add(2*detailsRevealTimeout + 2*time.Second))
+	if len(reoffered) != 0 || len(timedOut) != 1 || timedOut[0].peer != "silent-taker" {
+		t.Fatalf("Expected the silent taker to time out, got %v / %v", reoffered, timedOut)
+	}
+	if len(app.detailReveals) != 0 {
+		t.Errorf("Timed out session should be forgotten, %d left", len(app.detailReveals))
+	}
+}
+
+func TestMarketSnapshotOnlyCountsRequestedCoin(t *testing.T) {
+	app := newTestApp()
+
+	if snapshot := app.MarketSnapshot(StablecoinUSDC); snapshot.OrderCount != 0 || snapshot.BestBid != 0 || snapshot.BestAsk != 0 || snapshot.Mid != 0 {
+		t.Errorf("Empty book should give a zero snapshot, got %+v", snapshot)
+	}
+
+	expiry := time.Now().Add(time.Hour).Unix()
+	for _, o := range []struct {
+		id         OrderID
+		orderType  OrderType
+		coin       StablecoinType
+		min, max   uint64
+		amount     uint64
+		hasDetails bool
+	}{
+		{"usdc_sell_1", OrderTypeSell, StablecoinUSDC, 455, 470, 100, true},
+		{"usdc_sell_2", OrderTypeSell, StablecoinUSDC, 452, 460, 200, true},
+		{"usdc_buy", OrderTypeBuy, StablecoinUSDC, 440, 448, 50, true},
+		{"usdc_hidden", OrderTypeSell, StablecoinUSDC, 0, 0, 0, false},
+		{"usdt_sell", OrderTypeSell, StablecoinUSDT, 300, 310, 999, true},
+		{"usdt_buy", OrderTypeBuy, StablecoinUSDT, 500, 600, 999, true},
+	} {
+		app.orders[o.id] = &OrderAnnouncement{OrderID: o.id, OrderType: o.orderType, Stablecoin: o.coin, Expiry: expiry}
+		if o.hasDetails {
+			app.orderDetails[o.id] = &OrderDetails{OrderID: o.id, Amount: o.amount, MinPrice: o.min, MaxPrice: o.max}
+		}
+	}
+	// Expired orders are not part of the book
+	app.orders["usdc_expired"] = &OrderAnnouncement{OrderID: "usdc_expired", OrderType: OrderTypeSell, Stablecoin: StablecoinUSDC, Expiry: 1}
+	app.orderDetails["usdc_expired"] = &OrderDetails{OrderID: "usdc_expired", Amount: 1000, MinPrice: 1, MaxPrice: 2}
+
+	snapshot := app.MarketSnapshot(StablecoinUSDC)
+	if snapshot.OrderCount != 4 || snapshot.VisibleOrders != 3 {
+		t.Errorf("Expected 4 orders (3 visible), got %d (%d)", snapshot.OrderCount, snapshot.VisibleOrders)
+	}
+	if snapshot.BestAsk != 452 || snapshot.BestBid != 448 || snapshot.Mid != 450 {
+		t.Errorf("Unexpected prices: bid %d ask %d mid %d", snapshot.BestBid, snapshot.BestAsk, snapshot.Mid)
+	}
+	if snapshot.TotalAmount != 350 {
+		t.Errorf("Expected total amount 350, got %d", snapshot.TotalAmount)
+	}
+}
+
+func TestContentOrderIDMatchesAcrossNodes(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	announcement := &OrderAnnouncement{
+		OrderID:         "order_1",
+		ProofCommitment: bytes.Repeat([]byte{0x11}, 32),
+		Timestamp:       1700000000,
+	}
+	if _, ok := announcement.ContentID(); ok {
+		t.Error("Unsigned announcement should have no content ID")
+	}
+	if err := announcement.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+
+	// Two nodes deriving the ID from the same content agree on it
+	first := OrderIDFromContent(announcement.ProofCommitment, announcement.MakerPubKey, announcement.Timestamp)
+	second := OrderIDFromContent(bytes.Repeat([]byte{0x11}, 32), maker.cryptoMgr.GetPublicKey(), 1700000000)
+	if first != second {
+		t.Fatalf("Content IDs differ: %s vs %s", first, second)
+	}
+	if other := OrderIDFromContent(announcement.ProofCommitment, announcement.MakerPubKey, 1700000001); other == first {
+		t.Error("Different content should derive a different ID")
+	}
+
+	// The same signed content arriving under another ID collapses into the order already held
+	node := newTestApp()
+	payload, _ := json.Marshal(announcement)
+	node.handleMessagePayload("relay-a", "order_announcement", payload, nil)
+
+	relayed := *announcement
+	relayed.OrderID = "order_relayed"
+	if err := relayed.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+	payload, _ = json.Marshal(&relayed)
+	node.handleMessagePayload("relay-b", "order_announcement", payload, nil)
+
+	if len(node.orders) != 1 || node.orders["order_1"] == nil {
+		t.Errorf("Expected only order_1 to be held, got %d orders", len(node.orders))
+	}
+}
+
+func TestOrderByCommitmentIndex(t *testing.T) {
+	app := newTestApp()
+	first := &OrderAnnouncement{OrderID: "order_1", ProofCommitment: []byte{1, 2, 3}}
+	second := &OrderAnnouncement{OrderID: "order_2", ProofCommitment: []byte{4, 5, 6}}
+	app.ordersMux.Lock()
+	app.storeOrderLocked(first)
+	app.storeOrderLocked(second)
+	app.ordersMux.Unlock()
+
+	if order, ok := app.OrderByCommitment([]byte{4, 5, 6}); !ok || order.OrderID != "order_2" {
+		t.Errorf("Expected order_2 for its commitment, got %v", order)
+	}
+	if _, ok := app.OrderByCommitment([]byte{9, 9, 9}); ok {
+		t.Error("Unknown commitment should not match an order")
+	}
+
+	// Removing an order drops its index entry and leaves the others alone
+	if err := app.CancelOrder("order_1"); err != nil {
+		t.Fatalf("Failed to cancel: %v", err)
+	}
+	if _, ok := app.OrderByCommitment([]byte{1, 2, 3}); ok {
+		t.Error("Removed order should no longer be found by commitment")
+	}
+	if _, ok := app.OrderByCommitment([]byte{4, 5, 6}); !ok {
+		t.Error("Remaining order should still be indexed")
+	}
+
+	// Replacing an order re-indexes it under the new commitment
+	app.ordersMux.Lock()
+	app.storeOrderLocked(&OrderAnnouncement{OrderID: "order_2", ProofCommitment: []byte{7, 8, 9}})
+	app.ordersMux.Unlock()
+	if _, ok := app.OrderByCommitment([]byte{4, 5, 6}); ok {
+		t.Error("Stale commitment should not be indexed after replacement")
+	}
+	if len(app.ordersByCommitment) != len(app.orders) {
+		t.Errorf("Index has %d entries for %d orders", len(app.ordersByCommitment), len(app.orders))
+	}
+}
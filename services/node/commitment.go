@@ -0,0 +1,175 @@
+package node
+
+import (
+	"bytes"
+	"crypto/rand"
+	"crypto/sha256"
+	"crypto/subtle"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"io"
+
+	"golang.org/x/crypto/blake2b"
+	"golang.org/x/crypto/hkdf"
+)
+
+// CommitmentOpening reveals the values behind a liquidity commitment
+type CommitmentOpening struct {
+	Amount uint64 `json:"amount"`
+	Salt   []byte `json:"salt"` // 32-byte salt, random or from DeriveSalt
+}
+
+// Zeroize overwrites the opening's secret values in place
+func (o *CommitmentOpening) Zeroize() {
+	for i := range o.Salt {
+		o.Salt[i] = 0
+	}
+	o.Amount = 0
+}
+
+// ProofScheme names the commitment scheme behind an announcement's proof_commitment
+type ProofScheme string
+
+const (
+	ProofSchemeHash       ProofScheme = "hash"        // Blake2b commitment hash (ComputeCommitmentHash)
+	ProofSchemePedersen   ProofScheme = "pedersen"    // Compressed Pedersen commitment to the amount
+	ProofSchemeRangeProof ProofScheme = "range_proof" // Bulletproof range proof over a Pedersen commitment
+)
+
+// ErrUnsupportedProofScheme is returned when an announcement's proof scheme cannot be verified by this node
+var ErrUnsupportedProofScheme = errors.New("unsupported proof scheme")
+
+// Scheme returns the announcement's proof scheme. Announcements from before proof_scheme existed
+// carry a commitment hash.
+func (a *OrderAnnouncement) Scheme() ProofScheme {
+	if a.ProofScheme == "" {
+		return ProofSchemeHash
+	}
+	return a.ProofScheme
+}
+
+// VerifyOpening checks a challenged opening against the announcement's proof commitment,
+// dispatching on its proof scheme. Only commitment hashes can be opened by the node; Pedersen
+// and range-proof commitments are verified by the crypto library.
+func (a *OrderAnnouncement) VerifyOpening(opening *CommitmentOpening, minAmount uint64, nonce, response []byte) error {
+	switch scheme := a.Scheme(); scheme {
+	case ProofSchemeHash:
+		return VerifyChallengedOpening(a.ProofCommitment, a.OrderID, opening, minAmount, nonce, response)
+	case ProofSchemePedersen, ProofSchemeRangeProof:
+		return fmt.Errorf("%w: %s commitments cannot be opened by the node (order %s)", ErrUnsupportedProofScheme, scheme, a.OrderID)
+	default:
+		return fmt.Errorf("%w %q (order %s)", ErrUnsupportedProofScheme, scheme, a.OrderID)
+	}
+}
+
+// ChallengeNonceSize is the length of the verifier's liquidity challenge nonce
+const ChallengeNonceSize = 32
+
+// LiquidityChallengeMessage asks the maker to open an order's commitment bound to a fresh nonce
+type LiquidityChallengeMessage struct {
+	OrderID OrderID `json:"order_id"`
+	Nonce   []byte  `json:"nonce"` // Verifier-chosen, ChallengeNonceSize bytes
+}
+
+// LiquidityOpeningMessage is sent by the maker so a taker can verify the order's commitment
+type LiquidityOpeningMessage struct {
+	OrderID  OrderID           `json:"order_id"`
+	Opening  CommitmentOpening `json:"opening"`
+	Response []byte            `json:"response"` // ComputeChallengeResponse over the opening and the taker's nonce
+}
+
+// ComputeCommitmentHash computes Blake2b-512(amount_be || salt || order_id) truncated to 32 bytes.
+// Matches compute_commitment_hash in the Rust crypto library; the order ID binds the commitment to its order.
+func ComputeCommitmentHash(orderID OrderID, amount uint64, salt []byte) []byte {
+	var amountBytes [8]byte
+	binary.BigEndian.PutUint64(amountBytes[:], amount)
+
+	h, _ := blake2b.New512(nil)
+	h.Write(amountBytes[:])
+	h.Write(salt)
+	h.Write([]byte(orderID))
+	return h.Sum(nil)[:32]
+}
+
+// GenerateCommitment commits to an order's amount with a fresh random salt
+func GenerateCommitment(orderID OrderID, amount uint64) ([]byte, *CommitmentOpening, error) {
+	salt := make([]byte, 32)
+	if _, err := rand.Read(salt); err != nil {
+		return nil, nil, fmt.Errorf("failed to generate salt: %w", err)
+	}
+
+	opening := &CommitmentOpening{Amount: amount, Salt: salt}
+	return ComputeCommitmentHash(orderID, amount, salt), opening, nil
+}
+
+// DeriveSalt derives an order's 32-byte commitment salt from the node's secret seed with
+// HKDF-SHA256, so the maker can recompute an opening instead of storing it. Without the seed
+// the salt is unpredictable; different orders get unrelated salts.
+func DeriveSalt(nodeSeed []byte, orderID OrderID) []byte {
+	salt := make([]byte, 32)
+	kdf := hkdf.New(sha256.New, nodeSeed, []byte(orderID), []byte("blacktrace-commitment-salt"))
+	io.ReadFull(kdf, salt) // Cannot fail: 32 bytes is far below HKDF's output limit
+	return salt
+}
+
+// DeriveCommitment commits to an order's amount with the salt derived for it from nodeSeed
+func DeriveCommitment(nodeSeed []byte, orderID OrderID, amount uint64) ([]byte, *CommitmentOpening) {
+	salt := DeriveSalt(nodeSeed, orderID)
+	return ComputeCommitmentHash(orderID, amount, salt), &CommitmentOpening{Amount: amount, Salt: salt}
+}
+
+// VerifyCommitment checks an opening against an order's commitment hash and a minimum amount
+func VerifyCommitment(commitment []byte, orderID OrderID, opening *CommitmentOpening, minAmount uint64) error {
+	if len(commitment) != 32 {
+		return fmt.Errorf("invalid commitment length: %d bytes (expected 32)", len(commitment))
+	}
+	if len(opening.Salt) != 32 {
+		return fmt.Errorf("invalid salt length: %d bytes (expected 32)", len(opening.Salt))
+	}
+	if !bytes.Equal(ComputeCommitmentHash(orderID, opening.Amount, opening.Salt), commitment) {
+		return fmt.Errorf("opening does not match commitment")
+	}
+	if opening.Amount < minAmount {
+		return fmt.Errorf("committed amount %d is below required %d", opening.Amount, minAmount)
+	}
+	return nil
+}
+
+// NewChallengeNonce returns a random nonce for a liquidity challenge
+func NewChallengeNonce() ([]byte, error) {
+	nonce := make([]byte, ChallengeNonceSize)
+	if _, err := rand.Read(nonce); err != nil {
+		return nil, fmt.Errorf("failed to generate challenge nonce: %w", err)
+	}
+	return nonce, nil
+}
+
+// ComputeChallengeResponse computes Blake2b-256(amount_be || salt || order_id || nonce),
+// binding an opening to the verifier's challenge nonce
+func ComputeChallengeResponse(orderID OrderID, opening *CommitmentOpening, nonce []byte) []byte {
+	var amountBytes [8]byte
+	binary.BigEndian.PutUint64(amountBytes[:], opening.Amount)
+
+	h, _ := blake2b.New256(nil)
+	h.Write(amountBytes[:])
+	h.Write(opening.Salt)
+	h.Write([]byte(orderID))
+	h.Write(nonce)
+	return h.Sum(nil)
+}
+
+// VerifyChallengedOpening checks an opening against the commitment as VerifyCommitment does,
+// and that the response was computed for this opening and the verifier's nonce
+func VerifyChallengedOpening(commitment []byte, orderID OrderID, opening *CommitmentOpening, minAmount uint64, nonce, response []byte) error {
+	if len(nonce) != ChallengeNonceSize {
+		return fmt.Errorf("invalid challenge nonce length: %d bytes (expected %d)", len(nonce), ChallengeNonceSize)
+	}
+	if err := VerifyCommitment(commitment, orderID, opening, minAmount); err != nil {
+		return err
+	}
+	if subtle.ConstantTimeCompare(ComputeChallengeResponse(orderID, opening, nonce), response) != 1 {
+		return fmt.Errorf("challenge response does not match nonce")
+	}
+	return nil
+}
@@ -0,0 +1,134 @@
+package node
+
+import (
+	"bytes"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"testing"
+)
+
+func TestCommitmentBoundToOrder(t *testing.T) {
+	commitment, opening, err := GenerateCommitment("order_A", 10000)
+	if err != nil {
+		t.Fatalf("Failed to generate commitment: %v", err)
+	}
+
+	if err := VerifyCommitment(commitment, "order_A", opening, 10000); err != nil {
+		t.Fatalf("Commitment should verify for its own order: %v", err)
+	}
+	if err := VerifyCommitment(commitment, "order_B", opening, 10000); err == nil {
+		t.Error("Commitment for order_A must not verify for order_B")
+	}
+}
+
+func TestDerivedSaltReproducibleAndUniquePerOrder(t *testing.T) {
+	seed := bytes.Repeat([]byte{0x42}, 32)
+
+	salt := DeriveSalt(seed, "order_A")
+	if len(salt) != 32 {
+		t.Fatalf("Expected a 32-byte salt, got %d bytes", len(salt))
+	}
+	if !bytes.Equal(DeriveSalt(seed, "order_A"), salt) {
+		t.Error("Same seed and order should derive the same salt")
+	}
+	if bytes.Equal(DeriveSalt(seed, "order_B"), salt) {
+		t.Error("Different orders should derive different salts")
+	}
+	if bytes.Equal(DeriveSalt(bytes.Repeat([]byte{0x43}, 32), "order_A"), salt) {
+		t.Error("Another node's seed should derive a different salt")
+	}
+
+	commitment, opening := DeriveCommitment(seed, "order_A", 10000)
+	if err := VerifyCommitment(commitment, "order_A", opening, 10000); err != nil {
+		t.Errorf("Derived commitment should verify: %v", err)
+	}
+}
+
+// Same vector as the Rust crypto library's test_commitment_hash_vector
+func TestComputeCommitmentHashMatchesRust(t *testing.T) {
+	hash := ComputeCommitmentHash("order_A", 1, make([]byte, 32))
+	expected := "0926310ab221e21caf61ca6e0ed3249a0c384a1c718e3545480ec2bc2b7ccb0a"
+	if hex.EncodeToString(hash) != expected {
+		t.Errorf("Commitment hash mismatch: got %x, want %s", hash, expected)
+	}
+}
+
+func TestReplayedOpeningFailsForDifferentNonce(t *testing.T) {
+	commitment, opening, err := GenerateCommitment("order_A", 10000)
+	if err != nil {
+		t.Fatalf("Failed to generate commitment: %v", err)
+	}
+	first, _ := NewChallengeNonce()
+	second, _ := NewChallengeNonce()
+	response := ComputeChallengeResponse("order_A", opening, first)
+
+	if err := VerifyChallengedOpening(commitment, "order_A", opening, 10000, first, response); err != nil {
+		t.Fatalf("Response should verify for its own nonce: %v", err)
+	}
+	if err := VerifyChallengedOpening(commitment, "order_A", opening, 10000, second, response); err == nil {
+		t.Error("Opening and response replayed for a different nonce must not verify")
+	}
+
+	// The taker only holds its latest nonce, so a response to an earlier challenge is refused
+	app := newTestApp()
+	app.orders["order_A"] = &OrderAnnouncement{OrderID: "order_A", ProofCommitment: commitment}
+	app.orderDetails["order_A"] = &OrderDetails{OrderID: "order_A", Amount: 10000}
+	app.liquidityChallenges["order_A"] = second
+	if err := app.verifyLiquidityOpening("order_A", opening, response); err == nil {
+		t.Error("Replayed response should fail against the outstanding challenge")
+	}
+	if app.IsLiquidityVerified("order_A") {
+		t.Error("Order should not be marked verified by a replayed response")
+	}
+}
+
+func TestProofSchemeRoundTrip(t *testing.T) {
+	for _, scheme := range []ProofScheme{ProofSchemeHash, ProofSchemePedersen, ProofSchemeRangeProof} {
+		original := &OrderAnnouncement{
+			OrderID:         "order_A",
+			ProofCommitment: bytes.Repeat([]byte{0x5a}, 48),
+			ProofScheme:     scheme,
+		}
+		data, err := json.Marshal(original)
+		if err != nil {
+			t.Fatalf("Failed to marshal %s announcement: %v", scheme, err)
+		}
+
+		var decoded OrderAnnouncement
+		if err := json.Unmarshal(data, &decoded); err != nil {
+			t.Fatalf("Failed to unmarshal %s announcement: %v", scheme, err)
+		}
+		if decoded.Scheme() != scheme || !bytes.Equal(decoded.ProofCommitment, original.ProofCommitment) {
+			t.Errorf("%s announcement did not round-trip: got %s %x", scheme, decoded.Scheme(), decoded.ProofCommitment)
+		}
+	}
+}
+
+func TestAnnouncementWithoutSchemeVerifiesAsHash(t *testing.T) {
+	commitment, opening, err := GenerateCommitment("order_A", 10000)
+	if err != nil {
+		t.Fatalf("Failed to generate commitment: %v", err)
+	}
+
+	// An announcement from a node that predates proof_scheme
+	legacy, _ := json.Marshal(map[string]interface{}{"order_id": "order_A", "proof_commitment": commitment})
+	var announcement OrderAnnouncement
+	if err := json.Unmarshal(legacy, &announcement); err != nil {
+		t.Fatalf("Failed to unmarshal legacy announcement: %v", err)
+	}
+	if announcement.Scheme() != ProofSchemeHash {
+		t.Fatalf("Expected legacy announcement to default to %s, got %s", ProofSchemeHash, announcement.Scheme())
+	}
+
+	nonce, _ := NewChallengeNonce()
+	response := ComputeChallengeResponse("order_A", opening, nonce)
+	if err := announcement.VerifyOpening(opening, 10000, nonce, response); err != nil {
+		t.Fatalf("Hash commitment should verify: %v", err)
+	}
+
+	announcement.ProofScheme = ProofSchemePedersen
+	if err := announcement.VerifyOpening(opening, 10000, nonce, response); !errors.Is(err, ErrUnsupportedProofScheme) {
+		t.Errorf("Expected ErrUnsupportedProofScheme for a Pedersen commitment, got %v", err)
+	}
+}
@@ -16,16 +16,20 @@ import (
 	"golang.org/x/crypto/hkdf"
 )
 
+// ErrDecryption is returned when a ciphertext fails authentication: it was encrypted
+// to a different key or altered in transit
+var ErrDecryption = errors.New("decryption failed")
+
 // CryptoManager handles ECIES encryption and ECDSA signatures
 type CryptoManager struct {
-	privateKey *ecdsa.PrivateKey
+	privateKey Secret[*ecdsa.PrivateKey] // Signs messages and decrypts ECIES payloads
 	publicKey  *ecdsa.PublicKey
 }
 
 // NewCryptoManager creates a new crypto manager with the given private key
 func NewCryptoManager(privateKey *ecdsa.PrivateKey) *CryptoManager {
 	return &CryptoManager{
-		privateKey: privateKey,
+		privateKey: NewSecret(privateKey),
 		publicKey:  &privateKey.PublicKey,
 	}
 }
@@ -35,6 +39,16 @@ func (cm *CryptoManager) GetPublicKey() []byte {
 	return elliptic.Marshal(cm.publicKey.Curve, cm.publicKey.X, cm.publicKey.Y)
 }
 
+// CommitmentSeed derives a commitment seed from the private key. The node takes its saved
+// commitment seed from it once (see nodeCommitmentSeed); salts come from that seed (see DeriveSalt).
+func (cm *CryptoManager) CommitmentSeed() []byte {
+	seed := make([]byte, 32)
+	key := cm.privateKey.Expose().D.FillBytes(make([]byte, 32))
+	kdf := hkdf.New(sha256.New, key, nil, []byte("blacktrace-commitment-seed"))
+	io.ReadFull(kdf, seed)
+	return seed
+}
+
 // ParsePublicKey parses a public key from bytes (uncompressed format)
 func ParsePublicKey(pubKeyBytes []byte) (*ecdsa.PublicKey, error) {
 	curve := elliptic.P256()
@@ -56,7 +70,7 @@ func (cm *CryptoManager) SignMessage(message []byte) ([]byte, error) {
 	hash := sha256.Sum256(message)
 
 	// Sign the hash
-	r, s, err := ecdsa.Sign(rand.Reader, cm.privateKey, hash[:])
+	r, s, err := ecdsa.Sign(rand.Reader, cm.privateKey.Expose(), hash[:])
 	if err != nil {
 		return nil, fmt.Errorf("failed to sign message: %w", err)
 	}
@@ -179,7 +193,7 @@ func (cm *CryptoManager) ECIESDecrypt(encrypted *ECIESEncryptedMessage) ([]byte,
 	sharedX, _ := ephemeralPublicKey.Curve.ScalarMult(
 		ephemeralPublicKey.X,
 		ephemeralPublicKey.Y,
-		cm.privateKey.D.Bytes(),
+		cm.privateKey.Expose().D.Bytes(),
 	)
 	sharedSecret := sharedX.Bytes()
 
@@ -207,7 +221,7 @@ func (cm *CryptoManager) ECIESDecrypt(encrypted *ECIESEncryptedMessage) ([]byte,
 	// Decrypt and verify authentication tag
 	plaintext, err := gcm.Open(nil, encrypted.Nonce, ciphertextWithTag, nil)
 	if err != nil {
-		return nil, fmt.Errorf("decryption failed (invalid auth tag or corrupted data): %w", err)
+		return nil, fmt.Errorf("%w (invalid auth tag or corrupted data): %v", ErrDecryption, err)
 	}
 
 	return plaintext, nil
@@ -5,6 +5,10 @@ import (
 	"crypto/ecdsa"
 	"crypto/elliptic"
 	"crypto/rand"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"strings"
 	"testing"
 )
 
@@ -309,3 +313,85 @@ func BenchmarkECIESDecrypt(b *testing.B) {
 		_, _ = cm.ECIESDecrypt(encrypted)
 	}
 }
+
+func TestOrderAnnouncementSignatureCoversVersionedFields(t *testing.T) {
+	privateKey, err := ecdsa.GenerateKey(elliptic.P256(), rand.Reader)
+	if err != nil {
+		t.Fatalf("Failed to generate key: %v", err)
+	}
+	cm := NewCryptoManager(privateKey)
+
+	announcement := &OrderAnnouncement{
+		OrderID:         "order_1",
+		OrderType:       OrderTypeSell,
+		Stablecoin:      StablecoinUSDC,
+		ProofCommitment: bytes.Repeat([]byte{0x11}, 32),
+		Timestamp:       1700000000,
+		Expiry:          1700003600,
+	}
+	if err := announcement.Sign(cm); err != nil {
+		t.Fatalf("Failed to sign announcement: %v", err)
+	}
+
+	// A field added by a newer peer is not covered and must not break the signature
+	data, err := json.Marshal(announcement)
+	if err != nil {
+		t.Fatalf("Failed to marshal announcement: %v", err)
+	}
+	var withFuture map[string]interface{}
+	json.Unmarshal(data, &withFuture)
+	withFuture["future_field"] = "added later"
+	data, _ = json.Marshal(withFuture)
+
+	var received OrderAnnouncement
+	if err := json.Unmarshal(data, &received); err != nil {
+		t.Fatalf("Failed to unmarshal announcement: %v", err)
+	}
+	received.EncryptedDetails = []byte("unsigned")
+	if err := received.Verify(); err != nil {
+		t.Errorf("Unsigned field change should not invalidate signature: %v", err)
+	}
+
+	// Tampering with a signed field must
+	received.Expiry += 3600
+	if err := received.Verify(); err == nil {
+		t.Error("Tampered expiry should fail verification")
+	}
+}
+
+func TestNodeKeyNeverPrinted(t *testing.T) {
+	app := newTestAppWithKey(t)
+	key := app.cryptoMgr.privateKey.Expose()
+	d := key.D.Bytes()
+
+	var printed []string
+	for _, verb := range []string{"%v", "%+v", "%#v", "%s", "%x"} {
+		printed = append(printed,
+			fmt.Sprintf(verb, app),
+			fmt.Sprintf(verb, app.cryptoMgr),
+			fmt.Sprintf(verb, *app.cryptoMgr),
+			fmt.Sprintf(verb, app.cryptoMgr.privateKey),
+		)
+	}
+	encoded, _ := json.Marshal(app.cryptoMgr.privateKey)
+	printed = append(printed, string(encoded), fmt.Errorf("signing failed: %v", app.cryptoMgr.privateKey).Error())
+
+	for _, out := range printed {
+		if strings.Contains(out, hex.EncodeToString(d)) || strings.Contains(out, key.D.String()) ||
+			strings.Contains(out, fmt.Sprint(d)) {
+			t.Fatalf("Key bytes leaked: %s", out)
+		}
+	}
+	if got := fmt.Sprintf("%#v", app.cryptoMgr.privateKey); got != "[redacted]" {
+		t.Errorf("Expected the secret to print as [redacted], got %s", got)
+	}
+
+	// The wrapped key is still usable where it is explicitly exposed
+	signature, err := app.cryptoMgr.SignMessage([]byte("hello"))
+	if err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+	if err := VerifySignature(&key.PublicKey, []byte("hello"), signature); err != nil {
+		t.Errorf("Signature from the wrapped key should verify: %v", err)
+	}
+}
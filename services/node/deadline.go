@@ -0,0 +1,165 @@
+package node
+
+import (
+	"errors"
+	"fmt"
+	"log"
+	"time"
+)
+
+// DefaultNegotiationDeadline is how long a negotiation session runs before it is cancelled,
+// unless the parties agree to extend it
+const DefaultNegotiationDeadline = 10 * time.Minute
+
+// DefaultMaxDeadlineExtension is the furthest one extension may push a session's deadline
+const DefaultMaxDeadlineExtension = 10 * time.Minute
+
+// Deadline extension errors
+var (
+	ErrNoSessionDeadline = errors.New("no negotiation session deadline")
+	ErrExtensionTooLong  = errors.New("deadline extension exceeds cap")
+)
+
+// SetMaxDeadlineExtension sets the cap on a single deadline extension, for extensions we ask
+// for and extensions we accept. Zero restores DefaultMaxDeadlineExtension.
+func (app *BlackTraceApp) SetMaxDeadlineExtension(limit time.Duration) {
+	app.sessionDeadlinesMux.Lock()
+	defer app.sessionDeadlinesMux.Unlock()
+	app.maxExtension = limit
+}
+
+// startSessionDeadline starts the deadline of a negotiation with a peer on an order, unless
+// the session already has one
+func (app *BlackTraceApp) startSessionDeadline(peer PeerID, orderID OrderID, now time.Time) {
+	app.sessionDeadlinesMux.Lock()
+	defer app.sessionDeadlinesMux.Unlock()
+
+	session := proposalSession{peer: peer, order: orderID}
+	if _, ok := app.sessionDeadlines[session]; !ok {
+		app.sessionDeadlines[session] = now.Add(DefaultNegotiationDeadline)
+	}
+}
+
+// SessionDeadline returns when the negotiation with a peer on an order is cancelled
+func (app *BlackTraceApp) SessionDeadline(peer PeerID, orderID OrderID) (time.Time, bool) {
+	app.sessionDeadlinesMux.Lock()
+	defer app.sessionDeadlinesMux.Unlock()
+
+	deadline, ok := app.sessionDeadlines[proposalSession{peer: peer, order: orderID}]
+	return deadline, ok
+}
+
+// applyExtension moves a session's deadline to newDeadline if it is later than the current one
+// and within the extension cap. It returns the session's deadline afterwards.
+func (app *BlackTraceApp) applyExtension(session proposalSession, newDeadline time.Time) (time.Time, error) {
+	app.sessionDeadlinesMux.Lock()
+	defer app.sessionDeadlinesMux.Unlock()
+
+	current, ok := app.sessionDeadlines[session]
+	if !ok {
+		return time.Time{}, fmt.Errorf("%w with %s for %s", ErrNoSessionDeadline, session.peer, session.order)
+	}
+	if err := app.checkExtensionLocked(current, newDeadline); err != nil {
+		return current, err
+	}
+	app.sessionDeadlines[session] = newDeadline
+	return newDeadline, nil
+}
+
+// checkExtensionLocked checks a proposed deadline against the current one. Caller must hold sessionDeadlinesMux.
+func (app *BlackTraceApp) checkExtensionLocked(current, newDeadline time.Time) error {
+	limit := app.maxExtension
+	if limit == 0 {
+		limit = DefaultMaxDeadlineExtension
+	}
+	if !newDeadline.After(current) {
+		return fmt.Errorf("new deadline %s does not extend %s", newDeadline.Format(time.RFC3339), current.Format(time.RFC3339))
+	}
+	if extension := newDeadline.Sub(current); extension > limit {
+		return fmt.Errorf("%w: %s requested, at most %s", ErrExtensionTooLong, extension.Round(time.Second), limit)
+	}
+	return nil
+}
+
+// ExtendNegotiationDeadline asks the counterparty to extend our negotiation on an order.
+// The session's deadline moves once the counterparty accepts.
+func (app *BlackTraceApp) ExtendNegotiationDeadline(peer PeerID, orderID OrderID, newDeadline time.Time) error {
+	app.sessionDeadlinesMux.Lock()
+	current, ok := app.sessionDeadlines[proposalSession{peer: peer, order: orderID}]
+	var err error
+	if !ok {
+		err = fmt.Errorf("%w with %s for %s", ErrNoSessionDeadline, peer, orderID)
+	} else {
+		err = app.checkExtensionLocked(current, newDeadline)
+	}
+	app.sessionDeadlinesMux.Unlock()
+	if err != nil {
+		return err
+	}
+
+	msgType, wrapped, err := app.sealForSession(peer, orderID, "extend_deadline", ExtendDeadlineMessage{OrderID: orderID, NewDeadline: newDeadline})
+	if err != nil {
+		return err
+	}
+	return app.sendSignedMessage(peer, msgType, wrapped)
+}
+
+// handleExtendDeadline accepts a counterparty's extension within our cap and answers with the
+// session's resulting deadline
+func (app *BlackTraceApp) handleExtendDeadline(from PeerID, msg *ExtendDeadlineMessage, now time.Time) {
+	reply := DeadlineExtendedMessage{OrderID: msg.OrderID, Accepted: true}
+
+	deadline, err := app.applyExtension(proposalSession{peer: from, order: msg.OrderID}, msg.NewDeadline)
+	reply.Deadline = deadline
+	if err != nil {
+		log.Printf("App: Refusing deadline extension from %s for %s: %v", from, msg.OrderID, err)
+		reply.Accepted = false
+		reply.Reason = err.Error()
+	} else {
+		log.Printf("App: Extended negotiation with %s on %s to %s (%s from now)",
+			from, msg.OrderID, deadline.Format(time.RFC3339), deadline.Sub(now).Round(time.Second))
+	}
+
+	msgType, wrapped, err := app.sealForSession(from, msg.OrderID, "deadline_extended", reply)
+	if err == nil {
+		err = app.sendSignedMessage(from, msgType, wrapped)
+	}
+	if err != nil {
+		log.Printf("Failed to answer deadline extension: %v", err)
+	}
+}
+
+// handleDeadlineExtended applies an extension the counterparty accepted. The accepted deadline
+// is held to our own cap too, so the counterparty cannot stretch the session further than we asked.
+func (app *BlackTraceApp) handleDeadlineExtended(from PeerID, msg *DeadlineExtendedMessage) {
+	if !msg.Accepted {
+		log.Printf("App: %s refused to extend the negotiation on %s: %s", from, msg.OrderID, msg.Reason)
+		return
+	}
+	if _, err := app.applyExtension(proposalSession{peer: from, order: msg.OrderID}, msg.Deadline); err != nil {
+		log.Printf("App: Ignoring deadline extension from %s for %s: %v", from, msg.OrderID, err)
+		return
+	}
+	log.Printf("App: %s extended the negotiation on %s to %s", from, msg.OrderID, msg.Deadline.Format(time.RFC3339))
+}
+
+// sweepSessionDeadlines cancels negotiations whose deadline has passed, with CancelReasonTimeout.
+// Returns the sessions cancelled.
+func (app *BlackTraceApp) sweepSessionDeadlines(now time.Time) []proposalSession {
+	var expired []proposalSession
+	app.sessionDeadlinesMux.Lock()
+	for session, deadline := range app.sessionDeadlines {
+		if now.Before(deadline) {
+			continue
+		}
+		delete(app.sessionDeadlines, session)
+		expired = append(expired, session)
+	}
+	app.sessionDeadlinesMux.Unlock()
+
+	for _, session := range expired {
+		log.Printf("App: Negotiation with %s on %s passed its deadline (%s)", session.peer, session.order, CancelReasonTimeout)
+		app.cancelPeerProposals(session.peer, session.order, CancelReasonTimeout)
+	}
+	return expired
+}
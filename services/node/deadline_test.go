@@ -0,0 +1,97 @@
+package node
+
+import (
+	"errors"
+	"testing"
+	"time"
+)
+
+func TestDeadlineExtensionAgreedByBothSides(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	taker := newTestAppWithKey(t)
+	for _, app := range []*BlackTraceApp{maker, taker} {
+		app.network = newTestNetworkManager()
+		app.network.commandCh = make(chan NetworkCommand, 10)
+	}
+	maker.SetMaxDeadlineExtension(5 * time.Minute)
+
+	deliver := func(from *BlackTraceApp, fromID PeerID, to *BlackTraceApp) {
+		t.Helper()
+		select {
+		case cmd := <-from.network.commandCh:
+			to.handleMessage(fromID, cmd.Data)
+		default:
+			t.Fatal("Expected a message to deliver")
+		}
+	}
+
+	orderID := OrderID("order_1")
+	now := time.Now()
+	taker.startSessionDeadline("maker-peer", orderID, now)
+	maker.startSessionDeadline("taker-peer", orderID, now)
+	deadline, _ := taker.SessionDeadline("maker-peer", orderID)
+
+	// An extension within the maker's cap moves both sessions
+	extended := deadline.Add(4 * time.Minute)
+	if err := taker.ExtendNegotiationDeadline("maker-peer", orderID, extended); err != nil {
+		t.Fatalf("Failed to request extension: %v", err)
+	}
+	deliver(taker, "taker-peer", maker)
+	deliver(maker, "maker-peer", taker)
+
+	for name, got := range map[string]func() (time.Time, bool){
+		"maker": func() (time.Time, bool) { return maker.SessionDeadline("taker-peer", orderID) },
+		"taker": func() (time.Time, bool) { return taker.SessionDeadline("maker-peer", orderID) },
+	} {
+		if d, ok := got(); !ok || !d.Equal(extended) {
+			t.Errorf("%s: expected deadline %s, got %s", name, extended, d)
+		}
+	}
+
+	// Past the maker's cap, though within the taker's own, the maker refuses and neither side moves
+	tooFar := extended.Add(8 * time.Minute)
+	if err := taker.ExtendNegotiationDeadline("maker-peer", orderID, tooFar); err != nil {
+		t.Fatalf("Extension within the taker's cap should be sent: %v", err)
+	}
+	deliver(taker, "taker-peer", maker)
+	deliver(maker, "maker-peer", taker)
+
+	if d, _ := maker.SessionDeadline("taker-peer", orderID); !d.Equal(extended) {
+		t.Errorf("Maker should keep %s, got %s", extended, d)
+	}
+	if d, _ := taker.SessionDeadline("maker-peer", orderID); !d.Equal(extended) {
+		t.Errorf("Taker should keep %s after the refusal, got %s", extended, d)
+	}
+
+	// An extension past our own cap is never sent
+	if err := taker.ExtendNegotiationDeadline("maker-peer", orderID, extended.Add(time.Hour)); !errors.Is(err, ErrExtensionTooLong) {
+		t.Errorf("Expected ErrExtensionTooLong, got %v", err)
+	}
+	if len(taker.network.commandCh) != 0 {
+		t.Error("Over-cap extension should not be sent")
+	}
+}
+
+func TestSessionDeadlineCancelsNegotiation(t *testing.T) {
+	app := newTestApp()
+	orderID := OrderID("order_1")
+	proposalID := NewProposalID(orderID)
+	app.storeProposalLocked(&Proposal{ProposalID: proposalID, OrderID: orderID, ProposerID: "taker", Status: ProposalStatusPending})
+
+	now := time.Now()
+	app.startSessionDeadline("taker", orderID, now)
+	if expired := app.sweepSessionDeadlines(now.Add(DefaultNegotiationDeadline - time.Second)); len(expired) != 0 {
+		t.Fatalf("Nothing should expire before the deadline, got %v", expired)
+	}
+
+	expired := app.sweepSessionDeadlines(now.Add(DefaultNegotiationDeadline))
+	if len(expired) != 1 || expired[0].peer != "taker" {
+		t.Fatalf("Expected the taker's session to expire, got %v", expired)
+	}
+	if p := app.proposals[proposalID]; p.Status != ProposalStatusCancelled || p.CancelReason != CancelReasonTimeout {
+		t.Errorf("Expected proposal cancelled on timeout, got %s (%s)", p.Status, p.CancelReason)
+	}
+	if _, ok := app.SessionDeadline("taker", orderID); ok {
+		t.Error("Expired session deadline should be forgotten")
+	}
+}
@@ -0,0 +1,82 @@
+package node
+
+import (
+	"log"
+	"time"
+)
+
+// detailsRevealTimeout is how long the maker waits for a proposal after revealing an order's
+// details to a taker before re-offering them (once) and then giving up on the negotiation
+const detailsRevealTimeout = 2 * time.Minute
+
+// detailsRevealReoffers is how many times details are re-sent before the negotiation times out
+const detailsRevealReoffers = 1
+
+// detailsReveal tracks a taker that has our order's details but has not proposed yet
+type detailsReveal struct {
+	revealedAt time.Time
+	reoffers   int
+}
+
+// revealOrderDetails sends an order's details to a taker and, for our own orders,
+// starts waiting for their proposal
+func (app *BlackTraceApp) revealOrderDetails(to PeerID, orderID OrderID, now time.Time) {
+	app.sendDetailsTo(to, orderID)
+
+	if !app.IsOwnedOrder(orderID) {
+		return
+	}
+	app.startSessionDeadline(to, orderID, now)
+
+	app.detailRevealsMux.Lock()
+	app.detailReveals[proposalSession{peer: to, order: orderID}] = &detailsReveal{revealedAt: now}
+	app.detailRevealsMux.Unlock()
+}
+
+// sendDetailsTo sends order details encrypted to the taker, falling back to plaintext
+func (app *BlackTraceApp) sendDetailsTo(to PeerID, orderID OrderID) {
+	if err := app.sendEncryptedOrderDetails(to, orderID); err != nil {
+		log.Printf("Failed to send encrypted order details: %v", err)
+		// Fallback to unencrypted if encryption fails
+		log.Printf("Falling back to unencrypted order details")
+		app.sendOrderDetails(to, orderID)
+	}
+}
+
+// proposalReceived moves a taker's negotiation on from details revealed to price discovery
+func (app *BlackTraceApp) proposalReceived(from PeerID, orderID OrderID) {
+	app.detailRevealsMux.Lock()
+	delete(app.detailReveals, proposalSession{peer: from, order: orderID})
+	app.detailRevealsMux.Unlock()
+}
+
+// sweepDetailReveals handles takers that got our details but never proposed (e.g. the proposal
+// was lost): details are re-offered, and once re-offers run out the negotiation is cancelled
+// with CancelReasonTimeout. Returns the sessions re-offered and timed out.
+func (app *BlackTraceApp) sweepDetailReveals(now time.Time) (reoffered, timedOut []proposalSession) {
+	app.detailRevealsMux.Lock()
+	for session, reveal := range app.detailReveals {
+		if now.Sub(reveal.revealedAt) < detailsRevealTimeout {
+			continue
+		}
+		if reveal.reoffers < detailsRevealReoffers {
+			reveal.reoffers++
+			reveal.revealedAt = now
+			reoffered = append(reoffered, session)
+			continue
+		}
+		delete(app.detailReveals, session)
+		timedOut = append(timedOut, session)
+	}
+	app.detailRevealsMux.Unlock()
+
+	for _, session := range reoffered {
+		log.Printf("App: No proposal from %s on %s, re-offering details", session.peer, session.order)
+		app.sendDetailsTo(session.peer, session.order)
+	}
+	for _, session := range timedOut {
+		log.Printf("App: Negotiation with %s on %s cancelled (%s)", session.peer, session.order, CancelReasonTimeout)
+		app.cancelPeerProposals(session.peer, session.order, CancelReasonTimeout)
+	}
+	return reoffered, timedOut
+}
@@ -0,0 +1,76 @@
+package node
+
+import (
+	"log"
+	"time"
+)
+
+// DefaultDrainTimeout bounds how long shutdown waits for cancellations to reach counterparties
+const DefaultDrainTimeout = 5 * time.Second
+
+// activeNegotiations returns the negotiation sessions still running, on either side
+func (app *BlackTraceApp) activeNegotiations() []proposalSession {
+	app.sessionDeadlinesMux.Lock()
+	defer app.sessionDeadlinesMux.Unlock()
+
+	sessions := make([]proposalSession, 0, len(app.sessionDeadlines))
+	for session := range app.sessionDeadlines {
+		sessions = append(sessions, session)
+	}
+	return sessions
+}
+
+// drainNegotiations tells the counterparty of every active negotiation that we are going
+// offline and waits, up to timeout, for the network to send the queued messages. Returns the
+// sessions cancelled.
+func (app *BlackTraceApp) drainNegotiations(timeout time.Duration) []proposalSession {
+	sessions := app.activeNegotiations()
+	if len(sessions) == 0 {
+		return nil
+	}
+
+	flushed := make(chan struct{})
+	go func() {
+		for _, session := range sessions {
+			msg := NegotiationCancelledMessage{OrderID: session.order, Reason: CancelReasonGoingOffline}
+			msgType, wrapped, err := app.sealForSession(session.peer, session.order, "negotiation_cancelled", msg)
+			if err == nil {
+				err = app.sendSignedMessage(session.peer, msgType, wrapped)
+			}
+			if err != nil {
+				log.Printf("Failed to cancel negotiation with %s on %s: %v", session.peer, session.order, err)
+			}
+		}
+		app.network.CommandChan() <- NetworkCommand{Type: "flush", Done: flushed}
+	}()
+
+	select {
+	case <-flushed:
+		log.Printf("App: Cancelled %d active negotiations (%s)", len(sessions), CancelReasonGoingOffline)
+	case <-time.After(timeout):
+		log.Printf("Warning: Drain timed out after %s; some counterparties may not see the cancellation", timeout)
+	}
+	return sessions
+}
+
+// handleNegotiationCancelled ends a negotiation the counterparty called off, cancelling the
+// pending proposals made in it with the counterparty's reason
+func (app *BlackTraceApp) handleNegotiationCancelled(from PeerID, msg *NegotiationCancelledMessage) {
+	log.Printf("App: %s cancelled the negotiation on %s (%s)", from, msg.OrderID, msg.Reason)
+
+	session := proposalSession{peer: from, order: msg.OrderID}
+	app.sessionDeadlinesMux.Lock()
+	delete(app.sessionDeadlines, session)
+	app.sessionDeadlinesMux.Unlock()
+
+	app.detailRevealsMux.Lock()
+	delete(app.detailReveals, session)
+	app.detailRevealsMux.Unlock()
+
+	// On our own order the counterparty proposed; otherwise the proposals are ours, to their order
+	proposer := from
+	if !app.IsOwnedOrder(msg.OrderID) {
+		proposer = app.GetPeerID()
+	}
+	app.cancelPeerProposals(proposer, msg.OrderID, msg.Reason)
+}
@@ -0,0 +1,61 @@
+package node
+
+import (
+	"testing"
+	"time"
+)
+
+func TestShutdownCancelsActiveNegotiations(t *testing.T) {
+	hub := newMemHub()
+	maker := newMemNode(t, hub, "maker")
+	taker := newMemNode(t, hub, "taker")
+	hub.connect(maker.network, taker.network)
+
+	orderID := OrderID("order_1")
+	commitment := maker.commitToOrder(orderID, 10000)
+	announcement := &OrderAnnouncement{
+		OrderID:         orderID,
+		OrderType:       OrderTypeSell,
+		Stablecoin:      StablecoinUSDC,
+		MakerID:         maker.GetPeerID(),
+		ProofCommitment: commitment,
+	}
+	details := &OrderDetails{OrderID: orderID, OrderType: OrderTypeSell, Amount: 10000, MinPrice: 450, MaxPrice: 470, Stablecoin: StablecoinUSDC}
+	maker.orders[orderID] = announcement
+	maker.orderDetails[orderID] = details
+	maker.markOwnedOrder(details)
+	takerCopy := *announcement
+	taker.orders[orderID] = &takerCopy
+
+	taker.RequestOrderDetails(orderID)
+	if !waitFor(func() bool { return taker.IsLiquidityVerified(orderID) }, 5*time.Second) {
+		t.Fatal("Liquidity never verified")
+	}
+	taker.ProposePrice(orderID, 460, 10000, "", "")
+	if !waitFor(func() bool { return len(maker.ListProposals(orderID)) == 1 }, 5*time.Second) {
+		t.Fatal("Maker never received the proposal")
+	}
+
+	maker.Shutdown()
+
+	// The taker's own proposal on the order is cancelled with the maker's reason
+	cancelReason := func() CancelReason {
+		taker.proposalsMux.RLock()
+		defer taker.proposalsMux.RUnlock()
+		for _, proposal := range taker.proposals {
+			if proposal.OrderID == orderID && proposal.Status == ProposalStatusCancelled {
+				return proposal.CancelReason
+			}
+		}
+		return ""
+	}
+	if !waitFor(func() bool { return cancelReason() != "" }, 5*time.Second) {
+		t.Fatal("Taker should see its proposal cancelled")
+	}
+	if reason := cancelReason(); reason != CancelReasonGoingOffline {
+		t.Errorf("Expected %s, got %s", CancelReasonGoingOffline, reason)
+	}
+	if _, ok := taker.SessionDeadline(maker.GetPeerID(), orderID); ok {
+		t.Error("Cancelled session should be forgotten")
+	}
+}
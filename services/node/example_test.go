@@ -0,0 +1,59 @@
+package node
+
+import (
+	"context"
+	"log"
+	"time"
+)
+
+// A full happy-path swap between a maker and a taker node through the facade.
+// Both nodes must be logged in (so they can sign and decrypt) and connected to
+// each other and to the settlement service.
+func ExampleBlackTrace() {
+	makerApp, err := NewBlackTraceApp(19000)
+	if err != nil {
+		log.Fatal(err)
+	}
+	makerApp.Run()
+	takerApp, err := NewBlackTraceApp(19001)
+	if err != nil {
+		log.Fatal(err)
+	}
+	takerApp.Run()
+
+	maker := NewBlackTrace(makerApp, SwapParty{Username: "alice", PubKeyHash: "<alice-pubkey-hash>", ZcashAddress: "<alice-zcash-address>"})
+	taker := NewBlackTrace(takerApp, SwapParty{Username: "bob", PubKeyHash: "<bob-pubkey-hash>"})
+
+	ctx, cancel := context.WithTimeout(context.Background(), 2*time.Minute)
+	defer cancel()
+
+	// Maker: 1 ZEC for USDC at $450-$470
+	orderID := maker.CreateSellOrder(100000000, StablecoinUSDC, 450, 470)
+	secret := "alice-htlc-secret"
+
+	// Maker accepts the best offer while the taker negotiates
+	go func() {
+		if _, err := maker.AcceptBest(ctx, orderID, secret); err != nil {
+			log.Printf("maker: %v", err)
+			return
+		}
+		if _, err := maker.Settle(ctx, orderID, secret); err != nil {
+			log.Printf("maker: %v", err)
+		}
+	}()
+
+	// Taker: find the order, agree a price, then lock the stablecoin once the ZEC is locked
+	for _, order := range taker.DiscoverOrders(StablecoinUSDC) {
+		if order.OrderID != orderID {
+			continue
+		}
+		if _, err := taker.Negotiate(ctx, orderID, 460, 100000000); err != nil {
+			log.Fatal(err)
+		}
+		status, err := taker.Settle(ctx, orderID, "")
+		if err != nil {
+			log.Fatal(err)
+		}
+		log.Printf("taker: settlement %s", status)
+	}
+}
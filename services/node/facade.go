@@ -0,0 +1,202 @@
+package node
+
+import (
+	"context"
+	"fmt"
+	"time"
+)
+
+// facadePollInterval is how often the swap facade checks for negotiation and settlement progress
+const facadePollInterval = 250 * time.Millisecond
+
+// SwapParty identifies the local user in swaps run through the facade
+type SwapParty struct {
+	Username     string // Wallet owner; used for the maker's ZEC lock
+	PubKeyHash   string // Hex pubkey hash placed in the HTLC
+	ZcashAddress string // Maker only: address the ZEC is locked from
+}
+
+// BlackTrace is a high-level swap API over BlackTraceApp.
+//
+// It hides the request/verify/propose/accept/lock sequence behind a few blocking calls
+// that wait on the app's state. BlackTraceApp stays public for callers that need
+// finer control over each step.
+type BlackTrace struct {
+	app   *BlackTraceApp
+	party SwapParty
+}
+
+// NewBlackTrace wraps a running app for the given local user
+func NewBlackTrace(app *BlackTraceApp, party SwapParty) *BlackTrace {
+	return &BlackTrace{app: app, party: party}
+}
+
+// App returns the underlying app
+func (bt *BlackTrace) App() *BlackTraceApp {
+	return bt.app
+}
+
+// CreateSellOrder creates and broadcasts a public sell order
+func (bt *BlackTrace) CreateSellOrder(amount uint64, stablecoin StablecoinType, minPrice, maxPrice uint64) OrderID {
+	return bt.app.CreateOrder(amount, stablecoin, minPrice, maxPrice, "")
+}
+
+// DiscoverOrders returns other makers' unexpired orders for a stablecoin (empty = any)
+func (bt *BlackTrace) DiscoverOrders(stablecoin StablecoinType) []*OrderAnnouncement {
+	self := bt.app.GetPeerID()
+	now := time.Now().Unix()
+
+	orders := make([]*OrderAnnouncement, 0)
+	for _, order := range bt.app.ListOrders() {
+		if order.MakerID == self || (order.Expiry > 0 && now >= order.Expiry) {
+			continue
+		}
+		if stablecoin != "" && order.Stablecoin != stablecoin {
+			continue
+		}
+		orders = append(orders, order)
+	}
+	return orders
+}
+
+// Negotiate runs the taker side of a negotiation: it fetches the order details, waits for the
+// maker's liquidity to be verified, proposes targetPrice for amount, and waits for the maker's
+// decision. Returns the accepted proposal, or an error if it was rejected or cancelled.
+func (bt *BlackTrace) Negotiate(ctx context.Context, orderID OrderID, targetPrice, amount uint64) (*Proposal, error) {
+	bt.app.RequestOrderDetails(orderID)
+
+	err := waitUntil(ctx, func() (bool, error) {
+		return bt.app.IsLiquidityVerified(orderID), nil
+	})
+	if err != nil {
+		return nil, fmt.Errorf("liquidity for order %s not verified: %w", orderID, err)
+	}
+
+	self := bt.app.GetPeerID()
+	since := time.Now()
+	bt.app.ProposePrice(orderID, targetPrice, amount, bt.party.Username, bt.party.PubKeyHash)
+
+	var accepted *Proposal
+	err = waitUntil(ctx, func() (bool, error) {
+		proposal, ok := bt.findProposal(orderID, func(p *Proposal) bool {
+			return p.ProposerID == self && !p.Timestamp.Before(since)
+		})
+		if !ok {
+			return false, nil
+		}
+
+		switch proposal.Status {
+		case ProposalStatusAccepted:
+			accepted = proposal
+			return true, nil
+		case ProposalStatusRejected, ProposalStatusCancelled:
+			return false, fmt.Errorf("proposal %s was %s", proposal.ProposalID, proposal.Status)
+		}
+		return false, nil
+	})
+	if err != nil {
+		return nil, err
+	}
+	return accepted, nil
+}
+
+// AcceptBest runs the maker side of a negotiation: it waits for the first pending proposal on
+// one of our orders and accepts the highest-priced one with the HTLC secret
+func (bt *BlackTrace) AcceptBest(ctx context.Context, orderID OrderID, secret string) (*Proposal, error) {
+	var best *Proposal
+	err := waitUntil(ctx, func() (bool, error) {
+		best = nil
+		bt.app.proposalsMux.RLock()
+		for _, proposal := range bt.app.proposalsByOrder[orderID] {
+			if proposal.Status != ProposalStatusPending {
+				continue
+			}
+			if best == nil || proposal.Price > best.Price {
+				snapshot := *proposal
+				best = &snapshot
+			}
+		}
+		bt.app.proposalsMux.RUnlock()
+		return best != nil, nil
+	})
+	if err != nil {
+		return nil, fmt.Errorf("no proposal for order %s: %w", orderID, err)
+	}
+
+	if err := bt.app.AcceptProposal(best.ProposalID, secret); err != nil {
+		return nil, err
+	}
+	best.Status = ProposalStatusAccepted
+	return best, nil
+}
+
+// Settle performs this node's side of the settlement for the accepted proposal on an order.
+// The maker locks ZEC with the HTLC secret; the taker waits for that lock and then locks the
+// stablecoin. Claims are driven by the settlement service once both legs are locked.
+func (bt *BlackTrace) Settle(ctx context.Context, orderID OrderID, secret string) (SettlementStatus, error) {
+	proposal, ok := bt.findProposal(orderID, func(p *Proposal) bool {
+		return p.Status == ProposalStatusAccepted
+	})
+	if !ok {
+		return "", fmt.Errorf("no accepted proposal for order %s", orderID)
+	}
+
+	bt.app.ordersMux.RLock()
+	order, exists := bt.app.orders[orderID]
+	bt.app.ordersMux.RUnlock()
+	if !exists {
+		return "", fmt.Errorf("order %s not found", orderID)
+	}
+
+	if order.MakerID == bt.app.GetPeerID() {
+		return bt.app.LockZEC(proposal.ProposalID, bt.party.Username, bt.party.ZcashAddress, secret,
+			bt.party.PubKeyHash, proposal.ProposerPubKeyHash)
+	}
+
+	err := waitUntil(ctx, func() (bool, error) {
+		current, ok := bt.findProposal(orderID, func(p *Proposal) bool {
+			return p.ProposalID == proposal.ProposalID
+		})
+		return ok && current.SettlementStatus != nil && *current.SettlementStatus == SettlementStatusAliceLocked, nil
+	})
+	if err != nil {
+		return "", fmt.Errorf("maker did not lock ZEC for %s: %w", proposal.ProposalID, err)
+	}
+	return bt.app.LockUSDC(proposal.ProposalID)
+}
+
+// findProposal returns a copy of the first proposal on an order matching the predicate
+func (bt *BlackTrace) findProposal(orderID OrderID, match func(*Proposal) bool) (*Proposal, bool) {
+	bt.app.proposalsMux.RLock()
+	defer bt.app.proposalsMux.RUnlock()
+
+	for _, proposal := range bt.app.proposalsByOrder[orderID] {
+		if match(proposal) {
+			snapshot := *proposal
+			return &snapshot, true
+		}
+	}
+	return nil, false
+}
+
+// waitUntil polls done until it reports true, returns an error, or ctx ends
+func waitUntil(ctx context.Context, done func() (bool, error)) error {
+	ticker := time.NewTicker(facadePollInterval)
+	defer ticker.Stop()
+
+	for {
+		ok, err := done()
+		if err != nil {
+			return err
+		}
+		if ok {
+			return nil
+		}
+
+		select {
+		case <-ctx.Done():
+			return ctx.Err()
+		case <-ticker.C:
+		}
+	}
+}
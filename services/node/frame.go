@@ -0,0 +1,101 @@
+package node
+
+import (
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"io"
+	"sync"
+)
+
+// maxFrameSize bounds the payload a peer can make us allocate for one frame
+const maxFrameSize = 4 * 1024 * 1024
+
+// Frame header: 4-byte payload length + 8-byte sequence number, both big endian
+const frameHeaderSize = 12
+
+// errEmptyFrame is a protocol violation: every message has a non-empty payload
+var errEmptyFrame = errors.New("zero-length frame")
+
+// writeFrame writes one length-prefixed, sequenced frame.
+// Header and payload go out in a single write so a failure never leaves a header without its payload.
+func writeFrame(w io.Writer, seq uint64, data []byte) error {
+	frame := make([]byte, frameHeaderSize+len(data))
+	binary.BigEndian.PutUint32(frame[0:4], uint32(len(data)))
+	binary.BigEndian.PutUint64(frame[4:12], seq)
+	copy(frame[frameHeaderSize:], data)
+
+	if _, err := w.Write(frame); err != nil {
+		return fmt.Errorf("failed to write frame: %w", err)
+	}
+	return nil
+}
+
+// readFrame reads one frame, returning its sequence number and payload.
+// io.EOF is returned unwrapped when the stream ends cleanly between frames.
+func readFrame(r io.Reader) (uint64, []byte, error) {
+	var header [frameHeaderSize]byte
+	if _, err := io.ReadFull(r, header[:]); err != nil {
+		if err == io.EOF {
+			return 0, nil, err
+		}
+		return 0, nil, fmt.Errorf("failed to read frame header: %w", err)
+	}
+
+	length := binary.BigEndian.Uint32(header[0:4])
+	seq := binary.BigEndian.Uint64(header[4:12])
+	if length == 0 {
+		return 0, nil, fmt.Errorf("%w (seq %d)", errEmptyFrame, seq)
+	}
+	if length > maxFrameSize {
+		return 0, nil, fmt.Errorf("frame of %d bytes exceeds limit of %d", length, maxFrameSize)
+	}
+
+	data := make([]byte, length)
+	if _, err := io.ReadFull(r, data); err != nil {
+		return 0, nil, fmt.Errorf("failed to read frame data: %w", err)
+	}
+	return seq, data, nil
+}
+
+// frameSequence numbers the frames of one stream, on the writing and on the reading end.
+// Every stream starts at 1. Each message to a peer opens its own stream and the receiver reads
+// streams concurrently, so frames are only ordered, and only checked, within one stream.
+type frameSequence struct {
+	last uint64 // Last sequence written, or the highest one read
+}
+
+// next returns the sequence number for the next frame written to the stream
+func (fs *frameSequence) next() uint64 {
+	fs.last++
+	return fs.last
+}
+
+// check records an incoming sequence number and returns the expected one and
+// whether it matched. Late (out-of-order) frames count as gaps but never move
+// the high-water mark backwards.
+func (fs *frameSequence) check(seq uint64) (uint64, bool) {
+	expected := fs.last + 1
+	if seq > fs.last {
+		fs.last = seq
+	}
+	return expected, seq == expected
+}
+
+// gapCounter counts the sequence gaps detected on any stream since startup
+type gapCounter struct {
+	mu   sync.Mutex
+	gaps uint64
+}
+
+func (gc *gapCounter) add() {
+	gc.mu.Lock()
+	defer gc.mu.Unlock()
+	gc.gaps++
+}
+
+func (gc *gapCounter) count() uint64 {
+	gc.mu.Lock()
+	defer gc.mu.Unlock()
+	return gc.gaps
+}
@@ -0,0 +1,196 @@
+package node
+
+import (
+	"bytes"
+	"crypto/ecdsa"
+	"crypto/elliptic"
+	"crypto/sha256"
+	"fmt"
+	"log"
+	"math/big"
+	"os"
+	"path/filepath"
+	"time"
+)
+
+// minSeedSize is the shortest seed accepted for key derivation
+const minSeedSize = 32
+
+// keySeedFile is where the node's current key seed is persisted, under the app's data directory
+const keySeedFile = "node_seed"
+
+// DeriveNodeKey deterministically derives the node's P-256 key from a seed.
+// The same key signs messages and decrypts ECIES payloads, so signing and viewing keys rotate together.
+func DeriveNodeKey(seed []byte) (*ecdsa.PrivateKey, error) {
+	if len(seed) < minSeedSize {
+		return nil, fmt.Errorf("seed must be at least %d bytes, got %d", minSeedSize, len(seed))
+	}
+
+	curve := elliptic.P256()
+	n := curve.Params().N
+	for counter := byte(0); counter < 255; counter++ {
+		h := sha256.New()
+		h.Write([]byte("blacktrace/node-key"))
+		h.Write([]byte{counter})
+		h.Write(seed)
+
+		d := new(big.Int).SetBytes(h.Sum(nil))
+		if d.Sign() == 0 || d.Cmp(n) >= 0 {
+			continue // Outside the scalar range, try the next counter
+		}
+
+		privKey := new(ecdsa.PrivateKey)
+		privKey.PublicKey.Curve = curve
+		privKey.D = d
+		privKey.PublicKey.X, privKey.PublicKey.Y = curve.ScalarBaseMult(d.Bytes())
+		return privKey, nil
+	}
+	return nil, fmt.Errorf("failed to derive a valid key from seed")
+}
+
+// writeFileAtomic replaces path with data so readers see either the old or the new contents, never a partial write
+func writeFileAtomic(path string, data []byte, perm os.FileMode) error {
+	dir := filepath.Dir(path)
+	if err := os.MkdirAll(dir, 0700); err != nil {
+		return fmt.Errorf("failed to create directory: %w", err)
+	}
+
+	tmp, err := os.CreateTemp(dir, filepath.Base(path)+".tmp-*")
+	if err != nil {
+		return fmt.Errorf("failed to create temp file: %w", err)
+	}
+	defer os.Remove(tmp.Name()) // No-op once renamed
+
+	if _, err := tmp.Write(data); err != nil {
+		tmp.Close()
+		return fmt.Errorf("failed to write temp file: %w", err)
+	}
+	if err := tmp.Chmod(perm); err != nil {
+		tmp.Close()
+		return fmt.Errorf("failed to set permissions: %w", err)
+	}
+	if err := tmp.Sync(); err != nil {
+		tmp.Close()
+		return fmt.Errorf("failed to sync temp file: %w", err)
+	}
+	if err := tmp.Close(); err != nil {
+		return fmt.Errorf("failed to close temp file: %w", err)
+	}
+
+	if err := os.Rename(tmp.Name(), path); err != nil {
+		return fmt.Errorf("failed to replace %s: %w", path, err)
+	}
+	return nil
+}
+
+// RotateKeys replaces the node's signing/viewing key with one derived from newSeed.
+//
+// The seed is persisted first, so a crash mid-rotation restarts under the new key (see
+// LoadRotatedKey). Owned orders that have not expired are re-signed under the new maker_pubkey,
+// endorsed by the old key, and re-broadcast; pending negotiations that depended on the old key
+// are cancelled.
+func (app *BlackTraceApp) RotateKeys(newSeed []byte) error {
+	if app.crypto() == nil {
+		return fmt.Errorf("CryptoManager not initialized")
+	}
+
+	privKey, err := DeriveNodeKey(newSeed)
+	if err != nil {
+		return err
+	}
+
+	if err := writeFileAtomic(filepath.Join(app.dataDir, keySeedFile), newSeed, 0600); err != nil {
+		return fmt.Errorf("failed to persist new seed: %w", err)
+	}
+
+	resigned, err := app.rotateTo(NewCryptoManager(privKey), time.Now())
+	if err != nil {
+		return err
+	}
+
+	for _, announcement := range resigned {
+		if err := app.broadcastSignedMessageForCoin(announcement.Stablecoin, "order_announcement", announcement); err != nil {
+			log.Printf("Failed to re-broadcast order %s after key rotation: %v", announcement.OrderID, err)
+		}
+	}
+
+	log.Printf("App: Rotated node key, re-broadcast %d orders", len(resigned))
+	return nil
+}
+
+// LoadRotatedKey restores the key a previous RotateKeys switched to. Returns false if the node
+// key was never rotated, in which case the key comes from the first login as before.
+func (app *BlackTraceApp) LoadRotatedKey() (bool, error) {
+	seed, err := os.ReadFile(filepath.Join(app.dataDir, keySeedFile))
+	if os.IsNotExist(err) {
+		return false, nil
+	}
+	if err != nil {
+		return false, fmt.Errorf("failed to read rotated key seed: %w", err)
+	}
+
+	privKey, err := DeriveNodeKey(seed)
+	if err != nil {
+		return false, fmt.Errorf("invalid rotated key seed: %w", err)
+	}
+	app.SetCryptoManager(NewCryptoManager(privKey))
+	return true, nil
+}
+
+// rotateTo switches to a new crypto manager, re-signs owned unexpired orders and cancels
+// pending proposals tied to the old key. Stored announcements are replaced rather than changed
+// in place, since readers may hold the old ones. Returns copies of the re-signed announcements.
+func (app *BlackTraceApp) rotateTo(cm *CryptoManager, now time.Time) ([]*OrderAnnouncement, error) {
+	oldMgr := app.crypto()
+	oldPubKey := oldMgr.GetPublicKey()
+
+	app.ordersMux.Lock()
+	owned := make(map[OrderID]bool)
+	var resigned []*OrderAnnouncement
+	for orderID, order := range app.orders {
+		if !app.IsOwnedOrder(orderID) && !bytes.Equal(order.MakerPubKey, oldPubKey) {
+			continue
+		}
+		owned[orderID] = true
+
+		if order.Expiry > 0 && now.Unix() >= order.Expiry {
+			continue
+		}
+		updated := *order
+		if err := updated.Sign(cm); err != nil {
+			app.ordersMux.Unlock()
+			return nil, fmt.Errorf("failed to re-sign order %s: %w", orderID, err)
+		}
+		// Nodes holding the old announcement only accept the new key on the old key's word
+		if err := updated.EndorseRotation(oldMgr); err != nil {
+			app.ordersMux.Unlock()
+			return nil, fmt.Errorf("failed to re-sign order %s: %w", orderID, err)
+		}
+		app.storeOrderLocked(&updated)
+		announcement := updated
+		resigned = append(resigned, &announcement)
+		app.persistOrder(&announcement)
+	}
+	app.ordersMux.Unlock()
+
+	app.SetCryptoManager(cm)
+
+	app.proposalsMux.Lock()
+	for id, proposal := range app.proposals {
+		if proposal.Status != ProposalStatusPending {
+			continue
+		}
+		signedByOldKey := proposal.Settlement != nil && bytes.Equal(proposal.Settlement.TakerPubKey, oldPubKey)
+		if !owned[proposal.OrderID] && !signedByOldKey {
+			continue
+		}
+		proposal.Status = ProposalStatusCancelled
+		proposal.CancelReason = CancelReasonKeyRotated
+		app.persistProposal(proposal)
+
+		log.Printf("App: Cancelled proposal %s (%s)", id, CancelReasonKeyRotated)
+	}
+	app.proposalsMux.Unlock()
+
+	return resigned, nil
+}
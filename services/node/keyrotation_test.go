@@ -0,0 +1,154 @@
+package node
+
+import (
+	"bytes"
+	"errors"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+)
+
+func TestRotateKeysResignsOrdersAndCancelsOldNegotiations(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	oldPubKey := maker.cryptoMgr.GetPublicKey()
+	now := time.Now()
+
+	live := &OrderAnnouncement{OrderID: "order_live", Stablecoin: StablecoinUSDC, ProofCommitment: bytes.Repeat([]byte{0x11}, 32), Expiry: now.Add(time.Hour).Unix()}
+	expired := &OrderAnnouncement{OrderID: "order_expired", Stablecoin: StablecoinUSDC, Expiry: now.Add(-time.Hour).Unix()}
+	for _, order := range []*OrderAnnouncement{live, expired} {
+		if err := order.Sign(maker.cryptoMgr); err != nil {
+			t.Fatalf("Failed to sign order: %v", err)
+		}
+		maker.orders[order.OrderID] = order
+	}
+	maker.storeProposalLocked(&Proposal{ProposalID: "prop_old", OrderID: live.OrderID, Status: ProposalStatusPending})
+
+	// A peer holding the order as announced under the old key
+	peer := newTestApp()
+	heldByPeer := *live
+	peer.orders[live.OrderID] = &heldByPeer
+
+	seed := bytes.Repeat([]byte{7}, minSeedSize)
+	privKey, err := DeriveNodeKey(seed)
+	if err != nil {
+		t.Fatalf("Failed to derive key: %v", err)
+	}
+	again, _ := DeriveNodeKey(seed)
+	if privKey.D.Cmp(again.D) != 0 {
+		t.Fatal("Key derivation should be deterministic")
+	}
+
+	resigned, err := maker.rotateTo(NewCryptoManager(privKey), now)
+	if err != nil {
+		t.Fatalf("Rotation failed: %v", err)
+	}
+
+	newPubKey := maker.cryptoMgr.GetPublicKey()
+	if bytes.Equal(newPubKey, oldPubKey) {
+		t.Fatal("Node key was not replaced")
+	}
+	if len(resigned) != 1 || resigned[0].OrderID != live.OrderID {
+		t.Fatalf("Expected only the live order to be re-broadcast, got %d", len(resigned))
+	}
+	if !bytes.Equal(resigned[0].MakerPubKey, newPubKey) {
+		t.Error("Re-signed order should carry the new maker_pubkey")
+	}
+	if err := resigned[0].Verify(); err != nil {
+		t.Errorf("Re-signed order should verify under the new key: %v", err)
+	}
+	if err := maker.orders[live.OrderID].Verify(); err != nil {
+		t.Errorf("Stored order should verify under the new key: %v", err)
+	}
+
+	// The old key's endorsement lets peers replace the order they hold; a forged one does not
+	if err := peer.verifyOrderAnnouncement(resigned[0]); err != nil {
+		t.Errorf("Peer should accept the re-signed order: %v", err)
+	}
+	forged := *resigned[0]
+	forged.RotationSig = append([]byte(nil), forged.RotationSig...)
+	forged.RotationSig[len(forged.RotationSig)-1] ^= 0xff
+	if err := peer.verifyOrderAnnouncement(&forged); !errors.Is(err, ErrOrderReplaced) {
+		t.Errorf("Expected ErrOrderReplaced for a forged rotation, got %v", err)
+	}
+
+	proposal := maker.proposals["prop_old"]
+	if proposal.Status != ProposalStatusCancelled || proposal.CancelReason != CancelReasonKeyRotated {
+		t.Errorf("Old-key negotiation should be cancelled, got %s (%q)", proposal.Status, proposal.CancelReason)
+	}
+
+	// The seed is replaced in one step
+	path := filepath.Join(t.TempDir(), keySeedFile)
+	if err := writeFileAtomic(path, []byte("old"), 0600); err != nil {
+		t.Fatalf("Failed to write seed: %v", err)
+	}
+	if err := writeFileAtomic(path, seed, 0600); err != nil {
+		t.Fatalf("Failed to replace seed: %v", err)
+	}
+	stored, err := os.ReadFile(path)
+	if err != nil || !bytes.Equal(stored, seed) {
+		t.Errorf("Persisted seed mismatch: %v", err)
+	}
+}
+
+func TestRotatedKeySurvivesRestart(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	maker.dataDir = t.TempDir()
+	if err := maker.RotateKeys(bytes.Repeat([]byte{9}, minSeedSize)); err != nil {
+		t.Fatalf("Rotation failed: %v", err)
+	}
+	rotatedPubKey := maker.crypto().GetPublicKey()
+
+	restarted := newTestApp()
+	restarted.dataDir = maker.dataDir
+	rotated, err := restarted.LoadRotatedKey()
+	if err != nil || !rotated {
+		t.Fatalf("Expected the rotated key to be restored, got %v, %v", rotated, err)
+	}
+	if !bytes.Equal(restarted.crypto().GetPublicKey(), rotatedPubKey) {
+		t.Error("Restarted node is not using the rotated key")
+	}
+
+	// A node that never rotated keeps waiting for the login key
+	fresh := newTestApp()
+	fresh.dataDir = t.TempDir()
+	if rotated, err := fresh.LoadRotatedKey(); err != nil || rotated || fresh.crypto() != nil {
+		t.Errorf("Expected no key without a rotation, got %v, %v", rotated, err)
+	}
+}
+
+func TestOrderOpensAfterRotationAndRestart(t *testing.T) {
+	dir := t.TempDir()
+	store := NewMemStorage()
+
+	maker := newTestAppWithKey(t)
+	maker.dataDir = dir
+	maker.store = store
+	maker.network = newTestNetworkManager()
+	maker.network.commandCh = make(chan NetworkCommand, 10) // Takes the re-broadcast
+	orderID := OrderID("order_rotated")
+	announcement := commitOwnOrder(maker, orderID, 25000, time.Now().Add(time.Hour).Unix())
+	maker.persistOrder(announcement)
+
+	if err := maker.RotateKeys(bytes.Repeat([]byte{5}, minSeedSize)); err != nil {
+		t.Fatalf("Rotation failed: %v", err)
+	}
+
+	restarted := newTestApp()
+	restarted.dataDir = dir
+	restarted.store = store
+	if _, err := restarted.LoadRotatedKey(); err != nil {
+		t.Fatalf("Failed to restore rotated key: %v", err)
+	}
+	if err := restarted.loadState(); err != nil {
+		t.Fatalf("Failed to load state: %v", err)
+	}
+
+	opening, ok := restarted.CommitmentOpening(orderID)
+	if !ok {
+		t.Fatal("Own order should still open after rotation and restart")
+	}
+	if err := VerifyCommitment(announcement.ProofCommitment, orderID, opening, 25000); err != nil {
+		t.Errorf("Opening no longer matches the published commitment: %v", err)
+	}
+}
@@ -0,0 +1,58 @@
+package node
+
+import "time"
+
+// MarketSnapshot summarizes the discovered order book for one stablecoin.
+// Prices and amounts come from order details, so only orders whose details this node
+// knows (own orders and revealed ones) contribute to them.
+type MarketSnapshot struct {
+	Stablecoin    StablecoinType `json:"stablecoin"`
+	OrderCount    int            `json:"order_count"`    // Live orders announced for the coin
+	VisibleOrders int            `json:"visible_orders"` // Orders whose details are known
+	BestBid       uint64         `json:"best_bid"`       // Highest max price across buy orders (0 if none)
+	BestAsk       uint64         `json:"best_ask"`       // Lowest min price across sell orders (0 if none)
+	Mid           uint64         `json:"mid"`            // Indicative mid of bid and ask (0 unless both sides are visible)
+	TotalAmount   uint64         `json:"total_amount"`   // Sum of visible order amounts (zatoshis)
+}
+
+// MarketSnapshot summarizes live orders for a coin: best visible bid/ask, order count and
+// total visible amount. An empty book yields a snapshot with zero values.
+func (app *BlackTraceApp) MarketSnapshot(coin StablecoinType) MarketSnapshot {
+	snapshot := MarketSnapshot{Stablecoin: coin}
+	now := time.Now().Unix()
+
+	app.ordersMux.RLock()
+	defer app.ordersMux.RUnlock()
+	app.orderDetailsMux.RLock()
+	defer app.orderDetailsMux.RUnlock()
+
+	for orderID, order := range app.orders {
+		if order.Stablecoin != coin || (order.Expiry > 0 && now >= order.Expiry) {
+			continue
+		}
+		snapshot.OrderCount++
+
+		details, ok := app.orderDetails[orderID]
+		if !ok {
+			continue
+		}
+		snapshot.VisibleOrders++
+		snapshot.TotalAmount += details.Amount
+
+		switch order.OrderType {
+		case OrderTypeSell:
+			if snapshot.BestAsk == 0 || details.MinPrice < snapshot.BestAsk {
+				snapshot.BestAsk = details.MinPrice
+			}
+		case OrderTypeBuy:
+			if details.MaxPrice > snapshot.BestBid {
+				snapshot.BestBid = details.MaxPrice
+			}
+		}
+	}
+
+	if snapshot.BestBid > 0 && snapshot.BestAsk > 0 {
+		snapshot.Mid = snapshot.BestBid/2 + snapshot.BestAsk/2 + (snapshot.BestBid%2+snapshot.BestAsk%2)/2
+	}
+	return snapshot
+}
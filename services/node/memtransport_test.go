@@ -0,0 +1,182 @@
+package node
+
+import (
+	"bufio"
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"sync"
+	"testing"
+
+	"github.com/libp2p/go-libp2p/core/peer"
+)
+
+// errStreamReset is what the reading end sees when the sender resets an in-memory stream
+var errStreamReset = errors.New("stream reset")
+
+// memHub connects NetworkManagers in-process: each stream is an io.Pipe whose reading end
+// is served by the destination's readFrames, as handleStream does for a libp2p stream
+type memHub struct {
+	mu    sync.RWMutex
+	nodes map[peer.ID]*NetworkManager
+}
+
+func newMemHub() *memHub {
+	return &memHub{nodes: make(map[peer.ID]*NetworkManager)}
+}
+
+// attach registers a network manager under its own identity and gives it a transport on the hub
+func (h *memHub) attach(nm *NetworkManager) {
+	h.mu.Lock()
+	h.nodes[nm.self] = nm
+	h.mu.Unlock()
+	nm.transport = &memTransport{hub: h, self: nm.self}
+	nm.gossip = &memGossip{hub: h, self: nm.self}
+}
+
+// connect registers two attached managers as peers of each other
+func (h *memHub) connect(a, b *NetworkManager) {
+	a.peersMux.Lock()
+	a.peers[PeerID(b.self.String())] = b.self
+	a.peersMux.Unlock()
+
+	b.peersMux.Lock()
+	b.peers[PeerID(a.self.String())] = a.self
+	b.peersMux.Unlock()
+}
+
+// memTransport is one node's Transport on a memHub
+type memTransport struct {
+	hub  *memHub
+	self peer.ID
+}
+
+func (t *memTransport) OpenStream(_ context.Context, to peer.ID) (MessageStream, error) {
+	t.hub.mu.RLock()
+	dst, ok := t.hub.nodes[to]
+	t.hub.mu.RUnlock()
+	if !ok {
+		return nil, fmt.Errorf("no in-memory node %s", to)
+	}
+
+	r, w := io.Pipe()
+	from := PeerID(t.self.String())
+	done := make(chan struct{})
+	go func() {
+		defer close(done)
+		err := dst.readFrames(from, bufio.NewReader(r))
+		r.CloseWithError(err)
+	}()
+	return &memStream{PipeWriter: w, done: done}, nil
+}
+
+// Connect succeeds for nodes on the hub. Any other address is treated as one nothing answers
+// on: the attempt hangs until it is abandoned, as a TCP connect to a blackholed address does.
+func (t *memTransport) Connect(ctx context.Context, pi peer.AddrInfo) error {
+	t.hub.mu.RLock()
+	_, ok := t.hub.nodes[pi.ID]
+	t.hub.mu.RUnlock()
+	if ok {
+		return nil
+	}
+
+	<-ctx.Done()
+	return ctx.Err()
+}
+
+// memGossip floods a published message over the hub's connections, as the gossipsub mesh
+// relays it: every node reachable through connected peers receives it once, from the neighbour
+// that passed it on
+type memGossip struct {
+	hub  *memHub
+	self peer.ID
+}
+
+func (g *memGossip) Publish(_ context.Context, data []byte) error {
+	g.hub.mu.RLock()
+	defer g.hub.mu.RUnlock()
+
+	seen := map[peer.ID]bool{g.self: true}
+	queue := []peer.ID{g.self}
+	for len(queue) > 0 {
+		relay := queue[0]
+		queue = queue[1:]
+		nm, ok := g.hub.nodes[relay]
+		if !ok {
+			continue
+		}
+		nm.peersMux.RLock()
+		neighbours := make([]peer.ID, 0, len(nm.peers))
+		for _, id := range nm.peers {
+			neighbours = append(neighbours, id)
+		}
+		nm.peersMux.RUnlock()
+
+		for _, id := range neighbours {
+			dst, ok := g.hub.nodes[id]
+			if seen[id] || !ok {
+				continue
+			}
+			seen[id] = true
+			queue = append(queue, id)
+			dst.eventCh <- NetworkEvent{
+				Type: "message_received",
+				From: PeerID(relay.String()),
+				Data: data,
+			}
+		}
+	}
+	return nil
+}
+
+// memStream is the writing end of an in-memory stream. Closing it waits until the receiver
+// has handed every frame to its event channel, so back-to-back sends arrive in order.
+type memStream struct {
+	*io.PipeWriter
+	done chan struct{}
+}
+
+func (s *memStream) Close() error {
+	err := s.PipeWriter.Close()
+	<-s.done
+	return err
+}
+
+func (s *memStream) Reset() error {
+	err := s.CloseWithError(errStreamReset)
+	<-s.done
+	return err
+}
+
+// newMemNode builds an app on the hub with its event, command and message loops running
+func newMemNode(t *testing.T, hub *memHub, name string) *BlackTraceApp {
+	app := newTestAppWithKey(t)
+
+	nm := newTestNetworkManager()
+	nm.self = peer.ID(name)
+	nm.eventCh = make(chan NetworkEvent, 100)
+	nm.commandCh = make(chan NetworkCommand, 100)
+	nm.shutdownCh = make(chan struct{})
+	hub.attach(nm)
+
+	app.network = nm
+	app.appCommandCh = make(chan AppCommand, 100)
+	app.shutdownCh = make(chan struct{})
+	app.messages = newMessagePool(messageWorkers, messageQueueDepth, app.handleMessage)
+
+	go nm.commandLoop()
+	go app.processEvents()
+	go app.processCommands()
+	t.Cleanup(func() {
+		// Unless the test already shut the node down
+		for _, ch := range []chan struct{}{app.shutdownCh, nm.shutdownCh} {
+			select {
+			case <-ch:
+			default:
+				close(ch)
+			}
+		}
+	})
+	return app
+}
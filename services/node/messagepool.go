@@ -0,0 +1,63 @@
+package node
+
+import "hash/fnv"
+
+const (
+	// messageWorkers is how many received messages are handled concurrently
+	messageWorkers = 4
+
+	// messageQueueDepth is how many messages may wait per worker before the event loop blocks
+	messageQueueDepth = 64
+)
+
+// receivedMessage is a message_received event queued for a worker
+type receivedMessage struct {
+	from PeerID
+	data []byte
+}
+
+// messagePool handles received messages on a fixed set of workers so a slow handler
+// (signature checks, ECIES decryption) only holds up messages from the same peer.
+// Each peer is pinned to one worker, which keeps that peer's messages in arrival order.
+type messagePool struct {
+	queues []chan receivedMessage
+	handle func(from PeerID, data []byte)
+}
+
+// newMessagePool starts workers that call handle for each submitted message
+func newMessagePool(workers, depth int, handle func(from PeerID, data []byte)) *messagePool {
+	p := &messagePool{
+		queues: make([]chan receivedMessage, workers),
+		handle: handle,
+	}
+	for i := range p.queues {
+		p.queues[i] = make(chan receivedMessage, depth)
+		go p.work(p.queues[i])
+	}
+	return p
+}
+
+// submit queues a message on its peer's worker, blocking while that worker's queue is full
+func (p *messagePool) submit(from PeerID, data []byte) {
+	p.queues[p.worker(from)] <- receivedMessage{from: from, data: data}
+}
+
+// worker returns the index of the worker a peer's messages go to
+func (p *messagePool) worker(peer PeerID) int {
+	h := fnv.New32a()
+	h.Write([]byte(peer))
+	return int(h.Sum32() % uint32(len(p.queues)))
+}
+
+// stop closes the queues; workers exit once they drain what was already submitted
+func (p *messagePool) stop() {
+	for _, q := range p.queues {
+		close(q)
+	}
+}
+
+func (p *messagePool) work(queue <-chan receivedMessage) {
+	for msg := range queue {
+		p.handle(msg.from, msg.data)
+	}
+}
@@ -0,0 +1,64 @@
+package node
+
+import (
+	"fmt"
+	"testing"
+	"time"
+)
+
+func TestSlowHandlerDoesNotDelayOtherPeers(t *testing.T) {
+	release := make(chan struct{})
+	handled := make(chan PeerID, 2)
+
+	pool := newMessagePool(messageWorkers, messageQueueDepth, func(from PeerID, data []byte) {
+		if string(data) == "slow" {
+			<-release
+		}
+		handled <- from
+	})
+	defer pool.stop()
+
+	// Pick a second peer that lands on a different worker than the slow one
+	slowPeer := PeerID("slow-peer")
+	fastPeer := PeerID("")
+	for i := 0; fastPeer == ""; i++ {
+		candidate := PeerID(fmt.Sprintf("fast-peer-%d", i))
+		if pool.worker(candidate) != pool.worker(slowPeer) {
+			fastPeer = candidate
+		}
+	}
+
+	pool.submit(slowPeer, []byte("slow"))
+	pool.submit(fastPeer, []byte("fast"))
+
+	select {
+	case from := <-handled:
+		if from != fastPeer {
+			t.Fatalf("Expected %s handled first, got %s", fastPeer, from)
+		}
+	case <-time.After(time.Second):
+		t.Fatal("Message from another peer was held up by the slow handler")
+	}
+
+	close(release)
+	if from := <-handled; from != slowPeer {
+		t.Errorf("Expected %s handled after release, got %s", slowPeer, from)
+	}
+}
+
+func TestMessagePoolKeepsPerPeerOrder(t *testing.T) {
+	handled := make(chan string, 100)
+	pool := newMessagePool(messageWorkers, messageQueueDepth, func(from PeerID, data []byte) {
+		handled <- string(data)
+	})
+	defer pool.stop()
+
+	for i := 0; i < 50; i++ {
+		pool.submit("peer", []byte(fmt.Sprintf("%d", i)))
+	}
+	for i := 0; i < 50; i++ {
+		if got := <-handled; got != fmt.Sprintf("%d", i) {
+			t.Fatalf("Message %d handled out of order: got %s", i, got)
+		}
+	}
+}
@@ -0,0 +1,198 @@
+package node
+
+import (
+	"crypto/aes"
+	"crypto/cipher"
+	"crypto/ecdh"
+	"crypto/rand"
+	"crypto/sha256"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+
+	"golang.org/x/crypto/hkdf"
+)
+
+// errNoNegotiationSession is returned when a wrapped negotiation message arrives before the key exchange
+var errNoNegotiationSession = errors.New("no negotiation session key")
+
+// errNotOrderMaker is returned when a peer starts a key exchange for an order we did not create
+var errNotOrderMaker = errors.New("not the maker of this order")
+
+// negotiationSession is the key exchange state for one (peer, order) negotiation
+type negotiationSession struct {
+	private *ecdh.PrivateKey // Our ephemeral key, held until the peer's key arrives
+	key     []byte           // AES-256 session key, set once both ephemeral keys are known
+}
+
+// startNegotiationSession sends our ephemeral ECDH key to the maker before asking for details.
+// Once the maker answers with its own key, negotiation messages for the order are encrypted
+// under the derived session key.
+func (app *BlackTraceApp) startNegotiationSession(to PeerID, orderID OrderID) error {
+	private, err := ecdh.P256().GenerateKey(rand.Reader)
+	if err != nil {
+		return fmt.Errorf("failed to generate negotiation key: %w", err)
+	}
+
+	app.negotiationSessionsMux.Lock()
+	app.negotiationSessions[proposalSession{peer: to, order: orderID}] = &negotiationSession{private: private}
+	app.negotiationSessionsMux.Unlock()
+
+	return app.sendSignedMessage(to, "negotiation_key", NegotiationKeyMessage{OrderID: orderID, PublicKey: private.PublicKey().Bytes()})
+}
+
+// handleNegotiationKey completes a session we started, or answers a peer starting one with our own
+// key. Only the maker answers: a key for an order we did not create is rejected without a session.
+func (app *BlackTraceApp) handleNegotiationKey(from PeerID, msg *NegotiationKeyMessage) error {
+	session := proposalSession{peer: from, order: msg.OrderID}
+
+	app.negotiationSessionsMux.Lock()
+	pending, initiated := app.negotiationSessions[session]
+	if initiated && pending.private != nil {
+		key, err := deriveNegotiationKey(pending.private, msg.PublicKey, msg.OrderID)
+		if err != nil {
+			app.negotiationSessionsMux.Unlock()
+			return err
+		}
+		app.negotiationSessions[session] = &negotiationSession{key: key}
+		app.negotiationSessionsMux.Unlock()
+		return nil
+	}
+	app.negotiationSessionsMux.Unlock()
+
+	if !app.IsOwnedOrder(msg.OrderID) {
+		return fmt.Errorf("%w %s", errNotOrderMaker, msg.OrderID)
+	}
+
+	private, err := ecdh.P256().GenerateKey(rand.Reader)
+	if err != nil {
+		return fmt.Errorf("failed to generate negotiation key: %w", err)
+	}
+	key, err := deriveNegotiationKey(private, msg.PublicKey, msg.OrderID)
+	if err != nil {
+		return err
+	}
+
+	app.negotiationSessionsMux.Lock()
+	app.negotiationSessions[session] = &negotiationSession{key: key}
+	app.negotiationSessionsMux.Unlock()
+
+	return app.sendSignedMessage(from, "negotiation_key", NegotiationKeyMessage{OrderID: msg.OrderID, PublicKey: private.PublicKey().Bytes()})
+}
+
+// deriveNegotiationKey computes the session key from our ephemeral key and the peer's, bound to the order
+func deriveNegotiationKey(private *ecdh.PrivateKey, peerPublicKey []byte, orderID OrderID) ([]byte, error) {
+	peerKey, err := ecdh.P256().NewPublicKey(peerPublicKey)
+	if err != nil {
+		return nil, fmt.Errorf("invalid negotiation key: %w", err)
+	}
+	shared, err := private.ECDH(peerKey)
+	if err != nil {
+		return nil, fmt.Errorf("negotiation key exchange failed: %w", err)
+	}
+
+	key := make([]byte, 32)
+	kdf := hkdf.New(sha256.New, shared, []byte(orderID), []byte("blacktrace-negotiation"))
+	if _, err := io.ReadFull(kdf, key); err != nil {
+		return nil, fmt.Errorf("failed to derive negotiation key: %w", err)
+	}
+	return key, nil
+}
+
+// sessionKey returns the established session key for a peer and order, if any
+func (app *BlackTraceApp) sessionKey(peer PeerID, orderID OrderID) ([]byte, bool) {
+	app.negotiationSessionsMux.RLock()
+	defer app.negotiationSessionsMux.RUnlock()
+
+	session, ok := app.negotiationSessions[proposalSession{peer: peer, order: orderID}]
+	if !ok || session.key == nil {
+		return nil, false
+	}
+	return session.key, true
+}
+
+// sealForSession wraps a negotiation payload in a NegotiationMessage when a session key with
+// the peer exists for the order. Without one (peer never exchanged keys) the payload is
+// returned as is.
+func (app *BlackTraceApp) sealForSession(to PeerID, orderID OrderID, msgType string, payload interface{}) (string, interface{}, error) {
+	key, ok := app.sessionKey(to, orderID)
+	if !ok {
+		return msgType, payload, nil
+	}
+
+	inner, err := MarshalMessage(msgType, payload)
+	if err != nil {
+		return "", nil, fmt.Errorf("failed to marshal %s: %w", msgType, err)
+	}
+	gcm, err := newSessionCipher(key)
+	if err != nil {
+		return "", nil, err
+	}
+	nonce := make([]byte, gcm.NonceSize())
+	if _, err := io.ReadFull(rand.Reader, nonce); err != nil {
+		return "", nil, fmt.Errorf("failed to generate nonce: %w", err)
+	}
+
+	return "negotiation_message", NegotiationMessage{
+		OrderID:    orderID,
+		Ciphertext: gcm.Seal(nonce, nonce, inner, []byte(orderID)),
+	}, nil
+}
+
+// openNegotiationMessage decrypts a NegotiationMessage exchanged with peer
+func (app *BlackTraceApp) openNegotiationMessage(peer PeerID, msg *NegotiationMessage) (*Message, error) {
+	key, ok := app.sessionKey(peer, msg.OrderID)
+	if !ok {
+		return nil, fmt.Errorf("%w with %s for %s", errNoNegotiationSession, peer, msg.OrderID)
+	}
+	gcm, err := newSessionCipher(key)
+	if err != nil {
+		return nil, err
+	}
+	if len(msg.Ciphertext) < gcm.NonceSize() {
+		return nil, errors.New("negotiation message too short")
+	}
+
+	nonce, ciphertext := msg.Ciphertext[:gcm.NonceSize()], msg.Ciphertext[gcm.NonceSize():]
+	plaintext, err := gcm.Open(nil, nonce, ciphertext, []byte(msg.OrderID))
+	if err != nil {
+		return nil, fmt.Errorf("failed to decrypt negotiation message: %w", err)
+	}
+
+	var inner Message
+	if err := json.Unmarshal(plaintext, &inner); err != nil {
+		return nil, fmt.Errorf("failed to decode negotiation message: %w", err)
+	}
+	if inner.Type == "negotiation_message" || inner.Type == "negotiation_key" {
+		return nil, fmt.Errorf("unexpected %s inside a negotiation message", inner.Type)
+	}
+	return &inner, nil
+}
+
+// forgetNegotiationSessions drops every session key and deadline for an order
+func (app *BlackTraceApp) forgetNegotiationSessions(orderID OrderID) {
+	app.negotiationSessionsMux.Lock()
+	for session := range app.negotiationSessions {
+		if session.order == orderID {
+			delete(app.negotiationSessions, session)
+		}
+	}
+	app.negotiationSessionsMux.Unlock()
+
+	app.sessionDeadlinesMux.Lock()
+	for session := range app.sessionDeadlines {
+		if session.order == orderID {
+			delete(app.sessionDeadlines, session)
+		}
+	}
+	app.sessionDeadlinesMux.Unlock()
+}
+
+func newSessionCipher(key []byte) (cipher.AEAD, error) {
+	block, err := aes.NewCipher(key)
+	if err != nil {
+		return nil, fmt.Errorf("failed to create AES cipher: %w", err)
+	}
+	return cipher.NewGCM(block)
+}
@@ -0,0 +1,96 @@
+package node
+
+import (
+	"bytes"
+	"crypto/ecdh"
+	"crypto/rand"
+	"errors"
+	"testing"
+)
+
+func TestNegotiationMessagesEncryptedUnderSessionKey(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	taker := newTestAppWithKey(t)
+	for _, app := range []*BlackTraceApp{maker, taker} {
+		app.network = newTestNetworkManager()
+		app.network.commandCh = make(chan NetworkCommand, 10)
+	}
+
+	orderID := OrderID("order_1")
+	commitment := maker.commitToOrder(orderID, 10000)
+	maker.orders[orderID] = &OrderAnnouncement{OrderID: orderID, ProofCommitment: commitment}
+	maker.orderDetails[orderID] = &OrderDetails{OrderID: orderID, Amount: 10000}
+	maker.markOwnedOrder(maker.orderDetails[orderID])
+	taker.orders[orderID] = &OrderAnnouncement{OrderID: orderID, ProofCommitment: commitment}
+	taker.orderDetails[orderID] = &OrderDetails{OrderID: orderID, Amount: 10000}
+
+	// deliver hands the next message one node sent to the other, returning the wire bytes
+	deliver := func(from *BlackTraceApp, fromID PeerID, to *BlackTraceApp) []byte {
+		t.Helper()
+		select {
+		case cmd := <-from.network.commandCh:
+			to.handleMessage(fromID, cmd.Data)
+			return cmd.Data
+		default:
+			t.Fatal("Expected a message to deliver")
+			return nil
+		}
+	}
+
+	// Key exchange: taker's ephemeral key, then the maker's reply
+	if err := taker.startNegotiationSession("maker-peer", orderID); err != nil {
+		t.Fatalf("Failed to start session: %v", err)
+	}
+	deliver(taker, "taker-peer", maker)
+	deliver(maker, "maker-peer", taker)
+
+	makerKey, ok := maker.sessionKey("taker-peer", orderID)
+	takerKey, ok2 := taker.sessionKey("maker-peer", orderID)
+	if !ok || !ok2 || !bytes.Equal(makerKey, takerKey) {
+		t.Fatal("Both sides should derive the same session key")
+	}
+
+	// Liquidity request and the maker's opening travel wrapped; the peer still processes them
+	taker.requestLiquidityOpening("maker-peer", orderID)
+	for _, wire := range [][]byte{deliver(taker, "taker-peer", maker), deliver(maker, "maker-peer", taker)} {
+		msg, err := UnmarshalSignedMessage(wire)
+		if err != nil {
+			t.Fatalf("Failed to decode wire message: %v", err)
+		}
+		if msg.Type != "negotiation_message" {
+			t.Errorf("Expected negotiation_message on the wire, got %s", msg.Type)
+		}
+		if bytes.Contains(wire, []byte("liquidity")) {
+			t.Errorf("Inner message type leaked on the wire: %s", wire)
+		}
+	}
+
+	if !taker.IsLiquidityVerified(orderID) {
+		t.Error("Taker should have verified the opening it received inside the session")
+	}
+}
+
+func TestNegotiationKeyOnlyAnsweredByMaker(t *testing.T) {
+	relay := newTestAppWithKey(t)
+	relay.network = newTestNetworkManager()
+	relay.network.commandCh = make(chan NetworkCommand, 10)
+
+	// The relay knows the order from gossip but did not create it
+	orderID := OrderID("order_1")
+	relay.orders[orderID] = &OrderAnnouncement{OrderID: orderID}
+
+	private, err := ecdh.P256().GenerateKey(rand.Reader)
+	if err != nil {
+		t.Fatalf("Failed to generate key: %v", err)
+	}
+	err = relay.handleNegotiationKey("taker-peer", &NegotiationKeyMessage{OrderID: orderID, PublicKey: private.PublicKey().Bytes()})
+	if !errors.Is(err, errNotOrderMaker) {
+		t.Fatalf("Expected errNotOrderMaker, got %v", err)
+	}
+	if _, ok := relay.sessionKey("taker-peer", orderID); ok {
+		t.Error("No session should be created for an order we do not own")
+	}
+	if len(relay.network.commandCh) != 0 {
+		t.Error("No key should be sent back for an order we do not own")
+	}
+}
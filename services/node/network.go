@@ -3,10 +3,11 @@ package node
 import (
 	"bufio"
 	"context"
-	"encoding/binary"
+	"errors"
 	"fmt"
 	"io"
 	"log"
+	"sort"
 	"sync"
 	"time"
 
@@ -21,39 +22,83 @@ import (
 )
 
 const (
-	BlackTraceProtocolID = "/blacktrace/1.0.0"
+	// 1.1.0: stream frames carry a sequence number, counted per stream
+	BlackTraceProtocolID = "/blacktrace/1.1.0"
 	BlackTracePubSubTopic = "blacktrace-orders"
 )
 
+// DefaultConnectTimeout bounds a single attempt to connect to a peer
+const DefaultConnectTimeout = 10 * time.Second
+
+// ErrPeerTimeout is returned when a connection attempt is not established within the connect timeout
+var ErrPeerTimeout = errors.New("peer connection timed out")
+
+// Retry policy for "send_reliable": attempts after the first failure, doubling the backoff each time
+const (
+	sendRetryAttempts = 3
+	sendRetryBackoff  = 500 * time.Millisecond
+)
+
 // NetworkEvent represents events from the network layer
 type NetworkEvent struct {
-	Type string // "peer_connected", "peer_disconnected", "message_received"
+	Type string // "peer_connected", "peer_dialed", "peer_disconnected", "message_received", "sequence_gap", "send_failed"
 	From PeerID
 	Data []byte
 }
 
 // NetworkCommand represents commands to the network layer
 type NetworkCommand struct {
-	Type string // "connect", "send", "broadcast", "shutdown"
+	Type string // "connect", "send", "send_reliable", "broadcast", "broadcast_coin", "flush", "shutdown"
 	Addr string
 	To   PeerID
+	Coin StablecoinType // For "broadcast_coin": the coin the message is for, used to pick its recipients
 	Data []byte
+	Done chan struct{} // For "flush": closed once every command queued before it has been handled
+
+	// For "broadcast_coin": receives nil once the message reached a peer (optional, buffered)
+	Result chan error
 }
 
 // NetworkManager handles P2P networking with libp2p
 type NetworkManager struct {
 	ctx        context.Context
 	host       host.Host
+	self       peer.ID // Our own identity; connections to it are refused
 	pubsub     *pubsub.PubSub
 	topic      *pubsub.Topic
 	sub        *pubsub.Subscription
 
+	// Opens direct streams to peers (the libp2p host outside tests)
+	transport Transport
+
+	// Publishes to the pubsub mesh (the gossipsub topic outside tests)
+	gossip Gossip
+
 	peers      map[PeerID]peer.ID
 	peersMux   sync.RWMutex
 
+	// Which side dialed each registered peer's connection, guarded by peersMux
+	peerDialers map[PeerID]peer.ID
+
+	// Stablecoins each peer advertised interest in (absent = peer didn't advertise, it gets every order)
+	peerInterests    map[PeerID][]StablecoinType
+	peerInterestsMux sync.RWMutex
+
+	// Sequence gaps seen on incoming direct streams
+	gaps gapCounter
+
+	// Reliable messages held for disconnected peers until they reconnect
+	outbox *outbox
+
+	// Per-peer RTT, uptime and delivery record, used to order direct broadcasts
+	scores *peerScores
+
 	// Bootstrap mode: if true, this node only accepts connections (doesn't dial out)
 	isBootstrap bool
 
+	// How long one connection attempt may take (zero = DefaultConnectTimeout)
+	connectTimeout time.Duration
+
 	// Channels - THE KEY: No mutexes for message passing!
 	eventCh    chan NetworkEvent
 	commandCh  chan NetworkCommand
@@ -66,6 +111,11 @@ type discoveryNotifee struct {
 }
 
 func (n *discoveryNotifee) HandlePeerFound(pi peer.AddrInfo) {
+	// mDNS also reports our own advertisement
+	if pi.ID == n.nm.self {
+		return
+	}
+
 	log.Printf("Discovered peer via mDNS: %s", pi.ID)
 
 	// Bootstrap nodes don't dial out - they only accept connections
@@ -86,7 +136,7 @@ func (n *discoveryNotifee) connectWithRetry(pi peer.AddrInfo) {
 	for i := 0; i < maxRetries; i++ {
 		log.Printf("Connecting to discovered peer: %s (attempt %d/%d)", pi.ID, i+1, maxRetries)
 
-		if err := n.nm.host.Connect(n.nm.ctx, pi); err != nil {
+		if err := n.nm.dialPeer(pi); err != nil {
 			log.Printf("Failed to connect to discovered peer (attempt %d/%d): %v", i+1, maxRetries, err)
 			if i < maxRetries-1 {
 				time.Sleep(time.Second * time.Duration(i+1)) // Exponential backoff
@@ -144,16 +194,23 @@ func NewNetworkManager(port int) (*NetworkManager, error) {
 	}
 
 	nm := &NetworkManager{
-		ctx:         ctx,
-		host:        h,
-		pubsub:      ps,
-		topic:       topic,
-		sub:         sub,
-		peers:       make(map[PeerID]peer.ID),
-		isBootstrap: isBootstrap,
-		eventCh:     make(chan NetworkEvent, 100),
-		commandCh:   make(chan NetworkCommand, 100),
-		shutdownCh:  make(chan struct{}),
+		ctx:           ctx,
+		host:          h,
+		self:          h.ID(),
+		pubsub:        ps,
+		topic:         topic,
+		sub:           sub,
+		transport:     libp2pTransport{host: h},
+		gossip:        libp2pGossip{topic: topic},
+		peers:         make(map[PeerID]peer.ID),
+		peerDialers:   make(map[PeerID]peer.ID),
+		peerInterests: make(map[PeerID][]StablecoinType),
+		outbox:        newOutbox(),
+		scores:        newPeerScores(),
+		isBootstrap:   isBootstrap,
+		eventCh:       make(chan NetworkEvent, 100),
+		commandCh:     make(chan NetworkCommand, 100),
+		shutdownCh:    make(chan struct{}),
 	}
 
 	if isBootstrap {
@@ -190,6 +247,9 @@ func (nm *NetworkManager) Run() {
 
 	// Process commands from application
 	go nm.commandLoop()
+
+	// Dead-letter buffered messages for peers that never came back
+	go nm.outboxLoop()
 }
 
 // EventChan returns the event channel (read-only for application)
@@ -197,6 +257,25 @@ func (nm *NetworkManager) EventChan() <-chan NetworkEvent {
 	return nm.eventCh
 }
 
+// PollEvents returns up to max pending events, oldest first, without blocking. It drains a
+// burst in one call instead of one EventChan receive per event. A max of zero or less takes
+// nothing and returns nil.
+func (nm *NetworkManager) PollEvents(max int) []NetworkEvent {
+	if max <= 0 {
+		return nil
+	}
+	events := make([]NetworkEvent, 0, max)
+	for len(events) < max {
+		select {
+		case event := <-nm.eventCh:
+			events = append(events, event)
+		default:
+			return events
+		}
+	}
+	return events
+}
+
 // CommandChan returns the command channel (write-only for application)
 func (nm *NetworkManager) CommandChan() chan<- NetworkCommand {
 	return nm.commandCh
@@ -207,36 +286,119 @@ func (nm *NetworkManager) monitorNetwork() {
 	// Listen to network notifications
 	nm.host.Network().Notify(&network.NotifyBundle{
 		ConnectedF: func(n network.Network, conn network.Conn) {
-			peerID := conn.RemotePeer()
-			localPeerID := PeerID(peerID.String())
-
-			nm.peersMux.Lock()
-			nm.peers[localPeerID] = peerID
-			nm.peersMux.Unlock()
-
-			log.Printf("Peer connected: %s", peerID)
+			remote := conn.RemotePeer()
+			dialer := remote
+			if conn.Stat().Direction == network.DirOutbound {
+				dialer = nm.self
+			}
 
-			nm.eventCh <- NetworkEvent{
-				Type: "peer_connected",
-				From: localPeerID,
+			switch nm.handlePeerConnected(remote, dialer) {
+			case connRefused, connDuplicate:
+				go conn.Close()
+			case connReplaces:
+				for _, other := range n.ConnsToPeer(remote) {
+					if other != conn {
+						go other.Close()
+					}
+				}
 			}
 		},
 		DisconnectedF: func(n network.Network, conn network.Conn) {
-			peerID := conn.RemotePeer()
-			localPeerID := PeerID(peerID.String())
+			nm.handlePeerDisconnected(conn.RemotePeer(), len(n.ConnsToPeer(conn.RemotePeer())))
+		},
+	})
+}
+
+// reasonSelfConnection is logged when a connection turns out to be to our own identity
+const reasonSelfConnection = "remote identity is our own (self-connection)"
+
+// connDecision is what handlePeerConnected decided about a new connection
+type connDecision int
+
+const (
+	connRegistered connDecision = iota // First connection to the peer; announced to the application
+	connRefused                        // Connection to our own identity; close it
+	connDuplicate                      // Peer already connected over the canonical connection; close this one
+	connReplaces                       // This is the canonical connection; close the peer's other ones
+)
+
+// canonicalDialer returns which side's dial is kept when two peers connect to each other
+// at once: the connection dialed by the lower peer ID. Both ends compute the same answer,
+// so they close the same duplicate.
+func canonicalDialer(a, b peer.ID) peer.ID {
+	if a < b {
+		return a
+	}
+	return b
+}
+
+// handlePeerConnected registers a newly connected peer and announces it to the application.
+// dialer is the side that opened the connection. A connection to our own identity is never
+// registered. A second connection to an already registered peer (simultaneous dial) is not
+// announced again: the canonical one is kept and the caller closes the other.
+func (nm *NetworkManager) handlePeerConnected(peerID, dialer peer.ID) connDecision {
+	if peerID == nm.self {
+		log.Printf("Closing connection to %s: %s", peerID, reasonSelfConnection)
+		return connRefused
+	}
+	localPeerID := PeerID(peerID.String())
 
-			nm.peersMux.Lock()
-			delete(nm.peers, localPeerID)
+	nm.peersMux.Lock()
+	if _, exists := nm.peers[localPeerID]; exists {
+		canonical := canonicalDialer(nm.self, peerID)
+		if nm.peerDialers[localPeerID] == canonical || dialer != canonical {
 			nm.peersMux.Unlock()
+			log.Printf("Closing duplicate connection to %s", peerID)
+			return connDuplicate
+		}
+		nm.peerDialers[localPeerID] = dialer
+		nm.peersMux.Unlock()
+		log.Printf("Keeping canonical connection to %s, closing the duplicate", peerID)
+		return connReplaces
+	}
+	nm.peers[localPeerID] = peerID
+	nm.peerDialers[localPeerID] = dialer
+	nm.peersMux.Unlock()
 
-			log.Printf("Peer disconnected: %s", peerID)
+	nm.scores.connected(localPeerID, time.Now())
 
-			nm.eventCh <- NetworkEvent{
-				Type: "peer_disconnected",
-				From: localPeerID,
-			}
-		},
-	})
+	log.Printf("Peer connected: %s", peerID)
+
+	// Resume any negotiation interrupted by a previous drop
+	go nm.flushOutbox(localPeerID, nm.sendToPeer)
+
+	nm.eventCh <- NetworkEvent{
+		Type: "peer_connected",
+		From: localPeerID,
+	}
+	return connRegistered
+}
+
+// handlePeerDisconnected forgets a peer once its last connection closes. Closing a
+// duplicate connection leaves the canonical one open, so nothing is reported for it.
+func (nm *NetworkManager) handlePeerDisconnected(peerID peer.ID, remainingConns int) {
+	if peerID == nm.self || remainingConns > 0 {
+		return // Never registered, or still connected
+	}
+	localPeerID := PeerID(peerID.String())
+
+	nm.peersMux.Lock()
+	delete(nm.peers, localPeerID)
+	delete(nm.peerDialers, localPeerID)
+	nm.peersMux.Unlock()
+
+	nm.peerInterestsMux.Lock()
+	delete(nm.peerInterests, localPeerID)
+	nm.peerInterestsMux.Unlock()
+
+	nm.scores.forget(localPeerID)
+
+	log.Printf("Peer disconnected: %s", peerID)
+
+	nm.eventCh <- NetworkEvent{
+		Type: "peer_disconnected",
+		From: localPeerID,
+	}
 }
 
 // handleStream handles incoming streams (direct peer-to-peer messages)
@@ -246,31 +408,39 @@ func (nm *NetworkManager) handleStream(s network.Stream) {
 	peerID := s.Conn().RemotePeer()
 	localPeerID := PeerID(peerID.String())
 
-	reader := bufio.NewReader(s)
+	if err := nm.readFrames(localPeerID, bufio.NewReader(s)); err != nil {
+		log.Printf("Error reading frame from %s: %v", peerID, err)
+		if errors.Is(err, errEmptyFrame) {
+			s.Reset()
+		}
+	}
+}
 
+// readFrames delivers every frame on a stream to the application until it ends cleanly (nil) or fails.
+// A zero-length frame is a protocol violation: the peer is dropped and nothing is delivered for it.
+func (nm *NetworkManager) readFrames(from PeerID, r io.Reader) error {
+	var sequence frameSequence
 	for {
-		// Read length prefix (4 bytes, big endian)
-		var length uint32
-		if err := binary.Read(reader, binary.BigEndian, &length); err != nil {
-			if err != io.EOF {
-				log.Printf("Error reading length from %s: %v", peerID, err)
-			}
-			return
+		seq, data, err := readFrame(r)
+		if err == io.EOF {
+			return nil
 		}
-
-		// Read message data
-		data := make([]byte, length)
-		if _, err := io.ReadFull(reader, data); err != nil {
-			log.Printf("Error reading data from %s: %v", peerID, err)
-			return
+		if errors.Is(err, errEmptyFrame) {
+			nm.dropPeer(from, "protocol violation")
+			return err
+		}
+		if err != nil {
+			return err
 		}
 
-		log.Printf("Received %d bytes via stream from %s", len(data), peerID)
+		log.Printf("Received %d bytes via stream from %s (seq %d)", len(data), from, seq)
+
+		nm.checkSequence(from, &sequence, seq)
 
 		// Send to application via channel (NO MUTEX!)
 		nm.eventCh <- NetworkEvent{
 			Type: "message_received",
-			From: localPeerID,
+			From: from,
 			Data: data,
 		}
 	}
@@ -325,9 +495,29 @@ func (nm *NetworkManager) handleCommand(cmd NetworkCommand) {
 	case "connect":
 		go nm.connectToPeer(cmd.Addr)
 	case "send":
-		nm.sendToPeer(cmd.To, cmd.Data)
+		if err := nm.sendToPeer(cmd.To, cmd.Data); err != nil {
+			log.Printf("Send failed: %v", err)
+		}
+	case "send_reliable":
+		if !nm.HasPeer(cmd.To) {
+			nm.bufferForReconnect(cmd.To, cmd.Data)
+			return
+		}
+		// First attempt inline to keep ordering; retries run in the background
+		if err := nm.sendToPeer(cmd.To, cmd.Data); err != nil {
+			log.Printf("Send failed, retrying: %v", err)
+			go nm.retrySend(cmd.To, cmd.Data, sendRetryAttempts, sendRetryBackoff)
+		}
 	case "broadcast":
 		nm.broadcast(cmd.Data)
+	case "broadcast_coin":
+		err := nm.broadcastToInterested(cmd.Coin, cmd.Data)
+		if cmd.Result != nil {
+			cmd.Result <- err
+		}
+	case "flush":
+		// Commands run in order and sends complete inline, so everything queued earlier is out
+		close(cmd.Done)
 	case "shutdown":
 		nm.shutdown()
 	}
@@ -346,6 +536,10 @@ func (nm *NetworkManager) connectToPeer(addr string) {
 		log.Printf("Failed to parse peer info from %s: %v", addr, err)
 		return
 	}
+	if addrInfo.ID == nm.self {
+		log.Printf("Not connecting to %s: %s", addr, reasonSelfConnection)
+		return
+	}
 
 	// Wait a bit to let the target peer fully initialize
 	time.Sleep(500 * time.Millisecond)
@@ -355,65 +549,167 @@ func (nm *NetworkManager) connectToPeer(addr string) {
 	for i := 0; i < maxRetries; i++ {
 		log.Printf("Connecting to %s (attempt %d/%d)", addrInfo.ID, i+1, maxRetries)
 
-		if err := nm.host.Connect(nm.ctx, *addrInfo); err != nil {
+		if err := nm.dialPeer(*addrInfo); err != nil {
 			log.Printf("Failed to connect to %s (attempt %d/%d): %v", addr, i+1, maxRetries, err)
 			if i < maxRetries-1 {
 				time.Sleep(time.Second * time.Duration(i+1)) // Exponential backoff
 			}
 		} else {
 			log.Printf("Successfully connected to peer: %s", addrInfo.ID)
+
+			// The address we dialed is one we can dial again, unlike an inbound peer's
+			nm.eventCh <- NetworkEvent{
+				Type: "peer_dialed",
+				From: PeerID(addrInfo.ID.String()),
+				Data: []byte(addr),
+			}
 			return
 		}
 	}
 	log.Printf("Gave up connecting to %s after %d attempts", addr, maxRetries)
 }
 
+// SetConnectTimeout sets how long a single connection attempt may take before it is abandoned.
+// Call before Run.
+func (nm *NetworkManager) SetConnectTimeout(timeout time.Duration) {
+	nm.connectTimeout = timeout
+}
+
+// dialPeer makes one connection attempt, abandoning it after the connect timeout so an
+// unreachable address cannot block the caller for the OS TCP connect timeout
+func (nm *NetworkManager) dialPeer(pi peer.AddrInfo) error {
+	timeout := nm.connectTimeout
+	if timeout == 0 {
+		timeout = DefaultConnectTimeout
+	}
+	ctx, cancel := context.WithTimeout(nm.ctx, timeout)
+	defer cancel()
+
+	err := nm.transport.Connect(ctx, pi)
+	if err != nil && errors.Is(ctx.Err(), context.DeadlineExceeded) {
+		return fmt.Errorf("%w: %s after %s", ErrPeerTimeout, pi.ID, timeout)
+	}
+	return err
+}
+
 // sendToPeer sends a message to a specific peer via stream
-func (nm *NetworkManager) sendToPeer(localPeerID PeerID, data []byte) {
+func (nm *NetworkManager) sendToPeer(localPeerID PeerID, data []byte) error {
 	nm.peersMux.RLock()
 	peerID, ok := nm.peers[localPeerID]
 	nm.peersMux.RUnlock()
 
 	if !ok {
-		log.Printf("Peer %s not found", localPeerID)
-		return
+		return fmt.Errorf("peer %s not found", localPeerID)
 	}
 
 	// Open a new stream to the peer
-	s, err := nm.host.NewStream(nm.ctx, peerID, protocol.ID(BlackTraceProtocolID))
+	s, err := nm.transport.OpenStream(nm.ctx, peerID)
 	if err != nil {
-		log.Printf("Failed to open stream to %s: %v", peerID, err)
-		return
+		nm.scores.recordDelivery(localPeerID, false)
+		return fmt.Errorf("failed to open stream to %s: %w", peerID, err)
 	}
 	defer s.Close()
 
-	writer := bufio.NewWriter(s)
+	// The stream carries only this frame, so it is the stream's first
+	var sequence frameSequence
+	seq := sequence.next()
+	if err := nm.deliverFrame(localPeerID, bufio.NewWriter(s), seq, data); err != nil {
+		// Never leave a half-written frame on the stream
+		s.Reset()
+		return fmt.Errorf("error writing to %s: %w", peerID, err)
+	}
+	nm.scores.recordDelivery(localPeerID, true)
 
-	// Write length prefix
-	length := uint32(len(data))
-	if err := binary.Write(writer, binary.BigEndian, length); err != nil {
-		log.Printf("Error writing length to %s: %v", peerID, err)
+	log.Printf("Sent %d bytes via stream to %s (seq %d)", len(data), peerID, seq)
+	return nil
+}
+
+// deliverFrame writes and flushes one frame to a peer. A failed write may have left a partial
+// frame behind, so the peer is dropped instead of being reused with a desynced stream.
+func (nm *NetworkManager) deliverFrame(to PeerID, w *bufio.Writer, seq uint64, data []byte) error {
+	err := writeFrame(w, seq, data)
+	if err == nil {
+		err = w.Flush()
+	}
+	if err != nil {
+		nm.dropPeer(to, "failed write")
+		return err
+	}
+	return nil
+}
+
+// dropPeer forgets a peer whose connection is no longer usable and closes it.
+// The disconnect notification then reports it to the application as usual.
+func (nm *NetworkManager) dropPeer(localPeerID PeerID, reason string) {
+	nm.peersMux.Lock()
+	peerID, ok := nm.peers[localPeerID]
+	delete(nm.peers, localPeerID)
+	delete(nm.peerDialers, localPeerID)
+	nm.peersMux.Unlock()
+
+	if !ok {
 		return
 	}
 
-	// Write data
-	if _, err := writer.Write(data); err != nil {
-		log.Printf("Error writing data to %s: %v", peerID, err)
+	nm.peerInterestsMux.Lock()
+	delete(nm.peerInterests, localPeerID)
+	nm.peerInterestsMux.Unlock()
+
+	nm.scores.forget(localPeerID)
+
+	log.Printf("Dropping peer %s (%s)", localPeerID, reason)
+	if nm.host != nil {
+		if err := nm.host.Network().ClosePeer(peerID); err != nil {
+			log.Printf("Failed to close connection to %s: %v", peerID, err)
+		}
+	}
+}
+
+// retrySend retries a failed send with exponential backoff. If the peer has disconnected
+// the message is buffered for its reconnect; if it is connected but still unreachable the
+// message is dead-lettered back to the application as "send_failed".
+func (nm *NetworkManager) retrySend(to PeerID, data []byte, attempts int, backoff time.Duration) {
+	var err error
+	for i := 0; i < attempts; i++ {
+		time.Sleep(backoff << i)
+		if err = nm.sendToPeer(to, data); err == nil {
+			return
+		}
+		log.Printf("Retry %d/%d to %s failed: %v", i+1, attempts, to, err)
+	}
+
+	if !nm.HasPeer(to) {
+		nm.bufferForReconnect(to, data)
 		return
 	}
+	nm.deadLetter(to, data)
+}
 
-	// Flush
-	if err := writer.Flush(); err != nil {
-		log.Printf("Error flushing to %s: %v", peerID, err)
+// checkSequence validates an incoming frame's sequence number against the frames read
+// before it on the same stream, and reports gaps to the application
+func (nm *NetworkManager) checkSequence(from PeerID, sequence *frameSequence, seq uint64) {
+	expected, ok := sequence.check(seq)
+	if ok {
 		return
 	}
+	nm.gaps.add()
+
+	log.Printf("Sequence gap from %s: expected %d, got %d", from, expected, seq)
+
+	nm.eventCh <- NetworkEvent{
+		Type: "sequence_gap",
+		From: from,
+	}
+}
 
-	log.Printf("Sent %d bytes via stream to %s", len(data), peerID)
+// SequenceGaps returns the number of frame sequence gaps detected since startup
+func (nm *NetworkManager) SequenceGaps() uint64 {
+	return nm.gaps.count()
 }
 
 // broadcast sends a message to all peers via pubsub
 func (nm *NetworkManager) broadcast(data []byte) {
-	if err := nm.topic.Publish(nm.ctx, data); err != nil {
+	if err := nm.gossip.Publish(nm.ctx, data); err != nil {
 		log.Printf("Failed to publish to topic: %v", err)
 		return
 	}
@@ -421,6 +717,100 @@ func (nm *NetworkManager) broadcast(data []byte) {
 	log.Printf("Broadcast %d bytes via pubsub", len(data))
 }
 
+// SetPeerInterests records the stablecoins a peer advertised interest in
+func (nm *NetworkManager) SetPeerInterests(peerID PeerID, coins []StablecoinType) {
+	nm.peerInterestsMux.Lock()
+	nm.peerInterests[peerID] = coins
+	nm.peerInterestsMux.Unlock()
+
+	log.Printf("Peer %s advertised interest in: %v", peerID, coins)
+}
+
+// peersForCoin returns the connected peers that should receive traffic for a coin.
+// Peers that never advertised interests are included (broadcast-all fallback).
+func (nm *NetworkManager) peersForCoin(coin StablecoinType) []PeerID {
+	nm.peersMux.RLock()
+	defer nm.peersMux.RUnlock()
+	nm.peerInterestsMux.RLock()
+	defer nm.peerInterestsMux.RUnlock()
+
+	targets := make([]PeerID, 0, len(nm.peers))
+	for peerID := range nm.peers {
+		interests, advertised := nm.peerInterests[peerID]
+		if !advertised {
+			targets = append(targets, peerID)
+			continue
+		}
+		for _, c := range interests {
+			if c == coin {
+				targets = append(targets, peerID)
+				break
+			}
+		}
+	}
+
+	sort.Slice(targets, func(i, j int) bool { return targets[i] < targets[j] })
+	return targets
+}
+
+// broadcastOrder returns the peers interested in a coin, best-scored first
+func (nm *NetworkManager) broadcastOrder(coin StablecoinType) []PeerID {
+	return nm.scores.rank(nm.peersForCoin(coin), time.Now())
+}
+
+// interestsKnown reports whether any connected peer told us which stablecoins it wants
+func (nm *NetworkManager) interestsKnown() bool {
+	nm.peersMux.RLock()
+	defer nm.peersMux.RUnlock()
+	nm.peerInterestsMux.RLock()
+	defer nm.peerInterestsMux.RUnlock()
+
+	for peerID := range nm.peers {
+		if _, ok := nm.peerInterests[peerID]; ok {
+			return true
+		}
+	}
+	return false
+}
+
+// broadcastToInterested sends a message for a coin to the connected peers that want it,
+// best-scored first: those that advertised interest in the coin, and those that never
+// advertised any. Peers interested only in other coins get nothing. Until some connected
+// peer has advertised its interests there is nothing to route by, so the message is
+// published on the gossip mesh instead, which also relays it beyond our own peers.
+// Returns an error if the message reached no peer.
+func (nm *NetworkManager) broadcastToInterested(coin StablecoinType, data []byte) error {
+	if len(nm.PeerIDs()) == 0 {
+		return fmt.Errorf("no peers to send %s message to", coin)
+	}
+	if !nm.interestsKnown() {
+		if err := nm.gossip.Publish(nm.ctx, data); err != nil {
+			return fmt.Errorf("failed to publish %s message: %w", coin, err)
+		}
+		log.Printf("Published %d bytes for %s via pubsub (no peer interests known)", len(data), coin)
+		return nil
+	}
+
+	targets := nm.broadcastOrder(coin)
+	if len(targets) == 0 {
+		return fmt.Errorf("no connected peer is interested in %s", coin)
+	}
+	sent := 0
+	for _, peerID := range targets {
+		if err := nm.sendToPeer(peerID, data); err != nil {
+			log.Printf("Direct send of %s message failed: %v", coin, err)
+			continue
+		}
+		sent++
+	}
+	if sent == 0 {
+		return fmt.Errorf("%s message reached none of %d interested peers", coin, len(targets))
+	}
+
+	log.Printf("Sent %d bytes for %s to %d/%d interested peers", len(data), coin, sent, len(targets))
+	return nil
+}
+
 // shutdown cleanly shuts down the network manager
 func (nm *NetworkManager) shutdown() {
 	close(nm.shutdownCh)
@@ -448,9 +838,10 @@ type PeerConnection struct {
 
 // NodeStatus represents the node's status
 type NodeStatus struct {
-	PeerID     string
-	ListenAddr string
-	PeerCount  int
+	PeerID       string
+	ListenAddr   string
+	PeerCount    int
+	SequenceGaps uint64
 }
 
 // GetPeers returns list of connected peers
@@ -476,6 +867,29 @@ func (nm *NetworkManager) GetPeers() []PeerConnection {
 	return peers
 }
 
+// PeerIDs returns the connected peers in sorted order
+func (nm *NetworkManager) PeerIDs() []PeerID {
+	nm.peersMux.RLock()
+	defer nm.peersMux.RUnlock()
+
+	ids := make([]PeerID, 0, len(nm.peers))
+	for id := range nm.peers {
+		ids = append(ids, id)
+	}
+
+	sort.Slice(ids, func(i, j int) bool { return ids[i] < ids[j] })
+	return ids
+}
+
+// HasPeer reports whether a peer is currently connected
+func (nm *NetworkManager) HasPeer(id PeerID) bool {
+	nm.peersMux.RLock()
+	defer nm.peersMux.RUnlock()
+
+	_, ok := nm.peers[id]
+	return ok
+}
+
 // GetStatus returns the node's current status
 func (nm *NetworkManager) GetStatus() NodeStatus {
 	nm.peersMux.RLock()
@@ -488,8 +902,9 @@ func (nm *NetworkManager) GetStatus() NodeStatus {
 	}
 
 	return NodeStatus{
-		PeerID:     nm.host.ID().String(),
-		ListenAddr: listenAddr,
-		PeerCount:  peerCount,
+		PeerID:       nm.host.ID().String(),
+		ListenAddr:   listenAddr,
+		PeerCount:    peerCount,
+		SequenceGaps: nm.SequenceGaps(),
 	}
 }
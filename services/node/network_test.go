@@ -0,0 +1,518 @@
+package node
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"log"
+	"os"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/libp2p/go-libp2p/core/peer"
+)
+
+// newTestNetworkManager builds a NetworkManager with the given peers and no libp2p host
+func newTestNetworkManager(peerIDs ...PeerID) *NetworkManager {
+	nm := &NetworkManager{
+		ctx:           context.Background(),
+		peers:         make(map[PeerID]peer.ID),
+		peerDialers:   make(map[PeerID]peer.ID),
+		peerInterests: make(map[PeerID][]StablecoinType),
+		outbox:        newOutbox(),
+		scores:        newPeerScores(),
+		eventCh:       make(chan NetworkEvent, 10),
+	}
+	for _, id := range peerIDs {
+		nm.peers[id] = peer.ID(id)
+	}
+	return nm
+}
+
+func containsPeer(peers []PeerID, id PeerID) bool {
+	for _, p := range peers {
+		if p == id {
+			return true
+		}
+	}
+	return false
+}
+
+func TestPeersForCoinRoutesByInterest(t *testing.T) {
+	nm := newTestNetworkManager("usdc-peer", "legacy-peer")
+	nm.SetPeerInterests("usdc-peer", []StablecoinType{StablecoinUSDC})
+
+	// DAI order: the USDC-only peer must not receive it
+	daiTargets := nm.peersForCoin(StablecoinDAI)
+	if containsPeer(daiTargets, "usdc-peer") {
+		t.Errorf("USDC-only peer should not receive a DAI order, got targets %v", daiTargets)
+	}
+	if !containsPeer(daiTargets, "legacy-peer") {
+		t.Errorf("Peer without advertised interests should receive every order, got targets %v", daiTargets)
+	}
+
+	// USDC order: both peers receive it
+	usdcTargets := nm.peersForCoin(StablecoinUSDC)
+	if !containsPeer(usdcTargets, "usdc-peer") || !containsPeer(usdcTargets, "legacy-peer") {
+		t.Errorf("Expected both peers to receive a USDC order, got targets %v", usdcTargets)
+	}
+}
+
+func TestFrameRoundTrip(t *testing.T) {
+	var buf bytes.Buffer
+	if err := writeFrame(&buf, 7, []byte("hello")); err != nil {
+		t.Fatalf("Failed to write frame: %v", err)
+	}
+
+	seq, data, err := readFrame(&buf)
+	if err != nil {
+		t.Fatalf("Failed to read frame: %v", err)
+	}
+	if seq != 7 || string(data) != "hello" {
+		t.Errorf("Round trip mismatch: seq %d, data %q", seq, data)
+	}
+}
+
+// brokenConn accepts a few bytes and then fails, like a connection dropping mid-send
+type brokenConn struct {
+	written bytes.Buffer
+	limit   int
+}
+
+func (c *brokenConn) Write(p []byte) (int, error) {
+	room := c.limit - c.written.Len()
+	if room >= len(p) {
+		return c.written.Write(p)
+	}
+	c.written.Write(p[:room])
+	return room, errors.New("connection reset")
+}
+
+func TestFailedWriteDropsPeer(t *testing.T) {
+	nm := newTestNetworkManager("peer-a")
+	nm.SetPeerInterests("peer-a", []StablecoinType{StablecoinUSDC})
+
+	conn := &brokenConn{limit: 5}
+	if err := nm.deliverFrame("peer-a", bufio.NewWriterSize(conn, 16), 1, bytes.Repeat([]byte("x"), 64)); err == nil {
+		t.Fatal("Expected the write to fail")
+	}
+
+	if containsPeer(nm.PeerIDs(), "peer-a") {
+		t.Error("Peer should be dropped after a failed write")
+	}
+	if containsPeer(nm.peersForCoin(StablecoinUSDC), "peer-a") {
+		t.Error("Dropped peer should not be routed to")
+	}
+}
+
+func TestSequenceGapDetected(t *testing.T) {
+	nm := newTestNetworkManager("peer-a")
+
+	// Frames 1 and 2 arrive in order on one stream, then 4 arrives before 3
+	var buf bytes.Buffer
+	for _, seq := range []uint64{1, 2, 4, 3} {
+		if err := writeFrame(&buf, seq, []byte("msg")); err != nil {
+			t.Fatalf("Failed to write frame: %v", err)
+		}
+	}
+	if err := nm.readFrames("peer-a", &buf); err != nil {
+		t.Fatalf("Failed to read frames: %v", err)
+	}
+
+	// Both the skip to 4 and the late 3 are reported, and every frame is still delivered
+	if gaps := nm.SequenceGaps(); gaps != 2 {
+		t.Errorf("Expected 2 sequence gaps, got %d", gaps)
+	}
+	counts := map[string]int{}
+	for len(nm.eventCh) > 0 {
+		event := <-nm.eventCh
+		if event.From != "peer-a" {
+			t.Errorf("Unexpected event: %+v", event)
+		}
+		counts[event.Type]++
+	}
+	if counts["sequence_gap"] != 2 || counts["message_received"] != 4 {
+		t.Errorf("Expected 2 sequence_gap and 4 message_received events, got %v", counts)
+	}
+
+	// Another stream from the same peer starts again from 1 without a gap
+	buf.Reset()
+	if err := writeFrame(&buf, 1, []byte("msg")); err != nil {
+		t.Fatalf("Failed to write frame: %v", err)
+	}
+	if err := nm.readFrames("peer-a", &buf); err != nil {
+		t.Fatalf("Failed to read frames: %v", err)
+	}
+	if gaps := nm.SequenceGaps(); gaps != 2 {
+		t.Errorf("Fresh stream should not report a gap, got %d total", gaps)
+	}
+}
+
+func TestConcurrentSendsReportNoGaps(t *testing.T) {
+	hub := newMemHub()
+	sender := newMemNode(t, hub, "sender")
+	receiver := newMemNode(t, hub, "receiver")
+	hub.connect(sender.network, receiver.network)
+	to := PeerID(receiver.network.self.String())
+
+	// Each message opens its own stream, and the receiver reads the streams concurrently
+	const messages = 20
+	var wg sync.WaitGroup
+	for i := 0; i < messages; i++ {
+		wg.Add(1)
+		go func(i int) {
+			defer wg.Done()
+			if err := sender.network.sendToPeer(to, []byte(fmt.Sprintf("msg-%d", i))); err != nil {
+				t.Errorf("Send %d failed: %v", i, err)
+			}
+		}(i)
+	}
+	wg.Wait()
+
+	if gaps := receiver.network.SequenceGaps(); gaps != 0 {
+		t.Errorf("Concurrent streams should not report sequence gaps, got %d", gaps)
+	}
+}
+
+func TestRetrySendDeadLettersWhenPeerNeverReturns(t *testing.T) {
+	// The counterparty has disconnected, so every attempt fails
+	nm := newTestNetworkManager()
+
+	nm.retrySend("maker", []byte("proposal"), 3, time.Millisecond)
+
+	select {
+	case event := <-nm.eventCh:
+		t.Fatalf("Message should be buffered while the peer may reconnect, got %+v", event)
+	default:
+	}
+
+	nm.sweepOutbox(time.Now().Add(outboxMaxAge + time.Second))
+
+	select {
+	case event := <-nm.eventCh:
+		if event.Type != "send_failed" || event.From != "maker" || string(event.Data) != "proposal" {
+			t.Errorf("Unexpected event: %+v", event)
+		}
+	default:
+		t.Fatal("Expected a send_failed event once the buffered message expired")
+	}
+}
+
+func TestBufferedMessagesDeliveredAfterReconnect(t *testing.T) {
+	hub := newMemHub()
+	nm, maker := newTestNetworkManager(), newTestNetworkManager()
+	nm.self, maker.self = peer.ID("taker"), peer.ID("maker")
+	hub.attach(nm)
+	hub.attach(maker)
+	makerID := PeerID(maker.self.String())
+
+	// Maker drops mid-negotiation; the proposal and a follow-up are buffered
+	nm.handleCommand(NetworkCommand{Type: "send_reliable", To: makerID, Data: []byte("proposal")})
+	nm.handleCommand(NetworkCommand{Type: "send_reliable", To: makerID, Data: []byte("liquidity_request")})
+
+	// Same identity reconnects; the buffered messages go out over the transport
+	hub.connect(nm, maker)
+	nm.flushOutbox(makerID, nm.sendToPeer)
+
+	var delivered []string
+	for len(delivered) < 2 {
+		select {
+		case event := <-maker.eventCh:
+			if event.Type == "message_received" {
+				delivered = append(delivered, string(event.Data))
+			}
+		case <-time.After(time.Second):
+			t.Fatalf("Expected 2 buffered messages delivered, got %v", delivered)
+		}
+	}
+
+	if delivered[0] != "proposal" || delivered[1] != "liquidity_request" {
+		t.Errorf("Expected buffered messages delivered in order, got %v", delivered)
+	}
+	if fresh, _ := nm.outbox.take(makerID, time.Now()); len(fresh) != 0 {
+		t.Errorf("Outbox should be empty after flush, %d messages left", len(fresh))
+	}
+}
+
+func TestOutboxBounded(t *testing.T) {
+	o := newOutbox()
+	now := time.Now()
+
+	var evicted [][]byte
+	for i := 0; i < outboxMaxMessages+2; i++ {
+		evicted = append(evicted, o.push("maker", []byte{byte(i)}, now)...)
+	}
+
+	if len(evicted) != 2 || evicted[0][0] != 0 || evicted[1][0] != 1 {
+		t.Errorf("Expected the two oldest messages evicted, got %v", evicted)
+	}
+	if fresh, _ := o.take("maker", now); len(fresh) != outboxMaxMessages {
+		t.Errorf("Expected %d buffered messages, got %d", outboxMaxMessages, len(fresh))
+	}
+}
+
+func TestSelfConnectionNotRegistered(t *testing.T) {
+	nm := newTestNetworkManager()
+	nm.self = peer.ID("self-peer")
+
+	// Dialing our own listen address connects back to our own identity
+	if nm.handlePeerConnected(nm.self, nm.self) != connRefused {
+		t.Fatal("Self-connection should be refused")
+	}
+	if peers := nm.PeerIDs(); len(peers) != 0 {
+		t.Errorf("No self-peer should be registered, got %v", peers)
+	}
+	select {
+	case event := <-nm.eventCh:
+		t.Errorf("Self-connection should not emit events, got %+v", event)
+	default:
+	}
+}
+
+func TestPollEventsDrainsBurstInOrder(t *testing.T) {
+	nm := newTestNetworkManager()
+	for i := 0; i < 5; i++ {
+		nm.eventCh <- NetworkEvent{Type: "message", From: PeerID(fmt.Sprintf("peer-%d", i))}
+	}
+
+	first := nm.PollEvents(2)
+	rest := nm.PollEvents(10)
+	if len(first) != 2 || len(rest) != 3 {
+		t.Fatalf("Expected batches of 2 and 3 events, got %d and %d", len(first), len(rest))
+	}
+	for i, event := range append(first, rest...) {
+		if want := PeerID(fmt.Sprintf("peer-%d", i)); event.From != want {
+			t.Errorf("Event %d: expected %s, got %s", i, want, event.From)
+		}
+	}
+	if events := nm.PollEvents(10); len(events) != 0 {
+		t.Errorf("Expected no pending events, got %d", len(events))
+	}
+}
+
+func TestPollEventsWithoutRoomTakesNothing(t *testing.T) {
+	nm := newTestNetworkManager()
+	nm.eventCh <- NetworkEvent{Type: "message", From: "peer-0"}
+
+	for _, max := range []int{0, -1} {
+		if events := nm.PollEvents(max); events != nil {
+			t.Errorf("PollEvents(%d): expected nil, got %d events", max, len(events))
+		}
+	}
+	if len(nm.eventCh) != 1 {
+		t.Error("Pending event should be left for the next poll")
+	}
+}
+
+func TestSimultaneousConnectRegistersPeerOnce(t *testing.T) {
+	nm := newTestNetworkManager()
+	nm.self = peer.ID("b-self")
+	remote := peer.ID("a-remote")
+
+	// Both sides dial at once: our outbound connection arrives first, then the remote's inbound one
+	if got := nm.handlePeerConnected(remote, nm.self); got != connRegistered {
+		t.Fatalf("First connection should register the peer, got %v", got)
+	}
+	// The remote has the lower ID, so its dial is the canonical connection and replaces ours
+	if got := nm.handlePeerConnected(remote, remote); got != connReplaces {
+		t.Fatalf("Canonical connection should replace the duplicate, got %v", got)
+	}
+	// Closing our duplicate leaves the canonical connection up
+	nm.handlePeerDisconnected(remote, 1)
+	// A further connection is a duplicate of the canonical one
+	if got := nm.handlePeerConnected(remote, nm.self); got != connDuplicate {
+		t.Fatalf("Later connection should be closed as a duplicate, got %v", got)
+	}
+
+	if peers := nm.PeerIDs(); len(peers) != 1 || peers[0] != PeerID(remote) {
+		t.Errorf("Expected exactly one peer entry, got %v", peers)
+	}
+	if event := <-nm.eventCh; event.Type != "peer_connected" || event.From != PeerID(remote) {
+		t.Errorf("Expected one peer_connected for %s, got %+v", remote, event)
+	}
+	select {
+	case event := <-nm.eventCh:
+		t.Errorf("Expected a single event, got another: %+v", event)
+	default:
+	}
+}
+
+func TestZeroLengthFrameDropsPeer(t *testing.T) {
+	nm := newTestNetworkManager("peer-a")
+
+	var buf bytes.Buffer
+	if err := writeFrame(&buf, 1, []byte("hello")); err != nil {
+		t.Fatalf("Failed to write frame: %v", err)
+	}
+	if err := writeFrame(&buf, 2, nil); err != nil {
+		t.Fatalf("Failed to write frame: %v", err)
+	}
+
+	if err := nm.readFrames("peer-a", &buf); !errors.Is(err, errEmptyFrame) {
+		t.Fatalf("Expected errEmptyFrame, got %v", err)
+	}
+	if containsPeer(nm.PeerIDs(), "peer-a") {
+		t.Error("Peer sending a zero-length frame should be dropped")
+	}
+
+	// Only the valid frame reached the application
+	event := <-nm.eventCh
+	if event.Type != "message_received" || string(event.Data) != "hello" {
+		t.Errorf("Unexpected event: %+v", event)
+	}
+	select {
+	case event := <-nm.eventCh:
+		t.Errorf("No event should be emitted for the empty frame, got %+v", event)
+	default:
+	}
+}
+
+// waitFor polls cond until it holds or the deadline passes
+func waitFor(cond func() bool, timeout time.Duration) bool {
+	deadline := time.Now().Add(timeout)
+	for time.Now().Before(deadline) {
+		if cond() {
+			return true
+		}
+		time.Sleep(5 * time.Millisecond)
+	}
+	return cond()
+}
+
+func TestConcurrentNegotiationsOverInMemoryTransport(t *testing.T) {
+	log.SetOutput(io.Discard)
+	defer log.SetOutput(os.Stderr)
+
+	const pairs = 8
+	hub := newMemHub()
+
+	type negotiation struct {
+		maker, taker *BlackTraceApp
+		orderID      OrderID
+	}
+	negotiations := make([]negotiation, pairs)
+	for i := range negotiations {
+		maker := newMemNode(t, hub, fmt.Sprintf("maker-%d", i))
+		taker := newMemNode(t, hub, fmt.Sprintf("taker-%d", i))
+		hub.connect(maker.network, taker.network)
+
+		// Maker owns a committed order; the taker has its announcement
+		orderID := OrderID(fmt.Sprintf("order_%d", i))
+		commitment := maker.commitToOrder(orderID, 10000)
+		announcement := &OrderAnnouncement{
+			OrderID:         orderID,
+			OrderType:       OrderTypeSell,
+			Stablecoin:      StablecoinUSDC,
+			MakerID:         maker.GetPeerID(),
+			ProofCommitment: commitment,
+		}
+		details := &OrderDetails{OrderID: orderID, OrderType: OrderTypeSell, Amount: 10000, MinPrice: 450, MaxPrice: 470, Stablecoin: StablecoinUSDC}
+		maker.orders[orderID] = announcement
+		maker.orderDetails[orderID] = details
+		maker.markOwnedOrder(details)
+		takerCopy := *announcement
+		taker.orders[orderID] = &takerCopy
+
+		negotiations[i] = negotiation{maker: maker, taker: taker, orderID: orderID}
+	}
+
+	var wg sync.WaitGroup
+	errs := make(chan error, pairs)
+	for _, n := range negotiations {
+		wg.Add(1)
+		go func(n negotiation) {
+			defer wg.Done()
+
+			// Details, then the liquidity challenge, all inside the negotiation session
+			n.taker.RequestOrderDetails(n.orderID)
+			if !waitFor(func() bool { return n.taker.IsLiquidityVerified(n.orderID) }, 5*time.Second) {
+				errs <- fmt.Errorf("%s: liquidity never verified", n.orderID)
+				return
+			}
+
+			n.taker.ProposePrice(n.orderID, 460, 10000, "", "")
+			if !waitFor(func() bool { return len(n.maker.ListProposals(n.orderID)) == 1 }, 5*time.Second) {
+				errs <- fmt.Errorf("%s: maker never received the proposal", n.orderID)
+				return
+			}
+			proposal := n.maker.ListProposals(n.orderID)[0]
+			if proposal.ProposerID != n.taker.GetPeerID() || proposal.Price != 460 {
+				errs <- fmt.Errorf("%s: unexpected proposal %+v", n.orderID, proposal)
+			}
+		}(n)
+	}
+	wg.Wait()
+	close(errs)
+
+	for err := range errs {
+		t.Error(err)
+	}
+}
+
+func TestBroadcastOrderFollowsPeerScores(t *testing.T) {
+	nm := newTestNetworkManager("slow-peer", "fast-peer", "flaky-peer")
+	established := time.Now().Add(-2 * scoreUptimeReference)
+	for _, id := range []PeerID{"slow-peer", "fast-peer", "flaky-peer"} {
+		nm.scores.connected(id, established)
+	}
+
+	nm.RecordPeerRTT("slow-peer", 300*time.Millisecond)
+	nm.RecordPeerRTT("fast-peer", 20*time.Millisecond)
+	nm.RecordPeerRTT("flaky-peer", 20*time.Millisecond)
+	for i := 0; i < 10; i++ {
+		nm.scores.recordDelivery("fast-peer", true)
+		nm.scores.recordDelivery("flaky-peer", false)
+	}
+
+	order := nm.broadcastOrder(StablecoinUSDC)
+	want := []PeerID{"fast-peer", "slow-peer", "flaky-peer"}
+	if len(order) != len(want) {
+		t.Fatalf("Expected %v, got %v", want, order)
+	}
+	for i := range want {
+		if order[i] != want[i] {
+			t.Fatalf("Expected %v, got %v", want, order)
+		}
+	}
+
+	// A freshly connected peer with no record ranks below established well-behaved peers
+	nm.peers["new-peer"] = peer.ID("new-peer")
+	nm.scores.connected("new-peer", time.Now())
+	if order := nm.broadcastOrder(StablecoinUSDC); order[0] != "fast-peer" || order[len(order)-1] != "flaky-peer" {
+		t.Errorf("Expected fast-peer first and flaky-peer last, got %v", order)
+	}
+}
+
+func TestConnectToUnreachablePeerTimesOut(t *testing.T) {
+	hub := newMemHub()
+	nm, reachable := newTestNetworkManager(), newTestNetworkManager()
+	nm.self, reachable.self = peer.ID("dialer"), peer.ID("reachable")
+	hub.attach(nm)
+	hub.attach(reachable)
+	nm.SetConnectTimeout(50 * time.Millisecond)
+
+	if err := nm.dialPeer(peer.AddrInfo{ID: reachable.self}); err != nil {
+		t.Fatalf("Failed to connect to a reachable peer: %v", err)
+	}
+
+	// Nothing answers for this peer, so the attempt would otherwise hang
+	start := time.Now()
+	done := make(chan error, 1)
+	go func() { done <- nm.dialPeer(peer.AddrInfo{ID: peer.ID("blackholed")}) }()
+
+	select {
+	case err := <-done:
+		if !errors.Is(err, ErrPeerTimeout) {
+			t.Errorf("Expected ErrPeerTimeout, got %v", err)
+		}
+		if elapsed := time.Since(start); elapsed > time.Second {
+			t.Errorf("Connect took %s, expected it to give up after the 50ms timeout", elapsed)
+		}
+	case <-time.After(5 * time.Second):
+		t.Fatal("Connect to an unreachable peer hung")
+	}
+}
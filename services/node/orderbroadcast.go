@@ -0,0 +1,131 @@
+package node
+
+import (
+	"fmt"
+	"log"
+	"time"
+)
+
+// OrderBroadcastState records whether peers have been sent one of our orders
+type OrderBroadcastState string
+
+const (
+	OrderBroadcastPending   OrderBroadcastState = "pending_broadcast" // Stored locally, not yet published to the network
+	OrderBroadcastAnnounced OrderBroadcastState = "announced"         // Sent to interested peers, or gossiped
+)
+
+// Retry policy for announcing a new order
+const (
+	orderBroadcastAttempts = 3
+	orderBroadcastBackoff  = 2 * time.Second
+	orderBroadcastTimeout  = 10 * time.Second // How long one attempt waits for the network's result
+)
+
+// markBroadcastPending records, durably, that an order still has to be announced
+func (app *BlackTraceApp) markBroadcastPending(orderID OrderID) {
+	app.orderBroadcastsMux.Lock()
+	app.orderBroadcasts[orderID] = OrderBroadcastPending
+	app.orderBroadcastsMux.Unlock()
+	app.persist(storageNamespacePendingBroadcasts, string(orderID), orderID)
+}
+
+// markBroadcastAnnounced records that an order was published to the network
+func (app *BlackTraceApp) markBroadcastAnnounced(orderID OrderID) {
+	app.orderBroadcastsMux.Lock()
+	app.orderBroadcasts[orderID] = OrderBroadcastAnnounced
+	app.orderBroadcastsMux.Unlock()
+	app.forgetPendingBroadcast(orderID)
+}
+
+// forgetPendingBroadcast removes an order's persisted pending-broadcast marker
+func (app *BlackTraceApp) forgetPendingBroadcast(orderID OrderID) {
+	if app.store == nil {
+		return
+	}
+	if err := app.store.Delete(storageNamespacePendingBroadcasts, string(orderID)); err != nil {
+		log.Printf("Warning: Failed to delete pending broadcast marker for %s: %v", orderID, err)
+	}
+}
+
+// OrderBroadcastState returns whether one of our orders has been announced to peers
+func (app *BlackTraceApp) OrderBroadcastState(orderID OrderID) (OrderBroadcastState, bool) {
+	app.orderBroadcastsMux.RLock()
+	defer app.orderBroadcastsMux.RUnlock()
+
+	state, ok := app.orderBroadcasts[orderID]
+	return state, ok
+}
+
+// broadcastOrderOnce routes an order announcement to interested peers and waits for the
+// network's result: an error unless it reached a peer
+func (app *BlackTraceApp) broadcastOrderOnce(announcement *OrderAnnouncement, timeout time.Duration) error {
+	data, err := app.marshalOutbound("order_announcement", announcement)
+	if err != nil {
+		return err
+	}
+
+	result := make(chan error, 1)
+	app.network.CommandChan() <- NetworkCommand{
+		Type:   "broadcast_coin",
+		Coin:   announcement.Stablecoin,
+		Data:   data,
+		Result: result,
+	}
+	select {
+	case err := <-result:
+		return err
+	case <-time.After(timeout):
+		return fmt.Errorf("no broadcast result after %s", timeout)
+	}
+}
+
+// announceOrder broadcasts a pending order, retrying with exponential backoff, and marks it
+// announced once it reaches a peer. An order still failing after the last attempt
+// stays pending and is retried when the node next starts.
+func (app *BlackTraceApp) announceOrder(orderID OrderID, attempts int, backoff time.Duration) bool {
+	var err error
+	for i := 0; i < attempts; i++ {
+		if i > 0 {
+			time.Sleep(backoff << (i - 1))
+		}
+
+		app.ordersMux.RLock()
+		order, ok := app.orders[orderID]
+		var announcement OrderAnnouncement
+		if ok {
+			announcement = *order
+		}
+		app.ordersMux.RUnlock()
+		if !ok {
+			// Cancelled or expired while waiting; nothing left to announce
+			app.forgetPendingBroadcast(orderID)
+			return false
+		}
+
+		if err = app.broadcastOrderOnce(&announcement, orderBroadcastTimeout); err == nil {
+			app.markBroadcastAnnounced(orderID)
+			log.Printf("App: Order %s announced", orderID)
+			return true
+		}
+		log.Printf("App: Broadcast %d/%d of order %s failed: %v", i+1, attempts, orderID, err)
+	}
+
+	log.Printf("Warning: Order %s is stored locally but not announced (%v); it stays %s", orderID, err, OrderBroadcastPending)
+	return false
+}
+
+// retryPendingBroadcasts announces orders left pending by an earlier run
+func (app *BlackTraceApp) retryPendingBroadcasts() {
+	app.orderBroadcastsMux.RLock()
+	pending := make([]OrderID, 0)
+	for orderID, state := range app.orderBroadcasts {
+		if state == OrderBroadcastPending {
+			pending = append(pending, orderID)
+		}
+	}
+	app.orderBroadcastsMux.RUnlock()
+
+	for _, orderID := range pending {
+		app.announceOrder(orderID, orderBroadcastAttempts, orderBroadcastBackoff)
+	}
+}
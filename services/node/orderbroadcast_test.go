@@ -0,0 +1,137 @@
+package node
+
+import (
+	"bytes"
+	"testing"
+	"time"
+)
+
+func TestFailedOrderBroadcastStaysPendingAndIsRetried(t *testing.T) {
+	hub := newMemHub()
+	maker := newMemNode(t, hub, "maker")
+	maker.store = NewMemStorage()
+
+	// No peer is connected yet, so there is nobody to gossip the order to
+	orderID := OrderID("order_1")
+	maker.orders[orderID] = &OrderAnnouncement{
+		OrderID:         orderID,
+		Stablecoin:      StablecoinUSDC,
+		MakerID:         maker.GetPeerID(),
+		ProofCommitment: bytes.Repeat([]byte{0x11}, 32),
+	}
+	if err := maker.orders[orderID].Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign order: %v", err)
+	}
+	maker.markBroadcastPending(orderID)
+
+	if maker.announceOrder(orderID, 1, time.Millisecond) {
+		t.Fatal("Broadcast with no connected peers should fail")
+	}
+	if state, _ := maker.OrderBroadcastState(orderID); state != OrderBroadcastPending {
+		t.Fatalf("Expected %s after a failed broadcast, got %s", OrderBroadcastPending, state)
+	}
+
+	// The marker survives a restart, so the broadcast is retried then
+	restarted := newTestApp()
+	restarted.store = maker.store
+	if err := restarted.loadState(); err != nil {
+		t.Fatalf("Failed to load state: %v", err)
+	}
+	if state, _ := restarted.OrderBroadcastState(orderID); state != OrderBroadcastPending {
+		t.Errorf("Expected the restored order to be %s, got %s", OrderBroadcastPending, state)
+	}
+
+	// Once the peer is reachable, a retry announces the order
+	taker := newMemNode(t, hub, "taker")
+	hub.connect(maker.network, taker.network)
+	if !maker.announceOrder(orderID, 3, time.Millisecond) {
+		t.Fatal("Retry should announce the order")
+	}
+	if state, _ := maker.OrderBroadcastState(orderID); state != OrderBroadcastAnnounced {
+		t.Errorf("Expected %s, got %s", OrderBroadcastAnnounced, state)
+	}
+	received := func() bool {
+		taker.ordersMux.RLock()
+		defer taker.ordersMux.RUnlock()
+		return taker.orders[orderID] != nil
+	}
+	if !waitFor(received, 5*time.Second) {
+		t.Error("Taker should receive the announced order")
+	}
+	if records, _ := maker.store.ScanPrefix(storageNamespacePendingBroadcasts, ""); len(records) != 0 {
+		t.Errorf("Pending marker should be removed once announced, got %v", records)
+	}
+}
+
+func TestOrderAnnouncementGossipedBeyondDirectPeers(t *testing.T) {
+	hub := newMemHub()
+	maker := newMemNode(t, hub, "maker")
+	relay := newMemNode(t, hub, "relay")
+	taker := newMemNode(t, hub, "taker")
+
+	// A chain: the taker is two hops from the maker, and no peer has advertised interests
+	hub.connect(maker.network, relay.network)
+	hub.connect(relay.network, taker.network)
+
+	orderID := OrderID("order_1")
+	maker.orders[orderID] = &OrderAnnouncement{
+		OrderID:         orderID,
+		Stablecoin:      StablecoinUSDC,
+		MakerID:         maker.GetPeerID(),
+		ProofCommitment: bytes.Repeat([]byte{0x11}, 32),
+	}
+	if err := maker.orders[orderID].Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign order: %v", err)
+	}
+	if !maker.announceOrder(orderID, 1, time.Millisecond) {
+		t.Fatal("Broadcast should publish the order")
+	}
+
+	if !waitFor(func() bool { return holdsOrder(taker, orderID) }, 5*time.Second) {
+		t.Fatal("Taker two hops away should discover the order")
+	}
+}
+
+func TestUninterestedPeerReceivesNoOrders(t *testing.T) {
+	hub := newMemHub()
+	maker := newMemNode(t, hub, "maker")
+	usdcPeer := newMemNode(t, hub, "usdc-peer")
+	daiPeer := newMemNode(t, hub, "dai-peer")
+	hub.connect(maker.network, usdcPeer.network)
+	hub.connect(maker.network, daiPeer.network)
+
+	// The peers keep every order they are sent, so only routing can keep one away
+	maker.network.SetPeerInterests(PeerID(usdcPeer.network.self.String()), []StablecoinType{StablecoinUSDC})
+	maker.network.SetPeerInterests(PeerID(daiPeer.network.self.String()), []StablecoinType{StablecoinDAI})
+
+	announce := func(orderID OrderID, coin StablecoinType, fill byte) {
+		maker.orders[orderID] = &OrderAnnouncement{
+			OrderID:         orderID,
+			Stablecoin:      coin,
+			MakerID:         maker.GetPeerID(),
+			ProofCommitment: bytes.Repeat([]byte{fill}, 32),
+		}
+		if err := maker.orders[orderID].Sign(maker.cryptoMgr); err != nil {
+			t.Fatalf("Failed to sign order: %v", err)
+		}
+		if !maker.announceOrder(orderID, 1, time.Millisecond) {
+			t.Fatalf("Order %s should reach its interested peer", orderID)
+		}
+	}
+	announce("order_usdc", StablecoinUSDC, 0x11)
+	announce("order_dai", StablecoinDAI, 0x22)
+
+	if !waitFor(func() bool { return holdsOrder(usdcPeer, "order_usdc") && holdsOrder(daiPeer, "order_dai") }, 5*time.Second) {
+		t.Fatal("Each peer should receive the order for its coin")
+	}
+	if holdsOrder(usdcPeer, "order_dai") || holdsOrder(daiPeer, "order_usdc") {
+		t.Error("A peer received an order for a coin it did not advertise")
+	}
+}
+
+// holdsOrder reports whether a node has stored an order
+func holdsOrder(app *BlackTraceApp, orderID OrderID) bool {
+	app.ordersMux.RLock()
+	defer app.ordersMux.RUnlock()
+	return app.orders[orderID] != nil
+}
@@ -0,0 +1,69 @@
+package node
+
+import "encoding/hex"
+
+// storeOrderLocked adds or replaces an order and keeps the commitment index in step.
+// Caller must hold ordersMux for writing.
+func (app *BlackTraceApp) storeOrderLocked(order *OrderAnnouncement) {
+	if previous, ok := app.orders[order.OrderID]; ok {
+		app.unindexCommitmentLocked(previous)
+	}
+	app.orders[order.OrderID] = order
+	if len(order.ProofCommitment) > 0 {
+		app.ordersByCommitment[hex.EncodeToString(order.ProofCommitment)] = order.OrderID
+	}
+	if contentID, ok := order.ContentID(); ok {
+		app.ordersByContent[contentID] = order.OrderID
+	}
+}
+
+// removeOrderLocked deletes an order and its commitment index entry.
+// Caller must hold ordersMux for writing.
+func (app *BlackTraceApp) removeOrderLocked(orderID OrderID) {
+	if order, ok := app.orders[orderID]; ok {
+		app.unindexCommitmentLocked(order)
+	}
+	delete(app.orders, orderID)
+}
+
+// unindexCommitmentLocked drops the index entry for an order, unless another order has since claimed it
+func (app *BlackTraceApp) unindexCommitmentLocked(order *OrderAnnouncement) {
+	key := hex.EncodeToString(order.ProofCommitment)
+	if app.ordersByCommitment[key] == order.OrderID {
+		delete(app.ordersByCommitment, key)
+	}
+	if contentID, ok := order.ContentID(); ok && app.ordersByContent[contentID] == order.OrderID {
+		delete(app.ordersByContent, contentID)
+	}
+}
+
+// relayedDuplicate returns the order already held under another ID with the same content,
+// i.e. the same signed order reaching us through a different relay
+func (app *BlackTraceApp) relayedDuplicate(announcement *OrderAnnouncement) (OrderID, bool) {
+	contentID, ok := announcement.ContentID()
+	if !ok {
+		return "", false
+	}
+
+	app.ordersMux.RLock()
+	defer app.ordersMux.RUnlock()
+
+	existing, ok := app.ordersByContent[contentID]
+	if !ok || existing == announcement.OrderID {
+		return "", false
+	}
+	return existing, true
+}
+
+// OrderByCommitment finds the order announced with a proof commitment (e.g. one seen in a log)
+func (app *BlackTraceApp) OrderByCommitment(commitment []byte) (*OrderAnnouncement, bool) {
+	app.ordersMux.RLock()
+	defer app.ordersMux.RUnlock()
+
+	orderID, ok := app.ordersByCommitment[hex.EncodeToString(commitment)]
+	if !ok {
+		return nil, false
+	}
+	order, ok := app.orders[orderID]
+	return order, ok
+}
@@ -0,0 +1,74 @@
+package node
+
+import (
+	"bytes"
+	"log"
+	"time"
+)
+
+// defaultOrderRebroadcastInterval is how often own live orders are re-announced.
+// Well under the 1h order expiry, so peers that joined late still discover them.
+const defaultOrderRebroadcastInterval = 5 * time.Minute
+
+// SetOrderRebroadcastInterval sets how often own live orders are re-announced.
+// Must be called before Run; zero keeps the default.
+func (app *BlackTraceApp) SetOrderRebroadcastInterval(interval time.Duration) {
+	app.rebroadcastInterval = interval
+}
+
+// orderRebroadcastLoop periodically re-announces own orders that have not expired
+func (app *BlackTraceApp) orderRebroadcastLoop() {
+	interval := app.rebroadcastInterval
+	if interval <= 0 {
+		interval = defaultOrderRebroadcastInterval
+	}
+	ticker := time.NewTicker(interval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-app.shutdownCh:
+			return
+		case now := <-ticker.C:
+			app.rebroadcastOwnedOrders(now)
+		}
+	}
+}
+
+// rebroadcastOwnedOrders re-sends the stored announcement of every own unexpired order.
+// The announcement is sent unchanged, so receivers that already have it can recognise the repeat.
+func (app *BlackTraceApp) rebroadcastOwnedOrders(now time.Time) int {
+	app.ordersMux.RLock()
+	live := make([]*OrderAnnouncement, 0)
+	for orderID, order := range app.orders {
+		if !app.IsOwnedOrder(orderID) || (order.Expiry > 0 && now.Unix() >= order.Expiry) {
+			continue
+		}
+		announcement := *order
+		live = append(live, &announcement)
+	}
+	app.ordersMux.RUnlock()
+
+	for _, announcement := range live {
+		if err := app.broadcastSignedMessageForCoin(announcement.Stablecoin, "order_announcement", announcement); err != nil {
+			log.Printf("Failed to re-broadcast order %s: %v", announcement.OrderID, err)
+		}
+	}
+	if len(live) > 0 {
+		log.Printf("App: Re-broadcast %d live orders", len(live))
+	}
+	return len(live)
+}
+
+// isRepeatAnnouncement reports whether an announcement is one we already hold unchanged
+// (a periodic rebroadcast), as opposed to a new or re-signed order
+func (app *BlackTraceApp) isRepeatAnnouncement(announcement *OrderAnnouncement) bool {
+	app.ordersMux.RLock()
+	defer app.ordersMux.RUnlock()
+
+	known, ok := app.orders[announcement.OrderID]
+	return ok &&
+		known.Timestamp == announcement.Timestamp &&
+		known.Expiry == announcement.Expiry &&
+		bytes.Equal(known.Signature, announcement.Signature)
+}
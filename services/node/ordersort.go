@@ -0,0 +1,69 @@
+package node
+
+import (
+	"fmt"
+	"sort"
+)
+
+// OrderSort selects the sequence ListOrders returns orders in
+type OrderSort string
+
+const (
+	OrderSortTimestamp OrderSort = "timestamp" // Oldest first (the default)
+	OrderSortExpiry    OrderSort = "expiry"    // Soonest to expire first; orders that never expire last
+	OrderSortPrice     OrderSort = "price"     // Lowest revealed price range first; orders without revealed details last
+)
+
+// parseOrderSort reads a sort key, defaulting to OrderSortTimestamp when empty
+func parseOrderSort(s string) (OrderSort, error) {
+	switch OrderSort(s) {
+	case "", OrderSortTimestamp:
+		return OrderSortTimestamp, nil
+	case OrderSortExpiry, OrderSortPrice:
+		return OrderSort(s), nil
+	}
+	return "", fmt.Errorf("unknown order sort %q (want %q, %q or %q)", s, OrderSortTimestamp, OrderSortExpiry, OrderSortPrice)
+}
+
+// sortOrders sorts orders by the given key. Ties fall back to timestamp, then order ID, so
+// repeated listings of the same orders always come back in the same sequence.
+func (app *BlackTraceApp) sortOrders(orders []*OrderAnnouncement, by OrderSort) {
+	var prices map[OrderID]*OrderDetails
+	if by == OrderSortPrice {
+		prices = make(map[OrderID]*OrderDetails, len(orders))
+		app.orderDetailsMux.RLock()
+		for _, order := range orders {
+			if details, ok := app.orderDetails[order.OrderID]; ok {
+				prices[order.OrderID] = details
+			}
+		}
+		app.orderDetailsMux.RUnlock()
+	}
+
+	sort.Slice(orders, func(i, j int) bool {
+		a, b := orders[i], orders[j]
+		switch by {
+		case OrderSortExpiry:
+			if a.Expiry != b.Expiry {
+				// Zero means the order never expires
+				return b.Expiry == 0 || (a.Expiry != 0 && a.Expiry < b.Expiry)
+			}
+		case OrderSortPrice:
+			da, okA := prices[a.OrderID]
+			db, okB := prices[b.OrderID]
+			if okA != okB {
+				return okA
+			}
+			if okA && da.MinPrice != db.MinPrice {
+				return da.MinPrice < db.MinPrice
+			}
+			if okA && da.MaxPrice != db.MaxPrice {
+				return da.MaxPrice < db.MaxPrice
+			}
+		}
+		if a.Timestamp != b.Timestamp {
+			return a.Timestamp < b.Timestamp
+		}
+		return a.OrderID < b.OrderID
+	})
+}
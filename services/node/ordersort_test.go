@@ -0,0 +1,81 @@
+package node
+
+import "testing"
+
+// listOrders runs a list_orders command directly, without the app's command loop
+func listOrders(app *BlackTraceApp, by OrderSort) []OrderID {
+	responseCh := make(chan interface{}, 1)
+	app.handleAppCommand(AppCommand{Type: "list_orders", SortBy: by, ResponseCh: responseCh})
+
+	ids := make([]OrderID, 0)
+	for _, order := range (<-responseCh).([]*OrderAnnouncement) {
+		ids = append(ids, order.OrderID)
+	}
+	return ids
+}
+
+func sameOrderIDs(a, b []OrderID) bool {
+	if len(a) != len(b) {
+		return false
+	}
+	for i := range a {
+		if a[i] != b[i] {
+			return false
+		}
+	}
+	return true
+}
+
+func TestListOrdersIsStableAndSortsByExpiry(t *testing.T) {
+	app := newTestApp()
+	for _, order := range []*OrderAnnouncement{
+		{OrderID: "order_d", Timestamp: 200, Expiry: 0},
+		{OrderID: "order_b", Timestamp: 100, Expiry: 900},
+		{OrderID: "order_c", Timestamp: 100, Expiry: 500},
+		{OrderID: "order_a", Timestamp: 300, Expiry: 500},
+	} {
+		app.orders[order.OrderID] = order
+	}
+
+	// Same timestamp falls back to the order ID
+	first := listOrders(app, OrderSortTimestamp)
+	if want := []OrderID{"order_b", "order_c", "order_d", "order_a"}; !sameOrderIDs(first, want) {
+		t.Fatalf("Expected %v, got %v", want, first)
+	}
+	for i := 0; i < 10; i++ {
+		if again := listOrders(app, ""); !sameOrderIDs(again, first) {
+			t.Fatalf("Listing changed between calls: %v then %v", first, again)
+		}
+	}
+
+	// Soonest expiry first, ties by timestamp, orders that never expire last
+	if got, want := listOrders(app, OrderSortExpiry), []OrderID{"order_c", "order_a", "order_b", "order_d"}; !sameOrderIDs(got, want) {
+		t.Errorf("Expected %v by expiry, got %v", want, got)
+	}
+}
+
+func TestListOrdersByPriceRange(t *testing.T) {
+	app := newTestApp()
+	for _, id := range []OrderID{"order_a", "order_b", "order_c"} {
+		app.orders[id] = &OrderAnnouncement{OrderID: id, Timestamp: 100}
+	}
+	app.orderDetails["order_b"] = &OrderDetails{OrderID: "order_b", MinPrice: 440, MaxPrice: 460}
+	app.orderDetails["order_c"] = &OrderDetails{OrderID: "order_c", MinPrice: 440, MaxPrice: 450}
+
+	// Orders whose details have not been revealed have no price yet and go last
+	if got, want := listOrders(app, OrderSortPrice), []OrderID{"order_c", "order_b", "order_a"}; !sameOrderIDs(got, want) {
+		t.Errorf("Expected %v by price, got %v", want, got)
+	}
+}
+
+func TestParseOrderSort(t *testing.T) {
+	if by, err := parseOrderSort(""); err != nil || by != OrderSortTimestamp {
+		t.Errorf("Expected the timestamp default, got %q (%v)", by, err)
+	}
+	if by, err := parseOrderSort("expiry"); err != nil || by != OrderSortExpiry {
+		t.Errorf("Expected %q, got %q (%v)", OrderSortExpiry, by, err)
+	}
+	if _, err := parseOrderSort("popularity"); err == nil {
+		t.Error("Unknown sort key should be rejected")
+	}
+}
@@ -0,0 +1,131 @@
+package node
+
+import (
+	"bytes"
+	"errors"
+	"fmt"
+)
+
+// proofCommitmentSize is the length of a commitment hash or compressed Pedersen commitment
+const proofCommitmentSize = 32
+
+// Order announcement verification errors, one per check of verifyOrderAnnouncement
+var (
+	ErrAnnouncementSignature  = errors.New("invalid maker signature")
+	ErrAnnouncementCommitment = errors.New("malformed proof commitment")
+	ErrMissingLiquidityProof  = errors.New("missing liquidity proof")
+	ErrCommitmentReused       = errors.New("proof commitment already announced")
+	ErrOrderReplaced          = errors.New("order already held under another maker key")
+)
+
+// ErrNotOrderMaker is returned for order details sent by a peer other than the order's maker
+var ErrNotOrderMaker = errors.New("order details not sent by the order's maker")
+
+// verifyOrderAnnouncement is the single gate a received announcement passes before it is stored.
+// It runs, in order and stopping at the first failure:
+//
+//  1. signature: the maker's signature; unsigned announcements are rejected
+//  2. replacement: an order already held with a signature is only replaced by an announcement
+//     signed with the same maker key, or with a new key the held key endorsed in a rotation
+//  3. commitment: a proof scheme this node knows, with a well-formed commitment for it
+//  4. liquidity proof: range-proof announcements carry the proof after their commitment
+//     (hash and Pedersen commitments are opened later, under a liquidity challenge)
+//  5. uniqueness: the same maker key does not already hold the commitment for a different order.
+//     A copy under another key cannot claim the commitment: the maker's own order is still
+//     accepted after it, and only the real maker can open the commitment
+func (app *BlackTraceApp) verifyOrderAnnouncement(a *OrderAnnouncement) error {
+	if len(a.Signature) == 0 {
+		return fmt.Errorf("%w: order %s carries no maker signature", ErrAnnouncementSignature, a.OrderID)
+	}
+	if err := app.verifyAnnouncement(a); err != nil {
+		return fmt.Errorf("%w: %v", ErrAnnouncementSignature, err)
+	}
+
+	if err := app.verifyReplacement(a); err != nil {
+		return err
+	}
+
+	switch scheme := a.Scheme(); scheme {
+	case ProofSchemeHash, ProofSchemePedersen:
+		if len(a.ProofCommitment) != proofCommitmentSize {
+			return fmt.Errorf("%w: %s commitment is %d bytes (expected %d)",
+				ErrAnnouncementCommitment, scheme, len(a.ProofCommitment), proofCommitmentSize)
+		}
+	case ProofSchemeRangeProof:
+		if len(a.ProofCommitment) < proofCommitmentSize {
+			return fmt.Errorf("%w: %s commitment is %d bytes (expected at least %d)",
+				ErrAnnouncementCommitment, scheme, len(a.ProofCommitment), proofCommitmentSize)
+		}
+		if len(a.ProofCommitment) == proofCommitmentSize {
+			return fmt.Errorf("%w: order %s announces a range proof but carries only its commitment",
+				ErrMissingLiquidityProof, a.OrderID)
+		}
+	default:
+		return fmt.Errorf("%w: %w %q", ErrAnnouncementCommitment, ErrUnsupportedProofScheme, scheme)
+	}
+
+	if existing, ok := app.relayedDuplicate(a); ok {
+		return fmt.Errorf("%w: same order as %s", ErrCommitmentReused, existing)
+	}
+	if held, ok := app.OrderByCommitment(a.ProofCommitment); ok && held.OrderID != a.OrderID &&
+		bytes.Equal(held.MakerPubKey, a.MakerPubKey) {
+		return fmt.Errorf("%w by %s", ErrCommitmentReused, held.OrderID)
+	}
+	return nil
+}
+
+// verifyReplacement checks an announcement for an order ID we already hold. A held signed order
+// may be replaced by the same maker key (a rebroadcast or update), or by a new key carrying the
+// held key's rotation endorsement. Unsigned held orders have no owner to protect. A node that
+// missed an intermediate rotation holds a key the endorsement does not name and keeps the
+// order it has until it expires.
+func (app *BlackTraceApp) verifyReplacement(a *OrderAnnouncement) error {
+	app.ordersMux.RLock()
+	held, ok := app.orders[a.OrderID]
+	var heldKey []byte
+	if ok && len(held.Signature) > 0 {
+		heldKey = held.MakerPubKey
+	}
+	app.ordersMux.RUnlock()
+	if len(heldKey) == 0 {
+		return nil
+	}
+
+	switch {
+	case len(a.Signature) == 0:
+		return fmt.Errorf("%w: unsigned announcement for %s", ErrOrderReplaced, a.OrderID)
+	case bytes.Equal(a.MakerPubKey, heldKey):
+		return nil
+	case !bytes.Equal(a.RotatedFromKey, heldKey):
+		return fmt.Errorf("%w: %s signed by a different maker key", ErrOrderReplaced, a.OrderID)
+	}
+	if err := a.VerifyRotation(); err != nil {
+		return fmt.Errorf("%w: %v", ErrOrderReplaced, err)
+	}
+	return nil
+}
+
+// verifyDetailsSender checks that order details came from the identity that announced the
+// order: the announcement's maker peer, signing with the announcement's maker key. Details for
+// an order we hold no announcement of have nothing to be checked against.
+func (app *BlackTraceApp) verifyDetailsSender(from PeerID, signerPubKey []byte, orderID OrderID) error {
+	app.ordersMux.RLock()
+	order, ok := app.orders[orderID]
+	var makerID PeerID
+	var makerPubKey []byte
+	if ok {
+		makerID, makerPubKey = order.MakerID, order.MakerPubKey
+	}
+	app.ordersMux.RUnlock()
+	if !ok {
+		return nil
+	}
+
+	if makerID != "" && from != makerID {
+		return fmt.Errorf("%w: %s sent details for %s, announced by %s", ErrNotOrderMaker, from, orderID, makerID)
+	}
+	if len(makerPubKey) > 0 && !bytes.Equal(signerPubKey, makerPubKey) {
+		return fmt.Errorf("%w: details for %s not signed with the announcement's maker key", ErrNotOrderMaker, orderID)
+	}
+	return nil
+}
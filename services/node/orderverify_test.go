@@ -0,0 +1,195 @@
+package node
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+)
+
+func TestVerifyOrderAnnouncement(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	signed := func(id OrderID, scheme ProofScheme, commitment []byte) *OrderAnnouncement {
+		a := &OrderAnnouncement{
+			OrderID:         id,
+			ProofCommitment: commitment,
+			ProofScheme:     scheme,
+			Timestamp:       time.Now().Unix(),
+			Expiry:          time.Now().Add(time.Hour).Unix(),
+		}
+		if err := a.Sign(maker.cryptoMgr); err != nil {
+			t.Fatalf("Failed to sign: %v", err)
+		}
+		return a
+	}
+	commitment := bytes.Repeat([]byte{0x11}, 32)
+
+	node := newTestApp()
+	held := signed("order_held", ProofSchemeHash, bytes.Repeat([]byte{0x22}, 32))
+	node.ordersMux.Lock()
+	node.storeOrderLocked(held)
+	node.ordersMux.Unlock()
+
+	forged := signed("order_1", ProofSchemeHash, commitment)
+	forged.Expiry++ // Signed fields changed after signing
+
+	// Replacements of the held order that its maker did not sign
+	unsignedReplacement := &OrderAnnouncement{OrderID: held.OrderID, ProofCommitment: held.ProofCommitment}
+	otherKeyReplacement := &OrderAnnouncement{OrderID: held.OrderID, ProofCommitment: held.ProofCommitment, Timestamp: held.Timestamp}
+	if err := otherKeyReplacement.Sign(newTestAppWithKey(t).cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+
+	cases := map[string]struct {
+		announcement *OrderAnnouncement
+		want         error
+	}{
+		"bad signature":       {forged, ErrAnnouncementSignature},
+		"short commitment":    {signed("order_1", ProofSchemeHash, commitment[:16]), ErrAnnouncementCommitment},
+		"unknown scheme":      {signed("order_1", "lattice", commitment), ErrUnsupportedProofScheme},
+		"range proof missing": {signed("order_1", ProofSchemeRangeProof, commitment), ErrMissingLiquidityProof},
+		"commitment reused":   {signed("order_1", ProofSchemeHash, held.ProofCommitment), ErrCommitmentReused},
+		"unsigned":            {&OrderAnnouncement{OrderID: "order_4", ProofCommitment: commitment}, ErrAnnouncementSignature},
+	}
+	for name, tc := range cases {
+		if err := node.verifyOrderAnnouncement(tc.announcement); !errors.Is(err, tc.want) {
+			t.Errorf("%s: expected %v, got %v", name, tc.want, err)
+		}
+	}
+
+	// An order held with a signature is not replaced by one its maker did not sign
+	for name, a := range map[string]*OrderAnnouncement{
+		"unsigned replacement of a signed order": unsignedReplacement,
+		"replacement under another key":         otherKeyReplacement,
+	} {
+		if err := node.verifyOrderAnnouncement(a); !errors.Is(err, ErrOrderReplaced) {
+			t.Errorf("%s: expected ErrOrderReplaced, got %v", name, err)
+		}
+	}
+
+	// A bad signature stops the pipeline before the later checks run
+	forged.ProofCommitment = commitment[:16]
+	if err := node.verifyOrderAnnouncement(forged); errors.Is(err, ErrAnnouncementCommitment) {
+		t.Errorf("Expected the signature check to fail first, got %v", err)
+	}
+
+	for name, a := range map[string]*OrderAnnouncement{
+		"hash":        signed("order_1", ProofSchemeHash, commitment),
+		"pedersen":    signed("order_2", ProofSchemePedersen, commitment),
+		"range proof": signed("order_3", ProofSchemeRangeProof, append(commitment[:32:32], bytes.Repeat([]byte{0x33}, 64)...)),
+		"rebroadcast": held,
+	} {
+		if err := node.verifyOrderAnnouncement(a); err != nil {
+			t.Errorf("%s: expected the announcement to pass, got %v", name, err)
+		}
+	}
+}
+
+func TestSquattedCommitmentDoesNotBlockMaker(t *testing.T) {
+	maker, squatter := newTestAppWithKey(t), newTestAppWithKey(t)
+	genuine := &OrderAnnouncement{
+		OrderID:         "order_genuine",
+		Stablecoin:      StablecoinUSDC,
+		ProofCommitment: bytes.Repeat([]byte{0x11}, 32),
+		Timestamp:       time.Now().Unix(),
+		Expiry:          time.Now().Add(time.Hour).Unix(),
+	}
+	if err := genuine.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+
+	// Copies of the maker's commitment under new IDs reach the node first
+	unsignedCopy := *genuine
+	unsignedCopy.OrderID = "order_unsigned"
+	unsignedCopy.Signature, unsignedCopy.MakerPubKey = nil, nil
+	foreignCopy := *genuine
+	foreignCopy.OrderID = "order_squat"
+	foreignCopy.Timestamp++
+	if err := foreignCopy.Sign(squatter.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+
+	node := newTestApp()
+	send := func(from PeerID, sender *BlackTraceApp, a *OrderAnnouncement) {
+		payload, _ := json.Marshal(a)
+		node.handleMessagePayload(from, "order_announcement", payload, sender.cryptoMgr.GetPublicKey())
+	}
+	send("squatter", squatter, &unsignedCopy)
+	send("squatter", squatter, &foreignCopy)
+	if _, ok := node.orders[unsignedCopy.OrderID]; ok {
+		t.Error("Unsigned announcement should be rejected")
+	}
+
+	send("maker", maker, genuine)
+	if node.orders[genuine.OrderID] == nil {
+		t.Fatal("Maker's order was blocked by a copy of its commitment")
+	}
+	if held, ok := node.OrderByCommitment(genuine.ProofCommitment); !ok || held.OrderID != genuine.OrderID {
+		t.Errorf("Commitment should index the maker's order, got %v", held)
+	}
+
+	// The maker still cannot announce the same commitment twice
+	again := *genuine
+	again.OrderID = "order_again"
+	again.Timestamp++
+	if err := again.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+	if err := node.verifyOrderAnnouncement(&again); !errors.Is(err, ErrCommitmentReused) {
+		t.Errorf("Expected ErrCommitmentReused, got %v", err)
+	}
+}
+
+func TestDetailsFromNonMakerRejected(t *testing.T) {
+	hub := newMemHub()
+	impostor := newMemNode(t, hub, "impostor")
+	taker := newMemNode(t, hub, "taker")
+	hub.connect(impostor.network, taker.network)
+
+	// The order is announced and signed by a maker the taker is not connected to
+	maker := newTestAppWithKey(t)
+	orderID := OrderID("order_1")
+	announcement := &OrderAnnouncement{
+		OrderID:         orderID,
+		OrderType:       OrderTypeSell,
+		Stablecoin:      StablecoinUSDC,
+		MakerID:         "maker",
+		ProofCommitment: bytes.Repeat([]byte{0x11}, 32),
+		Timestamp:       time.Now().Unix(),
+	}
+	if err := announcement.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign announcement: %v", err)
+	}
+	takerCopy := *announcement
+	taker.orders[orderID] = &takerCopy
+
+	// Another peer holds the details and answers the request as if it were the maker
+	impostorCopy := *announcement
+	details := &OrderDetails{OrderID: orderID, OrderType: OrderTypeSell, Amount: 10000, MinPrice: 450, MaxPrice: 470, Stablecoin: StablecoinUSDC}
+	impostor.orders[orderID] = &impostorCopy
+	impostor.orderDetails[orderID] = details
+	impostor.markOwnedOrder(details)
+
+	taker.RequestOrderDetails(orderID)
+	if !waitFor(func() bool { return taker.OrderDetailsError(orderID) != nil }, 5*time.Second) {
+		t.Fatal("Taker should reject details from a peer that is not the maker")
+	}
+	if err := taker.OrderDetailsError(orderID); !errors.Is(err, ErrNotOrderMaker) {
+		t.Errorf("Expected ErrNotOrderMaker, got %v", err)
+	}
+	taker.orderDetailsMux.RLock()
+	_, stored := taker.orderDetails[orderID]
+	taker.orderDetailsMux.RUnlock()
+	if stored {
+		t.Error("Details from a non-maker peer must not be stored")
+	}
+
+	// The maker's own peer ID with a different signing key is rejected the same way
+	if err := taker.verifyDetailsSender("maker", impostor.cryptoMgr.GetPublicKey(), orderID); !errors.Is(err, ErrNotOrderMaker) {
+		t.Errorf("Expected ErrNotOrderMaker for a mismatched key, got %v", err)
+	}
+	if err := taker.verifyDetailsSender("maker", maker.cryptoMgr.GetPublicKey(), orderID); err != nil {
+		t.Errorf("Details from the maker should be accepted: %v", err)
+	}
+}
@@ -0,0 +1,167 @@
+package node
+
+import (
+	"log"
+	"sync"
+	"time"
+)
+
+// Bounds on messages held for a disconnected peer
+const (
+	outboxMaxMessages = 32              // Per peer; the oldest message is dead-lettered when full
+	outboxMaxAge      = 2 * time.Minute // Messages older than this are dead-lettered instead of delivered
+)
+
+// outboxSweepInterval is how often expired buffered messages are dead-lettered
+const outboxSweepInterval = 10 * time.Second
+
+type bufferedMessage struct {
+	data     []byte
+	queuedAt time.Time
+}
+
+// outbox holds reliable messages for peers that dropped, so an in-progress
+// negotiation can resume when the same peer identity reconnects
+type outbox struct {
+	mu      sync.Mutex
+	pending map[PeerID][]bufferedMessage
+}
+
+func newOutbox() *outbox {
+	return &outbox{pending: make(map[PeerID][]bufferedMessage)}
+}
+
+// push buffers a message for a peer, returning any message evicted to stay within the bound
+func (o *outbox) push(peer PeerID, data []byte, now time.Time) [][]byte {
+	o.mu.Lock()
+	defer o.mu.Unlock()
+
+	queue := append(o.pending[peer], bufferedMessage{data: data, queuedAt: now})
+
+	var evicted [][]byte
+	for len(queue) > outboxMaxMessages {
+		evicted = append(evicted, queue[0].data)
+		queue = queue[1:]
+	}
+
+	o.pending[peer] = queue
+	return evicted
+}
+
+// take removes a peer's buffered messages, split into those still fresh (in send order) and expired
+func (o *outbox) take(peer PeerID, now time.Time) (fresh []bufferedMessage, expired [][]byte) {
+	o.mu.Lock()
+	queue := o.pending[peer]
+	delete(o.pending, peer)
+	o.mu.Unlock()
+
+	for _, m := range queue {
+		if now.Sub(m.queuedAt) > outboxMaxAge {
+			expired = append(expired, m.data)
+		} else {
+			fresh = append(fresh, m)
+		}
+	}
+	return fresh, expired
+}
+
+// requeue puts undelivered messages back at the front of a peer's queue, keeping their age
+func (o *outbox) requeue(peer PeerID, messages []bufferedMessage) {
+	o.mu.Lock()
+	defer o.mu.Unlock()
+
+	queue := append(append([]bufferedMessage{}, messages...), o.pending[peer]...)
+	if len(queue) > outboxMaxMessages {
+		queue = queue[:outboxMaxMessages]
+	}
+	o.pending[peer] = queue
+}
+
+// expire removes and returns every message older than outboxMaxAge
+func (o *outbox) expire(now time.Time) map[PeerID][][]byte {
+	o.mu.Lock()
+	defer o.mu.Unlock()
+
+	expired := make(map[PeerID][][]byte)
+	for peer, queue := range o.pending {
+		kept := queue[:0]
+		for _, m := range queue {
+			if now.Sub(m.queuedAt) > outboxMaxAge {
+				expired[peer] = append(expired[peer], m.data)
+			} else {
+				kept = append(kept, m)
+			}
+		}
+		if len(kept) == 0 {
+			delete(o.pending, peer)
+		} else {
+			o.pending[peer] = kept
+		}
+	}
+	return expired
+}
+
+// bufferForReconnect holds a message until the peer reconnects
+func (nm *NetworkManager) bufferForReconnect(to PeerID, data []byte) {
+	log.Printf("Peer %s is disconnected, buffering %d-byte message until it reconnects", to, len(data))
+
+	for _, dropped := range nm.outbox.push(to, data, time.Now()) {
+		nm.deadLetter(to, dropped)
+	}
+}
+
+// flushOutbox delivers messages buffered while a peer was disconnected, in the order they were sent
+func (nm *NetworkManager) flushOutbox(peer PeerID, send func(PeerID, []byte) error) {
+	fresh, expired := nm.outbox.take(peer, time.Now())
+	for _, data := range expired {
+		nm.deadLetter(peer, data)
+	}
+
+	for i, m := range fresh {
+		if err := send(peer, m.data); err != nil {
+			log.Printf("Failed to flush buffered message to %s: %v", peer, err)
+			// Keep the rest for the next reconnect
+			nm.outbox.requeue(peer, fresh[i:])
+			return
+		}
+	}
+
+	if len(fresh) > 0 {
+		log.Printf("Delivered %d buffered messages to reconnected peer %s", len(fresh), peer)
+	}
+}
+
+// outboxLoop dead-letters buffered messages whose peer did not reconnect in time
+func (nm *NetworkManager) outboxLoop() {
+	ticker := time.NewTicker(outboxSweepInterval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-nm.shutdownCh:
+			return
+		case now := <-ticker.C:
+			nm.sweepOutbox(now)
+		}
+	}
+}
+
+// sweepOutbox dead-letters every buffered message that has outlived outboxMaxAge
+func (nm *NetworkManager) sweepOutbox(now time.Time) {
+	for peer, messages := range nm.outbox.expire(now) {
+		for _, data := range messages {
+			nm.deadLetter(peer, data)
+		}
+	}
+}
+
+// deadLetter reports an undeliverable message back to the application
+func (nm *NetworkManager) deadLetter(to PeerID, data []byte) {
+	log.Printf("Giving up on %d-byte message to %s", len(data), to)
+
+	nm.eventCh <- NetworkEvent{
+		Type: "send_failed",
+		From: to,
+		Data: data,
+	}
+}
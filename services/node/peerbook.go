@@ -0,0 +1,91 @@
+package node
+
+import (
+	"log"
+	"time"
+)
+
+// peerBookMaxAge is how long a peer may go unseen before it is no longer redialed on startup
+const peerBookMaxAge = 7 * 24 * time.Hour
+
+// PeerRecord is one entry of the persisted peer address book
+type PeerRecord struct {
+	PeerID    PeerID    `json:"peer_id"`
+	Addr      string    `json:"addr"`                 // Multiaddr we dialed, including /p2p/<peer ID>
+	PublicKey []byte    `json:"public_key,omitempty"` // Last signing key the peer used
+	LastSeen  time.Time `json:"last_seen"`
+}
+
+// rememberPeer adds a peer we dialed to the address book, or updates its address
+func (app *BlackTraceApp) rememberPeer(peerID PeerID, addr string) {
+	app.peerBookMux.Lock()
+	known, ok := app.peerBook[peerID]
+	if !ok {
+		known = &PeerRecord{PeerID: peerID}
+		app.peerBook[peerID] = known
+	}
+	known.Addr = addr
+	record := app.refreshPeerLocked(known)
+	app.peerBookMux.Unlock()
+
+	app.persist(storageNamespacePeers, string(peerID), record)
+}
+
+// touchKnownPeer refreshes an address book entry when the peer connects or disconnects.
+// Peers we never dialed are not added.
+func (app *BlackTraceApp) touchKnownPeer(peerID PeerID) {
+	app.peerBookMux.Lock()
+	known, ok := app.peerBook[peerID]
+	var record PeerRecord
+	if ok {
+		record = app.refreshPeerLocked(known)
+	}
+	app.peerBookMux.Unlock()
+
+	if ok {
+		app.persist(storageNamespacePeers, string(peerID), record)
+	}
+}
+
+// refreshPeerLocked stamps an entry as seen now with the peer's latest signing key and
+// returns a copy to persist. Caller must hold peerBookMux.
+func (app *BlackTraceApp) refreshPeerLocked(known *PeerRecord) PeerRecord {
+	app.peerKeysMux.RLock()
+	if key, ok := app.peerKeys[known.PeerID]; ok {
+		known.PublicKey = key
+	}
+	app.peerKeysMux.RUnlock()
+
+	known.LastSeen = time.Now()
+	return *known
+}
+
+// KnownPeers returns the address book
+func (app *BlackTraceApp) KnownPeers() []PeerRecord {
+	app.peerBookMux.RLock()
+	defer app.peerBookMux.RUnlock()
+
+	peers := make([]PeerRecord, 0, len(app.peerBook))
+	for _, known := range app.peerBook {
+		peers = append(peers, *known)
+	}
+	return peers
+}
+
+// dialKnownPeers reconnects to address book peers seen within peerBookMaxAge. Each dial goes
+// through the "connect" command, so it gets the same self-connection check and retry backoff
+// as a peer given on the command line. Bootstrap nodes only accept connections.
+func (app *BlackTraceApp) dialKnownPeers() {
+	if app.network.isBootstrap {
+		return
+	}
+
+	now := time.Now()
+	for _, known := range app.KnownPeers() {
+		if now.Sub(known.LastSeen) > peerBookMaxAge || app.network.HasPeer(known.PeerID) {
+			continue
+		}
+		log.Printf("App: Redialing known peer %s at %s", known.PeerID, known.Addr)
+		app.ConnectToPeer(known.Addr)
+	}
+}
@@ -0,0 +1,65 @@
+package node
+
+import (
+	"testing"
+	"time"
+)
+
+func TestKnownPeersRedialedAfterRestart(t *testing.T) {
+	app := newTestApp()
+	app.store = NewMemStorage()
+	app.network = newTestNetworkManager()
+
+	// A peer we dialed is remembered with its address and signing key
+	const addr = "/ip4/10.0.0.7/tcp/19001/p2p/taker"
+	app.peerKeys["taker"] = []byte{0x04, 0x01}
+	app.handleNetworkEvent(NetworkEvent{Type: "peer_dialed", From: "taker", Data: []byte(addr)})
+
+	// Inbound peers have no address we could dial back
+	app.touchKnownPeer("stranger")
+
+	// Nor is a peer unseen for too long redialed
+	app.persist(storageNamespacePeers, "gone", PeerRecord{
+		PeerID:   "gone",
+		Addr:     "/ip4/10.0.0.9/tcp/19001/p2p/gone",
+		LastSeen: time.Now().Add(-2 * peerBookMaxAge),
+	})
+
+	restarted := newTestApp()
+	restarted.store = app.store
+	if err := restarted.loadState(); err != nil {
+		t.Fatalf("Failed to load state: %v", err)
+	}
+	restarted.network = newTestNetworkManager()
+	restarted.network.commandCh = make(chan NetworkCommand, 10)
+
+	known := restarted.KnownPeers()
+	if len(known) != 2 {
+		t.Fatalf("Expected 2 peers in the restored address book, got %+v", known)
+	}
+	for _, record := range known {
+		if record.PeerID == "taker" && (record.Addr != addr || len(record.PublicKey) != 2) {
+			t.Errorf("Unexpected record for taker: %+v", record)
+		}
+	}
+
+	restarted.dialKnownPeers()
+	select {
+	case cmd := <-restarted.network.commandCh:
+		if cmd.Type != "connect" || cmd.Addr != addr {
+			t.Errorf("Expected a connect to %s, got %s %s", addr, cmd.Type, cmd.Addr)
+		}
+	default:
+		t.Fatal("Known peer was not redialed")
+	}
+	if len(restarted.network.commandCh) != 0 {
+		t.Errorf("Only the recently seen peer should be redialed, %d more commands queued", len(restarted.network.commandCh))
+	}
+
+	// Already connected peers are left alone
+	restarted.network.peers["taker"] = "taker"
+	restarted.dialKnownPeers()
+	if len(restarted.network.commandCh) != 0 {
+		t.Error("Connected peer should not be redialed")
+	}
+}
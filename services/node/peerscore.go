@@ -0,0 +1,129 @@
+package node
+
+import (
+	"sort"
+	"sync"
+	"time"
+)
+
+const (
+	// scoreRTTReference is the RTT at which a peer's latency factor halves
+	scoreRTTReference = 100 * time.Millisecond
+
+	// scoreUptimeReference is how long a peer must stay connected to count as fully established
+	scoreUptimeReference = 10 * time.Minute
+)
+
+type peerStats struct {
+	connectedAt time.Time
+	rtt         time.Duration // Zero until the first pong
+	delivered   uint64
+	failed      uint64
+}
+
+// peerScores tracks how well each peer behaves, so direct broadcasts reach the best peers first
+type peerScores struct {
+	mu    sync.Mutex
+	stats map[PeerID]*peerStats
+}
+
+func newPeerScores() *peerScores {
+	return &peerScores{stats: make(map[PeerID]*peerStats)}
+}
+
+// entry returns the peer's stats, creating them. Caller must hold s.mu.
+func (s *peerScores) entry(peer PeerID) *peerStats {
+	st, ok := s.stats[peer]
+	if !ok {
+		st = &peerStats{}
+		s.stats[peer] = st
+	}
+	return st
+}
+
+// connected starts a peer's uptime
+func (s *peerScores) connected(peer PeerID, now time.Time) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.entry(peer).connectedAt = now
+}
+
+// recordRTT stores the peer's latest averaged round-trip time
+func (s *peerScores) recordRTT(peer PeerID, rtt time.Duration) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.entry(peer).rtt = rtt
+}
+
+// recordDelivery counts a direct send to the peer as delivered or failed
+func (s *peerScores) recordDelivery(peer PeerID, ok bool) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	st := s.entry(peer)
+	if ok {
+		st.delivered++
+	} else {
+		st.failed++
+	}
+}
+
+// forget drops a peer's stats when it disconnects
+func (s *peerScores) forget(peer PeerID) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	delete(s.stats, peer)
+}
+
+// score rates a peer in (0, 1]: the product of its delivery success rate (smoothed so new peers
+// start at 1/2), a latency factor that halves at scoreRTTReference, and an uptime factor rising
+// from 1/2 to 1 over scoreUptimeReference. Unknown RTT or uptime count as 1/2.
+// Caller must hold s.mu.
+func (s *peerScores) score(peer PeerID, now time.Time) float64 {
+	st, ok := s.stats[peer]
+	if !ok {
+		return 0.5 * 0.5 * 0.5
+	}
+
+	delivery := float64(st.delivered+1) / float64(st.delivered+st.failed+2)
+
+	latency := 0.5
+	if st.rtt > 0 {
+		latency = 1 / (1 + float64(st.rtt)/float64(scoreRTTReference))
+	}
+
+	uptime := 0.5
+	if !st.connectedAt.IsZero() {
+		established := float64(now.Sub(st.connectedAt)) / float64(scoreUptimeReference)
+		if established > 1 {
+			established = 1
+		}
+		uptime = 0.5 + 0.5*established
+	}
+
+	return delivery * latency * uptime
+}
+
+// rank orders peers from highest to lowest score; equal scores keep peer ID order
+func (s *peerScores) rank(peers []PeerID, now time.Time) []PeerID {
+	s.mu.Lock()
+	scores := make(map[PeerID]float64, len(peers))
+	for _, p := range peers {
+		scores[p] = s.score(p, now)
+	}
+	s.mu.Unlock()
+
+	ranked := append([]PeerID(nil), peers...)
+	sort.SliceStable(ranked, func(i, j int) bool {
+		if scores[ranked[i]] != scores[ranked[j]] {
+			return scores[ranked[i]] > scores[ranked[j]]
+		}
+		return ranked[i] < ranked[j]
+	})
+	return ranked
+}
+
+// RecordPeerRTT feeds a measured round-trip time into the peer's broadcast score
+func (nm *NetworkManager) RecordPeerRTT(peer PeerID, rtt time.Duration) {
+	nm.scores.recordRTT(peer, rtt)
+}
@@ -0,0 +1,73 @@
+package node
+
+import (
+	"fmt"
+	"time"
+)
+
+// ProposalPreview is what a proposal would amount to, computed without sending or storing it
+type ProposalPreview struct {
+	OrderID          OrderID        `json:"order_id"`
+	Stablecoin       StablecoinType `json:"stablecoin"`
+	Price            uint64         `json:"price"`
+	Amount           uint64         `json:"amount"`            // ZEC amount the proposal would carry (zatoshis)
+	StablecoinAmount uint64         `json:"stablecoin_amount"` // Amount * Price, as the signed terms would state it
+	WithinRange      bool           `json:"within_range"`      // Price and amount are inside the maker's revealed order
+	Reasons          []string       `json:"reasons,omitempty"` // Why the proposal would be refused (empty if WithinRange)
+}
+
+// PreviewProposal checks a price and amount against an order's revealed details and returns
+// the settlement they imply, without touching the order's negotiation state. Amount 0 previews
+// the whole order, as checkCanPropose does. Returns ErrInvalidOrderState if the details have
+// not been revealed.
+func (app *BlackTraceApp) PreviewProposal(orderID OrderID, price, amount uint64) (*ProposalPreview, error) {
+	app.orderDetailsMux.RLock()
+	stored, ok := app.orderDetails[orderID]
+	var details OrderDetails
+	if ok {
+		details = *stored
+	}
+	app.orderDetailsMux.RUnlock()
+	if !ok {
+		return nil, fmt.Errorf("%w: details for order %s not revealed yet", ErrInvalidOrderState, orderID)
+	}
+
+	if amount == 0 {
+		amount = details.Amount
+	}
+	total, ok := stablecoinTotal(amount, price)
+	if !ok {
+		return nil, fmt.Errorf("%w: amount %d * price %d overflows", ErrInconsistentTerms, amount, price)
+	}
+
+	preview := &ProposalPreview{
+		OrderID:          orderID,
+		Stablecoin:       details.Stablecoin,
+		Price:            price,
+		Amount:           amount,
+		StablecoinAmount: total,
+	}
+	refuse := func(format string, args ...interface{}) {
+		preview.Reasons = append(preview.Reasons, fmt.Sprintf(format, args...))
+	}
+
+	if price < details.MinPrice || price > details.MaxPrice {
+		refuse("price $%d is outside the maker's range $%d-$%d", price, details.MinPrice, details.MaxPrice)
+	}
+	if amount > details.Amount {
+		refuse("amount %d exceeds the %d the order holds", amount, details.Amount)
+	}
+	app.ordersMux.RLock()
+	order, announced := app.orders[orderID]
+	expired := announced && order.Expiry > 0 && time.Now().Unix() >= order.Expiry
+	app.ordersMux.RUnlock()
+	if expired {
+		refuse("order has expired")
+	}
+	if err := app.checkPrice(details.Stablecoin, price); err != nil {
+		refuse("%v", err)
+	}
+
+	preview.WithinRange = len(preview.Reasons) == 0
+	return preview, nil
+}
@@ -0,0 +1,45 @@
+package node
+
+import (
+	"errors"
+	"testing"
+)
+
+func TestPreviewProposalPredictsOutcome(t *testing.T) {
+	app := newTestApp()
+	orderID := OrderID("order_1")
+
+	if _, err := app.PreviewProposal(orderID, 460, 10000); !errors.Is(err, ErrInvalidOrderState) {
+		t.Fatalf("Expected ErrInvalidOrderState before details are revealed, got %v", err)
+	}
+
+	app.orders[orderID] = &OrderAnnouncement{OrderID: orderID, Stablecoin: StablecoinUSDC}
+	app.orderDetails[orderID] = &OrderDetails{OrderID: orderID, Amount: 10000, MinPrice: 450, MaxPrice: 470, Stablecoin: StablecoinUSDC}
+
+	inRange, err := app.PreviewProposal(orderID, 460, 0)
+	if err != nil {
+		t.Fatalf("Failed to preview proposal: %v", err)
+	}
+	if !inRange.WithinRange || len(inRange.Reasons) != 0 {
+		t.Errorf("Expected an in-range preview, got %+v", inRange)
+	}
+	if inRange.Amount != 10000 || inRange.StablecoinAmount != 4600000 {
+		t.Errorf("Expected 10000 zatoshis for 4600000, got %d for %d", inRange.Amount, inRange.StablecoinAmount)
+	}
+
+	outOfRange, err := app.PreviewProposal(orderID, 480, 20000)
+	if err != nil {
+		t.Fatalf("Failed to preview proposal: %v", err)
+	}
+	if outOfRange.WithinRange || len(outOfRange.Reasons) != 2 {
+		t.Errorf("Expected the price and amount to be refused, got %+v", outOfRange)
+	}
+	if outOfRange.StablecoinAmount != 9600000 {
+		t.Errorf("Expected the implied total 9600000, got %d", outOfRange.StablecoinAmount)
+	}
+
+	// Previewing leaves no trace in the negotiation
+	if proposals := app.ListProposals(orderID); len(proposals) != 0 {
+		t.Errorf("Preview should not create proposals, got %d", len(proposals))
+	}
+}
@@ -0,0 +1,63 @@
+package node
+
+import (
+	"errors"
+	"fmt"
+	"log"
+)
+
+// ErrPriceDeviation is returned when a negotiated price strays too far from the oracle's reference
+var ErrPriceDeviation = errors.New("price deviates from reference")
+
+// PriceOracle supplies reference prices used to sanity-check negotiated prices
+type PriceOracle interface {
+	// ReferencePrice returns the reference price of ZEC in the given stablecoin, if known
+	ReferencePrice(coin StablecoinType) (uint64, bool)
+}
+
+// PriceCheck configures how proposed and accepted prices are compared with an oracle
+type PriceCheck struct {
+	Oracle              PriceOracle
+	MaxDeviationPercent uint64 // Largest allowed distance from the reference, in percent of it
+	Strict              bool   // Reject deviating prices instead of only warning
+}
+
+// SetPriceCheck installs (or, with nil, removes) the price sanity check used when signing terms
+func (app *BlackTraceApp) SetPriceCheck(check *PriceCheck) {
+	app.priceCheckMux.Lock()
+	defer app.priceCheckMux.Unlock()
+	app.priceCheck = check
+}
+
+// checkPrice compares a price with the oracle's reference. A deviation beyond the configured
+// percentage is logged as a warning, and returned as ErrPriceDeviation when the check is strict.
+// Without an oracle, or without a reference for the coin, every price passes.
+func (app *BlackTraceApp) checkPrice(coin StablecoinType, price uint64) error {
+	app.priceCheckMux.RLock()
+	check := app.priceCheck
+	app.priceCheckMux.RUnlock()
+
+	if check == nil || check.Oracle == nil {
+		return nil
+	}
+	reference, ok := check.Oracle.ReferencePrice(coin)
+	if !ok || reference == 0 {
+		return nil
+	}
+
+	diff := price - reference
+	if price < reference {
+		diff = reference - price
+	}
+	if float64(diff)*100 <= float64(reference)*float64(check.MaxDeviationPercent) {
+		return nil
+	}
+
+	err := fmt.Errorf("%w: $%d is more than %d%% from the %s reference $%d",
+		ErrPriceDeviation, price, check.MaxDeviationPercent, coin, reference)
+	log.Printf("Warning: %v", err)
+	if check.Strict {
+		return err
+	}
+	return nil
+}
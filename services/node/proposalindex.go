@@ -0,0 +1,35 @@
+package node
+
+// storeProposalLocked adds or replaces a proposal and keeps the per-order index in step.
+// Caller must hold proposalsMux for writing.
+func (app *BlackTraceApp) storeProposalLocked(proposal *Proposal) {
+	if previous, ok := app.proposals[proposal.ProposalID]; ok {
+		app.unindexProposalLocked(previous)
+	}
+	app.proposals[proposal.ProposalID] = proposal
+
+	byOrder, ok := app.proposalsByOrder[proposal.OrderID]
+	if !ok {
+		byOrder = make(map[ProposalID]*Proposal)
+		app.proposalsByOrder[proposal.OrderID] = byOrder
+	}
+	byOrder[proposal.ProposalID] = proposal
+}
+
+// removeProposalLocked deletes a proposal and its index entry.
+// Caller must hold proposalsMux for writing.
+func (app *BlackTraceApp) removeProposalLocked(proposalID ProposalID) {
+	if proposal, ok := app.proposals[proposalID]; ok {
+		app.unindexProposalLocked(proposal)
+	}
+	delete(app.proposals, proposalID)
+}
+
+// unindexProposalLocked drops a proposal from its order's entry, and the entry once it is empty
+func (app *BlackTraceApp) unindexProposalLocked(proposal *Proposal) {
+	byOrder := app.proposalsByOrder[proposal.OrderID]
+	delete(byOrder, proposal.ProposalID)
+	if len(byOrder) == 0 {
+		delete(app.proposalsByOrder, proposal.OrderID)
+	}
+}
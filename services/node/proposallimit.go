@@ -0,0 +1,99 @@
+package node
+
+import (
+	"log"
+	"sync"
+	"time"
+)
+
+// Per-peer, per-order proposal rate limit (token bucket)
+const (
+	proposalRate  = 2.0 // Tokens refilled per second
+	proposalBurst = 5.0 // Bucket capacity
+)
+
+// proposalAbuseThreshold is how many throttled proposals in a row get the peer's negotiation on
+// the order cancelled
+const proposalAbuseThreshold = 20
+
+type proposalSession struct {
+	peer  PeerID
+	order OrderID
+}
+
+type proposalBucket struct {
+	tokens    float64
+	updatedAt time.Time
+	throttled int // Consecutive proposals rejected by the limit
+}
+
+// proposalLimiter throttles how fast one peer can send proposals on one order,
+// independently of any network-level rate limiting
+type proposalLimiter struct {
+	mu      sync.Mutex
+	buckets map[proposalSession]*proposalBucket
+}
+
+func newProposalLimiter() *proposalLimiter {
+	return &proposalLimiter{buckets: make(map[proposalSession]*proposalBucket)}
+}
+
+// allow reports whether a proposal from peer on order may be processed now. abusive is set once
+// the peer has been throttled proposalAbuseThreshold times in a row.
+func (l *proposalLimiter) allow(peer PeerID, order OrderID, now time.Time) (ok bool, abusive bool) {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	key := proposalSession{peer: peer, order: order}
+	bucket, exists := l.buckets[key]
+	if !exists {
+		bucket = &proposalBucket{tokens: proposalBurst, updatedAt: now}
+		l.buckets[key] = bucket
+	}
+
+	bucket.tokens += now.Sub(bucket.updatedAt).Seconds() * proposalRate
+	if bucket.tokens > proposalBurst {
+		bucket.tokens = proposalBurst
+	}
+	bucket.updatedAt = now
+
+	if bucket.tokens < 1 {
+		bucket.throttled++
+		return false, bucket.throttled >= proposalAbuseThreshold
+	}
+	bucket.tokens--
+	bucket.throttled = 0
+	return true, false
+}
+
+// admitProposal applies the proposal rate limit for a peer and order, cancelling the peer's
+// pending proposals on the order if it keeps sending past the limit
+func (app *BlackTraceApp) admitProposal(from PeerID, orderID OrderID) bool {
+	ok, abusive := app.proposalLimit.allow(from, orderID, time.Now())
+	if ok {
+		return true
+	}
+
+	log.Printf("App: Rejecting proposal on %s from %s: rate limited", orderID, from)
+	if abusive {
+		app.cancelPeerProposals(from, orderID, CancelReasonRateLimited)
+	}
+	return false
+}
+
+// cancelPeerProposals cancels every pending proposal a peer made on an order
+func (app *BlackTraceApp) cancelPeerProposals(from PeerID, orderID OrderID, reason CancelReason) {
+	app.proposalsMux.Lock()
+	defer app.proposalsMux.Unlock()
+
+	for id, proposal := range app.proposalsByOrder[orderID] {
+		if proposal.ProposerID != from || proposal.Status != ProposalStatusPending {
+			continue
+		}
+		proposal.Status = ProposalStatusCancelled
+		proposal.CancelReason = reason
+		app.persistProposal(proposal)
+
+		log.Printf("App: Cancelled proposal %s (%s)", id, reason)
+	}
+}
@@ -0,0 +1,129 @@
+package node
+
+import (
+	"bytes"
+	"encoding/binary"
+	"errors"
+	"fmt"
+)
+
+// ErrInvalidProposalSignature is returned when a received proposal is unsigned or its signature does not verify
+var ErrInvalidProposalSignature = errors.New("invalid proposal signature")
+
+// ErrProposalReplaced is returned when a received proposal reuses the ID of one already held,
+// from another proposer key or after the held proposal left pending
+var ErrProposalReplaced = errors.New("proposal ID already in use")
+
+// proposalSigningTag domain-separates proposal signatures from other signed data
+const proposalSigningTag = "blacktrace/proposal"
+
+// SigningBytes returns the bytes a proposer signs for this proposal.
+//
+// Covers, in order: proposal_id, order_id, price, amount, proposer_id,
+// proposer_username, proposer_pubkey_hash, timestamp, proposer_pubkey.
+// Status, settlement status, hash lock and cancel reason change as the
+// negotiation moves on and are outside the signature.
+func (p *Proposal) SigningBytes() []byte {
+	var buf bytes.Buffer
+	writeField := func(b []byte) {
+		var length [4]byte
+		binary.BigEndian.PutUint32(length[:], uint32(len(b)))
+		buf.Write(length[:])
+		buf.Write(b)
+	}
+	writeUint := func(v uint64) {
+		var b [8]byte
+		binary.BigEndian.PutUint64(b[:], v)
+		buf.Write(b[:])
+	}
+
+	buf.WriteString(proposalSigningTag)
+	writeField([]byte(p.ProposalID))
+	writeField([]byte(p.OrderID))
+	writeUint(p.Price)
+	writeUint(p.Amount)
+	writeField([]byte(p.ProposerID))
+	writeField([]byte(p.ProposerUsername))
+	writeField([]byte(p.ProposerPubKeyHash))
+	writeUint(uint64(p.Timestamp.UnixNano()))
+	writeField(p.ProposerPubKey)
+
+	return buf.Bytes()
+}
+
+// Sign sets the proposer's public key and signs the proposal
+func (p *Proposal) Sign(cm *CryptoManager) error {
+	p.ProposerPubKey = cm.GetPublicKey()
+
+	signature, err := cm.SignMessage(p.SigningBytes())
+	if err != nil {
+		return fmt.Errorf("failed to sign proposal: %w", err)
+	}
+	p.Signature = signature
+	return nil
+}
+
+// Verify checks the proposer's signature. If signerPubKey is set (the key that signed the
+// message envelope the proposal arrived in) the proposal must be signed by that same key,
+// so a relaying peer cannot pass off someone else's proposal as its own or vice versa.
+func (p *Proposal) Verify(signerPubKey []byte) error {
+	if len(p.Signature) == 0 {
+		return fmt.Errorf("%w: proposal %s is not signed", ErrInvalidProposalSignature, p.ProposalID)
+	}
+	if signerPubKey != nil && !bytes.Equal(p.ProposerPubKey, signerPubKey) {
+		return fmt.Errorf("%w: proposal %s is signed by a different key than its sender",
+			ErrInvalidProposalSignature, p.ProposalID)
+	}
+
+	proposerPubKey, err := ParsePublicKey(p.ProposerPubKey)
+	if err != nil {
+		return fmt.Errorf("%w: invalid proposer public key: %v", ErrInvalidProposalSignature, err)
+	}
+	if err := VerifySignature(proposerPubKey, p.SigningBytes(), p.Signature); err != nil {
+		return fmt.Errorf("%w: proposal %s: %v", ErrInvalidProposalSignature, p.ProposalID, err)
+	}
+	return nil
+}
+
+// VerifyReceived checks a proposal received from peer from in a message envelope signed by
+// signerPubKey. Unlike Verify it requires the envelope signature, and the sender must be the
+// proposer named in the proposal: with the envelope check this binds ProposerID to
+// ProposerPubKey, so a peer cannot propose in another peer's name. Proposals are sent to the
+// maker directly, so a gossip-relayed proposal is refused too.
+func (p *Proposal) VerifyReceived(from PeerID, signerPubKey []byte) error {
+	if signerPubKey == nil {
+		return fmt.Errorf("%w: proposal %s arrived in an unsigned message", ErrInvalidProposalSignature, p.ProposalID)
+	}
+	if p.ProposerID != from {
+		return fmt.Errorf("%w: proposal %s names proposer %s but was sent by %s",
+			ErrInvalidProposalSignature, p.ProposalID, p.ProposerID, from)
+	}
+	return p.Verify(signerPubKey)
+}
+
+// storeReceivedProposal stores a verified proposal from a peer. A proposal ID already held may
+// only be reused by the same proposer key while the held proposal is still pending. The fields
+// outside the signature are set locally rather than taken from the wire: a received proposal
+// always starts pending.
+func (app *BlackTraceApp) storeReceivedProposal(proposal *Proposal) error {
+	proposal.Status = ProposalStatusPending
+	proposal.SettlementStatus = nil
+	proposal.HashLock = nil
+	proposal.CancelReason = ""
+	proposal.RevealRequested = false
+
+	app.proposalsMux.Lock()
+	defer app.proposalsMux.Unlock()
+
+	if held, ok := app.proposals[proposal.ProposalID]; ok {
+		if !bytes.Equal(held.ProposerPubKey, proposal.ProposerPubKey) || held.ProposerID != proposal.ProposerID {
+			return fmt.Errorf("%w: %s is held from another proposer", ErrProposalReplaced, proposal.ProposalID)
+		}
+		if held.Status != ProposalStatusPending {
+			return fmt.Errorf("%w: %s is already %s", ErrProposalReplaced, proposal.ProposalID, held.Status)
+		}
+	}
+	app.storeProposalLocked(proposal)
+	app.persistProposal(proposal)
+	return nil
+}
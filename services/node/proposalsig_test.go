@@ -0,0 +1,143 @@
+package node
+
+import (
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+)
+
+func signedTestProposal(t *testing.T, proposer *BlackTraceApp) *Proposal {
+	proposal := &Proposal{
+		ProposalID: NewProposalID("order_1"),
+		OrderID:    "order_1",
+		Price:      45,
+		Amount:     100000000,
+		ProposerID: "taker-peer",
+		Status:     ProposalStatusPending,
+		Timestamp:  time.Now(),
+	}
+	if err := proposal.Sign(proposer.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign proposal: %v", err)
+	}
+	return proposal
+}
+
+func TestSignedProposalAccepted(t *testing.T) {
+	maker, taker := newTestApp(), newTestAppWithKey(t)
+	proposal := signedTestProposal(t, taker)
+
+	payload, _ := json.Marshal(proposal)
+	maker.handleMessagePayload("taker-peer", "proposal", payload, taker.cryptoMgr.GetPublicKey())
+
+	stored, ok := maker.proposals[proposal.ProposalID]
+	if !ok {
+		t.Fatal("Correctly-signed proposal was not stored")
+	}
+	// The signature is kept with the proposal so the offer can be attributed later
+	if err := stored.Verify(nil); err != nil {
+		t.Errorf("Stored proposal no longer verifies: %v", err)
+	}
+}
+
+func TestForgedProposalRejected(t *testing.T) {
+	maker, taker, forger := newTestApp(), newTestAppWithKey(t), newTestAppWithKey(t)
+
+	// The offer is altered after signing
+	tampered := signedTestProposal(t, taker)
+	tampered.Price = 1
+	if err := tampered.Verify(nil); !errors.Is(err, ErrInvalidProposalSignature) {
+		t.Errorf("Expected ErrInvalidProposalSignature for a tampered proposal, got %v", err)
+	}
+
+	// A forger signs an offer naming the taker's key
+	forged := signedTestProposal(t, forger)
+	forged.ProposerPubKey = taker.cryptoMgr.GetPublicKey()
+	if err := forged.Verify(nil); !errors.Is(err, ErrInvalidProposalSignature) {
+		t.Errorf("Expected ErrInvalidProposalSignature for a forged proposal, got %v", err)
+	}
+
+	// A validly signed proposal relayed by a peer holding a different key
+	relayed := signedTestProposal(t, taker)
+	payload, _ := json.Marshal(relayed)
+	maker.handleMessagePayload("forger-peer", "proposal", payload, forger.cryptoMgr.GetPublicKey())
+
+	// And an unsigned one
+	unsigned := *relayed
+	unsigned.ProposalID = NewProposalID("order_1") + "_unsigned"
+	unsigned.Signature = nil
+	payload, _ = json.Marshal(&unsigned)
+	maker.handleMessagePayload("taker-peer", "proposal", payload, nil)
+
+	if len(maker.proposals) != 0 {
+		t.Errorf("Expected no proposals stored, got %d", len(maker.proposals))
+	}
+}
+
+func TestReceivedProposalCannotReplaceAnother(t *testing.T) {
+	maker, taker, intruder := newTestApp(), newTestAppWithKey(t), newTestAppWithKey(t)
+	send := func(from PeerID, sender *BlackTraceApp, p *Proposal) {
+		payload, _ := json.Marshal(p)
+		maker.handleMessagePayload(from, "proposal", payload, sender.cryptoMgr.GetPublicKey())
+	}
+
+	original := signedTestProposal(t, taker)
+	send("taker-peer", taker, original)
+	stored := maker.proposals[original.ProposalID]
+	if stored == nil {
+		t.Fatal("Proposal was not stored")
+	}
+
+	// Another key reusing the ID, in its own name or the taker's
+	for _, proposer := range []PeerID{"intruder-peer", "taker-peer"} {
+		squat := *original
+		squat.ProposerID = proposer
+		squat.Price = 1
+		if err := squat.Sign(intruder.cryptoMgr); err != nil {
+			t.Fatalf("Failed to sign proposal: %v", err)
+		}
+		send(proposer, intruder, &squat)
+	}
+	if maker.proposals[original.ProposalID] != stored || stored.Price != 45 {
+		t.Errorf("Proposal replaced by another key: %+v", maker.proposals[original.ProposalID])
+	}
+
+	// A proposal naming a proposer other than its sender
+	impersonated := signedTestProposal(t, intruder)
+	send("intruder-peer", intruder, impersonated)
+	if _, ok := maker.proposals[impersonated.ProposalID]; ok {
+		t.Error("Proposal sent on behalf of another peer was stored")
+	}
+
+	// Once accepted, not even the proposer can reset it
+	stored.Status = ProposalStatusAccepted
+	resent := *original
+	resent.Price = 46
+	if err := resent.Sign(taker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign proposal: %v", err)
+	}
+	send("taker-peer", taker, &resent)
+	if maker.proposals[original.ProposalID] != stored || stored.Status != ProposalStatusAccepted {
+		t.Errorf("Accepted proposal was replaced: %+v", maker.proposals[original.ProposalID])
+	}
+}
+
+func TestReceivedProposalStatusSetLocally(t *testing.T) {
+	maker, taker := newTestApp(), newTestAppWithKey(t)
+	proposal := signedTestProposal(t, taker)
+	// Status is outside the signature, so the wire value carries no weight
+	proposal.Status = ProposalStatusAccepted
+	ready := SettlementStatusReady
+	proposal.SettlementStatus = &ready
+
+	payload, _ := json.Marshal(proposal)
+	maker.handleMessagePayload("taker-peer", "proposal", payload, taker.cryptoMgr.GetPublicKey())
+
+	stored, ok := maker.proposals[proposal.ProposalID]
+	if !ok {
+		t.Fatal("Proposal was not stored")
+	}
+	if stored.Status != ProposalStatusPending || stored.SettlementStatus != nil {
+		t.Errorf("Expected a pending proposal, got %s / %v", stored.Status, stored.SettlementStatus)
+	}
+}
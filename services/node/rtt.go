@@ -0,0 +1,100 @@
+package node
+
+import (
+	"sync"
+	"time"
+)
+
+// rttAlpha is the weight of a new sample in the per-peer RTT moving average
+const rttAlpha = 0.2
+
+// pingInterval is how often every connected peer is pinged
+const pingInterval = 30 * time.Second
+
+// pingTimeout is how long a ping waits for its pong before being discarded
+const pingTimeout = time.Minute
+
+// PingMessage is sent as both "ping" and "pong"; the pong echoes the ping's nonce
+type PingMessage struct {
+	Nonce uint64 `json:"nonce"`
+}
+
+type pendingPing struct {
+	peer   PeerID
+	sentAt time.Time
+}
+
+// rttTracker measures round-trip times from ping/pong exchanges
+type rttTracker struct {
+	mu        sync.Mutex
+	nextNonce uint64
+	pending   map[uint64]pendingPing
+	ewma      map[PeerID]time.Duration
+}
+
+func newRTTTracker() *rttTracker {
+	return &rttTracker{
+		pending: make(map[uint64]pendingPing),
+		ewma:    make(map[PeerID]time.Duration),
+	}
+}
+
+// startPing records an outgoing ping and returns its nonce
+func (t *rttTracker) startPing(peer PeerID, now time.Time) uint64 {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+
+	// Drop pings whose pong never arrived
+	for nonce, p := range t.pending {
+		if now.Sub(p.sentAt) > pingTimeout {
+			delete(t.pending, nonce)
+		}
+	}
+
+	t.nextNonce++
+	t.pending[t.nextNonce] = pendingPing{peer: peer, sentAt: now}
+	return t.nextNonce
+}
+
+// recordPong completes a ping and folds the sample into the peer's average.
+// Pongs with an unknown nonce or from a different peer are ignored.
+func (t *rttTracker) recordPong(peer PeerID, nonce uint64, now time.Time) (time.Duration, bool) {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+
+	p, ok := t.pending[nonce]
+	if !ok || p.peer != peer {
+		return 0, false
+	}
+	delete(t.pending, nonce)
+
+	sample := now.Sub(p.sentAt)
+	if prev, ok := t.ewma[peer]; ok {
+		t.ewma[peer] = time.Duration(rttAlpha*float64(sample) + (1-rttAlpha)*float64(prev))
+	} else {
+		t.ewma[peer] = sample
+	}
+	return t.ewma[peer], true
+}
+
+// get returns the peer's averaged RTT, if any sample has been recorded
+func (t *rttTracker) get(peer PeerID) (time.Duration, bool) {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+
+	rtt, ok := t.ewma[peer]
+	return rtt, ok
+}
+
+// forget drops all RTT state for a peer
+func (t *rttTracker) forget(peer PeerID) {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+
+	delete(t.ewma, peer)
+	for nonce, p := range t.pending {
+		if p.peer == peer {
+			delete(t.pending, nonce)
+		}
+	}
+}
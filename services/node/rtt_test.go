@@ -0,0 +1,52 @@
+package node
+
+import (
+	"testing"
+	"time"
+)
+
+func TestRTTRecordedAfterPingPong(t *testing.T) {
+	tracker := newRTTTracker()
+	peer := PeerID("loopback")
+
+	if _, ok := tracker.get(peer); ok {
+		t.Fatal("No RTT should be recorded before any ping")
+	}
+
+	// Loopback peer echoes the ping nonce back as a pong
+	start := time.Now()
+	nonce := tracker.startPing(peer, start)
+	pong := PingMessage{Nonce: nonce}
+
+	rtt, ok := tracker.recordPong(peer, pong.Nonce, start.Add(40*time.Millisecond))
+	if !ok {
+		t.Fatal("Pong was not matched to its ping")
+	}
+	if rtt != 40*time.Millisecond {
+		t.Errorf("Expected first sample 40ms, got %v", rtt)
+	}
+
+	// Second sample is folded into the moving average
+	nonce = tracker.startPing(peer, start)
+	rtt, _ = tracker.recordPong(peer, nonce, start.Add(140*time.Millisecond))
+	if rtt != 60*time.Millisecond {
+		t.Errorf("Expected EWMA 60ms, got %v", rtt)
+	}
+
+	if got, ok := tracker.get(peer); !ok || got != rtt {
+		t.Errorf("Expected stored RTT %v, got %v (ok=%v)", rtt, got, ok)
+	}
+}
+
+func TestRTTIgnoresUnsolicitedPong(t *testing.T) {
+	tracker := newRTTTracker()
+	nonce := tracker.startPing("peer-a", time.Now())
+
+	// Pong with the right nonce from the wrong peer
+	if _, ok := tracker.recordPong("peer-b", nonce, time.Now()); ok {
+		t.Error("Pong from a different peer should be ignored")
+	}
+	if _, ok := tracker.recordPong("peer-a", nonce+1, time.Now()); ok {
+		t.Error("Pong with unknown nonce should be ignored")
+	}
+}
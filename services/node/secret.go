@@ -0,0 +1,36 @@
+package node
+
+import (
+	"fmt"
+	"io"
+)
+
+// redacted is what a Secret prints as, whatever the verb
+const redacted = "[redacted]"
+
+// Secret wraps a secret-bearing value (e.g. the node's signing/viewing key) so it never ends
+// up in logs or error messages: every fmt verb and JSON encoding print "[redacted]", and
+// reading the value takes an explicit Expose.
+type Secret[T any] struct {
+	value T
+}
+
+// NewSecret wraps value
+func NewSecret[T any](value T) Secret[T] {
+	return Secret[T]{value: value}
+}
+
+// Expose returns the wrapped value. Call it only where the secret is actually used.
+func (s Secret[T]) Expose() T {
+	return s.value
+}
+
+// Format implements fmt.Formatter, redacting the value for every verb (%v, %+v, %#v, %s, %x, ...)
+func (s Secret[T]) Format(f fmt.State, verb rune) {
+	io.WriteString(f, redacted)
+}
+
+// MarshalJSON keeps the value out of JSON output
+func (s Secret[T]) MarshalJSON() ([]byte, error) {
+	return []byte(`"` + redacted + `"`), nil
+}
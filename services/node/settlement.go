@@ -1,42 +1,90 @@
 package node
 
 import (
+	"crypto/sha256"
+	"encoding/hex"
 	"encoding/json"
 	"fmt"
 	"log"
 	"os"
+	"strings"
 	"time"
 
 	"github.com/nats-io/nats.go"
+	"golang.org/x/crypto/ripemd160"
 )
 
 // SettlementManager handles communication with the Rust settlement service via NATS
 type SettlementManager struct {
-	nc      *nats.Conn
-	enabled bool
-	app     *BlackTraceApp // Reference to app for updating proposals
+	nc           *nats.Conn
+	enabled      bool
+	nonCustodial bool           // SETTLEMENT_MODE=non-custodial: Alice's secret never leaves the node, only its hash
+	app          *BlackTraceApp // Reference to app for updating proposals
 }
 
+// settlementSchemaVersion is the settlement message schema version this node speaks
+const settlementSchemaVersion = 3
+
 // SettlementRequest represents a request to initiate HTLC settlement
 type SettlementRequest struct {
+	Version         int       `json:"version,omitempty"`
 	ProposalID      string    `json:"proposal_id"`
 	OrderID         string    `json:"order_id"`
 	MakerID         string    `json:"maker_id"`
 	TakerID         string    `json:"taker_id"`
-	Amount          uint64    `json:"amount"`
+	ZECZatoshi      uint64    `json:"zec_zatoshi"` // ZEC amount in zatoshis (1 ZEC = 1e8)
 	Price           uint64    `json:"price"`
 	Stablecoin      string    `json:"stablecoin"`
-	SettlementChain string    `json:"settlement_chain"` // "ztarknet", "solana", etc.
-	Secret          string    `json:"secret"`           // Alice's secret for HTLC
+	SettlementChain string    `json:"settlement_chain"`      // "ztarknet", "solana", etc.
+	Secret          string    `json:"secret,omitempty"`      // Alice's secret for HTLC (custodial mode)
+	SecretHash      string    `json:"secret_hash,omitempty"` // RIPEMD160(SHA256(secret)) hex (non-custodial mode)
 	Timestamp       time.Time `json:"timestamp"`
 }
 
+// settlementNonCustodial reports whether SETTLEMENT_MODE selects non-custodial settlement.
+// It must match the settlement service's mode, which rejects requests of the other kind.
+func settlementNonCustodial() bool {
+	return strings.EqualFold(strings.TrimSpace(os.Getenv("SETTLEMENT_MODE")), "non-custodial")
+}
+
+// secretHash returns the HTLC hash of Alice's secret, RIPEMD160(SHA256(secret)) in hex
+func secretHash(secret string) string {
+	shaHash := sha256.Sum256([]byte(secret))
+	ripemd := ripemd160.New()
+	ripemd.Write(shaHash[:])
+	return hex.EncodeToString(ripemd.Sum(nil))
+}
+
+// NewSettlementRequest builds the settlement request for an accepted proposal. In custodial mode
+// it carries Alice's secret; in non-custodial mode only the secret's hash.
+func (sm *SettlementManager) NewSettlementRequest(makerID PeerID, proposal *Proposal, stablecoin StablecoinType, secret string) SettlementRequest {
+	req := SettlementRequest{
+		Version:         settlementSchemaVersion,
+		ProposalID:      string(proposal.ProposalID),
+		OrderID:         string(proposal.OrderID),
+		MakerID:         string(makerID),
+		TakerID:         string(proposal.ProposerID),
+		ZECZatoshi:      proposal.Amount,
+		Price:           proposal.Price,
+		Stablecoin:      string(stablecoin),
+		SettlementChain: "ztarknet", // Default for now, will be from proposal in future
+		Timestamp:       time.Now(),
+	}
+	if sm.nonCustodial {
+		req.SecretHash = secretHash(secret)
+	} else {
+		req.Secret = secret
+	}
+	return req
+}
+
 // NewSettlementManager creates a new settlement manager
 func NewSettlementManager(app *BlackTraceApp) (*SettlementManager, error) {
 	natsURL := os.Getenv("NATS_URL")
+	nonCustodial := settlementNonCustodial()
 	if natsURL == "" {
 		log.Printf("Warning: NATS_URL not set, settlement service disabled")
-		return &SettlementManager{enabled: false, app: app}, nil
+		return &SettlementManager{enabled: false, nonCustodial: nonCustodial, app: app}, nil
 	}
 
 	// Connect to NATS
@@ -62,9 +110,10 @@ func NewSettlementManager(app *BlackTraceApp) (*SettlementManager, error) {
 	log.Printf("Settlement: Connected to NATS at %s", natsURL)
 
 	sm := &SettlementManager{
-		nc:      nc,
-		enabled: true,
-		app:     app,
+		nc:           nc,
+		enabled:      true,
+		nonCustodial: nonCustodial,
+		app:          app,
 	}
 
 	// Subscribe to settlement status updates
@@ -77,11 +126,21 @@ func NewSettlementManager(app *BlackTraceApp) (*SettlementManager, error) {
 		log.Printf("Warning: Failed to subscribe to settlement secrets: %v", err)
 	}
 
+	// Subscribe to reveal instructions (non-custodial mode's replacement for secret reveals)
+	if err := sm.subscribeToRevealInstructions(); err != nil {
+		log.Printf("Warning: Failed to subscribe to settlement reveal instructions: %v", err)
+	}
+
 	// Subscribe to HTLC hash parameters (sent when Alice locks ZEC)
 	if err := sm.subscribeToHTLCParams(); err != nil {
 		log.Printf("Warning: Failed to subscribe to HTLC params: %v", err)
 	}
 
+	// Subscribe to rejected settlement requests
+	if err := sm.subscribeToRejections(); err != nil {
+		log.Printf("Warning: Failed to subscribe to settlement rejections: %v", err)
+	}
+
 	return sm, nil
 }
 
@@ -104,8 +163,8 @@ func (sm *SettlementManager) PublishSettlementRequest(req SettlementRequest) err
 		return fmt.Errorf("failed to publish to NATS: %w", err)
 	}
 
-	log.Printf("Settlement: Published request for proposal %s (Chain: %s, Amount: %d, Price: $%d)",
-		req.ProposalID, req.SettlementChain, req.Amount, req.Price)
+	log.Printf("Settlement: Published request for proposal %s (Chain: %s, Amount: %d zatoshi, Price: $%d)",
+		req.ProposalID, req.SettlementChain, req.ZECZatoshi, req.Price)
 
 	return nil
 }
@@ -169,6 +228,7 @@ func (sm *SettlementManager) subscribeToStatusUpdates() error {
 		if proposal, exists := sm.app.proposals[ProposalID(proposalID)]; exists {
 			settlementStatus := SettlementStatus(status)
 			proposal.SettlementStatus = &settlementStatus
+			sm.app.persistProposal(proposal)
 			log.Printf("Settlement: Updated proposal %s settlement status to %s", proposalID, status)
 		}
 	})
@@ -203,6 +263,56 @@ func (sm *SettlementManager) subscribeToSecretReveals() error {
 	return err
 }
 
+// subscribeToRevealInstructions subscribes to the settlement service's reveal instructions. In
+// non-custodial mode the service never sees the secret; once both legs are locked it asks the
+// secret holder to reveal it by claiming.
+func (sm *SettlementManager) subscribeToRevealInstructions() error {
+	_, err := sm.nc.Subscribe("settlement.reveal.*", func(msg *nats.Msg) {
+		sm.handleRevealInstruction(msg.Data)
+	})
+
+	if err == nil {
+		log.Printf("Settlement: Subscribed to reveal instructions (settlement.reveal.*)")
+	}
+	return err
+}
+
+// handleRevealInstruction marks the proposal as due for the secret holder's claim
+func (sm *SettlementManager) handleRevealInstruction(data []byte) {
+	var instruction struct {
+		ProposalID string `json:"proposal_id"`
+		Action     string `json:"action"`
+		Hash       string `json:"hash"`
+		Chain      string `json:"chain"`
+	}
+	if err := json.Unmarshal(data, &instruction); err != nil {
+		log.Printf("Settlement: Error parsing reveal instruction: %v", err)
+		return
+	}
+	if instruction.Action != "reveal_secret" {
+		return
+	}
+
+	sm.app.proposalsMux.Lock()
+	defer sm.app.proposalsMux.Unlock()
+
+	proposal, exists := sm.app.proposals[ProposalID(instruction.ProposalID)]
+	if !exists {
+		log.Printf("Settlement: Warning - proposal %s not found, ignoring reveal instruction", instruction.ProposalID)
+		return
+	}
+	if proposal.HashLock != nil && *proposal.HashLock != instruction.Hash {
+		log.Printf("Settlement: Ignoring reveal instruction for %s: hash %s does not match hash_lock", instruction.ProposalID, instruction.Hash)
+		return
+	}
+
+	proposal.RevealRequested = true
+	bothLocked := SettlementStatusBothLocked
+	proposal.SettlementStatus = &bothLocked
+	sm.app.persistProposal(proposal)
+	log.Printf("Settlement: 🔓 Both legs locked for %s - claim on %s to reveal the secret", instruction.ProposalID, instruction.Chain)
+}
+
 // Close closes the NATS connection
 func (sm *SettlementManager) Close() {
 	if sm.enabled && sm.nc != nil {
@@ -220,32 +330,63 @@ func (sm *SettlementManager) IsEnabled() bool {
 // This is published when Alice locks ZEC, and Bob needs the hash to lock STRK
 func (sm *SettlementManager) subscribeToHTLCParams() error {
 	_, err := sm.nc.Subscribe("settlement.htlc.*", func(msg *nats.Msg) {
-		var params map[string]interface{}
-		if err := json.Unmarshal(msg.Data, &params); err != nil {
-			log.Printf("Settlement: Error parsing HTLC params: %v", err)
-			return
-		}
+		sm.handleHTLCParams(msg.Data)
+	})
+
+	if err == nil {
+		log.Printf("Settlement: Subscribed to HTLC params (settlement.htlc.*)")
+	}
+	return err
+}
 
-		proposalID, _ := params["proposal_id"].(string)
-		hashLock, _ := params["hash"].(string)
-		status, _ := params["status"].(string)
+// handleHTLCParams stores a settlement's hash_lock in its proposal. Only parameter messages
+// (status "ready" with a hash) are applied, so nothing else on the topic can clear a live hash lock.
+func (sm *SettlementManager) handleHTLCParams(data []byte) {
+	var params map[string]interface{}
+	if err := json.Unmarshal(data, &params); err != nil {
+		log.Printf("Settlement: Error parsing HTLC params: %v", err)
+		return
+	}
 
-		log.Printf("Settlement: 🔐 Received HTLC params for %s (hash: %s, status: %s)", proposalID, hashLock, status)
+	proposalID, _ := params["proposal_id"].(string)
+	hashLock, _ := params["hash"].(string)
+	status, _ := params["status"].(string)
 
-		// Store hash_lock in proposal so Bob can read it
-		sm.app.proposalsMux.Lock()
-		defer sm.app.proposalsMux.Unlock()
+	if status != "ready" || hashLock == "" {
+		log.Printf("Settlement: Ignoring non-parameter HTLC message for %s (status: %s)", proposalID, status)
+		return
+	}
 
-		if proposal, exists := sm.app.proposals[ProposalID(proposalID)]; exists {
-			proposal.HashLock = &hashLock
-			log.Printf("Settlement: Stored hash_lock in proposal %s", proposalID)
-		} else {
-			log.Printf("Settlement: Warning - proposal %s not found, cannot store hash_lock", proposalID)
+	log.Printf("Settlement: 🔐 Received HTLC params for %s (hash: %s, status: %s)", proposalID, hashLock, status)
+
+	// Store hash_lock in proposal so Bob can read it
+	sm.app.proposalsMux.Lock()
+	defer sm.app.proposalsMux.Unlock()
+
+	if proposal, exists := sm.app.proposals[ProposalID(proposalID)]; exists {
+		proposal.HashLock = &hashLock
+		log.Printf("Settlement: Stored hash_lock in proposal %s", proposalID)
+	} else {
+		log.Printf("Settlement: Warning - proposal %s not found, cannot store hash_lock", proposalID)
+	}
+}
+
+// subscribeToRejections subscribes to the settlement service's rejections of settlement requests
+func (sm *SettlementManager) subscribeToRejections() error {
+	_, err := sm.nc.Subscribe("settlement.rejected.*", func(msg *nats.Msg) {
+		var rejection map[string]interface{}
+		if err := json.Unmarshal(msg.Data, &rejection); err != nil {
+			log.Printf("Settlement: Error parsing settlement rejection: %v", err)
+			return
 		}
+
+		proposalID, _ := rejection["proposal_id"].(string)
+		reason, _ := rejection["reason"].(string)
+		log.Printf("Settlement: ⚠️  Settlement request for %s rejected: %s", proposalID, reason)
 	})
 
 	if err == nil {
-		log.Printf("Settlement: Subscribed to HTLC params (settlement.htlc.*)")
+		log.Printf("Settlement: Subscribed to settlement rejections (settlement.rejected.*)")
 	}
 	return err
 }
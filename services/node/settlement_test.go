@@ -0,0 +1,63 @@
+package node
+
+import (
+	"encoding/json"
+	"testing"
+)
+
+func TestNonCustodialRequestCarriesOnlySecretHash(t *testing.T) {
+	proposal := &Proposal{ProposalID: "order_1_proposal_1", OrderID: "order_1", ProposerID: "taker", Amount: 100000000, Price: 45}
+
+	custodial := (&SettlementManager{}).NewSettlementRequest("maker", proposal, "USDC", "secret")
+	if custodial.Secret != "secret" || custodial.SecretHash != "" {
+		t.Errorf("Custodial request should carry the secret only, got secret %q hash %q", custodial.Secret, custodial.SecretHash)
+	}
+
+	req := (&SettlementManager{nonCustodial: true}).NewSettlementRequest("maker", proposal, "USDC", "secret")
+	if req.SecretHash != secretHash("secret") || len(req.SecretHash) != 40 {
+		t.Errorf("Expected the 20-byte hash of the secret, got %q", req.SecretHash)
+	}
+	encoded, err := json.Marshal(req)
+	if err != nil {
+		t.Fatal(err)
+	}
+	var fields map[string]json.RawMessage
+	if err := json.Unmarshal(encoded, &fields); err != nil {
+		t.Fatal(err)
+	}
+	if _, leaked := fields["secret"]; leaked {
+		t.Errorf("Non-custodial request carries the secret: %s", encoded)
+	}
+}
+
+func TestRevealInstructionMarksProposal(t *testing.T) {
+	app := newTestApp()
+	sm := &SettlementManager{nonCustodial: true, app: app}
+	hash := secretHash("secret")
+	app.storeProposalLocked(&Proposal{ProposalID: "p1", HashLock: &hash})
+	app.storeProposalLocked(&Proposal{ProposalID: "p2", HashLock: &hash})
+
+	sm.handleRevealInstruction([]byte(`{"proposal_id":"p1","action":"reveal_secret","hash":"` + hash + `","chain":"ztarknet"}`))
+	if p := app.proposals["p1"]; !p.RevealRequested || p.SettlementStatus == nil || *p.SettlementStatus != SettlementStatusBothLocked {
+		t.Errorf("Expected p1 to be due for reveal, got %+v", p)
+	}
+
+	// An instruction naming another hash is not for this swap
+	sm.handleRevealInstruction([]byte(`{"proposal_id":"p2","action":"reveal_secret","hash":"00","chain":"ztarknet"}`))
+	if app.proposals["p2"].RevealRequested {
+		t.Error("Reveal instruction with a mismatched hash was applied")
+	}
+}
+
+func TestRejectionOnHTLCTopicKeepsHashLock(t *testing.T) {
+	app := newTestApp()
+	sm := &SettlementManager{app: app}
+	app.storeProposalLocked(&Proposal{ProposalID: "p1"})
+
+	sm.handleHTLCParams([]byte(`{"proposal_id":"p1","order_id":"o1","hash":"abcd","timeout":86400,"status":"ready"}`))
+	sm.handleHTLCParams([]byte(`{"proposal_id":"p1","order_id":"o1","status":"rejected","reason":"settlement already in progress for proposal p1"}`))
+
+	if got := app.proposals["p1"].HashLock; got == nil || *got != "abcd" {
+		t.Errorf("Rejection changed the live hash lock to %v", got)
+	}
+}
@@ -0,0 +1,345 @@
+package node
+
+import (
+	"bytes"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log"
+	"os"
+	"path/filepath"
+	"strings"
+	"sync"
+
+	"golang.org/x/crypto/blake2b"
+)
+
+// ErrNotFound is returned by Storage.Get for a missing key
+var ErrNotFound = errors.New("key not found")
+
+// ErrStateCorruption is returned when a persisted record fails its integrity check
+var ErrStateCorruption = errors.New("state corruption")
+
+// Storage namespaces for node state
+const (
+	storageNamespaceOrders            = "orders"
+	storageNamespaceOwnedOrders       = "owned_orders"       // Ownership marker, stored with the order's details
+	storageNamespaceProposals         = "proposals"
+	storageNamespacePendingBroadcasts = "pending_broadcasts" // Own orders not yet published to the network
+	storageNamespacePeers             = "peers"              // Address book of peers we dialed
+	storageNamespaceTranscripts       = "transcripts"        // Negotiation histories the settlement terms are signed against
+)
+
+// Storage is a namespaced key-value store the node persists its state through
+type Storage interface {
+	Put(namespace, key string, value []byte) error
+	Get(namespace, key string) ([]byte, error)                      // ErrNotFound if the key is missing
+	Delete(namespace, key string) error                             // Deleting a missing key is not an error
+	ScanPrefix(namespace, prefix string) (map[string][]byte, error) // All entries whose key starts with prefix
+}
+
+// MemStorage keeps everything in memory (tests, ephemeral nodes)
+type MemStorage struct {
+	mu   sync.RWMutex
+	data map[string]map[string][]byte
+}
+
+func NewMemStorage() *MemStorage {
+	return &MemStorage{data: make(map[string]map[string][]byte)}
+}
+
+func (m *MemStorage) Put(namespace, key string, value []byte) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	if m.data[namespace] == nil {
+		m.data[namespace] = make(map[string][]byte)
+	}
+	m.data[namespace][key] = append([]byte{}, value...)
+	return nil
+}
+
+func (m *MemStorage) Get(namespace, key string) ([]byte, error) {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	value, ok := m.data[namespace][key]
+	if !ok {
+		return nil, ErrNotFound
+	}
+	return append([]byte{}, value...), nil
+}
+
+func (m *MemStorage) Delete(namespace, key string) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	delete(m.data[namespace], key)
+	return nil
+}
+
+func (m *MemStorage) ScanPrefix(namespace, prefix string) (map[string][]byte, error) {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	entries := make(map[string][]byte)
+	for key, value := range m.data[namespace] {
+		if strings.HasPrefix(key, prefix) {
+			entries[key] = append([]byte{}, value...)
+		}
+	}
+	return entries, nil
+}
+
+// FileStorage keeps one file per key under <root>/<namespace>/, named by the hex-encoded key.
+// Writes are atomic, so a crash leaves either the old or the new value.
+type FileStorage struct {
+	root string
+}
+
+func NewFileStorage(root string) (*FileStorage, error) {
+	if err := os.MkdirAll(root, 0700); err != nil {
+		return nil, fmt.Errorf("failed to create storage directory: %w", err)
+	}
+	return &FileStorage{root: root}, nil
+}
+
+func (f *FileStorage) path(namespace, key string) string {
+	return filepath.Join(f.root, namespace, hex.EncodeToString([]byte(key)))
+}
+
+func (f *FileStorage) Put(namespace, key string, value []byte) error {
+	return writeFileAtomic(f.path(namespace, key), value, 0600)
+}
+
+func (f *FileStorage) Get(namespace, key string) ([]byte, error) {
+	value, err := os.ReadFile(f.path(namespace, key))
+	if os.IsNotExist(err) {
+		return nil, ErrNotFound
+	}
+	if err != nil {
+		return nil, fmt.Errorf("failed to read %s/%s: %w", namespace, key, err)
+	}
+	return value, nil
+}
+
+func (f *FileStorage) Delete(namespace, key string) error {
+	if err := os.Remove(f.path(namespace, key)); err != nil && !os.IsNotExist(err) {
+		return fmt.Errorf("failed to delete %s/%s: %w", namespace, key, err)
+	}
+	return nil
+}
+
+func (f *FileStorage) ScanPrefix(namespace, prefix string) (map[string][]byte, error) {
+	files, err := os.ReadDir(filepath.Join(f.root, namespace))
+	if os.IsNotExist(err) {
+		return map[string][]byte{}, nil
+	}
+	if err != nil {
+		return nil, fmt.Errorf("failed to list %s: %w", namespace, err)
+	}
+
+	entries := make(map[string][]byte)
+	for _, file := range files {
+		raw, err := hex.DecodeString(file.Name())
+		if err != nil {
+			continue // Not a key (e.g. an interrupted write's temp file)
+		}
+		key := string(raw)
+		if !strings.HasPrefix(key, prefix) {
+			continue
+		}
+
+		value, err := f.Get(namespace, key)
+		if err != nil {
+			return nil, err
+		}
+		entries[key] = value
+	}
+	return entries, nil
+}
+
+// persistOrder saves an order announcement (no-op without a store)
+func (app *BlackTraceApp) persistOrder(order *OrderAnnouncement) {
+	app.persist(storageNamespaceOrders, string(order.OrderID), order)
+}
+
+// forgetOrder removes a persisted order
+func (app *BlackTraceApp) forgetOrder(orderID OrderID) {
+	if app.store == nil {
+		return
+	}
+	if err := app.store.Delete(storageNamespaceOrders, string(orderID)); err != nil {
+		log.Printf("Warning: Failed to delete stored order %s: %v", orderID, err)
+	}
+}
+
+// persistProposal saves a proposal (no-op without a store). Callers may hold proposalsMux.
+func (app *BlackTraceApp) persistProposal(proposal *Proposal) {
+	app.persist(storageNamespaceProposals, string(proposal.ProposalID), proposal)
+}
+
+func (app *BlackTraceApp) persist(namespace, key string, value interface{}) {
+	if app.store == nil {
+		return
+	}
+	data, err := json.Marshal(value)
+	if err != nil {
+		log.Printf("Warning: Failed to encode %s/%s: %v", namespace, key, err)
+		return
+	}
+	if err := app.store.Put(namespace, key, sealRecord(data)); err != nil {
+		log.Printf("Warning: Failed to persist %s/%s: %v", namespace, key, err)
+	}
+}
+
+// sealRecord prefixes a record with the Blake2b-256 checksum of its contents
+func sealRecord(data []byte) []byte {
+	sum := blake2b.Sum256(data)
+	return append(sum[:], data...)
+}
+
+// openRecord checks a sealed record's checksum and decodes its contents into v
+func openRecord(namespace, key string, record []byte, v interface{}) error {
+	if len(record) < blake2b.Size256 {
+		return fmt.Errorf("%w: %s/%s is truncated", ErrStateCorruption, namespace, key)
+	}
+	sum := blake2b.Sum256(record[blake2b.Size256:])
+	if !bytes.Equal(sum[:], record[:blake2b.Size256]) {
+		return fmt.Errorf("%w: %s/%s checksum mismatch", ErrStateCorruption, namespace, key)
+	}
+	if err := json.Unmarshal(record[blake2b.Size256:], v); err != nil {
+		return fmt.Errorf("%w: %s/%s: %v", ErrStateCorruption, namespace, key, err)
+	}
+	return nil
+}
+
+// loadState restores orders, order ownership, proposals and the peer address book from the store.
+// Every record is verified before any state is touched; a corrupted record fails the whole load.
+func (app *BlackTraceApp) loadState() error {
+	if app.store == nil {
+		return nil
+	}
+
+	orderRecords, err := app.store.ScanPrefix(storageNamespaceOrders, "")
+	if err != nil {
+		return err
+	}
+	ownedRecords, err := app.store.ScanPrefix(storageNamespaceOwnedOrders, "")
+	if err != nil {
+		return err
+	}
+	proposalRecords, err := app.store.ScanPrefix(storageNamespaceProposals, "")
+	if err != nil {
+		return err
+	}
+	pendingRecords, err := app.store.ScanPrefix(storageNamespacePendingBroadcasts, "")
+	if err != nil {
+		return err
+	}
+	peerRecords, err := app.store.ScanPrefix(storageNamespacePeers, "")
+	if err != nil {
+		return err
+	}
+	transcriptRecords, err := app.store.ScanPrefix(storageNamespaceTranscripts, "")
+	if err != nil {
+		return err
+	}
+
+	orders := make([]*OrderAnnouncement, 0, len(orderRecords))
+	for key, record := range orderRecords {
+		var order OrderAnnouncement
+		if err := openRecord(storageNamespaceOrders, key, record, &order); err != nil {
+			return err
+		}
+		orders = append(orders, &order)
+	}
+	owned := make([]*OrderDetails, 0, len(ownedRecords))
+	for key, record := range ownedRecords {
+		var details OrderDetails
+		if err := openRecord(storageNamespaceOwnedOrders, key, record, &details); err != nil {
+			return err
+		}
+		owned = append(owned, &details)
+	}
+	proposals := make([]*Proposal, 0, len(proposalRecords))
+	for key, record := range proposalRecords {
+		var proposal Proposal
+		if err := openRecord(storageNamespaceProposals, key, record, &proposal); err != nil {
+			return err
+		}
+		proposals = append(proposals, &proposal)
+	}
+	pending := make([]OrderID, 0, len(pendingRecords))
+	for key, record := range pendingRecords {
+		var orderID OrderID
+		if err := openRecord(storageNamespacePendingBroadcasts, key, record, &orderID); err != nil {
+			return err
+		}
+		pending = append(pending, orderID)
+	}
+	knownPeers := make([]*PeerRecord, 0, len(peerRecords))
+	for key, record := range peerRecords {
+		var known PeerRecord
+		if err := openRecord(storageNamespacePeers, key, record, &known); err != nil {
+			return err
+		}
+		knownPeers = append(knownPeers, &known)
+	}
+	transcripts := make([]*storedTranscript, 0, len(transcriptRecords))
+	for key, record := range transcriptRecords {
+		var transcript storedTranscript
+		if err := openRecord(storageNamespaceTranscripts, key, record, &transcript); err != nil {
+			return err
+		}
+		transcripts = append(transcripts, &transcript)
+	}
+
+	app.ordersMux.Lock()
+	for _, order := range orders {
+		app.storeOrderLocked(order)
+	}
+	app.ordersMux.Unlock()
+
+	for _, details := range owned {
+		app.ownedOrdersMux.Lock()
+		app.ownedOrders[details.OrderID] = true
+		app.ownedOrdersMux.Unlock()
+
+		app.orderDetailsMux.Lock()
+		app.orderDetails[details.OrderID] = details
+		app.orderDetailsMux.Unlock()
+	}
+
+	app.proposalsMux.Lock()
+	for _, proposal := range proposals {
+		app.storeProposalLocked(proposal)
+	}
+	app.proposalsMux.Unlock()
+
+	// Terms signed after the restart carry the same history the counterparty holds
+	app.transcriptsMux.Lock()
+	for _, stored := range transcripts {
+		app.transcripts[transcriptKey{order: stored.OrderID, taker: stored.Taker}] = stored.restore()
+	}
+	app.transcriptsMux.Unlock()
+
+	// Announced when the node runs (see retryPendingBroadcasts)
+	app.orderBroadcastsMux.Lock()
+	for _, orderID := range pending {
+		app.orderBroadcasts[orderID] = OrderBroadcastPending
+	}
+	app.orderBroadcastsMux.Unlock()
+
+	// Redialed when the node runs (see dialKnownPeers)
+	app.peerBookMux.Lock()
+	for _, known := range knownPeers {
+		app.peerBook[known.PeerID] = known
+	}
+	app.peerBookMux.Unlock()
+
+	log.Printf("App: Restored %d orders (%d owned), %d proposals and %d known peers from storage",
+		len(orders), len(owned), len(proposals), len(knownPeers))
+	return nil
+}
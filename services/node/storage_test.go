@@ -0,0 +1,159 @@
+package node
+
+import (
+	"errors"
+	"os"
+	"testing"
+)
+
+func TestStorageBackends(t *testing.T) {
+	fileStorage, err := NewFileStorage(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to open file storage: %v", err)
+	}
+
+	for name, store := range map[string]Storage{"memory": NewMemStorage(), "file": fileStorage} {
+		t.Run(name, func(t *testing.T) {
+			for key, value := range map[string]string{
+				"order_1_proposal_1": "a",
+				"order_1_proposal_2": "b",
+				"order_2_proposal_1": "c",
+			} {
+				if err := store.Put(storageNamespaceProposals, key, []byte(value)); err != nil {
+					t.Fatalf("Put %s: %v", key, err)
+				}
+			}
+			// Same key in another namespace must not leak into the scan
+			if err := store.Put(storageNamespaceOrders, "order_1_proposal_3", []byte("x")); err != nil {
+				t.Fatalf("Put: %v", err)
+			}
+
+			value, err := store.Get(storageNamespaceProposals, "order_1_proposal_2")
+			if err != nil || string(value) != "b" {
+				t.Errorf("Get returned %q, %v", value, err)
+			}
+
+			entries, err := store.ScanPrefix(storageNamespaceProposals, "order_1_")
+			if err != nil {
+				t.Fatalf("ScanPrefix: %v", err)
+			}
+			if len(entries) != 2 || string(entries["order_1_proposal_1"]) != "a" || string(entries["order_1_proposal_2"]) != "b" {
+				t.Errorf("Unexpected scan result: %v", entries)
+			}
+
+			if err := store.Delete(storageNamespaceProposals, "order_1_proposal_1"); err != nil {
+				t.Fatalf("Delete: %v", err)
+			}
+			if _, err := store.Get(storageNamespaceProposals, "order_1_proposal_1"); !errors.Is(err, ErrNotFound) {
+				t.Errorf("Expected ErrNotFound after delete, got %v", err)
+			}
+			if err := store.Delete(storageNamespaceProposals, "missing"); err != nil {
+				t.Errorf("Deleting a missing key should not fail: %v", err)
+			}
+		})
+	}
+}
+
+func TestOrdersAndProposalsRestoredFromStorage(t *testing.T) {
+	store := NewMemStorage()
+
+	app := newTestApp()
+	app.store = store
+	order := &OrderAnnouncement{OrderID: "order_1", Stablecoin: StablecoinUSDC}
+	app.orders[order.OrderID] = order
+	app.persistOrder(order)
+	proposal := &Proposal{ProposalID: "order_1_proposal_1", OrderID: order.OrderID, Price: 45, Status: ProposalStatusPending}
+	app.storeProposalLocked(proposal)
+	app.persistProposal(proposal)
+	app.cancelProposal(proposal.ProposalID, CancelReasonCounterpartyDisconnected)
+
+	restarted := newTestApp()
+	restarted.store = store
+	if err := restarted.loadState(); err != nil {
+		t.Fatalf("Failed to load state: %v", err)
+	}
+
+	if _, ok := restarted.orders[order.OrderID]; !ok {
+		t.Error("Order should be restored")
+	}
+	restored, ok := restarted.proposals[proposal.ProposalID]
+	if !ok {
+		t.Fatal("Proposal should be restored")
+	}
+	if restored.Status != ProposalStatusCancelled || restored.Price != 45 {
+		t.Errorf("Restored proposal has stale state: %+v", restored)
+	}
+}
+
+func TestOrderOwnershipSurvivesRestart(t *testing.T) {
+	store := NewMemStorage()
+
+	app := newTestApp()
+	app.store = store
+	own := &OrderAnnouncement{OrderID: "order_own", Stablecoin: StablecoinUSDC}
+	discovered := &OrderAnnouncement{OrderID: "order_discovered", Stablecoin: StablecoinUSDC}
+	for _, order := range []*OrderAnnouncement{own, discovered} {
+		app.orders[order.OrderID] = order
+		app.persistOrder(order)
+	}
+	app.markOwnedOrder(&OrderDetails{OrderID: own.OrderID, Amount: 25000, MinPrice: 450, MaxPrice: 470})
+
+	restarted := newTestApp()
+	restarted.store = store
+	if err := restarted.loadState(); err != nil {
+		t.Fatalf("Failed to load state: %v", err)
+	}
+
+	if len(restarted.orders) != 2 {
+		t.Fatalf("Expected both orders restored, got %d", len(restarted.orders))
+	}
+	if !restarted.IsOwnedOrder(own.OrderID) {
+		t.Error("Own order should still be marked owned after restart")
+	}
+	if restarted.IsOwnedOrder(discovered.OrderID) {
+		t.Error("Discovered order should not be marked owned")
+	}
+	if details, ok := restarted.orderDetails[own.OrderID]; !ok || details.Amount != 25000 {
+		t.Error("Own order's details should be restored with its ownership")
+	}
+
+	// Cancelling drops the marker from storage too
+	if err := restarted.CancelOrder(own.OrderID); err != nil {
+		t.Fatalf("Failed to cancel: %v", err)
+	}
+	if _, err := store.Get(storageNamespaceOwnedOrders, string(own.OrderID)); !errors.Is(err, ErrNotFound) {
+		t.Errorf("Ownership record should be deleted on cancel, got %v", err)
+	}
+}
+
+func TestCorruptedRecordDetectedOnReload(t *testing.T) {
+	store, err := NewFileStorage(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to open file storage: %v", err)
+	}
+
+	app := newTestApp()
+	app.store = store
+	proposal := &Proposal{ProposalID: "order_1_proposal_1", OrderID: "order_1", Price: 45, Status: ProposalStatusPending}
+	app.persistProposal(proposal)
+
+	// Flip one byte of the record on disk, past the checksum
+	path := store.path(storageNamespaceProposals, string(proposal.ProposalID))
+	record, err := os.ReadFile(path)
+	if err != nil {
+		t.Fatalf("Failed to read record: %v", err)
+	}
+	record[len(record)-2] ^= 0x01
+	if err := os.WriteFile(path, record, 0600); err != nil {
+		t.Fatalf("Failed to write record: %v", err)
+	}
+
+	restarted := newTestApp()
+	restarted.store = store
+	if err := restarted.loadState(); !errors.Is(err, ErrStateCorruption) {
+		t.Fatalf("Expected ErrStateCorruption, got %v", err)
+	}
+	if len(restarted.proposals) != 0 {
+		t.Error("No state should be restored from a corrupted store")
+	}
+}
@@ -0,0 +1,279 @@
+package node
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"math/bits"
+)
+
+// ErrInconsistentTerms is returned when the stablecoin leg does not match amount * price
+var ErrInconsistentTerms = errors.New("inconsistent settlement terms")
+
+// settlementAmountTolerance is how far, in stablecoin base units, the stablecoin amount may
+// differ from amount * price to absorb rounding by either side
+const settlementAmountTolerance = 1
+
+// SettlementTerms are the final negotiated terms both parties sign
+type SettlementTerms struct {
+	ProposalID ProposalID     `json:"proposal_id"`
+	OrderID    OrderID        `json:"order_id"`
+	MakerID    PeerID         `json:"maker_id"`
+	TakerID    PeerID         `json:"taker_id"`
+	Stablecoin StablecoinType `json:"stablecoin"`
+	Price      uint64         `json:"price"`
+	Amount     uint64         `json:"amount"`
+
+	// Stablecoin leg, in the same units the settlement service uses for usdc_minor (amount * price)
+	StablecoinAmount uint64 `json:"stablecoin_amount"`
+
+	// Negotiation history the proposal was made against (see negotiationTranscript)
+	TranscriptHash TranscriptHash `json:"transcript_hash"`
+}
+
+// SignedSettlement is the co-signed record of agreed terms. The taker signs when proposing,
+// the maker countersigns when accepting and sends the result back to the taker.
+type SignedSettlement struct {
+	Terms          SettlementTerms `json:"terms"`
+	TakerPubKey    []byte          `json:"taker_pubkey"`
+	TakerSignature []byte          `json:"taker_signature"`
+	MakerPubKey    []byte          `json:"maker_pubkey,omitempty"`
+	MakerSignature []byte          `json:"maker_signature,omitempty"`
+}
+
+// termsForProposal derives the settlement terms from a proposal, its order and the negotiation
+// history it was made against
+func termsForProposal(proposal *Proposal, order *OrderAnnouncement, transcript TranscriptHash) SettlementTerms {
+	// Left at zero on overflow, which validateAmounts rejects
+	stablecoinAmount, _ := stablecoinTotal(proposal.Amount, proposal.Price)
+
+	return SettlementTerms{
+		ProposalID: proposal.ProposalID,
+		OrderID:    proposal.OrderID,
+		MakerID:    order.MakerID,
+		TakerID:    proposal.ProposerID,
+		Stablecoin: order.Stablecoin,
+		Price:      proposal.Price,
+		Amount:     proposal.Amount,
+
+		StablecoinAmount: stablecoinAmount,
+		TranscriptHash:   transcript,
+	}
+}
+
+// stablecoinTotal returns amount * price, or false if the product overflows
+func stablecoinTotal(amount, price uint64) (uint64, bool) {
+	hi, lo := bits.Mul64(amount, price)
+	return lo, hi == 0
+}
+
+// validateAmounts checks that the stablecoin leg matches amount * price within tolerance
+func (t SettlementTerms) validateAmounts(tolerance uint64) error {
+	expected, ok := stablecoinTotal(t.Amount, t.Price)
+	if !ok {
+		return fmt.Errorf("%w: amount %d * price %d overflows", ErrInconsistentTerms, t.Amount, t.Price)
+	}
+
+	diff := expected - t.StablecoinAmount
+	if t.StablecoinAmount > expected {
+		diff = t.StablecoinAmount - expected
+	}
+	if diff > tolerance {
+		return fmt.Errorf("%w: stablecoin amount %d, expected %d (amount %d * price %d)",
+			ErrInconsistentTerms, t.StablecoinAmount, expected, t.Amount, t.Price)
+	}
+	return nil
+}
+
+// signingBytes returns the bytes both parties sign
+func (t SettlementTerms) signingBytes() []byte {
+	data, _ := json.Marshal(t)
+	return append([]byte("blacktrace/settlement-terms"), data...)
+}
+
+// verifySignature checks one party's signature over the terms
+func (t SettlementTerms) verifySignature(pubKey, signature []byte) error {
+	key, err := ParsePublicKey(pubKey)
+	if err != nil {
+		return fmt.Errorf("invalid public key: %w", err)
+	}
+	return VerifySignature(key, t.signingBytes(), signature)
+}
+
+// Verify checks that the terms are consistent and carry both the taker's and the maker's signatures
+func (s *SignedSettlement) Verify() error {
+	if err := s.Terms.validateAmounts(settlementAmountTolerance); err != nil {
+		return err
+	}
+	if err := s.Terms.verifySignature(s.TakerPubKey, s.TakerSignature); err != nil {
+		return fmt.Errorf("taker signature: %w", err)
+	}
+	if len(s.MakerSignature) == 0 {
+		return fmt.Errorf("settlement %s is not countersigned by the maker", s.Terms.ProposalID)
+	}
+	if err := s.Terms.verifySignature(s.MakerPubKey, s.MakerSignature); err != nil {
+		return fmt.Errorf("maker signature: %w", err)
+	}
+	return nil
+}
+
+// signTermsAsTaker attaches the taker's signature over the proposal's terms
+func (app *BlackTraceApp) signTermsAsTaker(proposal *Proposal, order *OrderAnnouncement) error {
+	cryptoMgr := app.crypto()
+	if cryptoMgr == nil {
+		return fmt.Errorf("CryptoManager not initialized")
+	}
+
+	terms := termsForProposal(proposal, order, app.proposalTranscript(proposal))
+	if err := terms.validateAmounts(settlementAmountTolerance); err != nil {
+		return err
+	}
+	if err := app.checkPrice(terms.Stablecoin, terms.Price); err != nil {
+		return err
+	}
+	signature, err := cryptoMgr.SignMessage(terms.signingBytes())
+	if err != nil {
+		return fmt.Errorf("failed to sign settlement terms: %w", err)
+	}
+
+	proposal.Settlement = &SignedSettlement{
+		Terms:          terms,
+		TakerPubKey:    cryptoMgr.GetPublicKey(),
+		TakerSignature: signature,
+	}
+	return nil
+}
+
+// countersignSettlement checks the taker-signed terms against the maker's own view of the
+// proposal and order, then adds the maker's signature
+func (app *BlackTraceApp) countersignSettlement(proposal *Proposal, order *OrderAnnouncement) (*SignedSettlement, error) {
+	cryptoMgr := app.crypto()
+	if cryptoMgr == nil {
+		return nil, fmt.Errorf("CryptoManager not initialized")
+	}
+	if proposal.Settlement == nil {
+		return nil, fmt.Errorf("proposal %s carries no signed terms", proposal.ProposalID)
+	}
+
+	settlement := *proposal.Settlement
+	if err := settlement.Terms.validateAmounts(settlementAmountTolerance); err != nil {
+		return nil, err
+	}
+	if settlement.Terms.TranscriptHash != app.proposalTranscript(proposal) {
+		return nil, fmt.Errorf("signed terms for proposal %s were made against a different negotiation history", proposal.ProposalID)
+	}
+	if settlement.Terms != termsForProposal(proposal, order, settlement.Terms.TranscriptHash) {
+		return nil, fmt.Errorf("signed terms do not match proposal %s", proposal.ProposalID)
+	}
+	if err := settlement.Terms.verifySignature(settlement.TakerPubKey, settlement.TakerSignature); err != nil {
+		return nil, fmt.Errorf("taker signature: %w", err)
+	}
+	if err := app.checkPrice(settlement.Terms.Stablecoin, settlement.Terms.Price); err != nil {
+		return nil, err
+	}
+
+	signature, err := cryptoMgr.SignMessage(settlement.Terms.signingBytes())
+	if err != nil {
+		return nil, fmt.Errorf("failed to countersign settlement terms: %w", err)
+	}
+	settlement.MakerPubKey = cryptoMgr.GetPublicKey()
+	settlement.MakerSignature = signature
+
+	return &settlement, nil
+}
+
+// applyAgreedSettlement verifies the maker's countersigned settlement against the taker's own
+// proposal and moves it to Accepted (terms agreed). A mismatching settlement is rejected.
+func (app *BlackTraceApp) applyAgreedSettlement(settlement *SignedSettlement) error {
+	if err := settlement.Verify(); err != nil {
+		return err
+	}
+
+	order, err := app.verifySettlementAgainstOrder(settlement)
+	if err != nil {
+		return err
+	}
+
+	app.proposalsMux.Lock()
+	defer app.proposalsMux.Unlock()
+
+	proposal, ok := app.proposals[settlement.Terms.ProposalID]
+	if !ok {
+		return fmt.Errorf("proposal %s not found", settlement.Terms.ProposalID)
+	}
+	if proposal.Settlement == nil {
+		return fmt.Errorf("proposal %s was not signed by this node", proposal.ProposalID)
+	}
+	if settlement.Terms != termsForProposal(proposal, order, app.proposalTranscript(proposal)) ||
+		!bytes.Equal(settlement.TakerPubKey, proposal.Settlement.TakerPubKey) {
+		return fmt.Errorf("settlement does not match proposal %s", proposal.ProposalID)
+	}
+
+	proposal.Status = ProposalStatusAccepted
+	proposal.Settlement = settlement
+	app.persistProposal(proposal)
+	app.recordProposalState(proposal, ProposalStatusAccepted)
+	return nil
+}
+
+// verifySettlementAgainstOrder checks that signed terms describe a known order and, when the
+// order is signed, that the countersigning maker is the order's maker. Returns the order.
+func (app *BlackTraceApp) verifySettlementAgainstOrder(settlement *SignedSettlement) (*OrderAnnouncement, error) {
+	app.ordersMux.RLock()
+	order, ok := app.orders[settlement.Terms.OrderID]
+	app.ordersMux.RUnlock()
+	if !ok {
+		return nil, fmt.Errorf("order %s not found", settlement.Terms.OrderID)
+	}
+
+	if settlement.Terms.MakerID != order.MakerID || settlement.Terms.Stablecoin != order.Stablecoin {
+		return nil, fmt.Errorf("settlement terms do not match order %s", order.OrderID)
+	}
+	if len(order.MakerPubKey) > 0 && !bytes.Equal(order.MakerPubKey, settlement.MakerPubKey) {
+		return nil, fmt.Errorf("settlement countersigned by a key other than the order's maker")
+	}
+	return order, nil
+}
+
+// verifySettlementKeys checks the signing keys against the public keys already cached for both parties
+func (app *BlackTraceApp) verifySettlementKeys(settlement *SignedSettlement) error {
+	app.peerKeysMux.RLock()
+	defer app.peerKeysMux.RUnlock()
+
+	if known, ok := app.peerKeys[settlement.Terms.MakerID]; ok && !bytes.Equal(known, settlement.MakerPubKey) {
+		return fmt.Errorf("maker key does not match the key known for %s", settlement.Terms.MakerID)
+	}
+	if known, ok := app.peerKeys[settlement.Terms.TakerID]; ok && !bytes.Equal(known, settlement.TakerPubKey) {
+		return fmt.Errorf("taker key does not match the key known for %s", settlement.Terms.TakerID)
+	}
+	return nil
+}
+
+// storeSignedSettlement verifies a co-signed settlement received over the wire and records it.
+// Nothing is stored unless both signatures, both parties' keys, and the order all check out.
+func (app *BlackTraceApp) storeSignedSettlement(settlement *SignedSettlement) error {
+	if err := settlement.Verify(); err != nil {
+		return err
+	}
+	if err := app.verifySettlementKeys(settlement); err != nil {
+		return err
+	}
+	if _, err := app.verifySettlementAgainstOrder(settlement); err != nil {
+		return err
+	}
+
+	app.signedSettlementsMux.Lock()
+	app.signedSettlements[settlement.Terms.ProposalID] = settlement
+	app.signedSettlementsMux.Unlock()
+	return nil
+}
+
+// SignedSettlement returns the verified co-signed settlement received for a proposal
+func (app *BlackTraceApp) SignedSettlement(proposalID ProposalID) (*SignedSettlement, bool) {
+	app.signedSettlementsMux.RLock()
+	defer app.signedSettlementsMux.RUnlock()
+
+	settlement, ok := app.signedSettlements[proposalID]
+	return settlement, ok
+}
@@ -0,0 +1,230 @@
+package node
+
+import (
+	"bytes"
+	"crypto/ecdsa"
+	"crypto/elliptic"
+	"crypto/rand"
+	"encoding/json"
+	"errors"
+	"log"
+	"os"
+	"strings"
+	"testing"
+)
+
+func newTestAppWithKey(t *testing.T) *BlackTraceApp {
+	privateKey, err := ecdsa.GenerateKey(elliptic.P256(), rand.Reader)
+	if err != nil {
+		t.Fatalf("Failed to generate key: %v", err)
+	}
+	app := newTestApp()
+	app.cryptoMgr = NewCryptoManager(privateKey)
+	return app
+}
+
+// negotiateToAcceptance runs a proposal from taker to maker and returns the maker's countersigned settlement
+func negotiateToAcceptance(t *testing.T, maker, taker *BlackTraceApp) (*Proposal, *SignedSettlement) {
+	order := &OrderAnnouncement{OrderID: "order_1", Stablecoin: StablecoinUSDC, MakerID: "maker-peer"}
+	if err := order.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign order: %v", err)
+	}
+	maker.orders[order.OrderID] = order
+	takerOrder := *order
+	taker.orders[order.OrderID] = &takerOrder
+
+	proposal := &Proposal{
+		ProposalID: NewProposalID(order.OrderID),
+		OrderID:    order.OrderID,
+		Price:      45,
+		Amount:     100000000,
+		ProposerID: "taker-peer",
+		Status:     ProposalStatusPending,
+	}
+	if err := taker.signTermsAsTaker(proposal, &takerOrder); err != nil {
+		t.Fatalf("Taker failed to sign terms: %v", err)
+	}
+	taker.storeProposalLocked(proposal)
+
+	// Proposal travels to the maker over the wire
+	data, _ := json.Marshal(proposal)
+	var received Proposal
+	if err := json.Unmarshal(data, &received); err != nil {
+		t.Fatalf("Failed to unmarshal proposal: %v", err)
+	}
+	maker.storeProposalLocked(&received)
+
+	settlement, err := maker.countersignSettlement(&received, order)
+	if err != nil {
+		t.Fatalf("Maker failed to countersign: %v", err)
+	}
+	received.Status = ProposalStatusAccepted
+	received.Settlement = settlement
+	return &received, settlement
+}
+
+func TestBothSidesAgreeOnSignedSettlement(t *testing.T) {
+	maker, taker := newTestAppWithKey(t), newTestAppWithKey(t)
+	makerProposal, settlement := negotiateToAcceptance(t, maker, taker)
+
+	// Countersigned settlement is sent back to the taker
+	data, _ := json.Marshal(settlement)
+	var received SignedSettlement
+	if err := json.Unmarshal(data, &received); err != nil {
+		t.Fatalf("Failed to unmarshal settlement: %v", err)
+	}
+	if err := taker.applyAgreedSettlement(&received); err != nil {
+		t.Fatalf("Taker rejected a valid settlement: %v", err)
+	}
+
+	takerProposal := taker.proposals[makerProposal.ProposalID]
+	if takerProposal.Status != ProposalStatusAccepted || makerProposal.Status != ProposalStatusAccepted {
+		t.Fatalf("Both sides should have agreed terms: maker %s, taker %s", makerProposal.Status, takerProposal.Status)
+	}
+	makerJSON, _ := json.Marshal(makerProposal.Settlement)
+	takerJSON, _ := json.Marshal(takerProposal.Settlement)
+	if string(makerJSON) != string(takerJSON) {
+		t.Errorf("Settlements differ:\nmaker: %s\ntaker: %s", makerJSON, takerJSON)
+	}
+	if err := takerProposal.Settlement.Verify(); err != nil {
+		t.Errorf("Agreed settlement does not verify: %v", err)
+	}
+}
+
+func TestMismatchingSettlementRejected(t *testing.T) {
+	maker, taker := newTestAppWithKey(t), newTestAppWithKey(t)
+	_, settlement := negotiateToAcceptance(t, maker, taker)
+
+	// Maker countersigns a different price than the taker proposed
+	tampered := *settlement
+	tampered.Terms.Price = 40
+	signature, err := maker.cryptoMgr.SignMessage(tampered.Terms.signingBytes())
+	if err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+	tampered.MakerSignature = signature
+
+	if err := taker.applyAgreedSettlement(&tampered); err == nil {
+		t.Fatal("Taker should reject a settlement that does not match its proposal")
+	}
+	if status := taker.proposals[settlement.Terms.ProposalID].Status; status != ProposalStatusPending {
+		t.Errorf("Proposal should stay pending, got %s", status)
+	}
+}
+
+func TestReceivedSignedSettlementStoredOnlyWhenValid(t *testing.T) {
+	maker, taker := newTestAppWithKey(t), newTestAppWithKey(t)
+	_, settlement := negotiateToAcceptance(t, maker, taker)
+	proposalID := settlement.Terms.ProposalID
+
+	// One bad signature: the maker's no longer covers the terms
+	bad := *settlement
+	bad.MakerSignature = append([]byte{}, settlement.MakerSignature...)
+	bad.MakerSignature[len(bad.MakerSignature)-1] ^= 0xff
+	payload, _ := json.Marshal(&bad)
+	taker.handleMessagePayload("maker-peer", "signed_settlement", payload, nil)
+	if _, ok := taker.SignedSettlement(proposalID); ok {
+		t.Fatal("Settlement with a bad maker signature should not be stored")
+	}
+
+	payload, _ = json.Marshal(settlement)
+	taker.handleMessagePayload("maker-peer", "signed_settlement", payload, nil)
+	stored, ok := taker.SignedSettlement(proposalID)
+	if !ok {
+		t.Fatal("Valid settlement should be stored")
+	}
+	if stored.Terms != settlement.Terms {
+		t.Errorf("Stored terms differ: %+v", stored.Terms)
+	}
+}
+
+func TestSettlementAmountInvariant(t *testing.T) {
+	terms := SettlementTerms{ProposalID: "p1", OrderID: "order_1", Price: 45, Amount: 100000000, StablecoinAmount: 4500000000}
+	if err := terms.validateAmounts(settlementAmountTolerance); err != nil {
+		t.Errorf("Consistent terms rejected: %v", err)
+	}
+
+	// Off by one unit of rounding is tolerated
+	terms.StablecoinAmount++
+	if err := terms.validateAmounts(settlementAmountTolerance); err != nil {
+		t.Errorf("Rounding within tolerance rejected: %v", err)
+	}
+
+	// Both parties sign a stablecoin leg priced at 40 while the terms say 45
+	maker, taker := newTestAppWithKey(t), newTestAppWithKey(t)
+	mismatched := &SignedSettlement{Terms: terms}
+	mismatched.Terms.StablecoinAmount = 4000000000
+	for _, party := range []struct {
+		app       *BlackTraceApp
+		pubKey    *[]byte
+		signature *[]byte
+	}{
+		{taker, &mismatched.TakerPubKey, &mismatched.TakerSignature},
+		{maker, &mismatched.MakerPubKey, &mismatched.MakerSignature},
+	} {
+		signature, err := party.app.cryptoMgr.SignMessage(mismatched.Terms.signingBytes())
+		if err != nil {
+			t.Fatalf("Failed to sign: %v", err)
+		}
+		*party.pubKey = party.app.cryptoMgr.GetPublicKey()
+		*party.signature = signature
+	}
+
+	if err := mismatched.Verify(); !errors.Is(err, ErrInconsistentTerms) {
+		t.Errorf("Expected ErrInconsistentTerms, got %v", err)
+	}
+}
+
+// mockOracle serves fixed reference prices
+type mockOracle map[StablecoinType]uint64
+
+func (o mockOracle) ReferencePrice(coin StablecoinType) (uint64, bool) {
+	price, ok := o[coin]
+	return price, ok
+}
+
+func TestPriceOracleFlagsOutlierPrices(t *testing.T) {
+	var logs bytes.Buffer
+	log.SetOutput(&logs)
+	defer log.SetOutput(os.Stderr)
+
+	maker, taker := newTestAppWithKey(t), newTestAppWithKey(t)
+	order := &OrderAnnouncement{OrderID: "order_1", Stablecoin: StablecoinUSDC, MakerID: "maker-peer"}
+	propose := func(price uint64) (*Proposal, error) {
+		proposal := &Proposal{ProposalID: NewProposalID(order.OrderID), OrderID: order.OrderID, Price: price, Amount: 100000000, ProposerID: "taker-peer"}
+		return proposal, taker.signTermsAsTaker(proposal, order)
+	}
+
+	// Warning only: the outlier is signed but logged
+	taker.SetPriceCheck(&PriceCheck{Oracle: mockOracle{StablecoinUSDC: 45}, MaxDeviationPercent: 10})
+	if _, err := propose(90); err != nil {
+		t.Fatalf("Non-strict check should not reject: %v", err)
+	}
+	if !strings.Contains(logs.String(), ErrPriceDeviation.Error()) {
+		t.Errorf("Expected a price deviation warning, got %q", logs.String())
+	}
+
+	// Strict: the outlier is refused, an in-band price is not
+	logs.Reset()
+	taker.SetPriceCheck(&PriceCheck{Oracle: mockOracle{StablecoinUSDC: 45}, MaxDeviationPercent: 10, Strict: true})
+	if _, err := propose(90); !errors.Is(err, ErrPriceDeviation) {
+		t.Errorf("Expected ErrPriceDeviation, got %v", err)
+	}
+	inBand, err := propose(48)
+	if err != nil {
+		t.Fatalf("In-band price rejected: %v", err)
+	}
+	if strings.Contains(logs.String(), "$48") {
+		t.Errorf("In-band price should not be flagged: %q", logs.String())
+	}
+
+	// The maker applies its own oracle when accepting
+	maker.SetPriceCheck(&PriceCheck{Oracle: mockOracle{StablecoinUSDC: 30}, MaxDeviationPercent: 10, Strict: true})
+	if _, err := maker.countersignSettlement(inBand, order); !errors.Is(err, ErrPriceDeviation) {
+		t.Errorf("Expected the maker to reject against its reference, got %v", err)
+	}
+	maker.SetPriceCheck(nil)
+	if _, err := maker.countersignSettlement(inBand, order); err != nil {
+		t.Errorf("Without an oracle the terms should be accepted: %v", err)
+	}
+}
@@ -0,0 +1,191 @@
+package node
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+)
+
+// TranscriptHash is a running hash over a negotiation's history. Settlement terms carry the
+// hash their proposal was made against, so both signatures commit to the same history.
+type TranscriptHash [32]byte
+
+func (h TranscriptHash) String() string {
+	return hex.EncodeToString(h[:])
+}
+
+// MarshalText encodes the hash as hex, so it is a string in JSON
+func (h TranscriptHash) MarshalText() ([]byte, error) {
+	return []byte(h.String()), nil
+}
+
+// UnmarshalText decodes a hex transcript hash
+func (h *TranscriptHash) UnmarshalText(text []byte) error {
+	decoded, err := hex.DecodeString(string(text))
+	if err != nil {
+		return fmt.Errorf("invalid transcript hash: %w", err)
+	}
+	if len(decoded) != len(h) {
+		return fmt.Errorf("invalid transcript hash: %d bytes (expected %d)", len(decoded), len(h))
+	}
+	copy(h[:], decoded)
+	return nil
+}
+
+// transcriptKey identifies one negotiation: an order and the taker negotiating it with the
+// maker. Both parties derive it from a proposal alone.
+type transcriptKey struct {
+	order OrderID
+	taker PeerID
+}
+
+func transcriptKeyFor(proposal *Proposal) transcriptKey {
+	return transcriptKey{order: proposal.OrderID, taker: proposal.ProposerID}
+}
+
+// negotiationTranscript is the history of one negotiation: every proposal, and every status
+// change both parties learn of (acceptance and rejection, not local cancellations)
+type negotiationTranscript struct {
+	hash      TranscriptHash
+	proposals map[ProposalID]TranscriptHash // Hash each proposal was made against
+}
+
+// transcriptProposal is the part of a proposal both parties hold identically
+type transcriptProposal struct {
+	ProposalID ProposalID `json:"proposal_id"`
+	OrderID    OrderID    `json:"order_id"`
+	Price      uint64     `json:"price"`
+	Amount     uint64     `json:"amount"`
+	ProposerID PeerID     `json:"proposer_id"`
+}
+
+type transcriptState struct {
+	ProposalID ProposalID     `json:"proposal_id"`
+	Status     ProposalStatus `json:"status"`
+}
+
+func newNegotiationTranscript() *negotiationTranscript {
+	return &negotiationTranscript{proposals: make(map[ProposalID]TranscriptHash)}
+}
+
+// storedTranscript is a negotiation transcript as persisted with the rest of the node's state
+type storedTranscript struct {
+	OrderID   OrderID                       `json:"order_id"`
+	Taker     PeerID                        `json:"taker"`
+	Hash      TranscriptHash                `json:"hash"`
+	Proposals map[ProposalID]TranscriptHash `json:"proposals"`
+}
+
+func (s *storedTranscript) restore() *negotiationTranscript {
+	transcript := newNegotiationTranscript()
+	transcript.hash = s.Hash
+	for id, hash := range s.Proposals {
+		transcript.proposals[id] = hash
+	}
+	return transcript
+}
+
+// persistTranscriptLocked saves a negotiation's transcript after it changed.
+// Caller must hold transcriptsMux.
+func (app *BlackTraceApp) persistTranscriptLocked(key transcriptKey) {
+	transcript, ok := app.transcripts[key]
+	if !ok {
+		return
+	}
+	app.persist(storageNamespaceTranscripts, string(key.order)+"/"+string(key.taker), &storedTranscript{
+		OrderID:   key.order,
+		Taker:     key.taker,
+		Hash:      transcript.hash,
+		Proposals: transcript.proposals,
+	})
+}
+
+// extend folds one entry into the running hash
+func (t *negotiationTranscript) extend(kind string, entry interface{}) {
+	data, _ := json.Marshal(entry)
+
+	h := sha256.New()
+	h.Write([]byte("blacktrace/negotiation-transcript"))
+	h.Write(t.hash[:])
+	h.Write([]byte(kind))
+	h.Write([]byte{0})
+	h.Write(data)
+	copy(t.hash[:], h.Sum(nil))
+}
+
+// addProposal records a proposal. A proposal already recorded (a redelivery) is ignored.
+func (t *negotiationTranscript) addProposal(proposal *Proposal) {
+	if _, ok := t.proposals[proposal.ProposalID]; ok {
+		return
+	}
+	t.proposals[proposal.ProposalID] = t.hash
+	t.extend("proposal", transcriptProposal{
+		ProposalID: proposal.ProposalID,
+		OrderID:    proposal.OrderID,
+		Price:      proposal.Price,
+		Amount:     proposal.Amount,
+		ProposerID: proposal.ProposerID,
+	})
+}
+
+// setState records a proposal's status change
+func (t *negotiationTranscript) setState(proposalID ProposalID, status ProposalStatus) {
+	t.extend("state", transcriptState{ProposalID: proposalID, Status: status})
+}
+
+// transcriptLocked returns the transcript of a proposal's negotiation, starting one if needed.
+// Caller must hold transcriptsMux.
+func (app *BlackTraceApp) transcriptLocked(proposal *Proposal) *negotiationTranscript {
+	key := transcriptKeyFor(proposal)
+	transcript, ok := app.transcripts[key]
+	if !ok {
+		transcript = newNegotiationTranscript()
+		app.transcripts[key] = transcript
+	}
+	return transcript
+}
+
+// recordProposal adds a proposal we sent or received to its negotiation's transcript
+func (app *BlackTraceApp) recordProposal(proposal *Proposal) {
+	app.transcriptsMux.Lock()
+	defer app.transcriptsMux.Unlock()
+	app.transcriptLocked(proposal).addProposal(proposal)
+	app.persistTranscriptLocked(transcriptKeyFor(proposal))
+}
+
+// recordProposalState adds a status change both parties see to the proposal's transcript
+func (app *BlackTraceApp) recordProposalState(proposal *Proposal, status ProposalStatus) {
+	app.transcriptsMux.Lock()
+	defer app.transcriptsMux.Unlock()
+	app.transcriptLocked(proposal).setState(proposal.ProposalID, status)
+	app.persistTranscriptLocked(transcriptKeyFor(proposal))
+}
+
+// proposalTranscript returns the transcript hash a proposal's terms are signed against: the
+// history before the proposal, or the current history for a proposal not recorded yet
+func (app *BlackTraceApp) proposalTranscript(proposal *Proposal) TranscriptHash {
+	app.transcriptsMux.Lock()
+	defer app.transcriptsMux.Unlock()
+
+	transcript, ok := app.transcripts[transcriptKeyFor(proposal)]
+	if !ok {
+		return TranscriptHash{}
+	}
+	if hash, ok := transcript.proposals[proposal.ProposalID]; ok {
+		return hash
+	}
+	return transcript.hash
+}
+
+// NegotiationTranscript returns the current transcript hash of a taker's negotiation on an order
+func (app *BlackTraceApp) NegotiationTranscript(orderID OrderID, taker PeerID) (TranscriptHash, bool) {
+	app.transcriptsMux.Lock()
+	defer app.transcriptsMux.Unlock()
+
+	transcript, ok := app.transcripts[transcriptKey{order: orderID, taker: taker}]
+	if !ok {
+		return TranscriptHash{}, false
+	}
+	return transcript.hash, true
+}
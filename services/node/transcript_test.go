@@ -0,0 +1,116 @@
+package node
+
+import (
+	"encoding/json"
+	"testing"
+)
+
+func TestNegotiationTranscriptsAgree(t *testing.T) {
+	taker := newTestAppWithKey(t)
+	order := &OrderAnnouncement{OrderID: "order_1", Stablecoin: StablecoinUSDC, MakerID: "maker-peer"}
+	taker.orders[order.OrderID] = order
+
+	propose := func(id ProposalID, price uint64) *Proposal {
+		p := &Proposal{ProposalID: id, OrderID: order.OrderID, Price: price, Amount: 100000000, ProposerID: "taker-peer", Status: ProposalStatusPending}
+		if err := taker.signTermsAsTaker(p, order); err != nil {
+			t.Fatalf("Failed to sign terms: %v", err)
+		}
+		if err := p.Sign(taker.cryptoMgr); err != nil {
+			t.Fatalf("Failed to sign proposal: %v", err)
+		}
+		taker.storeProposalLocked(p)
+		taker.recordProposal(p)
+		return p
+	}
+	type message struct {
+		msgType string
+		payload []byte
+	}
+	proposalMsg := func(p *Proposal) message {
+		payload, _ := json.Marshal(p)
+		return message{"proposal", payload}
+	}
+	rejection, _ := json.Marshal(map[string]interface{}{"proposal_id": "p1", "status": "rejected"})
+
+	// The taker proposes, sees the rejection, and proposes again
+	first := proposalMsg(propose("p1", 45))
+	taker.handleMessagePayload("maker-peer", "rejection", rejection, nil)
+	second := proposalMsg(propose("p2", 46))
+
+	engine := func(stream ...message) *BlackTraceApp {
+		maker := newTestAppWithKey(t)
+		makerOrder := *order
+		maker.orders[order.OrderID] = &makerOrder
+		for _, msg := range stream {
+			maker.handleMessagePayload("taker-peer", msg.msgType, msg.payload, taker.cryptoMgr.GetPublicKey())
+		}
+		return maker
+	}
+	reject := message{"rejection", rejection}
+	a := engine(first, reject, second)
+	b := engine(first, reject, second)
+	divergent := engine(first, second, reject)
+
+	hashA, okA := a.NegotiationTranscript(order.OrderID, "taker-peer")
+	hashB, okB := b.NegotiationTranscript(order.OrderID, "taker-peer")
+	if !okA || !okB || hashA != hashB {
+		t.Fatalf("Same message stream should give the same transcript: %s, %s", hashA, hashB)
+	}
+	if takerHash, _ := taker.NegotiationTranscript(order.OrderID, "taker-peer"); takerHash != hashA {
+		t.Errorf("Taker and maker transcripts differ: %s, %s", takerHash, hashA)
+	}
+	if hashD, _ := divergent.NegotiationTranscript(order.OrderID, "taker-peer"); hashD == hashA {
+		t.Error("Reordered message stream should give a different transcript")
+	}
+
+	// The signed terms commit to the history: only a maker that saw the same one countersigns
+	settlement, err := a.countersignSettlement(a.proposals["p2"], a.orders[order.OrderID])
+	if err != nil {
+		t.Fatalf("Maker with the same history should countersign: %v", err)
+	}
+	if settlement.Terms.TranscriptHash != taker.proposals["p2"].Settlement.Terms.TranscriptHash {
+		t.Error("Countersigned terms should carry the taker's transcript hash")
+	}
+	if _, err := divergent.countersignSettlement(divergent.proposals["p2"], divergent.orders[order.OrderID]); err == nil {
+		t.Error("Maker with a different history should not countersign")
+	}
+}
+
+func TestTranscriptSurvivesRestart(t *testing.T) {
+	store := NewMemStorage()
+	maker := newTestApp()
+	maker.store = store
+
+	first := &Proposal{ProposalID: "p1", OrderID: "order_1", Price: 45, Amount: 100000000, ProposerID: "taker-peer"}
+	second := &Proposal{ProposalID: "p2", OrderID: "order_1", Price: 46, Amount: 100000000, ProposerID: "taker-peer"}
+	maker.recordProposal(first)
+	maker.recordProposalState(first, ProposalStatusRejected)
+	maker.recordProposal(second)
+	before, _ := maker.NegotiationTranscript("order_1", "taker-peer")
+
+	restarted := newTestApp()
+	restarted.store = store
+	if err := restarted.loadState(); err != nil {
+		t.Fatalf("Failed to load state: %v", err)
+	}
+
+	after, ok := restarted.NegotiationTranscript("order_1", "taker-peer")
+	if !ok || after != before {
+		t.Fatalf("Transcript not restored: got %s (found %v), want %s", after, ok, before)
+	}
+	// Terms countersigned after the restart commit to the history the proposal was made against
+	for _, p := range []*Proposal{first, second} {
+		if restarted.proposalTranscript(p) != maker.proposalTranscript(p) {
+			t.Errorf("Proposal %s signs against a different history after the restart", p.ProposalID)
+		}
+	}
+
+	// The restored history keeps extending the same way
+	third := &Proposal{ProposalID: "p3", OrderID: "order_1", Price: 47, Amount: 100000000, ProposerID: "taker-peer"}
+	maker.recordProposal(third)
+	restarted.recordProposal(third)
+	want, _ := maker.NegotiationTranscript("order_1", "taker-peer")
+	if got, _ := restarted.NegotiationTranscript("order_1", "taker-peer"); got != want {
+		t.Errorf("Restored transcript diverged: %s, want %s", got, want)
+	}
+}
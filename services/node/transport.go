@@ -0,0 +1,54 @@
+package node
+
+import (
+	"context"
+	"io"
+
+	pubsub "github.com/libp2p/go-libp2p-pubsub"
+	"github.com/libp2p/go-libp2p/core/host"
+	"github.com/libp2p/go-libp2p/core/peer"
+	"github.com/libp2p/go-libp2p/core/protocol"
+)
+
+// Transport connects to peers and opens direct message streams to them. Incoming streams are accepted by
+// whatever is registered to call NetworkManager.readFrames for them (handleStream for libp2p).
+type Transport interface {
+	OpenStream(ctx context.Context, to peer.ID) (MessageStream, error)
+	Connect(ctx context.Context, pi peer.AddrInfo) error // Establish a connection to a peer's addresses
+}
+
+// MessageStream is the sending end of a direct stream
+type MessageStream interface {
+	io.Writer
+	Close() error
+	Reset() error // Abort the stream so a half-written frame is never read
+}
+
+// libp2pTransport opens BlackTrace protocol streams on the libp2p host
+type libp2pTransport struct {
+	host host.Host
+}
+
+func (t libp2pTransport) OpenStream(ctx context.Context, to peer.ID) (MessageStream, error) {
+	return t.host.NewStream(ctx, to, protocol.ID(BlackTraceProtocolID))
+}
+
+func (t libp2pTransport) Connect(ctx context.Context, pi peer.AddrInfo) error {
+	return t.host.Connect(ctx, pi)
+}
+
+// Gossip publishes to the BlackTrace pubsub topic. The mesh relays each message on, so it
+// reaches peers beyond our direct connections. Received messages come in through pubsubLoop
+// (for libp2p) as "message_received" events.
+type Gossip interface {
+	Publish(ctx context.Context, data []byte) error
+}
+
+// libp2pGossip publishes on the joined gossipsub topic
+type libp2pGossip struct {
+	topic *pubsub.Topic
+}
+
+func (g libp2pGossip) Publish(ctx context.Context, data []byte) error {
+	return g.topic.Publish(ctx, data)
+}
@@ -37,9 +37,21 @@ const (
 type ProposalStatus string
 
 const (
-	ProposalStatusPending  ProposalStatus = "Pending"
-	ProposalStatusAccepted ProposalStatus = "Accepted"
-	ProposalStatusRejected ProposalStatus = "Rejected"
+	ProposalStatusPending   ProposalStatus = "Pending"
+	ProposalStatusAccepted  ProposalStatus = "Accepted"
+	ProposalStatusRejected  ProposalStatus = "Rejected"
+	ProposalStatusCancelled ProposalStatus = "Cancelled"
+)
+
+// CancelReason records why a proposal was cancelled
+type CancelReason string
+
+const (
+	CancelReasonCounterpartyDisconnected CancelReason = "counterparty_disconnected" // Negotiation message undeliverable after retries
+	CancelReasonKeyRotated               CancelReason = "key_rotated"               // Node key rotated while the negotiation was pending
+	CancelReasonRateLimited              CancelReason = "rate_limited"              // Proposer kept sending past the proposal rate limit
+	CancelReasonTimeout                  CancelReason = "timeout"                   // Taker never proposed after the order details were revealed
+	CancelReasonGoingOffline             CancelReason = "going_offline"             // Counterparty shut down mid-negotiation
 )
 
 // SettlementStatus represents the settlement state of an accepted proposal
@@ -62,8 +74,14 @@ type OrderAnnouncement struct {
 	MakerID          PeerID         `json:"maker_id"` // NEW: Needed to send encrypted proposals
 	EncryptedDetails []byte         `json:"encrypted_details"`
 	ProofCommitment  []byte         `json:"proof_commitment"`
+	ProofScheme      ProofScheme    `json:"proof_scheme,omitempty"` // How ProofCommitment was produced; empty means a commitment hash
 	Timestamp        int64          `json:"timestamp"`
 	Expiry           int64          `json:"expiry"`
+	MakerPubKey      []byte         `json:"maker_pubkey,omitempty"`      // Maker's signing key (65-byte uncompressed)
+	SignatureVersion uint8          `json:"signature_version,omitempty"` // Layout of SigningBytes the signature covers
+	Signature        []byte         `json:"signature,omitempty"`         // Maker's signature over SigningBytes
+	RotatedFromKey   []byte         `json:"rotated_from_key,omitempty"`  // Maker key this announcement replaces after a key rotation
+	RotationSig      []byte         `json:"rotation_sig,omitempty"`      // RotatedFromKey's signature handing the order to MakerPubKey
 }
 
 // OrderDetails revealed during negotiation
@@ -94,6 +112,43 @@ type EncryptedAcceptanceMessage struct {
 	EncryptedPayload []byte     `json:"encrypted_payload"` // ECIES encrypted acceptance details
 }
 
+// NegotiationKeyMessage carries one side's ephemeral ECDH key for a negotiation session
+type NegotiationKeyMessage struct {
+	OrderID   OrderID `json:"order_id"`
+	PublicKey []byte  `json:"public_key"` // Uncompressed P-256 point
+}
+
+// NegotiationMessage wraps a negotiation message encrypted under the session key
+type NegotiationMessage struct {
+	OrderID    OrderID `json:"order_id"`
+	Ciphertext []byte  `json:"ciphertext"` // Nonce || AES-256-GCM(Message)
+}
+
+// ExtendDeadlineMessage asks the counterparty to push a negotiation session's deadline out
+type ExtendDeadlineMessage struct {
+	OrderID     OrderID   `json:"order_id"`
+	NewDeadline time.Time `json:"new_deadline"`
+}
+
+// NegotiationCancelledMessage tells the counterparty a negotiation session is over
+type NegotiationCancelledMessage struct {
+	OrderID OrderID      `json:"order_id"`
+	Reason  CancelReason `json:"reason"`
+}
+
+// DeadlineExtendedMessage answers an ExtendDeadlineMessage with the session's resulting deadline
+type DeadlineExtendedMessage struct {
+	OrderID  OrderID   `json:"order_id"`
+	Deadline time.Time `json:"deadline"`
+	Accepted bool      `json:"accepted"`
+	Reason   string    `json:"reason,omitempty"` // Why the extension was refused
+}
+
+// InterestsMessage advertises which stablecoins a node wants order announcements for
+type InterestsMessage struct {
+	Stablecoins []StablecoinType `json:"stablecoins"`
+}
+
 // Proposal during price negotiation
 type Proposal struct {
 	ProposalID         ProposalID        `json:"proposal_id"`
@@ -106,6 +161,11 @@ type Proposal struct {
 	Status             ProposalStatus    `json:"status"`
 	SettlementStatus   *SettlementStatus `json:"settlement_status,omitempty"`   // Only set when Status is Accepted
 	HashLock           *string           `json:"hash_lock,omitempty"`           // HTLC hash lock (set when Alice locks ZEC)
+	RevealRequested    bool              `json:"reveal_requested,omitempty"`    // Non-custodial: both legs locked, the secret holder should claim
+	CancelReason       CancelReason      `json:"cancel_reason,omitempty"`       // Only set when Status is Cancelled
+	Settlement         *SignedSettlement `json:"settlement,omitempty"`          // Taker-signed terms, countersigned by the maker on acceptance
+	ProposerPubKey     []byte            `json:"proposer_pubkey,omitempty"`     // Key the proposer signed with (65-byte uncompressed)
+	Signature          []byte            `json:"signature,omitempty"`           // Proposer's signature over SigningBytes, kept for the audit log
 	Timestamp          time.Time         `json:"timestamp"`
 }
 
@@ -149,8 +209,16 @@ func MarshalMessage(msgType string, payload interface{}) ([]byte, error) {
 	return json.Marshal(msg)
 }
 
-// UnmarshalMessage parses a wire protocol message
+// UnmarshalMessage parses a wire protocol message, in either wire format
 func UnmarshalMessage(data []byte) (*Message, error) {
+	if len(data) > 0 && data[0] == bincodeTag {
+		signed, err := decodeBincode(data)
+		if err != nil {
+			return nil, err
+		}
+		return &Message{Type: signed.Type, Payload: signed.Payload}, nil
+	}
+
 	var msg Message
 	err := json.Unmarshal(data, &msg)
 	return &msg, err
@@ -186,10 +254,16 @@ func MarshalSignedMessage(msgType string, payload interface{}, cm *CryptoManager
 	return json.Marshal(signedMsg)
 }
 
-// UnmarshalSignedMessage parses and verifies a signed message
+// UnmarshalSignedMessage parses and verifies a signed message, in either wire format
 func UnmarshalSignedMessage(data []byte) (*SignedMessage, error) {
 	var msg SignedMessage
-	if err := json.Unmarshal(data, &msg); err != nil {
+	if len(data) > 0 && data[0] == bincodeTag {
+		decoded, err := decodeBincode(data)
+		if err != nil {
+			return nil, fmt.Errorf("failed to unmarshal signed message: %w", err)
+		}
+		msg = *decoded
+	} else if err := json.Unmarshal(data, &msg); err != nil {
 		return nil, fmt.Errorf("failed to unmarshal signed message: %w", err)
 	}
 
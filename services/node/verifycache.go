@@ -0,0 +1,109 @@
+package node
+
+import (
+	"container/list"
+	"encoding/binary"
+	"sync"
+	"time"
+
+	"golang.org/x/crypto/blake2b"
+)
+
+// DefaultVerifyCacheSize is how many announcement signature checks are remembered
+const DefaultVerifyCacheSize = 1024
+
+type verifyCacheEntry struct {
+	key     [32]byte
+	err     error     // Result of the check (nil = valid)
+	expires time.Time // The announcement's expiry; the entry is dropped after it
+}
+
+// verifyCache remembers recent announcement signature checks, so an announcement relayed to us
+// by many peers is verified once. Entries are keyed by a hash of everything the check reads,
+// evicted least recently used first and never used past the announcement's expiry.
+type verifyCache struct {
+	mu      sync.Mutex
+	size    int
+	entries map[[32]byte]*list.Element
+	lru     *list.List // Most recently used at the front
+
+	verify func(*OrderAnnouncement) error
+}
+
+func newVerifyCache(size int) *verifyCache {
+	return &verifyCache{
+		size:    size,
+		entries: make(map[[32]byte]*list.Element),
+		lru:     list.New(),
+		verify:  (*OrderAnnouncement).Verify,
+	}
+}
+
+// announcementVerifyKey hashes the inputs of an announcement's signature check: the signing
+// version, the signed bytes (which include the maker key and expiry) and the signature
+func announcementVerifyKey(a *OrderAnnouncement) [32]byte {
+	message, _ := a.SigningBytes(a.SignatureVersion)
+
+	h, _ := blake2b.New256(nil)
+	h.Write([]byte{a.SignatureVersion})
+	for _, field := range [][]byte{message, a.Signature} {
+		var length [4]byte
+		binary.BigEndian.PutUint32(length[:], uint32(len(field)))
+		h.Write(length[:])
+		h.Write(field)
+	}
+
+	var key [32]byte
+	copy(key[:], h.Sum(nil))
+	return key
+}
+
+// check returns the result of verifying the announcement's signature, from the cache when an
+// identical announcement was checked before. Announcements without a live expiry are verified
+// but not cached.
+func (c *verifyCache) check(a *OrderAnnouncement, now time.Time) error {
+	key := announcementVerifyKey(a)
+
+	c.mu.Lock()
+	if el, ok := c.entries[key]; ok {
+		entry := el.Value.(*verifyCacheEntry)
+		if now.Before(entry.expires) {
+			c.lru.MoveToFront(el)
+			c.mu.Unlock()
+			return entry.err
+		}
+		c.lru.Remove(el)
+		delete(c.entries, key)
+	}
+	c.mu.Unlock()
+
+	// Verify outside the lock; it is the expensive part
+	err := c.verify(a)
+
+	expires := time.Unix(a.Expiry, 0)
+	if a.Expiry == 0 || !now.Before(expires) {
+		return err
+	}
+
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	if _, ok := c.entries[key]; ok {
+		return err
+	}
+	c.entries[key] = c.lru.PushFront(&verifyCacheEntry{key: key, err: err, expires: expires})
+	for c.lru.Len() > c.size {
+		oldest := c.lru.Back()
+		c.lru.Remove(oldest)
+		delete(c.entries, oldest.Value.(*verifyCacheEntry).key)
+	}
+	return err
+}
+
+// verifyAnnouncement checks an announcement's maker signature, through the verification cache
+// when the app has one
+func (app *BlackTraceApp) verifyAnnouncement(a *OrderAnnouncement) error {
+	if app.verifyCache == nil {
+		return a.Verify()
+	}
+	return app.verifyCache.check(a, time.Now())
+}
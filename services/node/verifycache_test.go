@@ -0,0 +1,102 @@
+package node
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"testing"
+	"time"
+)
+
+// countVerifications makes the app's verification cache count the signature checks it runs
+func countVerifications(app *BlackTraceApp) *int {
+	calls := new(int)
+	app.verifyCache.verify = func(a *OrderAnnouncement) error {
+		*calls++
+		return a.Verify()
+	}
+	return calls
+}
+
+func TestRelayedAnnouncementVerifiedOnce(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	announcement := &OrderAnnouncement{
+		OrderID:         "order_1",
+		ProofCommitment: bytes.Repeat([]byte{0x11}, 32),
+		Timestamp:       time.Now().Unix(),
+		Expiry:          time.Now().Add(time.Hour).Unix(),
+	}
+	if err := announcement.Sign(maker.cryptoMgr); err != nil {
+		t.Fatalf("Failed to sign: %v", err)
+	}
+	payload, _ := json.Marshal(announcement)
+
+	node := newTestApp()
+	calls := countVerifications(node)
+	for i := 0; i < 10; i++ {
+		node.handleMessagePayload(PeerID(fmt.Sprintf("relay-%d", i)), "order_announcement", payload, nil)
+	}
+	if *calls != 1 {
+		t.Errorf("Expected one verification for 10 identical announcements, got %d", *calls)
+	}
+	if node.orders["order_1"] == nil {
+		t.Fatal("Verified announcement should be stored")
+	}
+
+	// A forged copy differs in its signature, so it is checked (and rejected) on its own
+	forged := *announcement
+	forged.Signature = append([]byte(nil), announcement.Signature...)
+	forged.Signature[len(forged.Signature)-1] ^= 0xff
+	for i := 0; i < 3; i++ {
+		if err := node.verifyCache.check(&forged, time.Now()); err == nil {
+			t.Fatal("Forged signature should not verify")
+		}
+	}
+	if *calls != 2 {
+		t.Errorf("Expected the forgery to be verified once, got %d verifications", *calls)
+	}
+}
+
+func TestVerifyCacheExpiryAndEviction(t *testing.T) {
+	maker := newTestAppWithKey(t)
+	now := time.Now()
+	sign := func(id OrderID) *OrderAnnouncement {
+		a := &OrderAnnouncement{OrderID: id, Timestamp: now.Unix(), Expiry: now.Add(time.Minute).Unix()}
+		if err := a.Sign(maker.cryptoMgr); err != nil {
+			t.Fatalf("Failed to sign: %v", err)
+		}
+		return a
+	}
+
+	cache := newVerifyCache(2)
+	calls := 0
+	cache.verify = func(a *OrderAnnouncement) error {
+		calls++
+		return a.Verify()
+	}
+
+	first, second, third := sign("order_1"), sign("order_2"), sign("order_3")
+	cache.check(first, now)
+	cache.check(first, now)
+	if calls != 1 {
+		t.Fatalf("Expected a cached result, got %d verifications", calls)
+	}
+
+	// Past the announcement's expiry the cached result is not used
+	cache.check(first, now.Add(2*time.Minute))
+	if calls != 2 {
+		t.Errorf("Expired entry should be re-verified, got %d verifications", calls)
+	}
+
+	// The least recently used entry is evicted once the cache is full
+	cache.check(first, now)
+	cache.check(second, now)
+	cache.check(first, now)
+	cache.check(third, now)
+	calls = 0
+	cache.check(first, now)
+	cache.check(second, now)
+	if calls != 1 {
+		t.Errorf("Expected only the evicted entry to be re-verified, got %d verifications", calls)
+	}
+}
@@ -0,0 +1,155 @@
+package node
+
+import (
+	"encoding/binary"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log"
+)
+
+// WireFormat is how signed message envelopes sent directly to a peer are encoded
+type WireFormat string
+
+const (
+	WireFormatJSON    WireFormat = "json"    // Interoperable default, readable on the wire
+	WireFormatBincode WireFormat = "bincode" // Compact binary envelope, used only when both ends choose it
+)
+
+// bincodeTag prefixes a bincode envelope. A JSON envelope never starts with a zero byte,
+// so receivers tell the two apart without knowing what was negotiated.
+const bincodeTag byte = 0x00
+
+// errMalformedBincode is returned for a bincode envelope that is truncated or has trailing bytes
+var errMalformedBincode = errors.New("malformed bincode envelope")
+
+// ParseWireFormat reads a wire format name, defaulting to JSON when empty
+func ParseWireFormat(s string) (WireFormat, error) {
+	switch WireFormat(s) {
+	case "", WireFormatJSON:
+		return WireFormatJSON, nil
+	case WireFormatBincode:
+		return WireFormatBincode, nil
+	}
+	return "", fmt.Errorf("unknown wire format %q (want %q or %q)", s, WireFormatJSON, WireFormatBincode)
+}
+
+// WireFormatMessage advertises the format a node prefers for messages sent to it
+type WireFormatMessage struct {
+	Preferred WireFormat `json:"preferred"`
+}
+
+// negotiateWireFormat is the format both ends use: bincode only if both prefer it. Each side
+// computes it from the same two preferences, so they always agree.
+func negotiateWireFormat(local, remote WireFormat) WireFormat {
+	if local == WireFormatBincode && remote == WireFormatBincode {
+		return WireFormatBincode
+	}
+	return WireFormatJSON
+}
+
+// encodeBincode writes a signed envelope as bincode does a struct of the same fields: each
+// string or byte field as a little-endian u64 length and its bytes, the timestamp as an i64.
+// The payload keeps its JSON bytes, since the signature covers them.
+func encodeBincode(msg *SignedMessage) []byte {
+	size := 1 + 8*5 + len(msg.Type) + len(msg.Payload) + len(msg.Signature) + len(msg.SignerPublicKey)
+	buf := make([]byte, 0, size)
+	buf = append(buf, bincodeTag)
+	for _, field := range [][]byte{[]byte(msg.Type), msg.Payload, msg.Signature, msg.SignerPublicKey} {
+		buf = binary.LittleEndian.AppendUint64(buf, uint64(len(field)))
+		buf = append(buf, field...)
+	}
+	return binary.LittleEndian.AppendUint64(buf, uint64(msg.Timestamp))
+}
+
+// decodeBincode reads an envelope written by encodeBincode, tag included
+func decodeBincode(data []byte) (*SignedMessage, error) {
+	if len(data) == 0 || data[0] != bincodeTag {
+		return nil, fmt.Errorf("%w: missing tag", errMalformedBincode)
+	}
+	rest := data[1:]
+
+	fields := make([][]byte, 4)
+	for i := range fields {
+		if len(rest) < 8 {
+			return nil, fmt.Errorf("%w: truncated length", errMalformedBincode)
+		}
+		n := binary.LittleEndian.Uint64(rest)
+		rest = rest[8:]
+		if n > uint64(len(rest)) {
+			return nil, fmt.Errorf("%w: field of %d bytes exceeds the %d left", errMalformedBincode, n, len(rest))
+		}
+		fields[i] = rest[:n]
+		rest = rest[n:]
+	}
+	if len(rest) != 8 {
+		return nil, fmt.Errorf("%w: expected an 8-byte timestamp, %d bytes left", errMalformedBincode, len(rest))
+	}
+
+	return &SignedMessage{
+		Type:            string(fields[0]),
+		Payload:         json.RawMessage(fields[1]),
+		Signature:       fields[2],
+		SignerPublicKey: fields[3],
+		Timestamp:       int64(binary.LittleEndian.Uint64(rest)),
+	}, nil
+}
+
+// SetWireFormat sets the format this node prefers for direct messages. Must be called before
+// Run; peers that do not also prefer it keep receiving JSON.
+func (app *BlackTraceApp) SetWireFormat(format WireFormat) {
+	app.wireFormat = format
+}
+
+// sendWireFormat advertises our preferred format to a newly connected peer
+func (app *BlackTraceApp) sendWireFormat(to PeerID) {
+	preferred := app.wireFormat
+	if preferred == "" {
+		preferred = WireFormatJSON
+	}
+	if err := app.sendSignedMessage(to, "wire_format", WireFormatMessage{Preferred: preferred}); err != nil {
+		log.Printf("Failed to send wire format to %s: %v", to, err)
+	}
+}
+
+// handleWireFormat records the format agreed with a peer from its advertised preference
+func (app *BlackTraceApp) handleWireFormat(from PeerID, msg *WireFormatMessage) {
+	agreed := negotiateWireFormat(app.wireFormat, msg.Preferred)
+
+	app.peerWireFormatsMux.Lock()
+	app.peerWireFormats[from] = agreed
+	app.peerWireFormatsMux.Unlock()
+
+	log.Printf("App: Using %s for messages to %s", agreed, from)
+}
+
+// forgetWireFormat drops a disconnected peer's agreement; it is negotiated again on reconnect
+func (app *BlackTraceApp) forgetWireFormat(peerID PeerID) {
+	app.peerWireFormatsMux.Lock()
+	delete(app.peerWireFormats, peerID)
+	app.peerWireFormatsMux.Unlock()
+}
+
+// PeerWireFormat returns the format agreed with a peer (JSON until negotiated)
+func (app *BlackTraceApp) PeerWireFormat(peerID PeerID) WireFormat {
+	app.peerWireFormatsMux.RLock()
+	defer app.peerWireFormatsMux.RUnlock()
+
+	if format, ok := app.peerWireFormats[peerID]; ok {
+		return format
+	}
+	return WireFormatJSON
+}
+
+// encodeForPeer re-encodes a JSON signed envelope in the format agreed with the peer.
+// Unsigned messages always stay JSON.
+func (app *BlackTraceApp) encodeForPeer(to PeerID, data []byte) []byte {
+	if app.PeerWireFormat(to) != WireFormatBincode {
+		return data
+	}
+	var msg SignedMessage
+	if err := json.Unmarshal(data, &msg); err != nil || len(msg.Signature) == 0 {
+		return data
+	}
+	return encodeBincode(&msg)
+}
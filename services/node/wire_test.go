@@ -0,0 +1,231 @@
+package node
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"os"
+	"path/filepath"
+	"reflect"
+	"testing"
+	"time"
+)
+
+// Wire-format fixtures pin the exact JSON bytes exchanged with other implementations.
+// If one of these tests fails, the serialization changed and interop is broken.
+
+func sampleProposal() Proposal {
+	return Proposal{
+		ProposalID:         "order_1736942400_proposal_1736942400000000000",
+		OrderID:            "order_1736942400",
+		Price:              45,
+		Amount:             100000000,
+		ProposerID:         "12D3KooWBobPeer",
+		ProposerUsername:   "bob",
+		ProposerPubKeyHash: "a1b2c3d4e5f60718293a4b5c6d7e8f9012345678",
+		Status:             ProposalStatusPending,
+		Timestamp:          time.Date(2025, 1, 15, 12, 0, 0, 0, time.UTC),
+	}
+}
+
+func sampleOrderAnnouncement() OrderAnnouncement {
+	commitment := make([]byte, 32)
+	for i := range commitment {
+		commitment[i] = byte(i)
+	}
+	return OrderAnnouncement{
+		OrderID:          "order_1736942400",
+		OrderType:        OrderTypeSell,
+		Stablecoin:       StablecoinUSDC,
+		MakerID:          "12D3KooWAlicePeer",
+		EncryptedDetails: []byte{1, 2, 3, 4},
+		ProofCommitment:  commitment,
+		Timestamp:        1736942400,
+		Expiry:           1736946000,
+	}
+}
+
+func sampleSignedSettlement(t *testing.T) SignedMessage {
+	req := SettlementRequest{
+		ProposalID:      "order_1736942400_proposal_1736942400000000000",
+		OrderID:         "order_1736942400",
+		MakerID:         "12D3KooWAlicePeer",
+		TakerID:         "12D3KooWBobPeer",
+		ZECZatoshi:      100000000,
+		Price:           45,
+		Stablecoin:      "USDC",
+		SettlementChain: "ztarknet",
+		Secret:          "alice-secret",
+		Timestamp:       time.Date(2025, 1, 15, 12, 5, 0, 0, time.UTC),
+	}
+	payload, err := json.Marshal(req)
+	if err != nil {
+		t.Fatalf("Failed to marshal settlement request: %v", err)
+	}
+
+	signature := []byte{0x30, 0x44}
+	for i := 0; i < 68; i++ {
+		signature = append(signature, byte(i))
+	}
+	pubKey := []byte{0x04}
+	for i := 0; i < 64; i++ {
+		pubKey = append(pubKey, byte(i))
+	}
+
+	return SignedMessage{
+		Type:            "settlement_request",
+		Payload:         payload,
+		Signature:       signature,
+		SignerPublicKey: pubKey,
+		Timestamp:       1736942700,
+	}
+}
+
+// checkWireFixture asserts sample serializes to exactly the fixture bytes and parses back unchanged
+func checkWireFixture(t *testing.T, name string, sample interface{}, parsed interface{}) {
+	t.Helper()
+
+	fixture, err := os.ReadFile(filepath.Join("testdata", "wire", name))
+	if err != nil {
+		t.Fatalf("Failed to read fixture %s: %v", name, err)
+	}
+
+	encoded, err := json.Marshal(sample)
+	if err != nil {
+		t.Fatalf("Failed to marshal %s: %v", name, err)
+	}
+	if !bytes.Equal(encoded, fixture) {
+		t.Errorf("%s: serialized bytes drifted from fixture\n got: %s\nwant: %s", name, encoded, fixture)
+	}
+
+	if err := json.Unmarshal(fixture, parsed); err != nil {
+		t.Fatalf("Failed to parse fixture %s: %v", name, err)
+	}
+	if !reflect.DeepEqual(reflect.ValueOf(parsed).Elem().Interface(), sample) {
+		t.Errorf("%s: parsed fixture does not match sample\n got: %+v\nwant: %+v", name, parsed, sample)
+	}
+}
+
+func TestWireFormatProposal(t *testing.T) {
+	checkWireFixture(t, "proposal.json", sampleProposal(), &Proposal{})
+}
+
+func TestWireFormatOrderAnnouncement(t *testing.T) {
+	checkWireFixture(t, "order_announcement.json", sampleOrderAnnouncement(), &OrderAnnouncement{})
+}
+
+func TestWireFormatSignedSettlement(t *testing.T) {
+	checkWireFixture(t, "signed_settlement.json", sampleSignedSettlement(t), &SignedMessage{})
+}
+
+func TestBincodeEnvelopeRoundTrip(t *testing.T) {
+	app := newTestAppWithKey(t)
+	data, err := app.marshalOutbound("order_request", OrderID("order_1"))
+	if err != nil {
+		t.Fatalf("Failed to sign message: %v", err)
+	}
+	var original SignedMessage
+	if err := json.Unmarshal(data, &original); err != nil {
+		t.Fatalf("Failed to parse signed message: %v", err)
+	}
+
+	encoded := encodeBincode(&original)
+	if encoded[0] != bincodeTag || len(encoded) >= len(data) {
+		t.Errorf("Expected a tagged envelope smaller than the %d JSON bytes, got %d bytes", len(data), len(encoded))
+	}
+
+	// The signature still verifies after the round trip
+	decoded, err := UnmarshalSignedMessage(encoded)
+	if err != nil {
+		t.Fatalf("Failed to parse bincode envelope: %v", err)
+	}
+	if !reflect.DeepEqual(*decoded, original) {
+		t.Errorf("Round trip changed the envelope\n got: %+v\nwant: %+v", *decoded, original)
+	}
+
+	// Tampering is caught the same way as in JSON
+	tampered := bytes.Replace(encoded, []byte("order_1"), []byte("order_2"), 1)
+	if _, err := UnmarshalSignedMessage(tampered); err == nil {
+		t.Error("Tampered bincode payload should fail verification")
+	}
+}
+
+func TestMalformedBincodeRejected(t *testing.T) {
+	sample := sampleSignedSettlement(t)
+	encoded := encodeBincode(&sample)
+
+	for name, data := range map[string][]byte{
+		"truncated":      encoded[:len(encoded)-1],
+		"trailing bytes": append(append([]byte{}, encoded...), 0x01),
+		"huge length":    append([]byte{bincodeTag}, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff),
+	} {
+		if _, err := decodeBincode(data); !errors.Is(err, errMalformedBincode) {
+			t.Errorf("%s: expected errMalformedBincode, got %v", name, err)
+		}
+	}
+}
+
+func TestWireFormatNegotiatedPerPeer(t *testing.T) {
+	hub := newMemHub()
+	maker := newMemNode(t, hub, "maker")
+	taker := newMemNode(t, hub, "taker")
+	jsonTaker := newMemNode(t, hub, "json-taker")
+	maker.SetWireFormat(WireFormatBincode)
+	taker.SetWireFormat(WireFormatBincode)
+	hub.connect(maker.network, taker.network)
+	hub.connect(maker.network, jsonTaker.network)
+
+	for _, pair := range [][2]*BlackTraceApp{{maker, taker}, {taker, maker}, {maker, jsonTaker}, {jsonTaker, maker}} {
+		pair[0].handleNetworkEvent(NetworkEvent{Type: "peer_connected", From: pair[1].GetPeerID()})
+	}
+	agreed := func(app, peer *BlackTraceApp) bool {
+		app.peerWireFormatsMux.RLock()
+		defer app.peerWireFormatsMux.RUnlock()
+		_, ok := app.peerWireFormats[peer.GetPeerID()]
+		return ok
+	}
+	if !waitFor(func() bool {
+		return agreed(maker, taker) && agreed(taker, maker) && agreed(maker, jsonTaker) && agreed(jsonTaker, maker)
+	}, 5*time.Second) {
+		t.Fatal("Wire formats were never exchanged")
+	}
+
+	// Bincode only where both ends prefer it
+	if got := maker.PeerWireFormat(taker.GetPeerID()); got != WireFormatBincode {
+		t.Errorf("Expected bincode between two bincode nodes, got %s", got)
+	}
+	if got := taker.PeerWireFormat(maker.GetPeerID()); got != WireFormatBincode {
+		t.Errorf("Expected bincode between two bincode nodes, got %s", got)
+	}
+	if got := maker.PeerWireFormat(jsonTaker.GetPeerID()); got != WireFormatJSON {
+		t.Errorf("Expected JSON with a peer that prefers it, got %s", got)
+	}
+
+	// Negotiation traffic flows in bincode both ways
+	orderID := OrderID("order_1")
+	commitment := maker.commitToOrder(orderID, 10000)
+	announcement := &OrderAnnouncement{
+		OrderID:         orderID,
+		OrderType:       OrderTypeSell,
+		Stablecoin:      StablecoinUSDC,
+		MakerID:         maker.GetPeerID(),
+		ProofCommitment: commitment,
+	}
+	details := &OrderDetails{OrderID: orderID, OrderType: OrderTypeSell, Amount: 10000, MinPrice: 450, MaxPrice: 470, Stablecoin: StablecoinUSDC}
+	maker.orders[orderID] = announcement
+	maker.orderDetails[orderID] = details
+	maker.markOwnedOrder(details)
+	takerCopy := *announcement
+	taker.orders[orderID] = &takerCopy
+
+	taker.RequestOrderDetails(orderID)
+	if !waitFor(func() bool { return taker.IsLiquidityVerified(orderID) }, 5*time.Second) {
+		t.Fatal("Liquidity never verified over bincode")
+	}
+
+	// A reconnect starts from JSON until the peer advertises again
+	maker.handleNetworkEvent(NetworkEvent{Type: "peer_disconnected", From: taker.GetPeerID()})
+	if agreed(maker, taker) {
+		t.Error("Agreement should be forgotten on disconnect")
+	}
+}
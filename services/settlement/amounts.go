@@ -0,0 +1,15 @@
+package main
+
+import (
+	"fmt"
+	"math/bits"
+)
+
+// computeTotalUSDC multiplies amount by price, returning an error instead of wrapping on overflow
+func computeTotalUSDC(amount, price uint64) (uint64, error) {
+	hi, lo := bits.Mul64(amount, price)
+	if hi != 0 {
+		return 0, fmt.Errorf("settlement total overflows: amount %d * price %d", amount, price)
+	}
+	return lo, nil
+}
@@ -0,0 +1,43 @@
+package main
+
+import (
+	"math"
+	"testing"
+)
+
+func TestComputeTotalUSDC(t *testing.T) {
+	total, err := computeTotalUSDC(150000000, 45)
+	if err != nil {
+		t.Fatalf("Unexpected error: %v", err)
+	}
+	if total != 6750000000 {
+		t.Errorf("Expected total 6750000000, got %d", total)
+	}
+}
+
+func TestSettlementRejectedOnTotalOverflow(t *testing.T) {
+	s := &SettlementService{settlements: make(map[string]*SettlementState)}
+	s.registerChain(&mockChain{name: "ztarknet"})
+
+	req := &SettlementRequest{
+		ProposalID:      "order_1_proposal_1",
+		OrderID:         "order_1",
+		ZECZatoshi:      math.MaxUint64 / 2,
+		Price:           3,
+		SettlementChain: "ztarknet",
+	}
+
+	state, err := s.initSettlement(req, []byte("secret"), "hash")
+	if err == nil {
+		t.Fatal("Expected overflow error, got nil")
+	}
+	if state.Status != "rejected" {
+		t.Errorf("Expected status rejected, got %s", state.Status)
+	}
+	if state.AmountUSDC != 0 {
+		t.Errorf("Expected no USDC total on rejection, got %d", state.AmountUSDC)
+	}
+	if s.settlements[req.ProposalID] != state {
+		t.Error("Rejected settlement should be recorded")
+	}
+}
@@ -0,0 +1,143 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log"
+	"time"
+)
+
+// ErrUnsupportedChain is returned for a settlement_chain with no registered implementation
+var ErrUnsupportedChain = errors.New("unsupported settlement chain")
+
+// SettlementChain carries out the stablecoin leg of a swap on one chain.
+// The coordinator picks the implementation named by the request's settlement_chain.
+type SettlementChain interface {
+	Name() string
+	Lock(state *SettlementState) error            // Lock the stablecoin against the secret hash
+	Claim(state *SettlementState) error           // Claim the locked stablecoin with the revealed secret
+	Refund(state *SettlementState) error          // Return the stablecoin to the taker after timeout
+	Confirm(state *SettlementState) (bool, error) // Whether the stablecoin lock is confirmed on-chain
+}
+
+// relayChain is a chain whose transactions are signed by the users' wallets, not the coordinator.
+// Each operation is published as an instruction on settlement.chain.<name>.<proposal_id>;
+// the wallet reports the result back through settlement.status.* updates.
+type relayChain struct {
+	name    string
+	rpcURL  string // Chain endpoint the wallet should use ("" = its own)
+	publish func(subject string, data []byte) error
+}
+
+func (c *relayChain) Name() string {
+	return c.name
+}
+
+func (c *relayChain) instruct(state *SettlementState, action string, extra map[string]interface{}) error {
+	instruction := map[string]interface{}{
+		"proposal_id": state.ProposalID,
+		"order_id":    state.OrderID,
+		"chain":       c.name,
+		"action":      action,
+		"hash":        state.HashHex,
+	}
+	if c.rpcURL != "" {
+		instruction["rpc_url"] = c.rpcURL
+	}
+	for k, v := range extra {
+		instruction[k] = v
+	}
+
+	data, _ := json.Marshal(instruction)
+	subject := fmt.Sprintf("settlement.chain.%s.%s", c.name, state.ProposalID)
+	if err := c.publish(subject, data); err != nil {
+		return fmt.Errorf("failed to publish %s instruction to %s: %w", action, c.name, err)
+	}
+	return nil
+}
+
+func (c *relayChain) Lock(state *SettlementState) error {
+	return c.instruct(state, "lock", map[string]interface{}{
+		"amount":  state.AmountUSDC,
+		"timeout": int64(time.Until(state.StablecoinTimeout).Seconds()),
+	})
+}
+
+func (c *relayChain) Claim(state *SettlementState) error {
+	return c.instruct(state, "claim", nil)
+}
+
+func (c *relayChain) Refund(state *SettlementState) error {
+	return c.instruct(state, "refund", nil)
+}
+
+// Confirm trusts the wallet's lock report; the status update is the confirmation
+func (c *relayChain) Confirm(state *SettlementState) (bool, error) {
+	return true, nil
+}
+
+// registerChain makes a chain available to settlements that name it
+func (s *SettlementService) registerChain(chain SettlementChain) {
+	if s.chains == nil {
+		s.chains = make(map[string]SettlementChain)
+	}
+	s.chains[chain.Name()] = chain
+}
+
+// chainByName returns the registered implementation for a settlement_chain value
+func (s *SettlementService) chainByName(name string) (SettlementChain, error) {
+	chain, ok := s.chains[name]
+	if !ok {
+		return nil, fmt.Errorf("%w %q", ErrUnsupportedChain, name)
+	}
+	return chain, nil
+}
+
+// lockStablecoinLeg dispatches the stablecoin lock to the settlement's chain. Caller must hold s.mu.
+func (s *SettlementService) lockStablecoinLeg(state *SettlementState) error {
+	chain, err := s.chainByName(state.Chain)
+	if err != nil {
+		return err
+	}
+
+	// The stablecoin leg must expire well before the ZEC leg for the swap to stay atomic
+	timeout := state.ZECTimeout.Add(-MinSwapTimelockGap)
+	if err := validateSwapTimelocks(state.ZECTimeout, timeout, MinSwapTimelockGap); err != nil {
+		return err
+	}
+	if !timeout.After(time.Now()) {
+		return fmt.Errorf("%w: no time left for the stablecoin leg of %s", ErrInvalidProposal, state.ProposalID)
+	}
+	state.StablecoinTimeout = timeout
+
+	log.Printf("Dispatching stablecoin lock for %s to %s", state.ProposalID, chain.Name())
+	return chain.Lock(state)
+}
+
+// claimStablecoinLeg dispatches the stablecoin claim to the settlement's chain. Caller must hold s.mu.
+func (s *SettlementService) claimStablecoinLeg(state *SettlementState) error {
+	chain, err := s.chainByName(state.Chain)
+	if err != nil {
+		return err
+	}
+	return chain.Claim(state)
+}
+
+// refundStablecoinLeg dispatches the stablecoin refund to the settlement's chain. Caller must hold s.mu.
+func (s *SettlementService) refundStablecoinLeg(state *SettlementState) error {
+	chain, err := s.chainByName(state.Chain)
+	if err != nil {
+		return err
+	}
+	return chain.Refund(state)
+}
+
+// confirmStablecoinLock asks the settlement's chain whether the stablecoin lock is final. Caller must hold s.mu.
+func (s *SettlementService) confirmStablecoinLock(state *SettlementState) (bool, error) {
+	chain, err := s.chainByName(state.Chain)
+	if err != nil {
+		return false, err
+	}
+	return chain.Confirm(state)
+}
@@ -0,0 +1,142 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/nats-io/nats.go"
+)
+
+// mockChain records the operations dispatched to it
+type mockChain struct {
+	name  string
+	calls []string
+}
+
+func (c *mockChain) Name() string { return c.name }
+
+func (c *mockChain) Lock(state *SettlementState) error {
+	c.calls = append(c.calls, "lock:"+state.ProposalID)
+	return nil
+}
+
+func (c *mockChain) Claim(state *SettlementState) error {
+	c.calls = append(c.calls, "claim:"+state.ProposalID)
+	return nil
+}
+
+func (c *mockChain) Refund(state *SettlementState) error {
+	c.calls = append(c.calls, "refund:"+state.ProposalID)
+	return nil
+}
+
+func (c *mockChain) Confirm(state *SettlementState) (bool, error) {
+	c.calls = append(c.calls, "confirm:"+state.ProposalID)
+	return true, nil
+}
+
+func TestSettlementDispatchesToRequestedChain(t *testing.T) {
+	s := newTestService()
+	starknet := &mockChain{name: "starknet"}
+	solana := &mockChain{name: "solana"}
+	s.registerChain(starknet)
+	s.registerChain(solana)
+
+	req := &SettlementRequest{ProposalID: "p1", OrderID: "order_1", ZECZatoshi: 100, Price: 2, SettlementChain: "solana"}
+	state, err := s.initSettlement(req, []byte("secret"), "hash")
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+	if state.Chain != "solana" {
+		t.Errorf("Expected chain solana, got %s", state.Chain)
+	}
+	state.ZECTimeout = time.Now().Add(MakerTimelockBlocks * ZcashBlockInterval)
+
+	if err := s.lockStablecoinLeg(state); err != nil {
+		t.Fatalf("Failed to dispatch lock: %v", err)
+	}
+	if err := s.claimStablecoinLeg(state); err != nil {
+		t.Fatalf("Failed to dispatch claim: %v", err)
+	}
+
+	if len(solana.calls) != 2 || solana.calls[0] != "lock:p1" || solana.calls[1] != "claim:p1" {
+		t.Errorf("Expected lock and claim on solana, got %v", solana.calls)
+	}
+	if len(starknet.calls) != 0 {
+		t.Errorf("Starknet should not be used, got %v", starknet.calls)
+	}
+}
+
+func TestSettlementRejectsUnknownChain(t *testing.T) {
+	s := newTestService()
+	s.registerChain(&mockChain{name: "starknet"})
+
+	req := &SettlementRequest{ProposalID: "p1", OrderID: "order_1", ZECZatoshi: 100, Price: 2, SettlementChain: "dogechain"}
+	_, err := s.initSettlement(req, []byte("secret"), "hash")
+	if !errors.Is(err, ErrUnsupportedChain) {
+		t.Fatalf("Expected ErrUnsupportedChain, got %v", err)
+	}
+	if _, ok := s.settlements["p1"]; ok {
+		t.Error("No settlement state should be stored for an unknown chain")
+	}
+}
+
+func TestSettlementRejectsProposalReusedAcrossOrders(t *testing.T) {
+	s := newTestService()
+	s.registerChain(&mockChain{name: "starknet"})
+
+	first := &SettlementRequest{ProposalID: "p1", OrderID: "order_1", ZECZatoshi: 100, Price: 2, SettlementChain: "starknet"}
+	state, err := s.initSettlement(first, []byte("secret"), "hash")
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+
+	second := &SettlementRequest{ProposalID: "p1", OrderID: "order_2", ZECZatoshi: 100, Price: 2, SettlementChain: "starknet"}
+	if _, err := s.initSettlement(second, []byte("other"), "hash2"); !errors.Is(err, ErrProposalOrderConflict) {
+		t.Fatalf("Expected ErrProposalOrderConflict, got %v", err)
+	}
+	if _, err := s.initSettlement(first, []byte("other"), "hash2"); !errors.Is(err, ErrDuplicateSettlement) {
+		t.Fatalf("Expected ErrDuplicateSettlement for a replayed request, got %v", err)
+	}
+	if s.settlements["p1"] != state || string(state.Secret) != "secret" {
+		t.Error("Existing settlement should be left untouched")
+	}
+}
+
+func TestReplayedRequestDoesNotAnnounceRejection(t *testing.T) {
+	s := newTestService()
+	s.registerChain(&mockChain{name: "ztarknet"})
+
+	now := time.Now()
+	req := validSettlementRequest(now)
+	state, err := s.initSettlement(&req, []byte(req.Secret), "hash")
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+
+	ob, err := openOutbox(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to open outbox: %v", err)
+	}
+	s.outbox = ob
+
+	// The live settlement's own request, replayed
+	data, err := json.Marshal(req)
+	if err != nil {
+		t.Fatal(err)
+	}
+	s.handleSettlementRequest(&nats.Msg{Subject: "settlement.request." + req.ProposalID, Data: data})
+
+	var sent []publishedMessage
+	if _, err := ob.flush(recordingPublisher(&sent)); err != nil {
+		t.Fatalf("Failed to flush: %v", err)
+	}
+	if len(sent) != 0 {
+		t.Errorf("Replayed request published %v", sent)
+	}
+	if s.settlements[req.ProposalID] != state || state.HashHex != "hash" {
+		t.Error("Live settlement should be left untouched")
+	}
+}
@@ -0,0 +1,190 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"net/url"
+	"strings"
+	"time"
+)
+
+// ErrInvalidConfig is returned when the settlement configuration fails validation at startup
+var ErrInvalidConfig = errors.New("invalid settlement config")
+
+// DefaultSwapTimeout is how long the ZEC leg stays locked before it becomes refundable
+const DefaultSwapTimeout = MakerTimelockBlocks * ZcashBlockInterval
+
+// defaultSettlementChains are the stablecoin chains relayed to when SETTLEMENT_CHAINS is unset
+var defaultSettlementChains = []string{"ztarknet", "starknet", "solana"}
+
+// ZcashRPCConfig is how the service reaches its Zcash node
+type ZcashRPCConfig struct {
+	URL      string
+	User     string
+	Password string
+}
+
+// ChainConfig configures one stablecoin settlement chain
+type ChainConfig struct {
+	RPCURL string // Endpoint passed to wallets with each instruction ("" = the wallet's own)
+}
+
+// SettlementConfig is the settlement service's configuration, read from the environment by
+// loadConfig and checked by Validate before the service starts
+type SettlementConfig struct {
+	NATSURL       string
+	Zcash         ZcashRPCConfig
+	Chains        map[string]ChainConfig // Stablecoin chains by settlement_chain name
+	Confirmations ConfirmationPolicy     // Confirmations each chain's locks need before the secret is revealed
+	SwapTimeout   time.Duration          // How long the ZEC leg stays locked; the stablecoin leg expires MinSwapTimelockGap earlier
+	RevealWindow  time.Duration          // How long a revealed secret stays live before the swap is abandoned
+	ClaimGrace    time.Duration          // Least time the ZEC leg must have left when the secret is revealed
+	Mode          SettlementMode
+	OutboxDir     string // Durable outbox directory ("" publishes directly)
+	StateDir      string // Settlement snapshot directory ("" keeps settlements in memory only)
+	Stream        string // JetStream stream ("" = core subscriptions)
+	LogLevel      string
+}
+
+// loadConfig reads the configuration from environment variables, applying defaults for unset ones
+func loadConfig(getenv func(string) string) (*SettlementConfig, error) {
+	withDefault := func(key, fallback string) string {
+		if v := getenv(key); v != "" {
+			return v
+		}
+		return fallback
+	}
+	duration := func(key string, fallback time.Duration) (time.Duration, error) {
+		v := getenv(key)
+		if v == "" {
+			return fallback, nil
+		}
+		d, err := time.ParseDuration(v)
+		if err != nil {
+			return 0, fmt.Errorf("invalid %s %q: %w", key, v, err)
+		}
+		return d, nil
+	}
+
+	cfg := &SettlementConfig{
+		NATSURL: withDefault("NATS_URL", "nats://localhost:4222"),
+		Zcash: ZcashRPCConfig{
+			URL:      withDefault("ZCASH_RPC_URL", "http://localhost:18232"),
+			User:     withDefault("ZCASH_RPC_USER", "blacktrace"),
+			Password: withDefault("ZCASH_RPC_PASSWORD", "regtest123"),
+		},
+		Chains:    make(map[string]ChainConfig),
+		OutboxDir: getenv("OUTBOX_DIR"),
+		StateDir:  getenv("STATE_DIR"),
+		Stream:    getenv("JETSTREAM_STREAM"),
+		LogLevel:  getenv("SETTLEMENT_LOG_LEVEL"),
+	}
+
+	chains := defaultSettlementChains
+	if v := getenv("SETTLEMENT_CHAINS"); v != "" {
+		chains = strings.Split(v, ",")
+	}
+	for _, name := range chains {
+		if name = strings.TrimSpace(name); name != "" {
+			cfg.Chains[name] = ChainConfig{}
+		}
+	}
+
+	// "chain=url,chain=url", like CONFIRMATIONS
+	for _, entry := range strings.Split(getenv("CHAIN_RPC_URLS"), ",") {
+		entry = strings.TrimSpace(entry)
+		if entry == "" {
+			continue
+		}
+		name, endpoint, ok := strings.Cut(entry, "=")
+		if !ok {
+			return nil, fmt.Errorf("invalid CHAIN_RPC_URLS entry %q (expected chain=url)", entry)
+		}
+		name = strings.TrimSpace(name)
+		chain, known := cfg.Chains[name]
+		if !known {
+			return nil, fmt.Errorf("CHAIN_RPC_URLS names %q, which is not in SETTLEMENT_CHAINS", name)
+		}
+		chain.RPCURL = strings.TrimSpace(endpoint)
+		cfg.Chains[name] = chain
+	}
+
+	var err error
+	if cfg.Confirmations, err = parseConfirmationPolicy(getenv("CONFIRMATIONS")); err != nil {
+		return nil, fmt.Errorf("invalid CONFIRMATIONS: %w", err)
+	}
+	if cfg.SwapTimeout, err = duration("SWAP_TIMEOUT", DefaultSwapTimeout); err != nil {
+		return nil, err
+	}
+	if cfg.RevealWindow, err = duration("SECRET_REVEAL_WINDOW", DefaultRevealWindow); err != nil {
+		return nil, err
+	}
+	if cfg.ClaimGrace, err = duration("CLAIM_GRACE", DefaultClaimGrace); err != nil {
+		return nil, err
+	}
+	if cfg.Mode, err = parseSettlementMode(getenv("SETTLEMENT_MODE")); err != nil {
+		return nil, fmt.Errorf("invalid SETTLEMENT_MODE: %w", err)
+	}
+	return cfg, nil
+}
+
+// validateEndpoint checks that an endpoint is an absolute URL
+func validateEndpoint(name, endpoint string) error {
+	u, err := url.Parse(endpoint)
+	if err != nil || u.Scheme == "" || u.Host == "" {
+		return fmt.Errorf("%w: %s %q is not an absolute URL", ErrInvalidConfig, name, endpoint)
+	}
+	return nil
+}
+
+// Validate checks the configuration is usable and the swap timing is safe
+func (c *SettlementConfig) Validate() error {
+	if err := validateEndpoint("NATS URL", c.NATSURL); err != nil {
+		return err
+	}
+	if err := validateEndpoint("Zcash RPC URL", c.Zcash.URL); err != nil {
+		return err
+	}
+
+	if len(c.Chains) == 0 {
+		return fmt.Errorf("%w: no settlement chains configured", ErrInvalidConfig)
+	}
+	for name, chain := range c.Chains {
+		if chain.RPCURL == "" {
+			continue
+		}
+		if err := validateEndpoint(name+" RPC URL", chain.RPCURL); err != nil {
+			return err
+		}
+	}
+
+	// The stablecoin leg gets what is left of the swap timeout after the safety gap
+	if minTimeout := MinSwapTimelockGap + MinTimelockBlocks*ZcashBlockInterval; c.SwapTimeout < minTimeout {
+		return fmt.Errorf("%w: swap timeout %s is below %s (%s safety gap plus a %d-block stablecoin leg)",
+			ErrInvalidConfig, c.SwapTimeout, minTimeout, MinSwapTimelockGap, MinTimelockBlocks)
+	}
+	if c.RevealWindow <= 0 {
+		return fmt.Errorf("%w: reveal window must be positive, got %s", ErrInvalidConfig, c.RevealWindow)
+	}
+	if c.ClaimGrace <= 0 || c.ClaimGrace >= c.SwapTimeout {
+		return fmt.Errorf("%w: claim grace %s must be positive and shorter than the swap timeout %s",
+			ErrInvalidConfig, c.ClaimGrace, c.SwapTimeout)
+	}
+	return nil
+}
+
+// swapTimelockBlocks is the ZEC leg's timelock in blocks: the swap timeout rounded up to whole blocks
+func swapTimelockBlocks(timeout time.Duration) int64 {
+	return int64((timeout + ZcashBlockInterval - 1) / ZcashBlockInterval)
+}
+
+// zecLegTimelock returns the ZEC leg's locktime height and the estimated wall-clock time it
+// becomes refundable, for a lock made at blockHeight and now
+func (s *SettlementService) zecLegTimelock(blockHeight int64, now time.Time) (uint32, time.Time) {
+	timeout := s.swapTimeout
+	if timeout == 0 {
+		timeout = DefaultSwapTimeout
+	}
+	blocks := swapTimelockBlocks(timeout)
+	return uint32(blockHeight + blocks), now.Add(time.Duration(blocks) * ZcashBlockInterval)
+}
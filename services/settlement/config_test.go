@@ -0,0 +1,144 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+)
+
+// envOf serves environment lookups from a map
+func envOf(vars map[string]string) func(string) string {
+	return func(key string) string { return vars[key] }
+}
+
+func TestLoadConfigDefaultsAndOverrides(t *testing.T) {
+	cfg, err := loadConfig(envOf(nil))
+	if err != nil {
+		t.Fatalf("Failed to load defaults: %v", err)
+	}
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Defaults should validate: %v", err)
+	}
+	if cfg.SwapTimeout != DefaultSwapTimeout || cfg.RevealWindow != DefaultRevealWindow || cfg.Mode != ModeCustodial {
+		t.Errorf("Unexpected defaults: %+v", cfg)
+	}
+	if len(cfg.Chains) != len(defaultSettlementChains) {
+		t.Errorf("Expected the default chains, got %v", cfg.Chains)
+	}
+
+	cfg, err = loadConfig(envOf(map[string]string{
+		"NATS_URL":             "nats://nats:4222",
+		"SETTLEMENT_CHAINS":    "solana, starknet",
+		"CHAIN_RPC_URLS":       "solana=http://solana:8899",
+		"CONFIRMATIONS":        "zcash=3",
+		"SWAP_TIMEOUT":         "36h",
+		"SECRET_REVEAL_WINDOW": "30m",
+		"CLAIM_GRACE":          "1h",
+		"SETTLEMENT_MODE":      "non-custodial",
+		"JETSTREAM_STREAM":     "SETTLEMENT",
+	}))
+	if err != nil {
+		t.Fatalf("Failed to load config: %v", err)
+	}
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Config should validate: %v", err)
+	}
+	if cfg.NATSURL != "nats://nats:4222" || cfg.Stream != "SETTLEMENT" || cfg.Mode != ModeNonCustodial {
+		t.Errorf("Unexpected config: %+v", cfg)
+	}
+	if len(cfg.Chains) != 2 || cfg.Chains["solana"].RPCURL != "http://solana:8899" || cfg.Chains["starknet"].RPCURL != "" {
+		t.Errorf("Unexpected chains: %+v", cfg.Chains)
+	}
+	if cfg.Confirmations.Required("zcash") != 3 {
+		t.Errorf("Expected 3 zcash confirmations, got %d", cfg.Confirmations.Required("zcash"))
+	}
+	if cfg.SwapTimeout != 36*time.Hour || cfg.ClaimGrace != time.Hour || cfg.RevealWindow != 30*time.Minute {
+		t.Errorf("Unexpected timeouts: %s, %s, %s", cfg.SwapTimeout, cfg.ClaimGrace, cfg.RevealWindow)
+	}
+}
+
+func TestLoadConfigRejectsMalformedValues(t *testing.T) {
+	cases := map[string]map[string]string{
+		"bad duration":        {"SWAP_TIMEOUT": "soon"},
+		"bad confirmations":   {"CONFIRMATIONS": "zcash=0"},
+		"bad mode":            {"SETTLEMENT_MODE": "trusted"},
+		"malformed rpc entry": {"CHAIN_RPC_URLS": "solana"},
+		"rpc for unknown":     {"SETTLEMENT_CHAINS": "solana", "CHAIN_RPC_URLS": "starknet=http://starknet:5050"},
+	}
+	for name, vars := range cases {
+		if _, err := loadConfig(envOf(vars)); err == nil {
+			t.Errorf("%s: expected an error", name)
+		}
+	}
+}
+
+func TestConfigValidationFailures(t *testing.T) {
+	cases := map[string]func(*SettlementConfig){
+		"relative NATS URL":         func(c *SettlementConfig) { c.NATSURL = "localhost:4222" },
+		"missing Zcash URL":         func(c *SettlementConfig) { c.Zcash.URL = "" },
+		"no chains":                 func(c *SettlementConfig) { c.Chains = map[string]ChainConfig{} },
+		"bad chain endpoint":        func(c *SettlementConfig) { c.Chains["solana"] = ChainConfig{RPCURL: "solana:8899"} },
+		"short swap timeout":        func(c *SettlementConfig) { c.SwapTimeout = MinSwapTimelockGap },
+		"zero reveal window":        func(c *SettlementConfig) { c.RevealWindow = 0 },
+		"grace beyond swap timeout": func(c *SettlementConfig) { c.ClaimGrace = c.SwapTimeout },
+	}
+	for name, mutate := range cases {
+		cfg, err := loadConfig(envOf(nil))
+		if err != nil {
+			t.Fatalf("Failed to load defaults: %v", err)
+		}
+		mutate(cfg)
+		if err := cfg.Validate(); !errors.Is(err, ErrInvalidConfig) {
+			t.Errorf("%s: expected ErrInvalidConfig, got %v", name, err)
+		}
+	}
+}
+
+func TestConfiguredSwapTimeoutSetsDeadlines(t *testing.T) {
+	cfg, err := loadConfig(envOf(map[string]string{"SWAP_TIMEOUT": "30h"}))
+	if err != nil {
+		t.Fatalf("Failed to load config: %v", err)
+	}
+	s := newTestService()
+	s.swapTimeout = cfg.SwapTimeout
+	chain := &mockChain{name: "starknet"}
+	s.registerChain(chain)
+
+	now := time.Now()
+	locktime, zecTimeout := s.zecLegTimelock(1000, now)
+	if locktime != 1000+180 {
+		t.Errorf("Expected a 180-block locktime for 30h, got height %d", locktime)
+	}
+	if !zecTimeout.Equal(now.Add(30 * time.Hour)) {
+		t.Errorf("Expected the ZEC leg to expire 30h from now, got %s", zecTimeout.Sub(now))
+	}
+
+	// The stablecoin leg's deadline follows from the configured timeout
+	state := &SettlementState{ProposalID: "p1", Chain: "starknet", ZECTimeout: zecTimeout}
+	if err := s.lockStablecoinLeg(state); err != nil {
+		t.Fatalf("Failed to lock stablecoin leg: %v", err)
+	}
+	if want := zecTimeout.Add(-MinSwapTimelockGap); !state.StablecoinTimeout.Equal(want) {
+		t.Errorf("Expected the stablecoin leg to expire at %s, got %s", want, state.StablecoinTimeout)
+	}
+}
+
+func TestRelayChainSendsConfiguredEndpoint(t *testing.T) {
+	var published []byte
+	chain := &relayChain{name: "solana", rpcURL: "http://solana:8899", publish: func(_ string, data []byte) error {
+		published = data
+		return nil
+	}}
+	if err := chain.Claim(&SettlementState{ProposalID: "p1"}); err != nil {
+		t.Fatalf("Failed to instruct claim: %v", err)
+	}
+
+	var instruction map[string]interface{}
+	if err := json.Unmarshal(published, &instruction); err != nil {
+		t.Fatalf("Failed to decode instruction: %v", err)
+	}
+	if instruction["rpc_url"] != "http://solana:8899" {
+		t.Errorf("Expected the configured endpoint in the instruction, got %v", instruction["rpc_url"])
+	}
+}
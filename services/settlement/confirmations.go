@@ -0,0 +1,83 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"strconv"
+	"strings"
+)
+
+// ErrInsufficientConfirmations is returned when a lock is not yet final under the confirmation policy
+var ErrInsufficientConfirmations = errors.New("insufficient confirmations")
+
+// defaultRequiredConfirmations applies to chains missing from the policy
+const defaultRequiredConfirmations = 6
+
+// ConfirmationPolicy maps a chain name to the confirmations its locks need before they are treated as final
+type ConfirmationPolicy map[string]uint64
+
+// DefaultConfirmationPolicy reflects each chain's finality: Zcash needs several blocks,
+// while Starknet and Solana locks are final once accepted/finalized
+func DefaultConfirmationPolicy() ConfirmationPolicy {
+	return ConfirmationPolicy{
+		"zcash":    6,
+		"ztarknet": 1,
+		"starknet": 1,
+		"solana":   1,
+	}
+}
+
+// parseConfirmationPolicy applies "chain=n,chain=n" overrides (CONFIRMATIONS env) to the defaults
+func parseConfirmationPolicy(spec string) (ConfirmationPolicy, error) {
+	policy := DefaultConfirmationPolicy()
+	for _, entry := range strings.Split(spec, ",") {
+		entry = strings.TrimSpace(entry)
+		if entry == "" {
+			continue
+		}
+		chain, value, ok := strings.Cut(entry, "=")
+		if !ok {
+			return nil, fmt.Errorf("invalid confirmation policy entry %q (expected chain=n)", entry)
+		}
+		n, err := strconv.ParseUint(strings.TrimSpace(value), 10, 64)
+		if err != nil || n == 0 {
+			return nil, fmt.Errorf("invalid confirmations for %s: %q", chain, value)
+		}
+		policy[strings.TrimSpace(chain)] = n
+	}
+	return policy, nil
+}
+
+// Required returns the confirmations a chain's locks need
+func (p ConfirmationPolicy) Required(chain string) uint64 {
+	if n, ok := p[chain]; ok {
+		return n
+	}
+	return defaultRequiredConfirmations
+}
+
+// Check returns ErrInsufficientConfirmations if a lock on chain has fewer confirmations than required
+func (p ConfirmationPolicy) Check(chain string, confirmations uint64) error {
+	if required := p.Required(chain); confirmations < required {
+		return fmt.Errorf("%w: %s lock has %d of %d", ErrInsufficientConfirmations, chain, confirmations, required)
+	}
+	return nil
+}
+
+// checkZECLockFinal checks the Zcash HTLC lock against the confirmation policy. Caller must hold s.mu.
+func (s *SettlementService) checkZECLockFinal(state *SettlementState) error {
+	if state.HTLCLockTxID == "" {
+		return fmt.Errorf("no Zcash lock transaction for %s", state.ProposalID)
+	}
+
+	tx, err := s.zcashClient.GetTransaction(state.HTLCLockTxID)
+	if err != nil {
+		return fmt.Errorf("failed to fetch Zcash lock transaction: %w", err)
+	}
+
+	confirmations, _ := tx["confirmations"].(float64)
+	if confirmations < 0 {
+		confirmations = 0
+	}
+	return s.confirmations.Check("zcash", uint64(confirmations))
+}
@@ -0,0 +1,43 @@
+package main
+
+import (
+	"errors"
+	"testing"
+)
+
+func TestConfirmationPolicyPerChain(t *testing.T) {
+	policy, err := parseConfirmationPolicy("zcash=10,solana=2")
+	if err != nil {
+		t.Fatalf("Failed to parse policy: %v", err)
+	}
+
+	if policy.Required("solana") >= policy.Required("zcash") {
+		t.Fatalf("Solana should need fewer confirmations than Zcash: solana=%d zcash=%d",
+			policy.Required("solana"), policy.Required("zcash"))
+	}
+
+	// Three confirmations finalise a Solana lock but not a Zcash one
+	if err := policy.Check("solana", 3); err != nil {
+		t.Errorf("Solana lock with 3 confirmations should be final: %v", err)
+	}
+	if err := policy.Check("zcash", 3); !errors.Is(err, ErrInsufficientConfirmations) {
+		t.Errorf("Expected ErrInsufficientConfirmations for Zcash, got %v", err)
+	}
+}
+
+func TestConfirmationPolicyDefaults(t *testing.T) {
+	policy, err := parseConfirmationPolicy("")
+	if err != nil {
+		t.Fatalf("Failed to parse empty policy: %v", err)
+	}
+	if policy.Required("starknet") != 1 || policy.Required("zcash") != 6 {
+		t.Errorf("Unexpected defaults: %v", policy)
+	}
+	if policy.Required("unknown-chain") != defaultRequiredConfirmations {
+		t.Errorf("Unknown chains should use the default of %d", defaultRequiredConfirmations)
+	}
+
+	if _, err := parseConfirmationPolicy("zcash"); err == nil {
+		t.Error("Expected an error for an entry without a count")
+	}
+}
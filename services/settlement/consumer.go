@@ -0,0 +1,138 @@
+package main
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"log"
+	"time"
+
+	"github.com/nats-io/nats.go"
+)
+
+// JetStream pull consumer tuning
+const (
+	pullBatch       = 16               // Messages fetched per pull
+	pullMaxWait     = 5 * time.Second  // How long a pull waits for messages
+	redeliveryDelay = 15 * time.Second // Wait before a retryable failure is delivered again
+	maxDeliveries   = 20               // Deliveries before the server gives up on a message
+)
+
+// errRetry marks a handling failure that may succeed if the message is delivered again later,
+// such as a lock the chain does not show yet
+var errRetry = errors.New("retry later")
+
+// retryable marks err as worth redelivering the message for
+func retryable(err error) error {
+	return fmt.Errorf("%w: %w", errRetry, err)
+}
+
+// ackable is a delivered message that must be acknowledged (a JetStream *nats.Msg)
+type ackable interface {
+	Ack(opts ...nats.AckOpt) error
+	NakWithDelay(delay time.Duration, opts ...nats.AckOpt) error
+	Term(opts ...nats.AckOpt) error
+}
+
+// delivery is one message from a durable consumer: what the handler sees, and how it is acknowledged
+type delivery struct {
+	msg *nats.Msg
+	ack ackable
+}
+
+// settleDelivery acknowledges a message after its handler returned. A handled message is acked;
+// a retryable failure is nak'd so the server delivers it again; any other failure is terminated,
+// since delivering it again would fail the same way.
+func settleDelivery(msg ackable, err error) error {
+	switch {
+	case err == nil:
+		return msg.Ack()
+	case errors.Is(err, errRetry):
+		return msg.NakWithDelay(redeliveryDelay)
+	default:
+		return msg.Term()
+	}
+}
+
+// handleBatch hands each delivery to handle and only then acknowledges it. Handlers return once
+// the settlement state is updated and saved to the state directory and any messages they publish
+// are recorded in the outbox, so a crash before the ack leaves the message unacknowledged and the
+// server redelivers it. Without a state directory settlements live in memory only: a restart
+// loses those whose messages were already acked, and their later status updates are retried
+// until the server gives up after maxDeliveries.
+func handleBatch(batch []delivery, handle func(*nats.Msg) error) {
+	for _, d := range batch {
+		err := handle(d.msg)
+		if ackErr := settleDelivery(d.ack, err); ackErr != nil {
+			log.Printf("Warning: Failed to acknowledge %s: %v", d.msg.Subject, ackErr)
+		}
+	}
+}
+
+// consume pulls from a durable JetStream consumer until the subscription or connection closes
+func consume(sub *nats.Subscription, handle func(*nats.Msg) error) {
+	for {
+		msgs, err := sub.Fetch(pullBatch, nats.MaxWait(pullMaxWait))
+		switch {
+		case errors.Is(err, nats.ErrTimeout), errors.Is(err, context.DeadlineExceeded):
+			continue
+		case errors.Is(err, nats.ErrConnectionClosed), errors.Is(err, nats.ErrBadSubscription):
+			log.Printf("Consumer on %s stopped: %v", sub.Subject, err)
+			return
+		case err != nil:
+			log.Printf("Warning: Pull from %s failed: %v", sub.Subject, err)
+			time.Sleep(time.Second)
+			continue
+		}
+
+		batch := make([]delivery, 0, len(msgs))
+		for _, msg := range msgs {
+			// A JetStream message's reply subject is its ack subject, not a requester to answer;
+			// requesters hear about rejections on settlement.rejected.<proposal_id> instead
+			batch = append(batch, delivery{msg: &nats.Msg{Subject: msg.Subject, Header: msg.Header, Data: msg.Data}, ack: msg})
+		}
+		handleBatch(batch, handle)
+	}
+}
+
+// startJetStream consumes settlement requests and status updates through durable pull consumers
+// on s.stream, creating the stream if it does not exist yet
+func (s *SettlementService) startJetStream() error {
+	js, err := s.nc.JetStream()
+	if err != nil {
+		return fmt.Errorf("failed to open JetStream: %w", err)
+	}
+
+	if _, err := js.StreamInfo(s.stream); errors.Is(err, nats.ErrStreamNotFound) {
+		_, err = js.AddStream(&nats.StreamConfig{
+			Name:     s.stream,
+			Subjects: []string{"settlement.request.*", "settlement.status.*"},
+		})
+		if err != nil {
+			return fmt.Errorf("failed to create stream %s: %w", s.stream, err)
+		}
+	} else if err != nil {
+		return fmt.Errorf("failed to look up stream %s: %w", s.stream, err)
+	}
+
+	consumers := []struct {
+		subject string
+		durable string
+		handle  func(*nats.Msg) error
+	}{
+		{"settlement.request.*", "settlement-requests", func(msg *nats.Msg) error {
+			s.handleSettlementRequest(msg) // Rejections are replies, not failures
+			return nil
+		}},
+		{"settlement.status.*", "settlement-status", s.handleStatusUpdate},
+	}
+	for _, c := range consumers {
+		sub, err := js.PullSubscribe(c.subject, c.durable,
+			nats.BindStream(s.stream), nats.AckExplicit(), nats.MaxDeliver(maxDeliveries))
+		if err != nil {
+			return fmt.Errorf("failed to subscribe to %s: %w", c.subject, err)
+		}
+		go consume(sub, c.handle)
+	}
+	return nil
+}
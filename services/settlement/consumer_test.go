@@ -0,0 +1,188 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"io"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+	"github.com/nats-io/nats.go"
+)
+
+// mockQueue stands in for a JetStream consumer: a nak'd message goes back on the queue
+type mockQueue struct {
+	pending     []*nats.Msg
+	acked       int
+	redelivered int
+	terminated  int
+}
+
+type mockDelivery struct {
+	queue *mockQueue
+	msg   *nats.Msg
+}
+
+func (d *mockDelivery) Ack(...nats.AckOpt) error {
+	d.queue.acked++
+	return nil
+}
+
+func (d *mockDelivery) NakWithDelay(time.Duration, ...nats.AckOpt) error {
+	d.queue.redelivered++
+	d.queue.pending = append(d.queue.pending, d.msg)
+	return nil
+}
+
+func (d *mockDelivery) Term(...nats.AckOpt) error {
+	d.queue.terminated++
+	return nil
+}
+
+// drain delivers until nothing is pending, the way consume pulls batches
+func (q *mockQueue) drain(handle func(*nats.Msg) error) {
+	for len(q.pending) > 0 {
+		batch := make([]delivery, 0, len(q.pending))
+		for _, msg := range q.pending {
+			batch = append(batch, delivery{msg: msg, ack: &mockDelivery{queue: q, msg: msg}})
+		}
+		q.pending = nil
+		handleBatch(batch, handle)
+	}
+}
+
+func TestRetryableFailureIsRedelivered(t *testing.T) {
+	queue := &mockQueue{pending: []*nats.Msg{{Subject: "settlement.status.p1"}}}
+
+	handled := 0
+	queue.drain(func(*nats.Msg) error {
+		handled++
+		if handled == 1 {
+			return retryable(errors.New("lock not yet confirmed"))
+		}
+		return nil
+	})
+
+	if handled != 2 || queue.redelivered != 1 || queue.acked != 1 || queue.terminated != 0 {
+		t.Errorf("Expected one redelivery then one ack, got handled=%d redelivered=%d acked=%d terminated=%d",
+			handled, queue.redelivered, queue.acked, queue.terminated)
+	}
+}
+
+func TestPermanentFailureIsTerminated(t *testing.T) {
+	queue := &mockQueue{pending: []*nats.Msg{{Subject: "settlement.status.p1"}}}
+
+	handled := 0
+	queue.drain(func(*nats.Msg) error {
+		handled++
+		return errors.New("order does not match settlement order")
+	})
+
+	if handled != 1 || queue.redelivered != 0 || queue.acked != 0 || queue.terminated != 1 {
+		t.Errorf("Expected the message to be terminated without redelivery, got handled=%d redelivered=%d acked=%d terminated=%d",
+			handled, queue.redelivered, queue.acked, queue.terminated)
+	}
+}
+
+// pendingChain reports its locks as not yet confirmed
+type pendingChain struct {
+	mockChain
+}
+
+func (c *pendingChain) Confirm(state *SettlementState) (bool, error) {
+	c.calls = append(c.calls, "confirm:"+state.ProposalID)
+	return false, nil
+}
+
+func TestStatusUpdateErrorsSayWhetherToRetry(t *testing.T) {
+	s := newTestService()
+	s.registerChain(&pendingChain{mockChain{name: "starknet"}})
+
+	req := &SettlementRequest{ProposalID: "p1", OrderID: "order_1", ZECZatoshi: 100, Price: 2, SettlementChain: "starknet"}
+	state, err := s.initSettlement(req, []byte("secret"), "hash")
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+	state.Swap = swap.Restore(swap.FunderLocked)
+
+	handle := func(update SettlementStatusUpdate) error {
+		update.Version = SchemaVersionCurrent
+		data, _ := json.Marshal(update)
+		return s.handleStatusUpdate(&nats.Msg{Data: data})
+	}
+
+	// A lock the chain does not show yet may be confirmed by the time the update is redelivered
+	err = handle(SettlementStatusUpdate{ProposalID: "p1", OrderID: "order_1", Action: "bob_lock_usdc", USDCMinor: 200})
+	if !errors.Is(err, errRetry) {
+		t.Errorf("Unconfirmed lock should be retryable, got %v", err)
+	}
+	if state.USDCLocked {
+		t.Error("Unconfirmed lock should not be recorded")
+	}
+
+	// A status update can be consumed before the request that creates its settlement
+	err = handle(SettlementStatusUpdate{ProposalID: "p2", Action: "bob_lock_usdc", USDCMinor: 200})
+	if !errors.Is(err, errRetry) {
+		t.Errorf("Unknown proposal should be retryable, got %v", err)
+	}
+
+	for name, update := range map[string]SettlementStatusUpdate{
+		"wrong order":  {ProposalID: "p1", OrderID: "order_2", Action: "bob_lock_usdc", USDCMinor: 200},
+		"wrong amount": {ProposalID: "p1", OrderID: "order_1", Action: "bob_lock_usdc", USDCMinor: 201},
+	} {
+		if err := handle(update); err == nil || errors.Is(err, errRetry) {
+			t.Errorf("%s: expected a permanent rejection, got %v", name, err)
+		}
+	}
+}
+
+func TestRedeliveredZECLockNeverBroadcastsTwice(t *testing.T) {
+	dir := t.TempDir()
+	before := newTestService()
+	before.registerChain(&mockChain{name: "starknet"})
+	var err error
+	if before.states, err = openStateStore(dir); err != nil {
+		t.Fatalf("Failed to open state store: %v", err)
+	}
+
+	req := &SettlementRequest{ProposalID: "p1", OrderID: "order_1", ZECZatoshi: 100, Price: 2, SettlementChain: "starknet"}
+	state, err := before.initSettlement(req, []byte("secret"), "hash")
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+	// The service stopped after recording the lock intent, before the broadcast result was saved
+	state.HTLCP2SHAddress = "t2htlc"
+	state.ZECLockIntent = time.Now()
+	if err := before.persistState(state); err != nil {
+		t.Fatalf("Failed to save state: %v", err)
+	}
+
+	// After the restart the service has no Zcash client: broadcasting would panic
+	s := newTestService()
+	s.events = newEventLogger(io.Discard, "info")
+	s.registerChain(&mockChain{name: "starknet"})
+	s.states = before.states
+	observer := &mockObserver{}
+	s.zecLeg = observer
+	s.restoreStates()
+
+	update := SettlementStatusUpdate{
+		Version: SchemaVersionCurrent, ProposalID: "p1", OrderID: "order_1", Action: "alice_lock_zec", ZECZatoshi: 100,
+		ZcashAddress: "tmAlice", AlicePubKeyHash: strings.Repeat("aa", 20), BobPubKeyHash: strings.Repeat("bb", 20),
+	}
+	data, _ := json.Marshal(update)
+
+	if err := s.handleStatusUpdate(&nats.Msg{Data: data}); !errors.Is(err, errRetry) {
+		t.Fatalf("Lock not yet visible on-chain should be retried, got %v", err)
+	}
+
+	observer.obs.Locked = true
+	if err := s.handleStatusUpdate(&nats.Msg{Data: data}); err != nil {
+		t.Fatalf("Redelivered lock rejected: %v", err)
+	}
+	if restored := s.settlements["p1"]; !restored.ZECLocked || restored.Status != "alice_locked" {
+		t.Errorf("Expected the on-chain lock to be adopted, got locked=%v status=%s", restored.ZECLocked, restored.Status)
+	}
+}
@@ -0,0 +1,81 @@
+package main
+
+import (
+	"fmt"
+	"io"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+)
+
+// Log levels for settlement events (SETTLEMENT_LOG_LEVEL)
+const (
+	logLevelInfo  = "info"  // One structured line per event
+	logLevelDebug = "debug" // Full multi-line banner per event
+)
+
+// logField is one key=value pair of a compact event line
+type logField struct {
+	Key   string
+	Value interface{}
+}
+
+// eventLogger writes settlement events either as compact structured lines or as banners
+type eventLogger struct {
+	mu    sync.Mutex
+	out   io.Writer
+	debug bool
+}
+
+// newEventLogger returns a logger for the given level; anything other than "debug" logs compactly
+func newEventLogger(out io.Writer, level string) *eventLogger {
+	return &eventLogger{
+		out:   out,
+		debug: strings.EqualFold(strings.TrimSpace(level), logLevelDebug),
+	}
+}
+
+// truncateID shortens an identifier for display to at most max characters, followed by "..."
+// when anything was cut. It counts and cuts on rune boundaries, so an ID with multi-byte
+// characters is never split mid-character, and an ID shorter than max is returned whole.
+func truncateID(id string, max int) string {
+	n := 0
+	for i := range id {
+		if n == max {
+			return id[:i] + "..."
+		}
+		n++
+	}
+	return id
+}
+
+// event logs a settlement event. At info level only the fields are written, on one line;
+// at debug level the banner is written instead.
+func (l *eventLogger) event(name string, fields []logField, banner func(w io.Writer)) {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	if l.debug {
+		banner(l.out)
+		return
+	}
+
+	var line strings.Builder
+	line.WriteString(time.Now().UTC().Format(time.RFC3339))
+	line.WriteString(" event=")
+	line.WriteString(name)
+	for _, f := range fields {
+		value := fmt.Sprint(f.Value)
+		if value == "" || strings.ContainsAny(value, " \t\n\"=") {
+			value = strconv.Quote(value)
+		}
+		line.WriteString(" ")
+		line.WriteString(f.Key)
+		line.WriteString("=")
+		line.WriteString(value)
+	}
+	line.WriteString("\n")
+
+	io.WriteString(l.out, line.String())
+}
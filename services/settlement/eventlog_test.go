@@ -0,0 +1,77 @@
+package main
+
+import (
+	"bytes"
+	"fmt"
+	"io"
+	"strings"
+	"testing"
+	"unicode/utf8"
+)
+
+func logTestEvent(l *eventLogger) {
+	l.event("settlement_request", []logField{
+		{"proposal_id", "order_1_proposal_1"},
+		{"maker", "maker peer"},
+	}, func(w io.Writer) {
+		fmt.Fprintln(w, "━━━━━━━━━━")
+		fmt.Fprintln(w, "📩 NEW SETTLEMENT REQUEST")
+		fmt.Fprintln(w, "━━━━━━━━━━")
+	})
+}
+
+func TestEventLoggerCompactAtInfo(t *testing.T) {
+	var out bytes.Buffer
+	logTestEvent(newEventLogger(&out, "info"))
+
+	got := out.String()
+	if strings.Count(got, "\n") != 1 {
+		t.Fatalf("Expected a single line at info level, got %q", got)
+	}
+	if !strings.Contains(got, `event=settlement_request proposal_id=order_1_proposal_1 maker="maker peer"`) {
+		t.Errorf("Unexpected compact line: %q", got)
+	}
+	if strings.Contains(got, "NEW SETTLEMENT REQUEST") {
+		t.Error("Banner should not be written at info level")
+	}
+}
+
+func TestEventLoggerBannerAtDebug(t *testing.T) {
+	var out bytes.Buffer
+	logTestEvent(newEventLogger(&out, "DEBUG"))
+
+	got := out.String()
+	if !strings.Contains(got, "📩 NEW SETTLEMENT REQUEST") {
+		t.Errorf("Expected banner at debug level, got %q", got)
+	}
+	if strings.Contains(got, "event=") {
+		t.Error("Compact line should not be written at debug level")
+	}
+}
+
+func TestTruncateIDOnRuneBoundary(t *testing.T) {
+	cases := []struct {
+		id   string
+		max  int
+		want string
+	}{
+		{"12D3KooWAlice", 8, "12D3KooW..."},
+		{"short", 8, "short"},
+		{"exactly8", 8, "exactly8"},
+		{"", 8, ""},
+		// Multi-byte runes whose bytes straddle the cut
+		{"makeré-peer", 6, "makeré..."},
+		{"naïve", 3, "naï..."},
+		{"ab日本語の名前", 3, "ab日..."},
+		{"🦀🦀🦀🦀", 2, "🦀🦀..."},
+	}
+	for _, c := range cases {
+		got := truncateID(c.id, c.max)
+		if got != c.want {
+			t.Errorf("truncateID(%q, %d) = %q, want %q", c.id, c.max, got, c.want)
+		}
+		if !utf8.ValidString(got) {
+			t.Errorf("truncateID(%q, %d) produced invalid UTF-8: %q", c.id, c.max, got)
+		}
+	}
+}
@@ -0,0 +1,51 @@
+package main
+
+import (
+	"log"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+// statusEvents are the lifecycle events node status updates claim. An update whose event the
+// swap's current state does not allow is rejected before it touches either chain.
+var statusEvents = map[string]swap.Event{
+	"alice_lock_zec":   swap.FunderLock,
+	"bob_lock_usdc":    swap.ClaimerLock,
+	"alice_claim_usdc": swap.ClaimerClaim,
+}
+
+// advanceSwap moves the settlement's lifecycle on by one event. An illegal event is logged and
+// leaves the lifecycle where it was. Caller must hold s.mu.
+func (state *SettlementState) advanceSwap(event swap.Event) error {
+	from := state.Swap.State()
+	to, err := state.Swap.Transition(event)
+	if err != nil {
+		log.Printf("Warning: settlement %s: %v", state.ProposalID, err)
+		return err
+	}
+	log.Printf("Settlement %s: %s → %s", state.ProposalID, from, to)
+	return nil
+}
+
+// swapStateForStatus rebuilds the lifecycle state of a settlement restored from its status
+func swapStateForStatus(state *SettlementState) swap.State {
+	switch state.Status {
+	case "ready":
+		return swap.Finalized
+	case "alice_locked":
+		return swap.FunderLocked
+	case "both_locked":
+		// both_locked is only set once the secret (or the reveal instruction) goes out
+		if state.USDCClaimed {
+			return swap.ClaimerClaimed
+		}
+		return swap.Revealed
+	case "refunding":
+		return swap.Refunding
+	case "completed":
+		return swap.Complete
+	case "rejected":
+		return swap.Aborted
+	}
+	return swap.Negotiating
+}
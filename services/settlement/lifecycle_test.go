@@ -0,0 +1,62 @@
+package main
+
+import (
+	"encoding/json"
+	"testing"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+	"github.com/nats-io/nats.go"
+)
+
+func TestStatusUpdatesFollowSwapLifecycle(t *testing.T) {
+	s := newTestService()
+	chain := &mockChain{name: "starknet"}
+	s.registerChain(chain)
+
+	req := &SettlementRequest{ProposalID: "p1", OrderID: "order_1", ZECZatoshi: 100, Price: 2, SettlementChain: "starknet"}
+	state, err := s.initSettlement(req, []byte("secret"), "hash")
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+	if state.Swap.State() != swap.Finalized {
+		t.Fatalf("New settlement should be finalized, got %s", state.Swap.State())
+	}
+
+	sendUpdate := func(update SettlementStatusUpdate) {
+		update.Version = SchemaVersionCurrent
+		data, _ := json.Marshal(update)
+		s.handleStatusUpdate(&nats.Msg{Data: data})
+	}
+
+	// Bob cannot lock, nor Alice claim, before Alice's ZEC leg is locked
+	sendUpdate(SettlementStatusUpdate{ProposalID: "p1", OrderID: "order_1", Action: "bob_lock_usdc", USDCMinor: 200})
+	sendUpdate(SettlementStatusUpdate{ProposalID: "p1", OrderID: "order_1", Action: "alice_claim_usdc", TxID: "claim"})
+	if len(chain.calls) != 0 || state.USDCClaimed || state.StablecoinClaimTx != "" {
+		t.Errorf("Out-of-order updates should be rejected, got calls %v claimed=%v", chain.calls, state.USDCClaimed)
+	}
+	if state.Swap.State() != swap.Finalized {
+		t.Errorf("Rejected updates should not move the lifecycle, got %s", state.Swap.State())
+	}
+}
+
+func TestResumeRestoresSwapLifecycle(t *testing.T) {
+	for status, want := range map[string]swap.State{
+		"ready":        swap.Finalized,
+		"alice_locked": swap.FunderLocked,
+		"both_locked":  swap.Revealed,
+		"refunding":    swap.Refunding,
+		"completed":    swap.Complete,
+	} {
+		s := newTestService()
+		s.registerChain(&mockChain{name: "starknet"})
+
+		state := snapshotState(status)
+		state.ZECLocked = status != "ready"
+		if _, err := s.resume(state); err != nil {
+			t.Fatalf("%s: failed to resume: %v", status, err)
+		}
+		if state.Swap.State() != want {
+			t.Errorf("%s: expected lifecycle %s, got %s", status, want, state.Swap.State())
+		}
+	}
+}
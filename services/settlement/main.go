@@ -5,7 +5,9 @@ import (
 	"crypto/sha256"
 	"encoding/hex"
 	"encoding/json"
+	"errors"
 	"fmt"
+	"io"
 	"log"
 	"net/http"
 	"os"
@@ -13,85 +15,147 @@ import (
 	"time"
 
 	"github.com/blacktrace/blacktrace/connectors/zcash"
+	"github.com/blacktrace/blacktrace/services/swap"
 	"github.com/nats-io/nats.go"
 	"golang.org/x/crypto/ripemd160"
 )
 
 // SettlementRequest represents the initial settlement request message
 type SettlementRequest struct {
+	Version         int       `json:"version,omitempty"` // Schema version; absent means SchemaVersionLegacy
 	ProposalID      string    `json:"proposal_id"`
 	OrderID         string    `json:"order_id"`
 	MakerID         string    `json:"maker_id"`
 	TakerID         string    `json:"taker_id"`
-	Amount          uint64    `json:"amount"`
+	ZECZatoshi      uint64    `json:"zec_zatoshi"`      // ZEC leg amount in zatoshis (1 ZEC = 1e8)
+	LegacyAmount    uint64    `json:"amount,omitempty"` // ZEC amount before SchemaVersionTyped; folded into ZECZatoshi on decode
 	Price           uint64    `json:"price"`
 	Stablecoin      string    `json:"stablecoin"`
 	SettlementChain string    `json:"settlement_chain"`
-	Secret          string    `json:"secret"` // Alice's secret for HTLC
+	Secret          string    `json:"secret"`                // Alice's secret for HTLC (custodial mode)
+	SecretHash      string    `json:"secret_hash,omitempty"` // RIPEMD160(SHA256(secret)) hex (non-custodial mode)
 	Timestamp       time.Time `json:"timestamp"`
 }
 
 // SettlementStatusUpdate represents status updates from the nodes
 type SettlementStatusUpdate struct {
+	Version          int       `json:"version,omitempty"` // Schema version; absent means SchemaVersionLegacy
 	ProposalID       string    `json:"proposal_id"`
 	OrderID          string    `json:"order_id"`
 	SettlementStatus string    `json:"settlement_status"`
 	Action           string    `json:"action"`
-	Amount           uint64    `json:"amount"`
-	AmountUSDC       uint64    `json:"amount_usdc"`
+	ZECZatoshi       uint64    `json:"zec_zatoshi,omitempty"`       // ZEC lock amount in zatoshis (alice_lock_zec)
+	USDCMinor        uint64    `json:"usdc_minor,omitempty"`        // Stablecoin lock amount in its minor units (bob_lock_usdc)
+	LegacyAmount     uint64    `json:"amount,omitempty"`            // ZECZatoshi before SchemaVersionTyped
+	LegacyAmountUSDC uint64    `json:"amount_usdc,omitempty"`       // USDCMinor before SchemaVersionTyped
 	ZcashAddress     string    `json:"zcash_address,omitempty"`     // User's personal Zcash address
 	Username         string    `json:"username,omitempty"`          // Username for wallet lookup
 	Secret           string    `json:"secret,omitempty"`            // Alice's secret for HTLC (provided by user)
 	AlicePubKeyHash  string    `json:"alice_pubkey_hash,omitempty"` // Alice's pubkey hash (hex) for HTLC claim
 	BobPubKeyHash    string    `json:"bob_pubkey_hash,omitempty"`   // Bob's pubkey hash (hex) for HTLC refund
+	TxID             string    `json:"tx_id,omitempty"`             // Stablecoin lock/claim transaction reported by the wallet
 	Timestamp        time.Time `json:"timestamp"`
 }
 
 // SettlementState tracks the state of a settlement
 type SettlementState struct {
-	ProposalID      string
-	OrderID         string
-	MakerID         string
-	TakerID         string
-	AmountZEC       uint64
-	AmountUSDC      uint64
-	Secret          []byte
-	HashHex         string
-	Status          string
-	ZECLocked       bool
-	USDCLocked      bool
-	HTLCScript      []byte // The HTLC Bitcoin Script
-	HTLCP2SHAddress string // The P2SH address for the HTLC
-	HTLCLockTxID    string // Transaction ID that locked funds to HTLC
-	HTLCLocktime    uint32 // Locktime for refund (24 hours from now in block height)
-	AlicePubKeyHash []byte // Alice's pubkey hash for HTLC claim (Bob provides secret + sig to claim)
-	BobPubKeyHash   []byte // Bob's pubkey hash for HTLC refund (after timeout)
-	CreatedAt       time.Time
-	UpdatedAt       time.Time
+	ProposalID        string
+	OrderID           string
+	MakerID           string
+	TakerID           string
+	AmountZEC         uint64
+	AmountUSDC        uint64
+	Stablecoin        string // Stablecoin the taker pays with (e.g. USDC)
+	Price             uint64 // Agreed price per ZEC
+	Secret            []byte
+	HashHex           string
+	Status            string
+	Swap              swap.StateMachine // Lifecycle the coordinator drives; Status is its external view
+	Chain             string            // settlement_chain carrying the stablecoin leg
+	ZECLocked         bool
+	USDCLocked        bool
+	HTLCScript        []byte    // The HTLC Bitcoin Script
+	HTLCP2SHAddress   string    // The P2SH address for the HTLC
+	HTLCLockTxID      string    // Transaction ID that locked funds to HTLC
+	ZECLockIntent     time.Time // When the ZEC lock was about to be broadcast; saved first, so a redelivered update never locks twice
+	HTLCClaimTxID     string    // Transaction ID that claimed the ZEC from the HTLC
+	StablecoinLockTx  string    // Stablecoin lock transaction, as reported by the wallet
+	StablecoinClaimTx string    // Stablecoin claim transaction, as reported by the wallet
+	HTLCLocktime      uint32    // Locktime for refund (24 hours from now in block height)
+	ZECTimeout        time.Time // Estimated wall-clock time the ZEC leg becomes refundable
+	StablecoinTimeout time.Time // When the stablecoin leg becomes refundable, set when its lock is dispatched
+	AlicePubKeyHash   []byte    // Alice's pubkey hash for HTLC claim (Bob provides secret + sig to claim)
+	BobPubKeyHash     []byte    // Bob's pubkey hash for HTLC refund (after timeout)
+	ZECClaimed        bool
+	USDCClaimed       bool
+	SecretRevealedAt  time.Time // When the secret was first published (zero if not revealed)
+	CreatedAt         time.Time
+	UpdatedAt         time.Time
+	CompletedAt       time.Time
 }
 
 // SettlementService coordinates HTLC settlements
 type SettlementService struct {
-	nc          *nats.Conn
-	zcashClient *zcash.Client
-	settlements map[string]*SettlementState
-	mu          sync.RWMutex
+	nc            *nats.Conn
+	zcashClient   *zcash.Client
+	settlements   map[string]*SettlementState
+	mu            sync.RWMutex
+	revealWindow  time.Duration // How long a revealed secret stays live before the swap is abandoned
+	chains        map[string]SettlementChain
+	confirmations ConfirmationPolicy // Confirmations each chain's locks need before the secret is revealed
+	events        *eventLogger       // Settlement event output (compact at info, banners at debug)
+	mode          SettlementMode     // Whether the service holds the preimage or only its hash
+	claimGrace    time.Duration      // Least time the ZEC leg must have left when the secret is revealed
+	swapTimeout   time.Duration      // How long the ZEC leg stays locked (0 = DefaultSwapTimeout)
+	outbox        *outbox            // Durable record of messages being published (nil publishes directly)
+	states        *stateStore        // Settlement snapshots for reports and restarts (nil keeps them in memory only)
+	zecLeg        legObserver        // On-chain view of the ZEC leg for reconciliation (nil skips it)
+	stream        string             // JetStream stream for durable, explicitly acked consumers ("" = core subscriptions)
 }
 
-// NewSettlementService creates a new settlement service
-func NewSettlementService(natsURL, zcashRPCURL, zcashUser, zcashPassword string) (*SettlementService, error) {
-	nc, err := nats.Connect(natsURL)
+// NewSettlementService creates a new settlement service from a validated configuration
+func NewSettlementService(cfg *SettlementConfig) (*SettlementService, error) {
+	nc, err := nats.Connect(cfg.NATSURL)
 	if err != nil {
 		return nil, fmt.Errorf("failed to connect to NATS: %w", err)
 	}
 
 	// Initialize Zcash RPC client
-	zcashClient := zcash.NewClient(zcashRPCURL, zcashUser, zcashPassword)
+	zcashClient := zcash.NewClient(cfg.Zcash.URL, cfg.Zcash.User, cfg.Zcash.Password)
 
 	service := &SettlementService{
-		nc:          nc,
-		zcashClient: zcashClient,
-		settlements: make(map[string]*SettlementState),
+		nc:            nc,
+		zcashClient:   zcashClient,
+		settlements:   make(map[string]*SettlementState),
+		revealWindow:  cfg.RevealWindow,
+		confirmations: cfg.Confirmations,
+		events:        newEventLogger(os.Stdout, cfg.LogLevel),
+		mode:          cfg.Mode,
+		claimGrace:    cfg.ClaimGrace,
+		swapTimeout:   cfg.SwapTimeout,
+		stream:        cfg.Stream,
+	}
+	service.zecLeg = &zcashObserver{client: zcashClient}
+
+	if cfg.OutboxDir != "" {
+		service.outbox, err = openOutbox(cfg.OutboxDir)
+		if err != nil {
+			nc.Close()
+			return nil, fmt.Errorf("failed to open outbox: %w", err)
+		}
+	}
+
+	if cfg.StateDir != "" {
+		service.states, err = openStateStore(cfg.StateDir)
+		if err != nil {
+			nc.Close()
+			return nil, fmt.Errorf("failed to open state directory: %w", err)
+		}
+	}
+
+	// Stablecoin legs are signed by the users' wallets; the coordinator relays instructions
+	for name, chain := range cfg.Chains {
+		service.registerChain(&relayChain{name: name, rpcURL: chain.RPCURL, publish: service.publish})
 	}
 
 	// Bootstrap the Zcash regtest node
@@ -121,9 +185,13 @@ func (s *SettlementService) createZcashHTLC(state *SettlementState, amountZatosh
 		return fmt.Errorf("failed to get block count: %w", err)
 	}
 
-	// Set locktime to current height + 144 blocks (approximately 24 hours)
-	locktime := uint32(blockHeight + 144)
+	// Set locktime to current height + the configured swap timeout in blocks
+	locktime, zecTimeout := s.zecLegTimelock(blockHeight, time.Now())
+	if err := validateLegTimelocks(blockHeight, locktime); err != nil {
+		return err
+	}
 	state.HTLCLocktime = locktime
+	state.ZECTimeout = zecTimeout
 
 	// Use real pubkey hashes from the settlement state
 	// These are set from the status update containing Alice's and Bob's pubkey hashes
@@ -170,13 +238,25 @@ func (s *SettlementService) createZcashHTLC(state *SettlementState, amountZatosh
 	log.Printf("HTLC P2SH Address: %s", p2shAddress)
 	log.Printf("Locktime: %d (block height)", locktime)
 
+	// The broadcast cannot be undone: record the intent first, so that if the service stops before
+	// the update is acked, its redelivery finds the lock attempt instead of broadcasting again
+	state.ZECLockIntent = time.Now()
+	if err := s.persistState(state); err != nil {
+		state.ZECLockIntent = time.Time{}
+		return fmt.Errorf("failed to record ZEC lock intent: %w", err)
+	}
+
 	// Create and broadcast transaction locking ZEC to HTLC from user's personal wallet
 	txid, err := s.zcashClient.CreateAndBroadcastHTLCLock(userZcashAddress, p2shAddress, amountZEC)
 	if err != nil {
+		// Nothing was broadcast, so a retry may lock
+		state.ZECLockIntent = time.Time{}
+		s.saveState(state)
 		return fmt.Errorf("failed to create HTLC lock transaction from %s: %w", userZcashAddress, err)
 	}
 
 	state.HTLCLockTxID = txid
+	s.saveState(state)
 
 	log.Printf("✅ HTLC Lock Transaction broadcast: %s", txid)
 
@@ -187,6 +267,29 @@ func (s *SettlementService) createZcashHTLC(state *SettlementState, amountZatosh
 	return nil
 }
 
+// confirmAttemptedZECLock handles a redelivered ZEC lock update for a settlement whose lock was
+// already attempted. It never broadcasts again: the lock counts once its transaction was recorded
+// or the chain shows it, and otherwise the update is retried while the transaction propagates.
+// Caller must hold s.mu.
+func (s *SettlementService) confirmAttemptedZECLock(state *SettlementState) error {
+	if state.HTLCLockTxID != "" {
+		log.Printf("ZEC lock for %s already broadcast (%s); not locking again", state.ProposalID, state.HTLCLockTxID)
+		return nil
+	}
+	if s.zecLeg == nil {
+		return fmt.Errorf("ZEC lock for %s was attempted at %s but its outcome is unknown", state.ProposalID, state.ZECLockIntent.Format(time.RFC3339))
+	}
+	obs, err := s.zecLeg.Observe(state)
+	if err != nil {
+		return fmt.Errorf("failed to check attempted ZEC lock: %w", err)
+	}
+	if !obs.Locked {
+		return fmt.Errorf("ZEC lock for %s was attempted at %s but is not on-chain yet", state.ProposalID, state.ZECLockIntent.Format(time.RFC3339))
+	}
+	log.Printf("ZEC lock for %s found on-chain at %s; not locking again", state.ProposalID, state.HTLCP2SHAddress)
+	return nil
+}
+
 // bootstrapZcash initializes the Zcash regtest node with blocks and test addresses
 func (s *SettlementService) bootstrapZcash() error {
 	log.Println("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
@@ -276,102 +379,228 @@ func generateSecretAndHash() ([]byte, string, error) {
 	return secret, hashHex, nil
 }
 
-// handleSettlementRequest handles new settlement requests
-func (s *SettlementService) handleSettlementRequest(msg *nats.Msg) {
-	var req SettlementRequest
-	if err := json.Unmarshal(msg.Data, &req); err != nil {
-		log.Printf("Error parsing settlement request: %v", err)
-		return
+// settlementSecret returns the secret and HTLC hash for a request. In non-custodial mode the
+// secret is always nil and the hash comes from the request.
+func (s *SettlementService) settlementSecret(req *SettlementRequest) ([]byte, string, error) {
+	if s.mode == ModeNonCustodial {
+		hashHex, err := requestSecretHash(req)
+		if err != nil {
+			return nil, "", err
+		}
+		log.Printf("Using counterparty-held secret (hash: %s)", hashHex)
+		return nil, hashHex, nil
 	}
 
 	// Use Alice's provided secret instead of generating a random one
-	var secret []byte
-	var hashHex string
-
 	if req.Secret != "" {
-		// Use Alice's secret
-		secret = []byte(req.Secret)
+		secret := []byte(req.Secret)
 		// Generate hash (SHA256 -> RIPEMD160 for Zcash compatibility)
 		shaHash := sha256.Sum256(secret)
 		ripemdHasher := ripemd160.New()
 		ripemdHasher.Write(shaHash[:])
 		ripemdHash := ripemdHasher.Sum(nil)
-		hashHex = hex.EncodeToString(ripemdHash)
+		hashHex := hex.EncodeToString(ripemdHash)
 		log.Printf("Using Alice's provided secret (hash: %s)", hashHex)
-	} else {
-		// Fallback to generating random secret (shouldn't happen)
-		var err error
-		secret, hashHex, err = generateSecretAndHash()
-		if err != nil {
-			log.Printf("Error generating secret: %v", err)
+		return secret, hashHex, nil
+	}
+
+	// Fallback to generating random secret (shouldn't happen)
+	secret, hashHex, err := generateSecretAndHash()
+	if err != nil {
+		return nil, "", err
+	}
+	log.Printf("WARNING: No secret provided, using generated secret")
+	return secret, hashHex, nil
+}
+
+// handleSettlementRequest handles new settlement requests
+func (s *SettlementService) handleSettlementRequest(msg *nats.Msg) {
+	req, err := decodeSettlementRequest(msg.Data)
+	if err != nil {
+		log.Printf("Error parsing settlement request: %v", err)
+		s.publishRejection(msg, &req, fmt.Errorf("malformed request: %w", err))
+		return
+	}
+
+	if err := validateSettlementRequest(&req, time.Now()); err != nil {
+		log.Printf("Error: Invalid settlement request %s: %v", req.ProposalID, err)
+		s.publishRejection(msg, &req, err)
+		return
+	}
+
+	secret, hashHex, err := s.settlementSecret(&req)
+	if err != nil {
+		log.Printf("Error: Rejecting settlement %s: %v", req.ProposalID, err)
+		s.publishRejection(msg, &req, err)
+		return
+	}
+
+	state, err := s.initSettlement(&req, secret, hashHex)
+	if err != nil {
+		log.Printf("Error: Rejecting settlement %s: %v", req.ProposalID, err)
+		if errors.Is(err, ErrDuplicateSettlement) || errors.Is(err, ErrProposalOrderConflict) {
+			// The proposal ID belongs to a live settlement; a replay only hears back on its own reply subject
+			s.replyRejection(msg, &req, err)
 			return
 		}
-		log.Printf("WARNING: No secret provided, using generated secret")
+		s.publishRejection(msg, &req, err)
+		return
+	}
+
+	// Log the settlement initialization
+	s.events.event("settlement_request", []logField{
+		{"proposal_id", req.ProposalID},
+		{"order_id", req.OrderID},
+		{"maker", req.MakerID},
+		{"taker", req.TakerID},
+		{"amount_zec", fmt.Sprintf("%.8f", float64(req.ZECZatoshi)/1e8)},
+		{"price", req.Price},
+		{"total_usd", fmt.Sprintf("%.2f", float64(state.AmountUSDC)/100.0)},
+		{"chain", state.Chain},
+		{"hash", hashHex},
+		{"status", state.Status},
+	}, func(w io.Writer) {
+		fmt.Fprintln(w, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
+		fmt.Fprintln(w, "📩 NEW SETTLEMENT REQUEST")
+		fmt.Fprintln(w, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
+		fmt.Fprintf(w, "\n  Proposal ID: %s\n", req.ProposalID)
+		fmt.Fprintf(w, "  Order ID:    %s\n\n", req.OrderID)
+		fmt.Fprintf(w, "  👥 Parties:\n")
+		fmt.Fprintf(w, "     Maker:    %s\n", req.MakerID)
+		fmt.Fprintf(w, "     Taker:    %s\n\n", req.TakerID)
+		fmt.Fprintf(w, "  💰 Trade:\n")
+		fmt.Fprintf(w, "     Amount:   %.8f ZEC\n", float64(req.ZECZatoshi)/1e8)
+		fmt.Fprintf(w, "     Price:    $%d\n", req.Price)
+		fmt.Fprintf(w, "     Total:    $%.2f\n\n", float64(state.AmountUSDC)/100.0)
+		fmt.Fprintf(w, "  🔐 HTLC Secret:\n")
+		if s.mode == ModeNonCustodial {
+			fmt.Fprintf(w, "     Source:   held by counterparties (hash only)\n")
+		} else {
+			fmt.Fprintf(w, "     Source:   Alice's provided secret\n")
+		}
+		fmt.Fprintf(w, "     Hash:     %s\n\n", hashHex)
+		fmt.Fprintln(w, "  ✅ Settlement initialized")
+		fmt.Fprintln(w, "  📌 Status: ready → waiting for Alice to lock ZEC")
+		fmt.Fprintln(w)
+	})
+
+	// Publish HTLC parameters to NATS
+	s.publishHTLCParams(state)
+}
+
+// rejectionMessage encodes a settlement request rejection
+func rejectionMessage(req *SettlementRequest, reason error) []byte {
+	rejection := map[string]interface{}{
+		"proposal_id": req.ProposalID,
+		"order_id":    req.OrderID,
+		"status":      "rejected",
+		"reason":      reason.Error(),
+	}
+	rejectionJSON, _ := json.Marshal(rejection)
+	return rejectionJSON
+}
+
+// replyRejection reports a rejected settlement request to the requester only, if it expects a reply.
+// Requests consumed from JetStream carry no reply subject, so this reaches core NATS requesters only.
+func (s *SettlementService) replyRejection(msg *nats.Msg, req *SettlementRequest, reason error) {
+	if msg.Reply == "" {
+		return
+	}
+	if err := msg.Respond(rejectionMessage(req, reason)); err != nil {
+		log.Printf("Error replying with settlement rejection: %v", err)
+	}
+}
+
+// publishRejection reports a rejected settlement request to the requester (if it expects a reply)
+// and on the proposal's rejection topic (if the proposal ID is known). Rejections never go out on
+// the HTLC topic: nodes read that as the live settlement's parameters.
+func (s *SettlementService) publishRejection(msg *nats.Msg, req *SettlementRequest, reason error) {
+	s.replyRejection(msg, req, reason)
+
+	if req.ProposalID != "" {
+		topic := fmt.Sprintf("settlement.rejected.%s", req.ProposalID)
+		if err := s.publish(topic, rejectionMessage(req, reason)); err != nil {
+			log.Printf("Error publishing settlement rejection: %v", err)
+		}
+	}
+}
+
+// Settlement request replay errors
+var (
+	ErrProposalOrderConflict = errors.New("proposal already settling a different order")
+	ErrDuplicateSettlement   = errors.New("settlement already in progress for proposal")
+)
+
+// initSettlement creates and stores the settlement state for a request.
+// Requests naming an unsupported settlement_chain, or arriving while the configured swap timeout
+// leaves the legs unsafe timelocks, are rejected without storing any state.
+// A proposal_id that is already in use is rejected too: for a different order_id the pair conflicts,
+// and for the same order a replayed request would otherwise reset the settlement and its secret.
+// If the USDC total cannot be computed the state is stored as "rejected" and an error is returned.
+func (s *SettlementService) initSettlement(req *SettlementRequest, secret []byte, hashHex string) (*SettlementState, error) {
+	chain, err := s.chainByName(req.SettlementChain)
+	if err != nil {
+		return nil, err
+	}
+	// The ZEC leg's timelock comes from the swap timeout; both legs must get a safe one
+	locktime, _ := s.zecLegTimelock(0, time.Now())
+	if err := validateLegTimelocks(0, locktime); err != nil {
+		return nil, err
 	}
 
-	// Create settlement state
 	state := &SettlementState{
 		ProposalID: req.ProposalID,
 		OrderID:    req.OrderID,
 		MakerID:    req.MakerID,
 		TakerID:    req.TakerID,
-		AmountZEC:  req.Amount,
-		AmountUSDC: req.Amount * req.Price,
+		AmountZEC:  req.ZECZatoshi,
+		Stablecoin: req.Stablecoin,
+		Price:      req.Price,
 		Secret:     secret,
 		HashHex:    hashHex,
 		Status:     "ready",
+		Swap:       swap.Restore(swap.Finalized),
+		Chain:      chain.Name(),
 		ZECLocked:  false,
 		USDCLocked: false,
 		CreatedAt:  time.Now(),
 		UpdatedAt:  time.Now(),
 	}
 
-	s.mu.Lock()
-	s.settlements[req.ProposalID] = state
-	s.mu.Unlock()
+	totalUSDC, err := computeTotalUSDC(req.ZECZatoshi, req.Price)
+	if err != nil {
+		state.Status = "rejected"
+		state.Secret = nil
+		state.advanceSwap(swap.Abort)
+	} else {
+		state.AmountUSDC = totalUSDC
+	}
 
-	// Log the settlement initialization
-	fmt.Println("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
-	fmt.Println("📩 NEW SETTLEMENT REQUEST")
-	fmt.Println("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
-	fmt.Printf("\n  Proposal ID: %s\n", req.ProposalID)
-	fmt.Printf("  Order ID:    %s\n\n", req.OrderID)
-	fmt.Printf("  👥 Parties:\n")
-	fmt.Printf("     Maker:    %s\n", req.MakerID)
-	fmt.Printf("     Taker:    %s\n\n", req.TakerID)
-	fmt.Printf("  💰 Trade:\n")
-	fmt.Printf("     Amount:   %.8f ZEC\n", float64(req.Amount)/1e8)
-	fmt.Printf("     Price:    $%d\n", req.Price)
-	fmt.Printf("     Total:    $%.2f\n\n", float64(state.AmountUSDC)/100.0)
-	fmt.Printf("  🔐 HTLC Secret:\n")
-	fmt.Printf("     Source:   Alice's provided secret\n")
-	fmt.Printf("     Hash:     %s\n\n", hashHex)
-	fmt.Println("  ✅ Settlement initialized")
-	fmt.Println("  📌 Status: ready → waiting for Alice to lock ZEC")
-	fmt.Println()
+	s.mu.Lock()
+	defer s.mu.Unlock()
 
-	// Publish HTLC parameters to NATS
-	htlcParams := map[string]interface{}{
-		"proposal_id": req.ProposalID,
-		"order_id":    req.OrderID,
-		"hash":        hashHex,
-		"timeout":     24 * 3600, // 24 hours in seconds
-		"status":      "ready",
+	if existing, ok := s.settlements[req.ProposalID]; ok {
+		if existing.OrderID != req.OrderID {
+			return nil, fmt.Errorf("%w: %s is bound to order %s, not %s", ErrProposalOrderConflict, req.ProposalID, existing.OrderID, req.OrderID)
+		}
+		if existing.Status != "rejected" {
+			return nil, fmt.Errorf("%w %s", ErrDuplicateSettlement, req.ProposalID)
+		}
 	}
+	s.settlements[req.ProposalID] = state
+	s.saveState(state)
 
-	paramsJSON, _ := json.Marshal(htlcParams)
-	topic := fmt.Sprintf("settlement.htlc.%s", req.ProposalID)
-	if err := s.nc.Publish(topic, paramsJSON); err != nil {
-		log.Printf("Error publishing HTLC params: %v", err)
-	}
+	return state, err
 }
 
-// handleStatusUpdate handles settlement status updates
-func (s *SettlementService) handleStatusUpdate(msg *nats.Msg) {
-	var update SettlementStatusUpdate
-	if err := json.Unmarshal(msg.Data, &update); err != nil {
+// handleStatusUpdate handles settlement status updates. It returns once the update is applied
+// (and, with a state directory, saved) or rejected; the error is retryable when the update may
+// apply if delivered again later.
+func (s *SettlementService) handleStatusUpdate(msg *nats.Msg) error {
+	update, err := decodeStatusUpdate(msg.Data)
+	if err != nil {
 		log.Printf("Error parsing status update: %v", err)
-		return
+		return err
 	}
 
 	s.mu.Lock()
@@ -379,7 +608,24 @@ func (s *SettlementService) handleStatusUpdate(msg *nats.Msg) {
 	if !exists {
 		s.mu.Unlock()
 		log.Printf("Unknown proposal ID: %s", update.ProposalID)
-		return
+		// The request may not have been applied yet, or its state was lost in a restart
+		// without STATE_DIR; retrying gives the request time to land
+		return retryable(fmt.Errorf("unknown proposal %s", update.ProposalID))
+	}
+	if update.OrderID != "" && update.OrderID != state.OrderID {
+		s.mu.Unlock()
+		log.Printf("Ignoring status update for %s: order %s does not match settlement order %s", update.ProposalID, update.OrderID, state.OrderID)
+		return fmt.Errorf("order %s does not match settlement order %s", update.OrderID, state.OrderID)
+	}
+	if err := verifyStatusAmount(state, &update); err != nil {
+		s.mu.Unlock()
+		log.Printf("Rejecting %s status update for %s: %v", update.Action, update.ProposalID, err)
+		return err
+	}
+	if event, ok := statusEvents[update.Action]; ok && !state.Swap.Can(event) {
+		s.mu.Unlock()
+		log.Printf("Rejecting %s status update for %s: not allowed while %s", update.Action, update.ProposalID, state.Swap.State())
+		return fmt.Errorf("%s not allowed while %s", update.Action, state.Swap.State())
 	}
 
 	// Update state based on action
@@ -387,21 +633,21 @@ func (s *SettlementService) handleStatusUpdate(msg *nats.Msg) {
 	case "alice_lock_zec":
 		// Create HTLC on Zcash blockchain
 		// Amount is in zatoshis (1 ZEC = 100,000,000 zatoshis = 1e8)
-		amountZatoshis := update.Amount // Amount in zatoshis
+		amountZatoshis := update.ZECZatoshi
 
 		// Use user's personal Zcash address (required)
 		zcashAddress := update.ZcashAddress
 		if zcashAddress == "" {
 			log.Printf("Error: No user Zcash address provided for HTLC lock")
 			s.mu.Unlock()
-			return
+			return fmt.Errorf("zcash_address is required")
 		}
 
 		// Extract pubkey hashes from the update (required for HTLC security)
 		if update.AlicePubKeyHash == "" || update.BobPubKeyHash == "" {
 			log.Printf("Error: Alice and Bob pubkey hashes are required for HTLC")
 			s.mu.Unlock()
-			return
+			return fmt.Errorf("alice_pubkey_hash and bob_pubkey_hash are required")
 		}
 
 		// Decode pubkey hashes from hex
@@ -409,13 +655,13 @@ func (s *SettlementService) handleStatusUpdate(msg *nats.Msg) {
 		if err != nil {
 			log.Printf("Error decoding Alice pubkey hash: %v", err)
 			s.mu.Unlock()
-			return
+			return err
 		}
 		bobPubKeyHash, err := hex.DecodeString(update.BobPubKeyHash)
 		if err != nil {
 			log.Printf("Error decoding Bob pubkey hash: %v", err)
 			s.mu.Unlock()
-			return
+			return err
 		}
 
 		// Store pubkey hashes in state for HTLC creation
@@ -424,94 +670,193 @@ func (s *SettlementService) handleStatusUpdate(msg *nats.Msg) {
 
 		log.Printf("Received pubkey hashes - Alice: %s, Bob: %s", update.AlicePubKeyHash, update.BobPubKeyHash)
 
-		err = s.createZcashHTLC(state, amountZatoshis, zcashAddress)
+		if state.ZECLockIntent.IsZero() {
+			err = s.createZcashHTLC(state, amountZatoshis, zcashAddress)
+		} else {
+			err = s.confirmAttemptedZECLock(state)
+		}
 		if err != nil {
 			log.Printf("Error creating Zcash HTLC: %v", err)
 			s.mu.Unlock()
-			return
+			if errors.Is(err, ErrInvalidProposal) {
+				return err
+			}
+			return retryable(err)
 		}
 
 		state.ZECLocked = true
 		state.Status = "alice_locked"
 		state.UpdatedAt = time.Now()
+		state.advanceSwap(swap.FunderLock)
+
+		s.events.event("zec_locked", []logField{
+			{"proposal_id", state.ProposalID},
+			{"status", state.Status},
+			{"amount_zec", fmt.Sprintf("%.8f", float64(update.ZECZatoshi)/1e8)},
+			{"htlc_address", state.HTLCP2SHAddress},
+			{"lock_txid", state.HTLCLockTxID},
+		}, func(w io.Writer) {
+			fmt.Fprintln(w, "\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
+			fmt.Fprintln(w, "📬 SETTLEMENT STATUS UPDATE - ZCASH HTLC CREATED")
+			fmt.Fprintln(w, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
+			fmt.Fprintf(w, "\n  Action:      %s\n", update.Action)
+			fmt.Fprintf(w, "  Status:      %s\n\n", state.Status)
+			fmt.Fprintf(w, "  🔒 Alice locked %.8f ZEC to HTLC\n", float64(update.ZECZatoshi)/1e8)
+			fmt.Fprintf(w, "  📍 HTLC Address: %s\n", state.HTLCP2SHAddress)
+			fmt.Fprintf(w, "  📜 Lock TX:      %s\n\n", state.HTLCLockTxID)
+			fmt.Fprintln(w, "  ✅ ZEC locked on Zcash blockchain")
+			fmt.Fprintln(w, "  📌 Status: alice_locked → waiting for Bob to lock USDC")
+			fmt.Fprintln(w)
+		})
 
-		fmt.Println("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
-		fmt.Println("📬 SETTLEMENT STATUS UPDATE - ZCASH HTLC CREATED")
-		fmt.Println("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
-		fmt.Printf("\n  Action:      %s\n", update.Action)
-		fmt.Printf("  Status:      %s\n\n", state.Status)
-		fmt.Printf("  🔒 Alice locked %.8f ZEC to HTLC\n", float64(update.Amount)/1e8)
-		fmt.Printf("  📍 HTLC Address: %s\n", state.HTLCP2SHAddress)
-		fmt.Printf("  📜 Lock TX:      %s\n\n", state.HTLCLockTxID)
-		fmt.Println("  ✅ ZEC locked on Zcash blockchain")
-		fmt.Println("  📌 Status: alice_locked → waiting for Bob to lock USDC")
-		fmt.Println()
+		if err := s.lockStablecoinLeg(state); err != nil {
+			log.Printf("Error dispatching stablecoin lock for %s: %v", state.ProposalID, err)
+		}
 
 	case "bob_lock_usdc":
+		confirmed, err := s.confirmStablecoinLock(state)
+		if err != nil {
+			log.Printf("Error confirming stablecoin lock for %s: %v", state.ProposalID, err)
+			s.mu.Unlock()
+			return retryable(err)
+		}
+		if !confirmed {
+			log.Printf("Stablecoin lock for %s not yet confirmed on %s", state.ProposalID, state.Chain)
+			s.mu.Unlock()
+			return retryable(fmt.Errorf("stablecoin lock not yet confirmed on %s", state.Chain))
+		}
+
+		// The secret must not be revealed before Alice's ZEC lock is final
+		if err := s.checkZECLockFinal(state); err != nil {
+			log.Printf("Not revealing secret for %s yet: %v", state.ProposalID, err)
+			s.mu.Unlock()
+			return retryable(err)
+		}
+
 		state.USDCLocked = true
+		state.StablecoinLockTx = update.TxID
+		state.advanceSwap(swap.ClaimerLock)
+
+		// Revealing with too little time left on the ZEC leg could let it expire before Bob claims
+		if err := s.guardReveal(state, time.Now()); err != nil {
+			s.saveState(state)
+			s.mu.Unlock()
+			return err
+		}
+
 		state.Status = "both_locked"
 		state.UpdatedAt = time.Now()
+		state.advanceSwap(swap.Reveal)
+
+		s.events.event("both_locked", []logField{
+			{"proposal_id", state.ProposalID},
+			{"status", state.Status},
+			{"amount_usdc", update.USDCMinor},
+			{"chain", state.Chain},
+			{"hash", state.HashHex},
+		}, func(w io.Writer) {
+			fmt.Fprintln(w, "\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
+			fmt.Fprintln(w, "📬 SETTLEMENT STATUS UPDATE")
+			fmt.Fprintln(w, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
+			fmt.Fprintf(w, "\n  Action:      %s\n", update.Action)
+			fmt.Fprintf(w, "  Status:      %s\n\n", state.Status)
+			fmt.Fprintf(w, "  🔒 Bob is locking $%d USDC\n\n", update.USDCMinor)
+			fmt.Fprintln(w, "  ✅ USDC lock confirmed")
+			fmt.Fprintln(w, "  🎉 BOTH ASSETS LOCKED!")
+			fmt.Fprintf(w, "\n  📌 Status: both_locked → ready for claiming\n\n")
+			if s.mode == ModeNonCustodial {
+				fmt.Fprintln(w, "  🔓 INSTRUCTING SECRET HOLDER TO REVEAL")
+			} else {
+				fmt.Fprintln(w, "  🔓 REVEALING SECRET FOR ATOMIC SWAP")
+				fmt.Fprintf(w, "\n  Secret (hex): %s\n", hex.EncodeToString(state.Secret))
+			}
+			fmt.Fprintf(w, "  Hash (hex):   %s\n\n", state.HashHex)
+			fmt.Fprintln(w, "  💡 Claims:")
+			fmt.Fprintf(w, "     1. Alice claims USDC on %s (reveals secret on-chain)\n", state.Chain)
+			fmt.Fprintf(w, "     2. Bob sees secret on %s, claims ZEC on Zcash\n", state.Chain)
+			fmt.Fprintln(w, "\n  ✨ ATOMIC SWAP READY FOR COMPLETION")
+			fmt.Fprintln(w)
+		})
 
-		fmt.Println("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
-		fmt.Println("📬 SETTLEMENT STATUS UPDATE")
-		fmt.Println("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
-		fmt.Printf("\n  Action:      %s\n", update.Action)
-		fmt.Printf("  Status:      %s\n\n", state.Status)
-		fmt.Printf("  🔒 Bob is locking $%d USDC\n\n", update.AmountUSDC)
-		fmt.Println("  ✅ USDC lock confirmed")
-		fmt.Println("  🎉 BOTH ASSETS LOCKED!")
-		fmt.Printf("\n  📌 Status: both_locked → ready for claiming\n\n")
-		fmt.Println("  🔓 REVEALING SECRET FOR ATOMIC SWAP")
-		fmt.Printf("\n  Secret (hex): %s\n", hex.EncodeToString(state.Secret))
-		fmt.Printf("  Hash (hex):   %s\n\n", state.HashHex)
-		fmt.Println("  💡 Claims:")
-		fmt.Println("     1. Alice claims USDC on Starknet (reveals secret on-chain)")
-		fmt.Println("     2. Bob sees secret on Starknet, claims ZEC on Zcash")
-		fmt.Println("\n  ✨ ATOMIC SWAP READY FOR COMPLETION")
-		fmt.Println()
-
-		// Publish secret reveal to NATS
-		secretReveal := map[string]interface{}{
-			"proposal_id": update.ProposalID,
-			"secret":      hex.EncodeToString(state.Secret),
-			"hash":        state.HashHex,
-			"status":      "both_locked",
+		if s.mode == ModeNonCustodial {
+			// The service never saw the preimage; the counterparties reveal it by claiming
+			s.publishRevealInstruction(state)
+		} else {
+			// Publish secret reveal to NATS (re-published by revealMonitor until the window lapses)
+			state.SecretRevealedAt = time.Now()
+			s.publishSecret(state)
 		}
 
-		secretJSON, _ := json.Marshal(secretReveal)
-		topic := fmt.Sprintf("settlement.secret.%s", update.ProposalID)
-		if err := s.nc.Publish(topic, secretJSON); err != nil {
-			log.Printf("Error publishing secret reveal: %v", err)
+		if err := s.claimStablecoinLeg(state); err != nil {
+			log.Printf("Error dispatching stablecoin claim for %s: %v", state.ProposalID, err)
 		}
+
+	case "alice_claim_usdc":
+		state.StablecoinClaimTx = update.TxID
+		state.markClaimed("usdc")
+		state.advanceSwap(swap.ClaimerClaim)
+		log.Printf("USDC claim confirmed for %s", update.ProposalID)
 	}
 
+	s.saveState(state)
 	s.mu.Unlock()
+	return nil
 }
 
 // Start begins listening for NATS messages
 func (s *SettlementService) Start() error {
-	// Subscribe to settlement requests
-	_, err := s.nc.Subscribe("settlement.request.*", func(msg *nats.Msg) {
-		s.handleSettlementRequest(msg)
-	})
-	if err != nil {
-		return fmt.Errorf("failed to subscribe to settlement.request.*: %w", err)
+	// Messages recorded before a crash but never sent go out before anything new
+	s.sweepOutbox()
+
+	// Settlements saved before the restart are resumed before new messages arrive
+	s.restoreStates()
+
+	if s.stream != "" {
+		// Durable pull consumers: a message is acked only once it has been applied
+		if err := s.startJetStream(); err != nil {
+			return err
+		}
+	} else {
+		// Subscribe to settlement requests
+		_, err := s.nc.Subscribe("settlement.request.*", func(msg *nats.Msg) {
+			s.handleSettlementRequest(msg)
+		})
+		if err != nil {
+			return fmt.Errorf("failed to subscribe to settlement.request.*: %w", err)
+		}
+
+		// Subscribe to status updates
+		_, err = s.nc.Subscribe("settlement.status.*", func(msg *nats.Msg) {
+			if err := s.handleStatusUpdate(msg); err != nil {
+				log.Printf("Warning: Status update on %s not applied: %v", msg.Subject, err)
+			}
+		})
+		if err != nil {
+			return fmt.Errorf("failed to subscribe to settlement.status.*: %w", err)
+		}
 	}
 
-	// Subscribe to status updates
-	_, err = s.nc.Subscribe("settlement.status.*", func(msg *nats.Msg) {
-		s.handleStatusUpdate(msg)
+	// Answer operator report queries (request/reply)
+	_, err := s.nc.Subscribe("settlement.query.report", func(msg *nats.Msg) {
+		s.handleReportQuery(msg)
 	})
 	if err != nil {
-		return fmt.Errorf("failed to subscribe to settlement.status.*: %w", err)
+		return fmt.Errorf("failed to subscribe to settlement.query.report: %w", err)
 	}
 
 	log.Println("✓ Subscribed to settlement requests")
 	log.Println("✓ Subscribed to settlement status updates")
+	log.Println("✓ Serving settlement reports on settlement.query.report")
 
 	// Start background block miner for regtest (mines 1 block every 60 seconds)
 	go s.backgroundBlockMiner()
 
+	// Re-publish revealed secrets and abandon swaps whose reveal window lapsed
+	go s.revealMonitor()
+
+	// Correct local lock/claim flags that missed an on-chain event
+	go s.reconcileLoop()
+
 	log.Println("\n🦀 Settlement Service Ready - Waiting for settlement requests...")
 
 	return nil
@@ -631,7 +976,7 @@ func (s *SettlementService) handleCreateAddress(w http.ResponseWriter, r *http.R
 		}
 	}
 
-	log.Printf("  Private key: %s...", privKey[:10])
+	log.Printf("  Private key: %s", truncateID(privKey, 10))
 	log.Printf("  Public key: %s", pubKey)
 	log.Printf("  PubKey hash: %s", pubKeyHash)
 
@@ -690,7 +1035,7 @@ func (s *SettlementService) handleFundAddress(w http.ResponseWriter, r *http.Req
 		return
 	}
 
-	log.Printf("✓ Sent %.8f ZEC to %s (txid: %s)", req.Amount, req.Address, txid[:16]+"...")
+	log.Printf("✓ Sent %.8f ZEC to %s (txid: %s)", req.Amount, req.Address, truncateID(txid, 16))
 
 	// Mine a block immediately to confirm the transaction
 	blocks, err := s.zcashClient.Generate(1)
@@ -979,11 +1324,8 @@ func (s *SettlementService) handleClaimZEC(w http.ResponseWriter, r *http.Reques
 	s.zcashClient.Generate(1)
 	log.Printf("⛏️ Mined 1 block to confirm HTLC claim")
 
-	// Update settlement state
-	s.mu.Lock()
-	state.Status = "completed"
-	state.UpdatedAt = time.Now()
-	s.mu.Unlock()
+	// Update settlement state (secret is scrubbed once the USDC leg is also claimed)
+	s.completeSwap(state, txid, time.Now())
 
 	log.Printf("✅ ZEC claimed successfully! TX: %s", txid)
 
@@ -1084,34 +1426,27 @@ func (s *SettlementService) StartHTTPServer(port string) {
 }
 
 func main() {
-	// Get configuration from environment
-	natsURL := os.Getenv("NATS_URL")
-	if natsURL == "" {
-		natsURL = "nats://localhost:4222"
-	}
-
-	zcashRPCURL := os.Getenv("ZCASH_RPC_URL")
-	if zcashRPCURL == "" {
-		zcashRPCURL = "http://localhost:18232"
-	}
-
-	zcashUser := os.Getenv("ZCASH_RPC_USER")
-	if zcashUser == "" {
-		zcashUser = "blacktrace"
+	cfg, err := loadConfig(os.Getenv)
+	if err != nil {
+		log.Fatalf("Invalid configuration: %v", err)
 	}
-
-	zcashPassword := os.Getenv("ZCASH_RPC_PASSWORD")
-	if zcashPassword == "" {
-		zcashPassword = "regtest123"
+	if err := cfg.Validate(); err != nil {
+		log.Fatalf("Invalid configuration: %v", err)
 	}
 
 	log.Printf("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
 	log.Printf("🦀 BLACKTRACE SETTLEMENT SERVICE")
 	log.Printf("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
-	log.Printf("\n📡 Connecting to NATS at %s...", natsURL)
-	log.Printf("⚡ Connecting to Zcash at %s...\n", zcashRPCURL)
+	log.Printf("\n📡 Connecting to NATS at %s...", cfg.NATSURL)
+	log.Printf("⚡ Connecting to Zcash at %s...\n", cfg.Zcash.URL)
+	if cfg.Mode == ModeNonCustodial {
+		log.Printf("🔐 Mode: Non-custodial (service holds only the HTLC hash)")
+	} else {
+		log.Printf("🔐 Mode: Demo (service holds the HTLC secret)")
+	}
+	log.Printf("⏱  Swap timeout %s, reveal window %s, claim grace %s", cfg.SwapTimeout, cfg.RevealWindow, cfg.ClaimGrace)
 
-	service, err := NewSettlementService(natsURL, zcashRPCURL, zcashUser, zcashPassword)
+	service, err := NewSettlementService(cfg)
 	if err != nil {
 		log.Fatalf("Failed to create settlement service: %v", err)
 	}
@@ -0,0 +1,73 @@
+package main
+
+import (
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log"
+	"strings"
+)
+
+// SettlementMode selects whether the service holds the swap preimage
+type SettlementMode string
+
+const (
+	// ModeCustodial is the demo mode: the service holds Alice's secret and publishes it once both legs lock
+	ModeCustodial SettlementMode = "custodial"
+	// ModeNonCustodial keeps only the hash; the counterparties hold the preimage and are told when to reveal it
+	ModeNonCustodial SettlementMode = "non-custodial"
+)
+
+// Non-custodial settlement request errors
+var (
+	ErrPreimageNotAccepted = errors.New("non-custodial mode does not accept a secret")
+	ErrSecretHashRequired  = errors.New("secret_hash is required in non-custodial mode")
+)
+
+// parseSettlementMode parses the SETTLEMENT_MODE setting; empty selects custodial
+func parseSettlementMode(s string) (SettlementMode, error) {
+	switch SettlementMode(strings.ToLower(strings.TrimSpace(s))) {
+	case "", ModeCustodial:
+		return ModeCustodial, nil
+	case ModeNonCustodial:
+		return ModeNonCustodial, nil
+	}
+	return "", fmt.Errorf("unknown settlement mode %q (want %q or %q)", s, ModeCustodial, ModeNonCustodial)
+}
+
+// requestSecretHash returns the HTLC hash for a non-custodial request. The request must carry
+// only the 20-byte RIPEMD160(SHA256(secret)) hash; a request carrying the preimage is rejected
+// rather than silently dropped, so a misconfigured client learns it leaked the secret.
+func requestSecretHash(req *SettlementRequest) (string, error) {
+	if req.Secret != "" {
+		return "", ErrPreimageNotAccepted
+	}
+	if req.SecretHash == "" {
+		return "", ErrSecretHashRequired
+	}
+	hash, err := hex.DecodeString(req.SecretHash)
+	if err != nil || len(hash) != 20 {
+		return "", fmt.Errorf("secret_hash must be 20 bytes of hex")
+	}
+	return hex.EncodeToString(hash), nil
+}
+
+// publishRevealInstruction tells the counterparties that both legs are locked and the secret holder
+// should reveal the preimage by claiming. Used instead of publishSecret in non-custodial mode.
+// Caller must hold s.mu.
+func (s *SettlementService) publishRevealInstruction(state *SettlementState) {
+	instruction := map[string]interface{}{
+		"proposal_id": state.ProposalID,
+		"action":      "reveal_secret",
+		"hash":        state.HashHex,
+		"chain":       state.Chain,
+		"status":      state.Status,
+	}
+
+	instructionJSON, _ := json.Marshal(instruction)
+	topic := fmt.Sprintf("settlement.reveal.%s", state.ProposalID)
+	if err := s.publish(topic, instructionJSON); err != nil {
+		log.Printf("Error publishing reveal instruction: %v", err)
+	}
+}
@@ -0,0 +1,125 @@
+package main
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/node"
+	"golang.org/x/crypto/ripemd160"
+)
+
+func TestNonCustodialModeNeverStoresSecret(t *testing.T) {
+	s := newTestService()
+	s.mode = ModeNonCustodial
+	s.registerChain(&mockChain{name: "ztarknet"})
+
+	now := time.Now()
+	hashHex := strings.Repeat("ab", 20)
+
+	// A request carrying the preimage is refused outright
+	leaky := validSettlementRequest(now)
+	leaky.SecretHash = hashHex
+	if _, _, err := s.settlementSecret(&leaky); !errors.Is(err, ErrPreimageNotAccepted) {
+		t.Fatalf("Expected ErrPreimageNotAccepted, got %v", err)
+	}
+
+	req := validSettlementRequest(now)
+	req.Secret = ""
+	req.SecretHash = hashHex
+	secret, gotHash, err := s.settlementSecret(&req)
+	if err != nil {
+		t.Fatalf("Hash-only request rejected: %v", err)
+	}
+	if secret != nil {
+		t.Fatalf("Non-custodial mode produced a secret")
+	}
+
+	state, err := s.initSettlement(&req, secret, gotHash)
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+	if state.HashHex != hashHex {
+		t.Errorf("Expected hash %s, got %s", hashHex, state.HashHex)
+	}
+
+	state.ZECTimeout = now.Add(MakerTimelockBlocks * ZcashBlockInterval)
+	if err := s.lockStablecoinLeg(state); err != nil {
+		t.Fatalf("Failed to dispatch lock: %v", err)
+	}
+	if err := s.claimStablecoinLeg(state); err != nil {
+		t.Fatalf("Failed to dispatch claim: %v", err)
+	}
+	s.expireRevealWindows(now.Add(2 * s.revealWindow))
+
+	for id, stored := range s.settlements {
+		if stored.Secret != nil {
+			t.Errorf("Settlement %s stored a secret in non-custodial mode", id)
+		}
+	}
+}
+
+func TestParseSettlementMode(t *testing.T) {
+	for in, want := range map[string]SettlementMode{
+		"":              ModeCustodial,
+		"custodial":     ModeCustodial,
+		"Non-Custodial": ModeNonCustodial,
+	} {
+		got, err := parseSettlementMode(in)
+		if err != nil || got != want {
+			t.Errorf("parseSettlementMode(%q) = %q, %v; want %q", in, got, err, want)
+		}
+	}
+	if _, err := parseSettlementMode("escrow"); err == nil {
+		t.Error("Expected an unknown mode to be rejected")
+	}
+}
+
+func TestNodeRequestAcceptedInNonCustodialMode(t *testing.T) {
+	t.Setenv("NATS_URL", "")
+	t.Setenv("SETTLEMENT_MODE", "non-custodial")
+	sm, err := node.NewSettlementManager(nil)
+	if err != nil {
+		t.Fatalf("Failed to create settlement manager: %v", err)
+	}
+	proposal := &node.Proposal{ProposalID: "order_1_proposal_1", OrderID: "order_1", ProposerID: "taker", Amount: 100000000, Price: 45}
+	data, err := json.Marshal(sm.NewSettlementRequest("maker", proposal, node.StablecoinUSDC, "alice-secret"))
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	s := newTestService()
+	s.mode = ModeNonCustodial
+	s.registerChain(&mockChain{name: "ztarknet"})
+
+	req, err := decodeSettlementRequest(data)
+	if err != nil {
+		t.Fatalf("Node request failed to decode: %v", err)
+	}
+	if err := validateSettlementRequest(&req, time.Now()); err != nil {
+		t.Fatalf("Node request failed validation: %v", err)
+	}
+	secret, hashHex, err := s.settlementSecret(&req)
+	if err != nil {
+		t.Fatalf("Node request rejected in non-custodial mode: %v", err)
+	}
+	state, err := s.initSettlement(&req, secret, hashHex)
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+	if state.Secret != nil {
+		t.Error("Settlement stored a secret in non-custodial mode")
+	}
+
+	// The hash must be the one Alice's secret opens
+	sha := sha256.Sum256([]byte("alice-secret"))
+	ripemd := ripemd160.New()
+	ripemd.Write(sha[:])
+	if want := hex.EncodeToString(ripemd.Sum(nil)); state.HashHex != want {
+		t.Errorf("Expected hash %s, got %s", want, state.HashHex)
+	}
+}
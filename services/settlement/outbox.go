@@ -0,0 +1,167 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"log"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+)
+
+// outboxEntry is a NATS message the service decided to publish
+type outboxEntry struct {
+	ID         string    `json:"id"`
+	Topic      string    `json:"topic"`
+	Data       []byte    `json:"data"`
+	RecordedAt time.Time `json:"recorded_at"`
+}
+
+// outbox durably records messages before they are published, so a crash between deciding to
+// publish and the publish succeeding cannot lose them. Each entry is one file under dir, written
+// atomically and removed once the message is sent; whatever is left is republished on startup.
+type outbox struct {
+	dir string
+	mu  sync.Mutex
+	seq uint64
+}
+
+// openOutbox opens (creating if needed) the outbox directory
+func openOutbox(dir string) (*outbox, error) {
+	if err := os.MkdirAll(dir, 0700); err != nil {
+		return nil, fmt.Errorf("failed to create outbox directory: %w", err)
+	}
+	return &outbox{dir: dir}, nil
+}
+
+func (o *outbox) path(id string) string {
+	return filepath.Join(o.dir, id+".json")
+}
+
+// record writes an entry for a message about to be published. IDs sort in recording order.
+func (o *outbox) record(topic string, data []byte, now time.Time) (outboxEntry, error) {
+	o.mu.Lock()
+	o.seq++
+	entry := outboxEntry{
+		ID:         fmt.Sprintf("%020d-%06d", now.UnixNano(), o.seq),
+		Topic:      topic,
+		Data:       data,
+		RecordedAt: now,
+	}
+	o.mu.Unlock()
+
+	encoded, err := json.Marshal(entry)
+	if err != nil {
+		return entry, err
+	}
+
+	tmp := o.path(entry.ID) + ".tmp"
+	if err := os.WriteFile(tmp, encoded, 0600); err != nil {
+		return entry, fmt.Errorf("failed to write outbox entry: %w", err)
+	}
+	if err := os.Rename(tmp, o.path(entry.ID)); err != nil {
+		os.Remove(tmp)
+		return entry, fmt.Errorf("failed to commit outbox entry: %w", err)
+	}
+	return entry, nil
+}
+
+// markSent removes an entry once its message was published
+func (o *outbox) markSent(id string) error {
+	if err := os.Remove(o.path(id)); err != nil && !os.IsNotExist(err) {
+		return fmt.Errorf("failed to clear outbox entry %s: %w", id, err)
+	}
+	return nil
+}
+
+// pending returns the unsent entries in recording order. Leftover temp files from an
+// interrupted write were never committed, so they are discarded.
+func (o *outbox) pending() ([]outboxEntry, error) {
+	files, err := os.ReadDir(o.dir)
+	if err != nil {
+		return nil, fmt.Errorf("failed to list outbox: %w", err)
+	}
+
+	var entries []outboxEntry
+	for _, file := range files {
+		name := file.Name()
+		if strings.HasSuffix(name, ".tmp") {
+			os.Remove(filepath.Join(o.dir, name))
+			continue
+		}
+		if !strings.HasSuffix(name, ".json") {
+			continue
+		}
+
+		raw, err := os.ReadFile(filepath.Join(o.dir, name))
+		if err != nil {
+			return nil, fmt.Errorf("failed to read outbox entry %s: %w", name, err)
+		}
+		var entry outboxEntry
+		if err := json.Unmarshal(raw, &entry); err != nil {
+			log.Printf("Warning: Skipping unreadable outbox entry %s: %v", name, err)
+			continue
+		}
+		entries = append(entries, entry)
+	}
+
+	sort.Slice(entries, func(i, j int) bool { return entries[i].ID < entries[j].ID })
+	return entries, nil
+}
+
+// flush republishes every unsent entry, oldest first, stopping at the first failure so
+// messages keep their order. It returns how many entries were sent.
+func (o *outbox) flush(publish func(topic string, data []byte) error) (int, error) {
+	entries, err := o.pending()
+	if err != nil {
+		return 0, err
+	}
+
+	for i, entry := range entries {
+		if err := publish(entry.Topic, entry.Data); err != nil {
+			return i, fmt.Errorf("failed to republish outbox entry %s: %w", entry.ID, err)
+		}
+		if err := o.markSent(entry.ID); err != nil {
+			return i + 1, err
+		}
+	}
+	return len(entries), nil
+}
+
+// publish sends a NATS message through the outbox: the message is recorded, published, then
+// marked sent. A failed publish leaves the entry for the startup sweep. Without an outbox the
+// message is published directly.
+func (s *SettlementService) publish(topic string, data []byte) error {
+	if s.outbox == nil {
+		return s.nc.Publish(topic, data)
+	}
+
+	entry, err := s.outbox.record(topic, data, time.Now())
+	if err != nil {
+		log.Printf("Warning: Publishing %s without an outbox record: %v", topic, err)
+		return s.nc.Publish(topic, data)
+	}
+	if err := s.nc.Publish(topic, data); err != nil {
+		return err
+	}
+	return s.outbox.markSent(entry.ID)
+}
+
+// sweepOutbox republishes messages recorded before a crash but never sent
+func (s *SettlementService) sweepOutbox() {
+	if s.outbox == nil {
+		return
+	}
+
+	sent, err := s.outbox.flush(s.nc.Publish)
+	if err != nil {
+		log.Printf("Warning: Outbox sweep stopped after %d messages: %v", sent, err)
+		return
+	}
+	if sent > 0 {
+		log.Printf("📤 Republished %d unsent outbox messages", sent)
+	}
+}
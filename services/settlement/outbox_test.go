@@ -0,0 +1,91 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+)
+
+type publishedMessage struct {
+	topic string
+	data  string
+}
+
+func recordingPublisher(sent *[]publishedMessage) func(topic string, data []byte) error {
+	return func(topic string, data []byte) error {
+		*sent = append(*sent, publishedMessage{topic: topic, data: string(data)})
+		return nil
+	}
+}
+
+func TestOutboxRepublishesUnsentAfterRestart(t *testing.T) {
+	dir := t.TempDir()
+	before, err := openOutbox(dir)
+	if err != nil {
+		t.Fatalf("Failed to open outbox: %v", err)
+	}
+
+	// One message goes out normally; the next is recorded and then the service crashes
+	now := time.Now()
+	sentEntry, err := before.record("settlement.record.p0", []byte(`{"n":0}`), now)
+	if err != nil {
+		t.Fatalf("Failed to record: %v", err)
+	}
+	if err := before.markSent(sentEntry.ID); err != nil {
+		t.Fatalf("Failed to mark sent: %v", err)
+	}
+	if _, err := before.record("settlement.secret.p1", []byte(`{"n":1}`), now); err != nil {
+		t.Fatalf("Failed to record: %v", err)
+	}
+	if _, err := before.record("settlement.secret.p1", []byte(`{"n":2}`), now); err != nil {
+		t.Fatalf("Failed to record: %v", err)
+	}
+	// A write interrupted by the crash was never committed
+	if err := os.WriteFile(filepath.Join(dir, "torn.json.tmp"), []byte("{"), 0600); err != nil {
+		t.Fatalf("Failed to write temp file: %v", err)
+	}
+
+	after, err := openOutbox(dir)
+	if err != nil {
+		t.Fatalf("Failed to reopen outbox: %v", err)
+	}
+	var sent []publishedMessage
+	n, err := after.flush(recordingPublisher(&sent))
+	if err != nil {
+		t.Fatalf("Failed to flush: %v", err)
+	}
+	if n != 2 || len(sent) != 2 || sent[0].data != `{"n":1}` || sent[1].data != `{"n":2}` ||
+		sent[0].topic != "settlement.secret.p1" {
+		t.Fatalf("Expected the two unsent messages in order, got %v", sent)
+	}
+
+	if pending, err := after.pending(); err != nil || len(pending) != 0 {
+		t.Errorf("Outbox should be empty after the sweep, got %v, %v", pending, err)
+	}
+	if _, err := os.Stat(filepath.Join(dir, "torn.json.tmp")); !os.IsNotExist(err) {
+		t.Error("Uncommitted temp file should be discarded")
+	}
+}
+
+func TestPublishKeepsEntryWhenSendFails(t *testing.T) {
+	s := newTestService()
+	ob, err := openOutbox(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to open outbox: %v", err)
+	}
+	s.outbox = ob
+
+	// The test service has no NATS connection, so the publish itself fails
+	if err := s.publish("settlement.htlc.p1", []byte("params")); err == nil {
+		t.Fatal("Expected the publish to fail without a connection")
+	}
+
+	var sent []publishedMessage
+	if _, err := ob.flush(recordingPublisher(&sent)); err != nil {
+		t.Fatalf("Failed to flush: %v", err)
+	}
+	if len(sent) != 1 || sent[0].topic != "settlement.htlc.p1" || sent[0].data != "params" {
+		t.Errorf("Failed publish should be retried from the outbox, got %v", sent)
+	}
+}
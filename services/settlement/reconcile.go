@@ -0,0 +1,147 @@
+package main
+
+import (
+	"fmt"
+	"log"
+	"math"
+	"time"
+
+	"github.com/blacktrace/blacktrace/connectors/zcash"
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+// reconcileInterval is how often local settlement state is checked against the chains
+const reconcileInterval = 5 * time.Minute
+
+// legObservation is a swap leg's state as seen on-chain
+type legObservation struct {
+	Locked  bool
+	Claimed bool
+}
+
+// legObserver reports the on-chain state of one leg of a settlement. Chains that only relay
+// instructions to the users' wallets cannot see the leg themselves and do not implement it.
+type legObserver interface {
+	Observe(state *SettlementState) (legObservation, error)
+}
+
+// zcashObserver reads the ZEC leg from the Zcash node
+type zcashObserver struct {
+	client *zcash.Client
+}
+
+// txConfirmed reports whether a transaction is known to the node with at least one confirmation
+func (o *zcashObserver) txConfirmed(txid string) (bool, error) {
+	if txid == "" {
+		return false, nil
+	}
+	tx, err := o.client.GetTransaction(txid)
+	if err != nil {
+		return false, err
+	}
+	confirmations, _ := tx["confirmations"].(float64)
+	return confirmations > 0, nil
+}
+
+// Observe treats the leg as locked once its lock transaction confirms or the HTLC address holds
+// the swap amount, and as claimed once its claim transaction confirms
+func (o *zcashObserver) Observe(state *SettlementState) (legObservation, error) {
+	var obs legObservation
+	if state.HTLCP2SHAddress == "" {
+		return obs, nil
+	}
+
+	locked, err := o.txConfirmed(state.HTLCLockTxID)
+	if err != nil {
+		return obs, fmt.Errorf("failed to fetch Zcash lock transaction: %w", err)
+	}
+	if !locked {
+		balance, err := o.client.GetAddressBalance(state.HTLCP2SHAddress)
+		if err != nil {
+			return obs, fmt.Errorf("failed to fetch HTLC balance: %w", err)
+		}
+		locked = balance > 0 && uint64(math.Round(balance*1e8)) >= state.AmountZEC
+	}
+	obs.Locked = locked
+
+	claimed, err := o.txConfirmed(state.HTLCClaimTxID)
+	if err != nil {
+		return obs, fmt.Errorf("failed to fetch Zcash claim transaction: %w", err)
+	}
+	obs.Claimed = claimed
+	return obs, nil
+}
+
+// reconcileLeg brings one leg's local flags in line with what the chain reports. A lock or claim
+// the chain shows but local state missed is recorded; a flag the chain does not show yet is only
+// logged, since the chain's view may lag a transaction that is still propagating.
+// Returns whether local state changed. Caller must hold s.mu.
+func reconcileLeg(state *SettlementState, leg string, obs legObservation, locked *bool, claimed bool) bool {
+	changed := false
+	if obs.Locked && !*locked {
+		log.Printf("🔎 Reconcile %s: %s lock is on-chain but was missed locally - marking locked", state.ProposalID, leg)
+		*locked = true
+		state.UpdatedAt = time.Now()
+		changed = true
+	} else if !obs.Locked && *locked && !claimed {
+		log.Printf("Warning: Reconcile %s: %s marked locked but no lock is visible on-chain", state.ProposalID, leg)
+	}
+
+	if obs.Claimed && !claimed {
+		log.Printf("🔎 Reconcile %s: %s claim is on-chain but was missed locally - marking claimed", state.ProposalID, leg)
+		state.markClaimed(leg)
+		changed = true
+	} else if !obs.Claimed && claimed {
+		log.Printf("Warning: Reconcile %s: %s marked claimed but no claim is visible on-chain", state.ProposalID, leg)
+	}
+	return changed
+}
+
+// reconcile compares every open settlement with the chains and corrects local lock and claim
+// flags that missed an on-chain event. Returns the IDs of the settlements corrected.
+func (s *SettlementService) reconcile() []string {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	var corrected []string
+	for id, state := range s.settlements {
+		if swap.Terminal(state.Swap.State()) {
+			continue
+		}
+		changed := false
+
+		if s.zecLeg != nil {
+			if obs, err := s.zecLeg.Observe(state); err != nil {
+				log.Printf("Warning: Reconcile %s: ZEC leg: %v", id, err)
+			} else if reconcileLeg(state, "zec", obs, &state.ZECLocked, state.ZECClaimed) {
+				changed = true
+			}
+		}
+
+		if observer, ok := s.chains[state.Chain].(legObserver); ok {
+			if obs, err := observer.Observe(state); err != nil {
+				log.Printf("Warning: Reconcile %s: %s leg: %v", id, state.Chain, err)
+			} else if reconcileLeg(state, "usdc", obs, &state.USDCLocked, state.USDCClaimed) {
+				changed = true
+			}
+		}
+
+		if changed {
+			s.saveState(state)
+			corrected = append(corrected, id)
+		}
+	}
+	return corrected
+}
+
+// reconcileLoop periodically reconciles local settlement state with the chains
+func (s *SettlementService) reconcileLoop() {
+	ticker := time.NewTicker(reconcileInterval)
+	defer ticker.Stop()
+
+	for range ticker.C {
+		if corrected := s.reconcile(); len(corrected) > 0 {
+			log.Printf("🔎 Reconciliation corrected %d settlements: %v", len(corrected), corrected)
+		}
+	}
+}
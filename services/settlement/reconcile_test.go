@@ -0,0 +1,76 @@
+package main
+
+import (
+	"errors"
+	"testing"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+// mockObserver reports a fixed on-chain view of a leg
+type mockObserver struct {
+	obs legObservation
+	err error
+}
+
+func (o *mockObserver) Observe(state *SettlementState) (legObservation, error) {
+	return o.obs, o.err
+}
+
+// observingChain is a mockChain that can also see its leg on-chain
+type observingChain struct {
+	mockChain
+	mockObserver
+}
+
+func TestReconcileRecordsMissedLock(t *testing.T) {
+	s := newTestService()
+	solana := &observingChain{mockChain: mockChain{name: "solana"}}
+	s.registerChain(solana)
+	s.zecLeg = &mockObserver{obs: legObservation{Locked: true}}
+
+	// The ZEC lock was seen, but the taker's stablecoin lock report never arrived
+	state := &SettlementState{ProposalID: "p1", Chain: "solana", Status: "alice_locked", ZECLocked: true}
+	state.Swap = swap.Restore(swap.FunderLocked)
+	s.settlements["p1"] = state
+
+	if corrected := s.reconcile(); len(corrected) != 0 {
+		t.Fatalf("Nothing should be corrected while the chains agree, got %v", corrected)
+	}
+
+	solana.obs = legObservation{Locked: true}
+	corrected := s.reconcile()
+	if len(corrected) != 1 || corrected[0] != "p1" {
+		t.Fatalf("Expected p1 to be corrected, got %v", corrected)
+	}
+	if !state.USDCLocked {
+		t.Error("Stablecoin lock seen on-chain should be recorded locally")
+	}
+	if !state.ZECLocked || state.ZECClaimed || state.USDCClaimed {
+		t.Errorf("Other flags should be untouched, got zec_locked=%v zec_claimed=%v usdc_claimed=%v",
+			state.ZECLocked, state.ZECClaimed, state.USDCClaimed)
+	}
+}
+
+func TestReconcileOnlyLogsWhatTheChainCannotSee(t *testing.T) {
+	s := newTestService()
+	s.registerChain(&mockChain{name: "starknet"}) // relays to wallets, cannot observe
+	s.zecLeg = &mockObserver{err: errors.New("node unreachable")}
+
+	state := &SettlementState{ProposalID: "p1", Chain: "starknet", ZECLocked: true, USDCLocked: true}
+	state.Swap = swap.Restore(swap.ClaimerLocked)
+	s.settlements["p1"] = state
+
+	if corrected := s.reconcile(); len(corrected) != 0 {
+		t.Fatalf("Unobservable legs should not be corrected, got %v", corrected)
+	}
+	if !state.ZECLocked || !state.USDCLocked {
+		t.Error("Flags should be kept when the chain cannot be read")
+	}
+
+	// A flag the chain does not show yet is kept; the chain may be lagging
+	s.zecLeg = &mockObserver{}
+	if corrected := s.reconcile(); len(corrected) != 0 || !state.ZECLocked {
+		t.Errorf("A lock not yet visible on-chain should be kept, got %v", corrected)
+	}
+}
@@ -0,0 +1,113 @@
+package main
+
+import (
+	"encoding/json"
+	"log"
+	"time"
+
+	"github.com/nats-io/nats.go"
+)
+
+// TimeRange selects settlements created in [From, To)
+type TimeRange struct {
+	From time.Time `json:"from"`
+	To   time.Time `json:"to"`
+}
+
+// SettlementReport summarizes settlements over a time range
+type SettlementReport struct {
+	Range                TimeRange      `json:"range"`
+	CountsByStatus       map[string]int `json:"counts_by_status"`
+	Completed            int            `json:"completed"`
+	Failed               int            `json:"failed"`
+	Pending              int            `json:"pending"`
+	VolumeZEC            uint64         `json:"volume_zec"`  // Zatoshis across completed settlements
+	VolumeUSDC           uint64         `json:"volume_usdc"` // USDC across completed settlements
+	AvgCompletionSeconds float64        `json:"avg_completion_seconds"`
+}
+
+// isFailedStatus reports whether a settlement status is terminal without a completed swap
+func isFailedStatus(status string) bool {
+	return status == "rejected" || status == "refunding"
+}
+
+// report summarizes settlements created within the given range. With a state directory the
+// figures come from the saved snapshots, so they include settlements from before a restart;
+// without one only the settlements this process has seen are counted.
+func (s *SettlementService) report(r TimeRange) SettlementReport {
+	s.mu.RLock()
+	defer s.mu.RUnlock()
+
+	if s.states != nil {
+		states, err := s.states.load()
+		if err == nil {
+			return summarize(r, states)
+		}
+		log.Printf("Warning: Reporting from memory, saved settlements unreadable: %v", err)
+	}
+
+	states := make([]*SettlementState, 0, len(s.settlements))
+	for _, state := range s.settlements {
+		states = append(states, state)
+	}
+	return summarize(r, states)
+}
+
+// summarize aggregates the settlements created within the given range
+func summarize(r TimeRange, states []*SettlementState) SettlementReport {
+	rep := SettlementReport{
+		Range:          r,
+		CountsByStatus: make(map[string]int),
+	}
+
+	var totalCompletion time.Duration
+	for _, state := range states {
+		if state.CreatedAt.Before(r.From) || !state.CreatedAt.Before(r.To) {
+			continue
+		}
+
+		rep.CountsByStatus[state.Status]++
+
+		switch {
+		case state.Status == "completed":
+			rep.Completed++
+			rep.VolumeZEC += state.AmountZEC
+			rep.VolumeUSDC += state.AmountUSDC
+			totalCompletion += state.CompletedAt.Sub(state.CreatedAt)
+		case isFailedStatus(state.Status):
+			rep.Failed++
+		default:
+			rep.Pending++
+		}
+	}
+
+	if rep.Completed > 0 {
+		rep.AvgCompletionSeconds = totalCompletion.Seconds() / float64(rep.Completed)
+	}
+
+	return rep
+}
+
+// handleReportQuery answers settlement.query.report requests; an empty range means the last 24 hours
+func (s *SettlementService) handleReportQuery(msg *nats.Msg) {
+	var r TimeRange
+	if len(msg.Data) > 0 {
+		if err := json.Unmarshal(msg.Data, &r); err != nil {
+			log.Printf("Error parsing report query: %v", err)
+			errJSON, _ := json.Marshal(map[string]string{"error": "invalid time range"})
+			msg.Respond(errJSON)
+			return
+		}
+	}
+	if r.To.IsZero() {
+		r.To = time.Now()
+	}
+	if r.From.IsZero() {
+		r.From = r.To.Add(-24 * time.Hour)
+	}
+
+	reportJSON, _ := json.Marshal(s.report(r))
+	if err := msg.Respond(reportJSON); err != nil {
+		log.Printf("Error responding to report query: %v", err)
+	}
+}
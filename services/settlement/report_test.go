@@ -0,0 +1,99 @@
+package main
+
+import (
+	"testing"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+func TestSettlementReportAggregates(t *testing.T) {
+	s := newTestService()
+	base := time.Date(2025, 1, 15, 0, 0, 0, 0, time.UTC)
+
+	add := func(id, status string, created time.Time, zec, usdc uint64, completedAfter time.Duration) {
+		state := &SettlementState{
+			ProposalID: id,
+			Status:     status,
+			AmountZEC:  zec,
+			AmountUSDC: usdc,
+			CreatedAt:  created,
+		}
+		if status == "completed" {
+			state.CompletedAt = created.Add(completedAfter)
+		}
+		s.settlements[id] = state
+	}
+
+	add("c1", "completed", base.Add(1*time.Hour), 100000000, 4500, 10*time.Minute)
+	add("c2", "completed", base.Add(2*time.Hour), 200000000, 9000, 20*time.Minute)
+	add("r1", "rejected", base.Add(3*time.Hour), 0, 0, 0)
+	add("f1", "refunding", base.Add(4*time.Hour), 50000000, 2250, 0)
+	add("p1", "alice_locked", base.Add(5*time.Hour), 10000000, 450, 0)
+	add("p2", "ready", base.Add(6*time.Hour), 10000000, 450, 0)
+	// Outside the range
+	add("old", "completed", base.Add(-time.Hour), 999, 999, time.Minute)
+
+	rep := s.report(TimeRange{From: base, To: base.Add(24 * time.Hour)})
+
+	if rep.Completed != 2 || rep.Failed != 2 || rep.Pending != 2 {
+		t.Errorf("Expected 2/2/2 completed/failed/pending, got %d/%d/%d", rep.Completed, rep.Failed, rep.Pending)
+	}
+	if rep.CountsByStatus["completed"] != 2 || rep.CountsByStatus["rejected"] != 1 ||
+		rep.CountsByStatus["refunding"] != 1 || rep.CountsByStatus["alice_locked"] != 1 || rep.CountsByStatus["ready"] != 1 {
+		t.Errorf("Unexpected counts by status: %v", rep.CountsByStatus)
+	}
+	if rep.VolumeZEC != 300000000 {
+		t.Errorf("Expected ZEC volume 300000000, got %d", rep.VolumeZEC)
+	}
+	if rep.VolumeUSDC != 13500 {
+		t.Errorf("Expected USDC volume 13500, got %d", rep.VolumeUSDC)
+	}
+	if rep.AvgCompletionSeconds != 900 {
+		t.Errorf("Expected average completion 900s, got %v", rep.AvgCompletionSeconds)
+	}
+}
+
+func TestSettlementReportSurvivesRestart(t *testing.T) {
+	dir := t.TempDir()
+	base := time.Date(2025, 1, 15, 0, 0, 0, 0, time.UTC)
+
+	store, err := openStateStore(dir)
+	if err != nil {
+		t.Fatalf("Failed to open state store: %v", err)
+	}
+	before := newTestService()
+	before.states = store
+	for _, state := range []*SettlementState{
+		{ProposalID: "c1", Status: "completed", AmountZEC: 100000000, AmountUSDC: 4500, CreatedAt: base, CompletedAt: base.Add(10 * time.Minute)},
+		{ProposalID: "r1", Status: "rejected", CreatedAt: base.Add(time.Hour)},
+	} {
+		before.settlements[state.ProposalID] = state
+		before.saveState(state)
+	}
+
+	// A restarted service has nothing in memory until it resumes, but reports from the saved states
+	after := newTestService()
+	after.states = store
+	rep := after.report(TimeRange{From: base, To: base.Add(24 * time.Hour)})
+
+	if rep.Completed != 1 || rep.Failed != 1 || rep.Pending != 0 {
+		t.Errorf("Expected 1/1/0 completed/failed/pending, got %d/%d/%d", rep.Completed, rep.Failed, rep.Pending)
+	}
+	if rep.VolumeZEC != 100000000 || rep.VolumeUSDC != 4500 {
+		t.Errorf("Expected volumes 100000000/4500, got %d/%d", rep.VolumeZEC, rep.VolumeUSDC)
+	}
+	if rep.AvgCompletionSeconds != 600 {
+		t.Errorf("Expected average completion 600s, got %v", rep.AvgCompletionSeconds)
+	}
+
+	states, err := store.load()
+	if err != nil {
+		t.Fatalf("Failed to load states: %v", err)
+	}
+	for _, state := range states {
+		if state.ProposalID == "c1" && state.Swap.State() != swap.Complete {
+			t.Errorf("Expected c1 restored as %s, got %s", swap.Complete, state.Swap.State())
+		}
+	}
+}
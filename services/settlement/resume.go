@@ -0,0 +1,104 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"log"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+// resumeAction is what resume does to continue a restored settlement
+type resumeAction string
+
+const (
+	resumeNone             resumeAction = "none"               // Finished or abandoned; nothing to continue
+	resumeRepublishParams  resumeAction = "republish_params"   // ready: Alice still needs the HTLC params to lock ZEC
+	resumeRedispatchLock   resumeAction = "redispatch_lock"    // alice_locked: Bob's stablecoin lock is still outstanding
+	resumeRepublishSecret  resumeAction = "republish_secret"   // both_locked: the secret (or reveal instruction) must reach the claimer
+	resumeAwaitWindowLapse resumeAction = "await_window_lapse" // both_locked but the reveal window lapsed; the reveal monitor refunds it
+)
+
+// resumeActionFor picks the step that continues a settlement from its saved status and lock flags
+func (s *SettlementService) resumeActionFor(state *SettlementState, now time.Time) resumeAction {
+	switch state.Status {
+	case "ready":
+		return resumeRepublishParams
+	case "alice_locked":
+		if state.ZECLocked && !state.USDCLocked {
+			return resumeRedispatchLock
+		}
+	case "both_locked":
+		if !state.ZECLocked || !state.USDCLocked {
+			return resumeNone
+		}
+		if s.mode == ModeNonCustodial || state.secretLive(now, s.revealWindow) {
+			return resumeRepublishSecret
+		}
+		return resumeAwaitWindowLapse
+	}
+	return resumeNone
+}
+
+// resume re-registers a settlement restored from a snapshot and repeats the step it was waiting on.
+// The NATS status subscription and the reveal monitor cover every registered settlement, so
+// registering the state is what re-establishes its watchers; the returned action is the rest.
+func (s *SettlementService) resume(state *SettlementState) (resumeAction, error) {
+	if _, err := s.chainByName(state.Chain); err != nil {
+		return resumeNone, err
+	}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if _, ok := s.settlements[state.ProposalID]; ok {
+		return resumeNone, fmt.Errorf("%w %s", ErrDuplicateSettlement, state.ProposalID)
+	}
+	s.settlements[state.ProposalID] = state
+	if state.Swap.State() == swap.Negotiating {
+		state.Swap = swap.Restore(swapStateForStatus(state))
+	}
+
+	action := s.resumeActionFor(state, time.Now())
+	log.Printf("Resuming settlement %s at %s: %s", state.ProposalID, state.Status, action)
+
+	switch action {
+	case resumeRepublishParams:
+		s.publishHTLCParams(state)
+
+	case resumeRedispatchLock:
+		if err := s.lockStablecoinLeg(state); err != nil {
+			return action, fmt.Errorf("failed to re-dispatch stablecoin lock for %s: %w", state.ProposalID, err)
+		}
+
+	case resumeRepublishSecret:
+		if s.mode == ModeNonCustodial {
+			s.publishRevealInstruction(state)
+		} else {
+			s.publishSecret(state)
+		}
+		if err := s.claimStablecoinLeg(state); err != nil {
+			return action, fmt.Errorf("failed to re-dispatch stablecoin claim for %s: %w", state.ProposalID, err)
+		}
+	}
+
+	return action, nil
+}
+
+// publishHTLCParams publishes the HTLC parameters Alice locks ZEC against
+func (s *SettlementService) publishHTLCParams(state *SettlementState) {
+	htlcParams := map[string]interface{}{
+		"proposal_id": state.ProposalID,
+		"order_id":    state.OrderID,
+		"hash":        state.HashHex,
+		"timeout":     24 * 3600, // 24 hours in seconds
+		"status":      "ready",
+	}
+
+	paramsJSON, _ := json.Marshal(htlcParams)
+	topic := fmt.Sprintf("settlement.htlc.%s", state.ProposalID)
+	if err := s.publish(topic, paramsJSON); err != nil {
+		log.Printf("Error publishing HTLC params: %v", err)
+	}
+}
@@ -0,0 +1,106 @@
+package main
+
+import (
+	"bytes"
+	"testing"
+	"time"
+)
+
+// snapshotState is a settlement as it would be reloaded after a restart
+func snapshotState(status string) *SettlementState {
+	return &SettlementState{
+		ProposalID: "p1",
+		OrderID:    "order_1",
+		AmountZEC:  100000000,
+		AmountUSDC: 4500,
+		Secret:     bytes.Repeat([]byte{0xab}, 32),
+		HashHex:    "hash",
+		Status:     status,
+		Chain:      "starknet",
+		ZECTimeout: time.Now().Add(MakerTimelockBlocks * ZcashBlockInterval),
+	}
+}
+
+func TestResumeReadyRepublishesParams(t *testing.T) {
+	s := newTestService()
+	chain := &mockChain{name: "starknet"}
+	s.registerChain(chain)
+
+	state := snapshotState("ready")
+	action, err := s.resume(state)
+	if err != nil {
+		t.Fatalf("Failed to resume: %v", err)
+	}
+	if action != resumeRepublishParams {
+		t.Errorf("Expected %s, got %s", resumeRepublishParams, action)
+	}
+	if s.settlements["p1"] != state {
+		t.Error("Resumed settlement should be registered")
+	}
+	if len(chain.calls) != 0 {
+		t.Errorf("Nothing should be dispatched before Alice locks, got %v", chain.calls)
+	}
+}
+
+func TestResumeAliceLockedRedispatchesStablecoinLock(t *testing.T) {
+	s := newTestService()
+	chain := &mockChain{name: "starknet"}
+	s.registerChain(chain)
+
+	state := snapshotState("alice_locked")
+	state.ZECLocked = true
+	action, err := s.resume(state)
+	if err != nil {
+		t.Fatalf("Failed to resume: %v", err)
+	}
+	if action != resumeRedispatchLock {
+		t.Errorf("Expected %s, got %s", resumeRedispatchLock, action)
+	}
+	if len(chain.calls) != 1 || chain.calls[0] != "lock:p1" {
+		t.Errorf("Expected the stablecoin lock to be re-dispatched, got %v", chain.calls)
+	}
+	if state.StablecoinTimeout.IsZero() {
+		t.Error("Re-dispatched lock should set the stablecoin timeout")
+	}
+}
+
+func TestResumeBothLockedRepublishesSecret(t *testing.T) {
+	s := newTestService()
+	chain := &mockChain{name: "starknet"}
+	s.registerChain(chain)
+
+	state := snapshotState("both_locked")
+	state.ZECLocked = true
+	state.USDCLocked = true
+	state.SecretRevealedAt = time.Now().Add(-time.Minute)
+	action, err := s.resume(state)
+	if err != nil {
+		t.Fatalf("Failed to resume: %v", err)
+	}
+	if action != resumeRepublishSecret {
+		t.Errorf("Expected %s, got %s", resumeRepublishSecret, action)
+	}
+	if len(chain.calls) != 1 || chain.calls[0] != "claim:p1" {
+		t.Errorf("Expected the stablecoin claim to be re-dispatched, got %v", chain.calls)
+	}
+
+	// Once the reveal window has lapsed the secret is not re-published; the monitor refunds instead
+	lapsed := newTestService()
+	lapsedChain := &mockChain{name: "starknet"}
+	lapsed.registerChain(lapsedChain)
+
+	state = snapshotState("both_locked")
+	state.ZECLocked = true
+	state.USDCLocked = true
+	state.SecretRevealedAt = time.Now().Add(-2 * lapsed.revealWindow)
+	action, err = lapsed.resume(state)
+	if err != nil {
+		t.Fatalf("Failed to resume: %v", err)
+	}
+	if action != resumeAwaitWindowLapse {
+		t.Errorf("Expected %s, got %s", resumeAwaitWindowLapse, action)
+	}
+	if len(lapsedChain.calls) != 0 {
+		t.Errorf("Nothing should be dispatched after the window lapsed, got %v", lapsedChain.calls)
+	}
+}
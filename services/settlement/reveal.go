@@ -0,0 +1,124 @@
+package main
+
+import (
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"log"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+// DefaultRevealWindow is how long a revealed secret stays live before the swap is considered abandoned
+const DefaultRevealWindow = 2 * time.Hour
+
+// revealCheckInterval is how often the reveal monitor re-publishes live secrets and expires stale ones
+const revealCheckInterval = 60 * time.Second
+
+// zeroize overwrites a byte slice in place
+func zeroize(b []byte) {
+	for i := range b {
+		b[i] = 0
+	}
+}
+
+// scrubSecret zeroizes the settlement's secret and drops the reference. Caller must hold s.mu.
+func (state *SettlementState) scrubSecret() {
+	zeroize(state.Secret)
+	state.Secret = nil
+}
+
+// secretLive reports whether the secret is revealed and still within its reveal window
+func (state *SettlementState) secretLive(now time.Time, window time.Duration) bool {
+	return state.Status == "both_locked" && state.Secret != nil && !state.SecretRevealedAt.IsZero() &&
+		now.Sub(state.SecretRevealedAt) < window
+}
+
+// publishSecret publishes the secret reveal for a settlement. Caller must hold s.mu.
+func (s *SettlementService) publishSecret(state *SettlementState) {
+	secretReveal := map[string]interface{}{
+		"proposal_id": state.ProposalID,
+		"secret":      hex.EncodeToString(state.Secret),
+		"hash":        state.HashHex,
+		"status":      state.Status,
+	}
+
+	secretJSON, _ := json.Marshal(secretReveal)
+	topic := fmt.Sprintf("settlement.secret.%s", state.ProposalID)
+	if err := s.publish(topic, secretJSON); err != nil {
+		log.Printf("Error publishing secret reveal: %v", err)
+	}
+}
+
+// markClaimed records a confirmed claim on one leg and scrubs the secret once both legs are claimed.
+// Caller must hold s.mu.
+func (state *SettlementState) markClaimed(leg string) {
+	switch leg {
+	case "zec":
+		state.ZECClaimed = true
+	case "usdc":
+		state.USDCClaimed = true
+	}
+	state.UpdatedAt = time.Now()
+
+	if state.ZECClaimed && state.USDCClaimed {
+		state.scrubSecret()
+		log.Printf("✅ Both legs claimed for %s - secret scrubbed", state.ProposalID)
+	}
+}
+
+// expireRevealWindows scrubs secrets whose reveal window has lapsed. Settlements with no
+// confirmed claim on either leg are abandoned and moved to "refunding"; their IDs are returned.
+func (s *SettlementService) expireRevealWindows(now time.Time) []string {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	var expired []string
+	for id, state := range s.settlements {
+		if state.Secret == nil || state.SecretRevealedAt.IsZero() {
+			continue
+		}
+		if now.Sub(state.SecretRevealedAt) < s.revealWindow {
+			continue
+		}
+
+		state.scrubSecret()
+		state.UpdatedAt = now
+		if state.ZECClaimed || state.USDCClaimed {
+			s.saveState(state)
+			continue
+		}
+
+		state.Status = "refunding"
+		state.advanceSwap(swap.Refund)
+		expired = append(expired, id)
+
+		log.Printf("⏰ Reveal window lapsed for %s without claim confirmation - abandoned, refunding", id)
+
+		if err := s.refundStablecoinLeg(state); err != nil {
+			log.Printf("Error dispatching stablecoin refund for %s: %v", id, err)
+		}
+		s.saveState(state)
+	}
+
+	return expired
+}
+
+// revealMonitor re-publishes live secrets for late subscribers and abandons swaps whose window lapsed
+func (s *SettlementService) revealMonitor() {
+	ticker := time.NewTicker(revealCheckInterval)
+	defer ticker.Stop()
+
+	for now := range ticker.C {
+		s.expireRevealWindows(now)
+
+		s.mu.Lock()
+		for _, state := range s.settlements {
+			if state.secretLive(now, s.revealWindow) {
+				s.publishSecret(state)
+			}
+		}
+		s.mu.Unlock()
+	}
+}
@@ -0,0 +1,78 @@
+package main
+
+import (
+	"bytes"
+	"testing"
+	"time"
+)
+
+func newTestService() *SettlementService {
+	return &SettlementService{
+		settlements:  make(map[string]*SettlementState),
+		revealWindow: time.Hour,
+	}
+}
+
+func TestRevealWindowLapseMovesToRefunding(t *testing.T) {
+	s := newTestService()
+	secret := bytes.Repeat([]byte{0xab}, 32)
+	now := time.Now()
+
+	state := &SettlementState{
+		ProposalID:       "p1",
+		Status:           "both_locked",
+		Secret:           secret,
+		SecretRevealedAt: now.Add(-2 * time.Hour),
+	}
+	s.settlements["p1"] = state
+
+	expired := s.expireRevealWindows(now)
+
+	if len(expired) != 1 || expired[0] != "p1" {
+		t.Fatalf("Expected p1 to be abandoned, got %v", expired)
+	}
+	if state.Status != "refunding" {
+		t.Errorf("Expected status refunding, got %s", state.Status)
+	}
+	if state.Secret != nil {
+		t.Error("Secret should be dropped from state")
+	}
+	if !bytes.Equal(secret, make([]byte, 32)) {
+		t.Errorf("Secret bytes should be zeroized, got %x", secret)
+	}
+}
+
+func TestRevealWindowNotLapsedKeepsSecret(t *testing.T) {
+	s := newTestService()
+	now := time.Now()
+
+	state := &SettlementState{
+		ProposalID:       "p1",
+		Status:           "both_locked",
+		Secret:           []byte("secret"),
+		SecretRevealedAt: now.Add(-30 * time.Minute),
+	}
+	s.settlements["p1"] = state
+
+	if expired := s.expireRevealWindows(now); len(expired) != 0 {
+		t.Fatalf("Expected nothing to expire, got %v", expired)
+	}
+	if state.Status != "both_locked" || state.Secret == nil {
+		t.Error("Live settlement should be untouched")
+	}
+}
+
+func TestSecretScrubbedWhenBothLegsClaimed(t *testing.T) {
+	secret := []byte("secret")
+	state := &SettlementState{ProposalID: "p1", Status: "both_locked", Secret: secret}
+
+	state.markClaimed("usdc")
+	if state.Secret == nil {
+		t.Fatal("Secret should be kept until both legs are claimed")
+	}
+
+	state.markClaimed("zec")
+	if state.Secret != nil || !bytes.Equal(secret, make([]byte, len(secret))) {
+		t.Error("Secret should be zeroized once both legs are claimed")
+	}
+}
@@ -0,0 +1,179 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+)
+
+// Settlement message schema versions. Messages without a version field predate versioning and
+// are read as SchemaVersionLegacy.
+const (
+	SchemaVersionLegacy  = 1                  // settlement_chain optional, defaulting to legacyChain
+	SchemaVersionChain   = 2                  // settlement_chain required
+	SchemaVersionTyped   = 3                  // Amounts in unit-named fields (zec_zatoshi, usdc_minor)
+	SchemaVersionCurrent = SchemaVersionTyped // Version this service writes and upgrades to
+)
+
+// legacyChain is the chain every node settled on before requests named one
+const legacyChain = "ztarknet"
+
+// Schema errors
+var (
+	ErrUnsupportedVersion = errors.New("unsupported schema version")
+	ErrAmountMismatch     = errors.New("typed and legacy amounts disagree")
+)
+
+// requiredRequestFields must be present in every settlement request, whatever their value;
+// validateSettlementRequest then checks the values themselves. The ZEC amount is required too,
+// under its versioned name (see amountField).
+var requiredRequestFields = []string{
+	"proposal_id", "order_id", "maker_id", "taker_id", "price", "stablecoin", "timestamp",
+}
+
+// requiredStatusFields must be present in every status update
+var requiredStatusFields = []string{"proposal_id", "action"}
+
+// amountField names an amount on the wire: the unit-named field from SchemaVersionTyped on,
+// and the bare field older messages carry instead
+type amountField struct {
+	typed  string
+	legacy string
+}
+
+var (
+	zecAmount  = amountField{typed: "zec_zatoshi", legacy: "amount"}
+	usdcAmount = amountField{typed: "usdc_minor", legacy: "amount_usdc"}
+)
+
+// requiredStatusAmounts is the amount each lock action must carry
+var requiredStatusAmounts = map[string]amountField{
+	"alice_lock_zec": zecAmount,
+	"bob_lock_usdc":  usdcAmount,
+}
+
+// resolve sets the typed amount of a decoded message. Before SchemaVersionTyped the amount is read
+// from the legacy field; from it on a legacy field sent alongside must agree with the typed one.
+// A required amount that is absent is rejected rather than read as zero.
+func (f amountField) resolve(present map[string]json.RawMessage, version int, typed *uint64, legacy uint64, required bool) error {
+	if version < SchemaVersionTyped {
+		if required && !hasField(present, f.legacy) {
+			return fmt.Errorf("%s is required", f.legacy)
+		}
+		*typed = legacy
+		return nil
+	}
+
+	if required && !hasField(present, f.typed) {
+		return fmt.Errorf("%s is required", f.typed)
+	}
+	if hasField(present, f.legacy) && legacy != *typed {
+		return fmt.Errorf("%w: %s is %d, %s is %d", ErrAmountMismatch, f.typed, *typed, f.legacy, legacy)
+	}
+	return nil
+}
+
+// decodeSettlementRequest parses a settlement request, upgrading legacy requests to the current
+// schema. Unknown fields are deliberately ignored, so nodes on a newer schema can add fields
+// without this service rejecting their requests; missing required fields and versions newer than
+// SchemaVersionCurrent are rejected. On error the request holds whatever could be decoded, for
+// the rejection reply.
+func decodeSettlementRequest(data []byte) (SettlementRequest, error) {
+	var req SettlementRequest
+	if err := json.Unmarshal(data, &req); err != nil {
+		return req, err
+	}
+
+	version, err := schemaVersion(req.Version)
+	if err != nil {
+		return req, err
+	}
+
+	present, err := jsonFields(data)
+	if err != nil {
+		return req, err
+	}
+	required := requiredRequestFields
+	if version >= SchemaVersionChain {
+		required = append(required[:len(required):len(required)], "settlement_chain")
+	}
+	if err := requireFields(present, required); err != nil {
+		return req, err
+	}
+	if err := zecAmount.resolve(present, version, &req.ZECZatoshi, req.LegacyAmount, true); err != nil {
+		return req, err
+	}
+
+	if req.SettlementChain == "" {
+		req.SettlementChain = legacyChain
+	}
+	req.Version = SchemaVersionCurrent
+	return req, nil
+}
+
+// decodeStatusUpdate parses a status update from a node, with the same tolerance of unknown
+// fields and strictness on required ones as decodeSettlementRequest. Lock actions must carry the
+// amount of the leg they lock.
+func decodeStatusUpdate(data []byte) (SettlementStatusUpdate, error) {
+	var update SettlementStatusUpdate
+	if err := json.Unmarshal(data, &update); err != nil {
+		return update, err
+	}
+	version, err := schemaVersion(update.Version)
+	if err != nil {
+		return update, err
+	}
+	present, err := jsonFields(data)
+	if err != nil {
+		return update, err
+	}
+	if err := requireFields(present, requiredStatusFields); err != nil {
+		return update, err
+	}
+
+	required := requiredStatusAmounts[update.Action]
+	if err := zecAmount.resolve(present, version, &update.ZECZatoshi, update.LegacyAmount, required == zecAmount); err != nil {
+		return update, err
+	}
+	if err := usdcAmount.resolve(present, version, &update.USDCMinor, update.LegacyAmountUSDC, required == usdcAmount); err != nil {
+		return update, err
+	}
+	update.Version = SchemaVersionCurrent
+	return update, nil
+}
+
+// schemaVersion resolves a message's version, treating an absent one as legacy
+func schemaVersion(version int) (int, error) {
+	switch version {
+	case 0:
+		return SchemaVersionLegacy, nil
+	case SchemaVersionLegacy, SchemaVersionChain, SchemaVersionTyped:
+		return version, nil
+	}
+	return 0, fmt.Errorf("%w: %d (supported: %d-%d)", ErrUnsupportedVersion, version, SchemaVersionLegacy, SchemaVersionCurrent)
+}
+
+// jsonFields returns the raw fields of a JSON object, for presence checks
+func jsonFields(data []byte) (map[string]json.RawMessage, error) {
+	var present map[string]json.RawMessage
+	if err := json.Unmarshal(data, &present); err != nil {
+		return nil, err
+	}
+	return present, nil
+}
+
+// hasField reports whether a field is present with a non-null value
+func hasField(present map[string]json.RawMessage, field string) bool {
+	raw, ok := present[field]
+	return ok && string(raw) != "null"
+}
+
+// requireFields checks that each named field is present in the JSON object
+func requireFields(present map[string]json.RawMessage, fields []string) error {
+	for _, field := range fields {
+		if !hasField(present, field) {
+			return fmt.Errorf("%s is required", field)
+		}
+	}
+	return nil
+}
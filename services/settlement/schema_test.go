@@ -0,0 +1,166 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"strings"
+	"testing"
+	"time"
+)
+
+func TestDecodeSettlementRequestIgnoresUnknownFields(t *testing.T) {
+	req := validSettlementRequest(time.Now())
+	req.Version = SchemaVersionCurrent
+	data, _ := json.Marshal(req)
+
+	// A newer node adds fields this service does not know yet
+	var fields map[string]interface{}
+	if err := json.Unmarshal(data, &fields); err != nil {
+		t.Fatalf("Failed to unmarshal request: %v", err)
+	}
+	fields["fee_tier"] = "fast"
+	fields["routing"] = map[string]interface{}{"hops": 2}
+	data, _ = json.Marshal(fields)
+
+	decoded, err := decodeSettlementRequest(data)
+	if err != nil {
+		t.Fatalf("Unknown fields should be accepted, got: %v", err)
+	}
+	if decoded.ProposalID != req.ProposalID || decoded.ZECZatoshi != req.ZECZatoshi || decoded.SettlementChain != "ztarknet" {
+		t.Errorf("Decoded request does not match: %+v", decoded)
+	}
+}
+
+func TestDecodeSettlementRequestRejectsMissingRequiredField(t *testing.T) {
+	req := validSettlementRequest(time.Now())
+	data, _ := json.Marshal(req)
+
+	var fields map[string]interface{}
+	if err := json.Unmarshal(data, &fields); err != nil {
+		t.Fatalf("Failed to unmarshal request: %v", err)
+	}
+	delete(fields, "settlement_chain")
+	data, _ = json.Marshal(fields)
+
+	if _, err := decodeSettlementRequest(data); err == nil || !strings.Contains(err.Error(), "settlement_chain") {
+		t.Errorf("Expected missing settlement_chain to be rejected, got %v", err)
+	}
+}
+
+func TestDecodeSettlementRequestRejectsMissingAmount(t *testing.T) {
+	req := validSettlementRequest(time.Now())
+	data, _ := json.Marshal(req)
+
+	var fields map[string]interface{}
+	if err := json.Unmarshal(data, &fields); err != nil {
+		t.Fatalf("Failed to unmarshal request: %v", err)
+	}
+	delete(fields, "zec_zatoshi")
+
+	// A missing amount is rejected, not read as zero, even when the legacy field is sent instead
+	for _, legacy := range []interface{}{nil, req.ZECZatoshi} {
+		if legacy != nil {
+			fields["amount"] = legacy
+		}
+		data, _ = json.Marshal(fields)
+		if _, err := decodeSettlementRequest(data); err == nil || !strings.Contains(err.Error(), "zec_zatoshi") {
+			t.Errorf("Expected missing zec_zatoshi to be rejected (amount=%v), got %v", legacy, err)
+		}
+	}
+
+	// Legacy requests carry the amount in the bare field, and still must carry it
+	fields["version"] = SchemaVersionChain
+	data, _ = json.Marshal(fields)
+	decoded, err := decodeSettlementRequest(data)
+	if err != nil || decoded.ZECZatoshi != req.ZECZatoshi {
+		t.Fatalf("Legacy amount should be read as zec_zatoshi, got %d, %v", decoded.ZECZatoshi, err)
+	}
+	delete(fields, "amount")
+	data, _ = json.Marshal(fields)
+	if _, err := decodeSettlementRequest(data); err == nil || !strings.Contains(err.Error(), "amount") {
+		t.Errorf("Expected missing legacy amount to be rejected, got %v", err)
+	}
+}
+
+func TestDecodeSettlementRequestRejectsInconsistentAmounts(t *testing.T) {
+	req := validSettlementRequest(time.Now())
+	req.LegacyAmount = req.ZECZatoshi
+	data, _ := json.Marshal(req)
+	if _, err := decodeSettlementRequest(data); err != nil {
+		t.Fatalf("Agreeing amounts should be accepted, got %v", err)
+	}
+
+	req.LegacyAmount = req.ZECZatoshi / 100
+	data, _ = json.Marshal(req)
+	if _, err := decodeSettlementRequest(data); !errors.Is(err, ErrAmountMismatch) {
+		t.Errorf("Expected ErrAmountMismatch, got %v", err)
+	}
+}
+
+func TestDecodeSettlementRequestVersions(t *testing.T) {
+	req := validSettlementRequest(time.Now())
+	req.Version = 0
+	req.SettlementChain = ""
+	req.LegacyAmount, req.ZECZatoshi = req.ZECZatoshi, 0
+	data, _ := json.Marshal(req)
+
+	// Unversioned requests are legacy ones, which settled on ztarknet without naming it
+	decoded, err := decodeSettlementRequest(data)
+	if err != nil {
+		t.Fatalf("Legacy request should be accepted, got: %v", err)
+	}
+	if decoded.Version != SchemaVersionCurrent || decoded.SettlementChain != legacyChain || decoded.ZECZatoshi != req.LegacyAmount {
+		t.Errorf("Legacy request should be upgraded, got version %d chain %q amount %d",
+			decoded.Version, decoded.SettlementChain, decoded.ZECZatoshi)
+	}
+
+	req.Version = SchemaVersionCurrent + 1
+	data, _ = json.Marshal(req)
+	if _, err := decodeSettlementRequest(data); !errors.Is(err, ErrUnsupportedVersion) {
+		t.Errorf("Expected ErrUnsupportedVersion, got %v", err)
+	}
+}
+
+func TestDecodeStatusUpdate(t *testing.T) {
+	if _, err := decodeStatusUpdate([]byte(`{"proposal_id":"p1","action":"alice_lock_zec","amount":1,"memo":"new"}`)); err != nil {
+		t.Errorf("Unknown fields should be accepted, got: %v", err)
+	}
+	if _, err := decodeStatusUpdate([]byte(`{"proposal_id":"p1","amount":1}`)); err == nil {
+		t.Error("Expected missing action to be rejected")
+	}
+	if _, err := decodeStatusUpdate([]byte(`{"version":9,"proposal_id":"p1","action":"alice_lock_zec"}`)); !errors.Is(err, ErrUnsupportedVersion) {
+		t.Errorf("Expected ErrUnsupportedVersion, got %v", err)
+	}
+}
+
+func TestDecodeStatusUpdateRequiresLockAmount(t *testing.T) {
+	cases := map[string]struct {
+		data    string
+		missing string
+	}{
+		"zec lock":            {`{"version":3,"proposal_id":"p1","action":"alice_lock_zec"}`, "zec_zatoshi"},
+		"usdc lock":           {`{"version":3,"proposal_id":"p1","action":"bob_lock_usdc"}`, "usdc_minor"},
+		"usdc lock null":      {`{"version":3,"proposal_id":"p1","action":"bob_lock_usdc","usdc_minor":null}`, "usdc_minor"},
+		"usdc lock wrong leg": {`{"version":3,"proposal_id":"p1","action":"bob_lock_usdc","zec_zatoshi":5}`, "usdc_minor"},
+		"legacy zec lock":     {`{"proposal_id":"p1","action":"alice_lock_zec"}`, "amount"},
+		"legacy usdc lock":    {`{"proposal_id":"p1","action":"bob_lock_usdc"}`, "amount_usdc"},
+	}
+	for name, tc := range cases {
+		if _, err := decodeStatusUpdate([]byte(tc.data)); err == nil || !strings.Contains(err.Error(), tc.missing+" is required") {
+			t.Errorf("%s: expected missing %s to be rejected, got %v", name, tc.missing, err)
+		}
+	}
+
+	// Claims carry no amount
+	if _, err := decodeStatusUpdate([]byte(`{"version":3,"proposal_id":"p1","action":"alice_claim_usdc"}`)); err != nil {
+		t.Errorf("Claim should need no amount, got %v", err)
+	}
+
+	update, err := decodeStatusUpdate([]byte(`{"proposal_id":"p1","action":"bob_lock_usdc","amount_usdc":4500}`))
+	if err != nil || update.USDCMinor != 4500 {
+		t.Errorf("Legacy amount_usdc should be read as usdc_minor, got %d, %v", update.USDCMinor, err)
+	}
+	if _, err := decodeStatusUpdate([]byte(`{"version":3,"proposal_id":"p1","action":"bob_lock_usdc","usdc_minor":4500,"amount_usdc":45}`)); !errors.Is(err, ErrAmountMismatch) {
+		t.Errorf("Expected ErrAmountMismatch, got %v", err)
+	}
+}
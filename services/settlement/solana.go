@@ -0,0 +1,85 @@
+package main
+
+import (
+	"encoding/hex"
+	"fmt"
+	"math"
+	"math/bits"
+	"time"
+)
+
+// Base units of each leg: the ZEC leg is in zatoshis, the Solana leg in lamports
+const (
+	zatoshisPerZEC = 100_000_000
+	lamportsPerSOL = 1_000_000_000
+)
+
+// SolanaLockParams are the inputs to the Solana HTLC contract's lock instruction
+type SolanaLockParams struct {
+	HashLock [20]byte // HASH160 of the secret, same hash as the Zcash HTLC
+	Amount   uint64   // Lamports locked on Solana
+	Timeout  int64    // Unix timestamp after which the sender can refund
+}
+
+// solanaLockLamports is the SOL leg of a swap in lamports: amountZEC zatoshis at price SOL per ZEC
+func solanaLockLamports(amountZEC, price uint64) (uint64, error) {
+	if amountZEC == 0 || price == 0 {
+		return 0, fmt.Errorf("lock amount must be greater than 0: %d zatoshis at price %d", amountZEC, price)
+	}
+	hi, zatoshiPrice := bits.Mul64(amountZEC, price)
+	hi2, lamports := bits.Mul64(zatoshiPrice, lamportsPerSOL/zatoshisPerZEC)
+	if hi != 0 || hi2 != 0 {
+		return 0, fmt.Errorf("lock amount overflows: %d zatoshis at price %d", amountZEC, price)
+	}
+	return lamports, nil
+}
+
+// toSolanaLockParams converts a settlement's agreed terms into Solana lock inputs.
+// The amount is the SOL leg in lamports, from the ZEC amount and the price in SOL per ZEC;
+// AmountUSDC is the stablecoin total and is not used. The timeout is derived from the Zcash HTLC locktime: the remaining blocks at
+// currentHeight, less TimelockSafetyMarginBlocks so the taker's leg expires first,
+// multiplied by avgBlockSecs and measured from now.
+func (state *SettlementState) toSolanaLockParams(currentHeight uint64, avgBlockSecs uint64) (*SolanaLockParams, error) {
+	hash, err := hex.DecodeString(state.HashHex)
+	if err != nil {
+		return nil, fmt.Errorf("invalid secret hash: %w", err)
+	}
+	if len(hash) != 20 {
+		return nil, fmt.Errorf("invalid secret hash length: %d bytes (expected 20)", len(hash))
+	}
+
+	lamports, err := solanaLockLamports(state.AmountZEC, state.Price)
+	if err != nil {
+		return nil, err
+	}
+
+	if avgBlockSecs == 0 {
+		return nil, fmt.Errorf("average block time must be greater than 0")
+	}
+	if uint64(state.HTLCLocktime) <= currentHeight {
+		return nil, fmt.Errorf("HTLC locktime %d is not after current height %d", state.HTLCLocktime, currentHeight)
+	}
+
+	makerBlocks := uint64(state.HTLCLocktime) - currentHeight
+	if makerBlocks < TimelockSafetyMarginBlocks+MinTimelockBlocks {
+		return nil, fmt.Errorf("%w: only %d blocks left on maker's HTLC, need at least %d",
+			ErrInvalidProposal, makerBlocks, TimelockSafetyMarginBlocks+MinTimelockBlocks)
+	}
+	takerBlocks := makerBlocks - TimelockSafetyMarginBlocks
+	if err := validateTimelocks(makerBlocks, takerBlocks); err != nil {
+		return nil, err
+	}
+
+	now := time.Now().Unix()
+	if takerBlocks > uint64(math.MaxInt64-now)/avgBlockSecs {
+		return nil, fmt.Errorf("timeout overflows: %d blocks at %ds per block", takerBlocks, avgBlockSecs)
+	}
+
+	params := &SolanaLockParams{
+		Amount:  lamports,
+		Timeout: now + int64(takerBlocks*avgBlockSecs),
+	}
+	copy(params.HashLock[:], hash)
+
+	return params, nil
+}
@@ -0,0 +1,70 @@
+package main
+
+import (
+	"encoding/hex"
+	"math"
+	"testing"
+	"time"
+)
+
+func TestToSolanaLockParams(t *testing.T) {
+	_, hashHex, err := generateSecretAndHash()
+	if err != nil {
+		t.Fatalf("Failed to generate secret: %v", err)
+	}
+
+	// 1.5 ZEC at 3 SOL per ZEC; the stablecoin total must not leak into the SOL leg
+	state := &SettlementState{
+		HashHex:      hashHex,
+		AmountZEC:    150_000_000,
+		Price:        3,
+		AmountUSDC:   450_000_000,
+		HTLCLocktime: 1144,
+	}
+
+	before := time.Now().Unix()
+	params, err := state.toSolanaLockParams(1000, 75)
+	if err != nil {
+		t.Fatalf("Unexpected error: %v", err)
+	}
+	after := time.Now().Unix()
+
+	if len(params.HashLock) != 20 {
+		t.Fatalf("Expected 20-byte hash lock, got %d", len(params.HashLock))
+	}
+	if hex.EncodeToString(params.HashLock[:]) != hashHex {
+		t.Errorf("Hash lock does not round-trip: got %x, want %s", params.HashLock, hashHex)
+	}
+	if params.Amount != 4_500_000_000 {
+		t.Errorf("Expected 4.5 SOL (4500000000 lamports), got %d", params.Amount)
+	}
+
+	// (144 - 72 safety margin) blocks * 75s = 1.5 hours
+	if params.Timeout < before+5400 || params.Timeout > after+5400 {
+		t.Errorf("Expected timeout ~1.5h from now, got %d (now %d)", params.Timeout, before)
+	}
+}
+
+func TestToSolanaLockParamsValidation(t *testing.T) {
+	valid := SettlementState{HashHex: hex.EncodeToString(make([]byte, 20)), AmountZEC: 1, Price: 1, HTLCLocktime: 200}
+
+	cases := map[string]func(s *SettlementState) (uint64, uint64){
+		"bad hash":         func(s *SettlementState) (uint64, uint64) { s.HashHex = "zz"; return 100, 75 },
+		"short hash":       func(s *SettlementState) (uint64, uint64) { s.HashHex = "abcd"; return 100, 75 },
+		"zero amount":      func(s *SettlementState) (uint64, uint64) { s.AmountZEC = 0; return 100, 75 },
+		"zero price":       func(s *SettlementState) (uint64, uint64) { s.Price = 0; return 100, 75 },
+		"amount overflow":  func(s *SettlementState) (uint64, uint64) { s.AmountZEC = math.MaxUint64 / 5; s.Price = 1; return 100, 75 },
+		"expired locktime": func(s *SettlementState) (uint64, uint64) { return 200, 75 },
+		"inside margin":    func(s *SettlementState) (uint64, uint64) { return 120, 75 },
+		"zero block time":  func(s *SettlementState) (uint64, uint64) { return 100, 0 },
+		"timeout overflow": func(s *SettlementState) (uint64, uint64) { s.HTLCLocktime = 1 << 31; return 0, 1 << 40 },
+	}
+
+	for name, mutate := range cases {
+		state := valid
+		height, blockSecs := mutate(&state)
+		if _, err := state.toSolanaLockParams(height, blockSecs); err == nil {
+			t.Errorf("%s: expected error, got nil", name)
+		}
+	}
+}
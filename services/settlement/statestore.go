@@ -0,0 +1,125 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"log"
+	"net/url"
+	"os"
+	"path/filepath"
+	"strings"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+// stateStore keeps a snapshot of every settlement on disk, one file per proposal, rewritten
+// atomically whenever the settlement changes. Reports are built from it, so they cover
+// settlements from before a restart, and Start resumes the open ones from it.
+type stateStore struct {
+	dir string
+}
+
+// openStateStore opens (creating if needed) the settlement state directory
+func openStateStore(dir string) (*stateStore, error) {
+	if err := os.MkdirAll(dir, 0700); err != nil {
+		return nil, fmt.Errorf("failed to create state directory: %w", err)
+	}
+	return &stateStore{dir: dir}, nil
+}
+
+func (st *stateStore) path(proposalID string) string {
+	return filepath.Join(st.dir, url.PathEscape(proposalID)+".json")
+}
+
+// save writes a settlement's snapshot, replacing the previous one. The swap lifecycle is not
+// encoded; load rebuilds it from the saved status.
+func (st *stateStore) save(state *SettlementState) error {
+	encoded, err := json.Marshal(state)
+	if err != nil {
+		return err
+	}
+
+	final := st.path(state.ProposalID)
+	tmp := final + ".tmp"
+	if err := os.WriteFile(tmp, encoded, 0600); err != nil {
+		return fmt.Errorf("failed to write settlement state: %w", err)
+	}
+	if err := os.Rename(tmp, final); err != nil {
+		os.Remove(tmp)
+		return fmt.Errorf("failed to commit settlement state: %w", err)
+	}
+	return nil
+}
+
+// load reads every saved settlement. Leftover temp files from an interrupted write were never
+// committed, so they are discarded.
+func (st *stateStore) load() ([]*SettlementState, error) {
+	files, err := os.ReadDir(st.dir)
+	if err != nil {
+		return nil, fmt.Errorf("failed to list settlement states: %w", err)
+	}
+
+	var states []*SettlementState
+	for _, file := range files {
+		name := file.Name()
+		if strings.HasSuffix(name, ".tmp") {
+			os.Remove(filepath.Join(st.dir, name))
+			continue
+		}
+		if !strings.HasSuffix(name, ".json") {
+			continue
+		}
+
+		raw, err := os.ReadFile(filepath.Join(st.dir, name))
+		if err != nil {
+			return nil, fmt.Errorf("failed to read settlement state %s: %w", name, err)
+		}
+		var state SettlementState
+		if err := json.Unmarshal(raw, &state); err != nil {
+			log.Printf("Warning: Skipping unreadable settlement state %s: %v", name, err)
+			continue
+		}
+		state.Swap = swap.Restore(swapStateForStatus(&state))
+		states = append(states, &state)
+	}
+	return states, nil
+}
+
+// saveState snapshots a settlement after it changed. A failed write is logged: the in-memory
+// state is still current and the next change writes the snapshot again. Caller must hold s.mu.
+func (s *SettlementService) saveState(state *SettlementState) {
+	if err := s.persistState(state); err != nil {
+		log.Printf("Warning: Settlement %s not persisted: %v", state.ProposalID, err)
+	}
+}
+
+// persistState snapshots a settlement and reports a failed write, for state that must be on disk
+// before an irreversible step. Without a state directory there is nothing to write. Caller must
+// hold s.mu.
+func (s *SettlementService) persistState(state *SettlementState) error {
+	if s.states == nil {
+		return nil
+	}
+	return s.states.save(state)
+}
+
+// restoreStates resumes the settlements saved before the service last stopped
+func (s *SettlementService) restoreStates() {
+	if s.states == nil {
+		return
+	}
+
+	states, err := s.states.load()
+	if err != nil {
+		log.Printf("Warning: Failed to restore settlement state: %v", err)
+		return
+	}
+	for _, state := range states {
+		if _, err := s.resume(state); err != nil {
+			log.Printf("Warning: Failed to resume settlement %s: %v", state.ProposalID, err)
+		}
+	}
+	if len(states) > 0 {
+		log.Printf("📂 Restored %d settlements from %s", len(states), s.states.dir)
+	}
+}
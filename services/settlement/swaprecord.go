@@ -0,0 +1,84 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"log"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+// SwapRecord is the accounting export of a finished swap, published on settlement.record.<proposal_id>.
+// It never carries the secret preimage; the hash identifies the swap on both chains.
+type SwapRecord struct {
+	ProposalID          string    `json:"proposal_id"`
+	OrderID             string    `json:"order_id"`
+	MakerID             string    `json:"maker_id"`
+	TakerID             string    `json:"taker_id"`
+	AmountZEC           uint64    `json:"amount_zec"`        // Zatoshis
+	AmountStablecoin    uint64    `json:"amount_stablecoin"` // Cents
+	Stablecoin          string    `json:"stablecoin"`
+	SettlementChain     string    `json:"settlement_chain"`
+	Price               uint64    `json:"price"`
+	SecretHash          string    `json:"secret_hash"`
+	ZECLockTxID         string    `json:"zec_lock_txid"`
+	ZECClaimTxID        string    `json:"zec_claim_txid"`
+	StablecoinLockTxID  string    `json:"stablecoin_lock_txid,omitempty"`
+	StablecoinClaimTxID string    `json:"stablecoin_claim_txid,omitempty"`
+	CreatedAt           time.Time `json:"created_at"`
+	CompletedAt         time.Time `json:"completed_at"`
+	Status              string    `json:"status"`
+}
+
+// newSwapRecord builds the accounting record for a settlement. Caller must hold s.mu.
+func newSwapRecord(state *SettlementState) SwapRecord {
+	return SwapRecord{
+		ProposalID:          state.ProposalID,
+		OrderID:             state.OrderID,
+		MakerID:             state.MakerID,
+		TakerID:             state.TakerID,
+		AmountZEC:           state.AmountZEC,
+		AmountStablecoin:    state.AmountUSDC,
+		Stablecoin:          state.Stablecoin,
+		SettlementChain:     state.Chain,
+		Price:               state.Price,
+		SecretHash:          state.HashHex,
+		ZECLockTxID:         state.HTLCLockTxID,
+		ZECClaimTxID:        state.HTLCClaimTxID,
+		StablecoinLockTxID:  state.StablecoinLockTx,
+		StablecoinClaimTxID: state.StablecoinClaimTx,
+		CreatedAt:           state.CreatedAt,
+		CompletedAt:         state.CompletedAt,
+		Status:              state.Status,
+	}
+}
+
+// completeSwap marks a settlement completed once its ZEC leg is claimed and emits its SwapRecord
+func (s *SettlementService) completeSwap(state *SettlementState, claimTxID string, now time.Time) SwapRecord {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	state.Status = "completed"
+	state.CompletedAt = now
+	state.HTLCClaimTxID = claimTxID
+	state.markClaimed("zec")
+	// The ZEC claim already happened on-chain, so an out-of-order lifecycle is only logged
+	if state.advanceSwap(swap.FunderClaim) == nil {
+		state.advanceSwap(swap.Settle)
+	}
+	s.saveState(state)
+
+	record := newSwapRecord(state)
+	s.publishSwapRecord(record)
+	return record
+}
+
+// publishSwapRecord emits a swap record for bookkeeping consumers
+func (s *SettlementService) publishSwapRecord(record SwapRecord) {
+	recordJSON, _ := json.Marshal(record)
+	topic := fmt.Sprintf("settlement.record.%s", record.ProposalID)
+	if err := s.publish(topic, recordJSON); err != nil {
+		log.Printf("Error publishing swap record: %v", err)
+	}
+}
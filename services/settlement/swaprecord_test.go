@@ -0,0 +1,70 @@
+package main
+
+import (
+	"bytes"
+	"encoding/hex"
+	"encoding/json"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+func TestCompletedSwapRecordExcludesSecret(t *testing.T) {
+	s := newTestService()
+	s.registerChain(&mockChain{name: "ztarknet"})
+
+	now := time.Now()
+	req := validSettlementRequest(now)
+	secret := bytes.Repeat([]byte{0xcd}, 32)
+	state, err := s.initSettlement(&req, secret, strings.Repeat("ab", 20))
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+
+	// Walk the mocked swap through both locks and the USDC claim
+	state.ZECLocked = true
+	state.HTLCLockTxID = "zec-lock"
+	state.USDCLocked = true
+	state.StablecoinLockTx = "usdc-lock"
+	state.Status = "both_locked"
+	state.StablecoinClaimTx = "usdc-claim"
+	state.markClaimed("usdc")
+	state.Swap = swap.Restore(swap.ClaimerClaimed)
+
+	record := s.completeSwap(state, "zec-claim", now)
+
+	if record.ProposalID != req.ProposalID || record.OrderID != req.OrderID ||
+		record.MakerID != req.MakerID || record.TakerID != req.TakerID {
+		t.Errorf("Record has wrong identifiers: %+v", record)
+	}
+	if record.AmountZEC != req.ZECZatoshi || record.AmountStablecoin != state.AmountUSDC || record.AmountStablecoin == 0 {
+		t.Errorf("Record has wrong amounts: %+v", record)
+	}
+	if record.Stablecoin != "USDC" || record.Price != req.Price || record.SettlementChain != "ztarknet" {
+		t.Errorf("Record has wrong terms: %+v", record)
+	}
+	if record.SecretHash != state.HashHex {
+		t.Errorf("Expected hash %s, got %s", state.HashHex, record.SecretHash)
+	}
+	if record.ZECLockTxID != "zec-lock" || record.ZECClaimTxID != "zec-claim" ||
+		record.StablecoinLockTxID != "usdc-lock" || record.StablecoinClaimTxID != "usdc-claim" {
+		t.Errorf("Record has wrong transaction IDs: %+v", record)
+	}
+	if record.Status != "completed" || record.CreatedAt.IsZero() || !record.CompletedAt.Equal(now) {
+		t.Errorf("Record has wrong status or timestamps: %+v", record)
+	}
+	if state.Swap.State() != swap.Complete {
+		t.Errorf("Expected swap lifecycle %s, got %s", swap.Complete, state.Swap.State())
+	}
+
+	encoded, err := json.Marshal(record)
+	if err != nil {
+		t.Fatalf("Failed to marshal record: %v", err)
+	}
+	if strings.Contains(string(encoded), hex.EncodeToString(bytes.Repeat([]byte{0xcd}, 32))) ||
+		strings.Contains(string(encoded), "secret\"") {
+		t.Errorf("Swap record leaks the secret: %s", encoded)
+	}
+}
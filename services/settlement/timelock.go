@@ -0,0 +1,114 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"log"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+)
+
+const (
+	// MinTimelockBlocks is the shortest timelock either HTLC leg may use;
+	// anything shorter is close to immediately refundable
+	MinTimelockBlocks = 20
+
+	// MakerTimelockBlocks is the maker's (Alice's ZEC) leg timelock by default, ~24 hours (SWAP_TIMEOUT overrides it)
+	MakerTimelockBlocks = 144
+
+	// TimelockSafetyMarginBlocks is how much earlier the taker's leg must expire than the
+	// maker's, so the maker cannot refund ZEC after learning the secret from the taker's claim
+	TimelockSafetyMarginBlocks = 72
+
+	// ZcashBlockInterval is the block time MakerTimelockBlocks is sized for (144 blocks ≈ 24 hours)
+	ZcashBlockInterval = 10 * time.Minute
+
+	// MinSwapTimelockGap is how long before the ZEC leg the stablecoin leg must expire,
+	// the safety margin expressed in wall-clock time
+	MinSwapTimelockGap = TimelockSafetyMarginBlocks * ZcashBlockInterval
+
+	// DefaultClaimGrace is how much time the ZEC (funder) leg must have left when the secret is
+	// revealed, so Bob can still claim ZEC if the reveal or his claim is delayed
+	DefaultClaimGrace = 2 * time.Hour
+)
+
+// ErrInvalidProposal is returned when settlement terms are unsafe to execute
+var ErrInvalidProposal = errors.New("invalid proposal")
+
+// ErrClaimGraceExceeded is returned when the funder leg expires too soon to reveal the secret safely
+var ErrClaimGraceExceeded = errors.New("funder leg too close to expiry to reveal")
+
+// validateTimelocks checks both legs' timelocks (in blocks from now) for safety and atomicity
+func validateTimelocks(makerBlocks, takerBlocks uint64) error {
+	if makerBlocks < MinTimelockBlocks {
+		return fmt.Errorf("%w: maker timelock %d blocks is below minimum %d", ErrInvalidProposal, makerBlocks, MinTimelockBlocks)
+	}
+	if takerBlocks < MinTimelockBlocks {
+		return fmt.Errorf("%w: taker timelock %d blocks is below minimum %d", ErrInvalidProposal, takerBlocks, MinTimelockBlocks)
+	}
+	if makerBlocks < takerBlocks+TimelockSafetyMarginBlocks {
+		return fmt.Errorf("%w: maker timelock %d blocks must exceed taker timelock %d by at least %d",
+			ErrInvalidProposal, makerBlocks, takerBlocks, TimelockSafetyMarginBlocks)
+	}
+	return nil
+}
+
+// validateLegTimelocks checks the timelocks a ZEC locktime set at blockHeight leaves both legs:
+// the ZEC leg runs until locktime and the stablecoin leg must expire TimelockSafetyMarginBlocks earlier
+func validateLegTimelocks(blockHeight int64, locktime uint32) error {
+	if int64(locktime) <= blockHeight {
+		return fmt.Errorf("%w: locktime %d is not after height %d", ErrInvalidProposal, locktime, blockHeight)
+	}
+	makerBlocks := uint64(int64(locktime) - blockHeight)
+	if makerBlocks < TimelockSafetyMarginBlocks {
+		return fmt.Errorf("%w: maker timelock %d blocks leaves no room for the %d-block safety margin",
+			ErrInvalidProposal, makerBlocks, TimelockSafetyMarginBlocks)
+	}
+	return validateTimelocks(makerBlocks, makerBlocks-TimelockSafetyMarginBlocks)
+}
+
+// validateSwapTimelocks checks that the stablecoin (claimer-side) leg expires at least minGap before
+// the ZEC (funder-side) leg; otherwise the maker could refund ZEC after learning the secret
+func validateSwapTimelocks(zecTimeout, stablecoinTimeout time.Time, minGap time.Duration) error {
+	if zecTimeout.IsZero() || stablecoinTimeout.IsZero() {
+		return fmt.Errorf("%w: both legs need a timeout", ErrInvalidProposal)
+	}
+	if stablecoinTimeout.Add(minGap).After(zecTimeout) {
+		return fmt.Errorf("%w: stablecoin leg expires at %s, must be at least %s before the ZEC leg at %s",
+			ErrInvalidProposal, stablecoinTimeout.Format(time.RFC3339), minGap, zecTimeout.Format(time.RFC3339))
+	}
+	return nil
+}
+
+// checkClaimGrace returns ErrClaimGraceExceeded unless the ZEC leg has at least grace left at now
+func checkClaimGrace(zecTimeout, now time.Time, grace time.Duration) error {
+	if zecTimeout.IsZero() {
+		return fmt.Errorf("%w: ZEC leg has no timeout", ErrClaimGraceExceeded)
+	}
+	if remaining := zecTimeout.Sub(now); remaining < grace {
+		return fmt.Errorf("%w: %s left on the ZEC leg, grace is %s", ErrClaimGraceExceeded, remaining.Round(time.Second), grace)
+	}
+	return nil
+}
+
+// guardReveal is checked just before the secret would be revealed. If the ZEC leg is inside the
+// claim grace period the swap is abandoned instead: the secret is scrubbed unrevealed, the
+// settlement moves to "refunding" and the stablecoin refund is dispatched. Caller must hold s.mu.
+func (s *SettlementService) guardReveal(state *SettlementState, now time.Time) error {
+	err := checkClaimGrace(state.ZECTimeout, now, s.claimGrace)
+	if err == nil {
+		return nil
+	}
+
+	state.scrubSecret()
+	state.Status = "refunding"
+	state.UpdatedAt = now
+	state.advanceSwap(swap.Refund)
+	log.Printf("⏰ Not revealing secret for %s: %v - refunding", state.ProposalID, err)
+
+	if refundErr := s.refundStablecoinLeg(state); refundErr != nil {
+		log.Printf("Error dispatching stablecoin refund for %s: %v", state.ProposalID, refundErr)
+	}
+	return err
+}
@@ -0,0 +1,151 @@
+package main
+
+import (
+	"errors"
+	"testing"
+	"time"
+)
+
+func TestValidateTimelocksTooShort(t *testing.T) {
+	cases := map[string][2]uint64{
+		"zero taker":  {144, 0},
+		"short taker": {144, MinTimelockBlocks - 1},
+		"short maker": {MinTimelockBlocks - 1, MinTimelockBlocks},
+	}
+
+	for name, tl := range cases {
+		err := validateTimelocks(tl[0], tl[1])
+		if !errors.Is(err, ErrInvalidProposal) {
+			t.Errorf("%s: expected ErrInvalidProposal, got %v", name, err)
+		}
+	}
+}
+
+func TestValidateTimelocksAsymmetry(t *testing.T) {
+	// Valid asymmetric pair: maker's leg outlasts taker's by the safety margin
+	if err := validateTimelocks(MakerTimelockBlocks, MakerTimelockBlocks-TimelockSafetyMarginBlocks); err != nil {
+		t.Errorf("Expected valid asymmetric pair, got %v", err)
+	}
+
+	// Equal timelocks are not atomic-safe
+	if err := validateTimelocks(100, 100); !errors.Is(err, ErrInvalidProposal) {
+		t.Errorf("Expected ErrInvalidProposal for symmetric timelocks, got %v", err)
+	}
+}
+
+func TestValidateSwapTimelocks(t *testing.T) {
+	zec := time.Now().Add(24 * time.Hour)
+
+	// Safe: the stablecoin leg expires well before the ZEC leg
+	if err := validateSwapTimelocks(zec, zec.Add(-MinSwapTimelockGap), MinSwapTimelockGap); err != nil {
+		t.Errorf("Expected safe ordering to pass, got %v", err)
+	}
+
+	cases := map[string]time.Time{
+		"simultaneous":   zec,
+		"reversed":       zec.Add(time.Hour),
+		"gap too narrow": zec.Add(-MinSwapTimelockGap + time.Minute),
+	}
+	for name, stablecoin := range cases {
+		if err := validateSwapTimelocks(zec, stablecoin, MinSwapTimelockGap); !errors.Is(err, ErrInvalidProposal) {
+			t.Errorf("%s: expected ErrInvalidProposal, got %v", name, err)
+		}
+	}
+}
+
+func TestStablecoinLockWaitsForSafeTimelocks(t *testing.T) {
+	s := newTestService()
+	chain := &mockChain{name: "starknet"}
+	s.registerChain(chain)
+	state := &SettlementState{ProposalID: "p1", Chain: "starknet"}
+
+	// No ZEC leg yet, and a ZEC leg too close to expiry, leave no room for the stablecoin leg
+	for _, zec := range []time.Time{{}, time.Now().Add(MinSwapTimelockGap / 2)} {
+		state.ZECTimeout = zec
+		if err := s.lockStablecoinLeg(state); !errors.Is(err, ErrInvalidProposal) {
+			t.Errorf("Expected ErrInvalidProposal for ZEC timeout %v, got %v", zec, err)
+		}
+	}
+	if len(chain.calls) != 0 {
+		t.Fatalf("Unsafe lock should not be dispatched, got %v", chain.calls)
+	}
+
+	state.ZECTimeout = time.Now().Add(MakerTimelockBlocks * ZcashBlockInterval)
+	if err := s.lockStablecoinLeg(state); err != nil {
+		t.Fatalf("Failed to dispatch lock: %v", err)
+	}
+	if gap := state.ZECTimeout.Sub(state.StablecoinTimeout); gap < MinSwapTimelockGap {
+		t.Errorf("Stablecoin leg expires only %s before the ZEC leg", gap)
+	}
+}
+
+func TestRevealNearFunderExpiryRefundsInstead(t *testing.T) {
+	s := newTestService()
+	s.claimGrace = DefaultClaimGrace
+	chain := &mockChain{name: "starknet"}
+	s.registerChain(chain)
+
+	now := time.Now()
+	state := &SettlementState{
+		ProposalID: "p1",
+		Chain:      "starknet",
+		Status:     "alice_locked",
+		Secret:     []byte("secret"),
+		ZECLocked:  true,
+		USDCLocked: true,
+		ZECTimeout: now.Add(DefaultClaimGrace / 2),
+	}
+
+	if err := s.guardReveal(state, now); !errors.Is(err, ErrClaimGraceExceeded) {
+		t.Fatalf("Expected ErrClaimGraceExceeded, got %v", err)
+	}
+	if state.Status != "refunding" {
+		t.Errorf("Expected status refunding, got %s", state.Status)
+	}
+	if state.Secret != nil || !state.SecretRevealedAt.IsZero() {
+		t.Error("Secret should be scrubbed without being revealed")
+	}
+	if len(chain.calls) != 1 || chain.calls[0] != "refund:p1" {
+		t.Errorf("Expected a stablecoin refund, got %v", chain.calls)
+	}
+
+	// With enough time left on the ZEC leg the reveal goes ahead
+	safe := &SettlementState{ProposalID: "p2", Chain: "starknet", ZECTimeout: now.Add(2 * DefaultClaimGrace)}
+	if err := s.guardReveal(safe, now); err != nil {
+		t.Errorf("Reveal with time to spare should be allowed: %v", err)
+	}
+	if len(chain.calls) != 1 {
+		t.Errorf("Safe reveal should dispatch nothing, got %v", chain.calls)
+	}
+}
+
+func TestValidateLegTimelocks(t *testing.T) {
+	if err := validateLegTimelocks(1000, 1000+MakerTimelockBlocks); err != nil {
+		t.Errorf("Expected the default timelock to be safe, got %v", err)
+	}
+
+	cases := map[string]uint32{
+		"expired":          1000,
+		"inside margin":    1000 + TimelockSafetyMarginBlocks - 1,
+		"short stablecoin": 1000 + TimelockSafetyMarginBlocks + MinTimelockBlocks - 1,
+	}
+	for name, locktime := range cases {
+		if err := validateLegTimelocks(1000, locktime); !errors.Is(err, ErrInvalidProposal) {
+			t.Errorf("%s: expected ErrInvalidProposal, got %v", name, err)
+		}
+	}
+}
+
+func TestSettlementRejectsUnsafeSwapTimeout(t *testing.T) {
+	s := newTestService()
+	s.registerChain(&mockChain{name: "starknet"})
+	s.swapTimeout = MinSwapTimelockGap
+
+	req := &SettlementRequest{ProposalID: "p1", OrderID: "order_1", ZECZatoshi: 100, Price: 2, SettlementChain: "starknet"}
+	if _, err := s.initSettlement(req, []byte("secret"), "hash"); !errors.Is(err, ErrInvalidProposal) {
+		t.Fatalf("Expected ErrInvalidProposal, got %v", err)
+	}
+	if _, ok := s.settlements["p1"]; ok {
+		t.Error("No settlement state should be stored for unsafe timelocks")
+	}
+}
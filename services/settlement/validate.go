@@ -0,0 +1,67 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"time"
+)
+
+// knownStablecoins are the stablecoins a settlement can be quoted in
+var knownStablecoins = map[string]bool{
+	"USDC": true,
+	"USDT": true,
+	"DAI":  true,
+	"STRK": true,
+}
+
+// maxRequestAge and maxRequestSkew bound how stale or how far ahead a request timestamp may be
+const (
+	maxRequestAge  = 10 * time.Minute
+	maxRequestSkew = 2 * time.Minute
+)
+
+// validateSettlementRequest checks a decoded request before any settlement state is created
+func validateSettlementRequest(req *SettlementRequest, now time.Time) error {
+	switch {
+	case req.ProposalID == "":
+		return fmt.Errorf("proposal_id is required")
+	case req.OrderID == "":
+		return fmt.Errorf("order_id is required")
+	case req.MakerID == "":
+		return fmt.Errorf("maker_id is required")
+	case req.TakerID == "":
+		return fmt.Errorf("taker_id is required")
+	case req.ZECZatoshi == 0:
+		return fmt.Errorf("zec_zatoshi must be greater than 0")
+	case req.Price == 0:
+		return fmt.Errorf("price must be greater than 0")
+	case !knownStablecoins[req.Stablecoin]:
+		return fmt.Errorf("unknown stablecoin %q", req.Stablecoin)
+	case req.Timestamp.IsZero():
+		return fmt.Errorf("timestamp is required")
+	case now.Sub(req.Timestamp) > maxRequestAge:
+		return fmt.Errorf("request is stale (timestamp %s)", req.Timestamp.Format(time.RFC3339))
+	case req.Timestamp.Sub(now) > maxRequestSkew:
+		return fmt.Errorf("request timestamp %s is in the future", req.Timestamp.Format(time.RFC3339))
+	}
+	return nil
+}
+
+// ErrStatusAmountMismatch is returned when a lock status update names a different amount than the settlement
+var ErrStatusAmountMismatch = errors.New("status update amount does not match settlement")
+
+// verifyStatusAmount checks that a lock status update carries the amount the settlement expects
+// for that leg, before the update is allowed to mark the leg locked
+func verifyStatusAmount(state *SettlementState, update *SettlementStatusUpdate) error {
+	switch update.Action {
+	case "alice_lock_zec":
+		if update.ZECZatoshi != state.AmountZEC {
+			return fmt.Errorf("%w: %d zatoshis, expected %d", ErrStatusAmountMismatch, update.ZECZatoshi, state.AmountZEC)
+		}
+	case "bob_lock_usdc":
+		if update.USDCMinor != state.AmountUSDC {
+			return fmt.Errorf("%w: %d stablecoin, expected %d", ErrStatusAmountMismatch, update.USDCMinor, state.AmountUSDC)
+		}
+	}
+	return nil
+}
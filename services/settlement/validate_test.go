@@ -0,0 +1,121 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/blacktrace/blacktrace/services/swap"
+	"github.com/nats-io/nats.go"
+)
+
+func validSettlementRequest(now time.Time) SettlementRequest {
+	return SettlementRequest{
+		Version:         SchemaVersionCurrent,
+		ProposalID:      "order_1_proposal_1",
+		OrderID:         "order_1",
+		MakerID:         "maker",
+		TakerID:         "taker",
+		ZECZatoshi:      100000000,
+		Price:           45,
+		Stablecoin:      "USDC",
+		SettlementChain: "ztarknet",
+		Secret:          "secret",
+		Timestamp:       now,
+	}
+}
+
+func TestValidateSettlementRequestValid(t *testing.T) {
+	now := time.Now()
+	req := validSettlementRequest(now)
+	if err := validateSettlementRequest(&req, now); err != nil {
+		t.Fatalf("Expected valid request, got: %v", err)
+	}
+}
+
+func TestValidateSettlementRequestInvalidFields(t *testing.T) {
+	now := time.Now()
+
+	cases := map[string]func(r *SettlementRequest){
+		"empty proposal_id":  func(r *SettlementRequest) { r.ProposalID = "" },
+		"empty order_id":     func(r *SettlementRequest) { r.OrderID = "" },
+		"empty maker_id":     func(r *SettlementRequest) { r.MakerID = "" },
+		"empty taker_id":     func(r *SettlementRequest) { r.TakerID = "" },
+		"zero amount":        func(r *SettlementRequest) { r.ZECZatoshi = 0 },
+		"zero price":         func(r *SettlementRequest) { r.Price = 0 },
+		"unknown stablecoin": func(r *SettlementRequest) { r.Stablecoin = "FOO" },
+		"missing timestamp":  func(r *SettlementRequest) { r.Timestamp = time.Time{} },
+		"stale timestamp":    func(r *SettlementRequest) { r.Timestamp = now.Add(-time.Hour) },
+		"future timestamp":   func(r *SettlementRequest) { r.Timestamp = now.Add(time.Hour) },
+	}
+
+	for name, mutate := range cases {
+		req := validSettlementRequest(now)
+		mutate(&req)
+		if err := validateSettlementRequest(&req, now); err == nil {
+			t.Errorf("%s: expected validation error, got nil", name)
+		}
+	}
+}
+
+func TestStatusUpdateAmountVerifiedBeforeApply(t *testing.T) {
+	s := newTestService()
+	chain := &mockChain{name: "starknet"}
+	s.registerChain(chain)
+
+	req := &SettlementRequest{ProposalID: "p1", OrderID: "order_1", ZECZatoshi: 100, Price: 2, SettlementChain: "starknet"}
+	state, err := s.initSettlement(req, []byte("secret"), "hash")
+	if err != nil {
+		t.Fatalf("Failed to init settlement: %v", err)
+	}
+
+	// Alice's ZEC lock is already in, so Bob's lock is the next legal update
+	state.Swap = swap.Restore(swap.FunderLocked)
+
+	sendUpdate := func(update SettlementStatusUpdate) {
+		update.Version = SchemaVersionCurrent
+		data, _ := json.Marshal(update)
+		s.handleStatusUpdate(&nats.Msg{Data: data})
+	}
+
+	// Wrong amounts are rejected before anything touches the chain or the lock flags
+	sendUpdate(SettlementStatusUpdate{ProposalID: "p1", OrderID: "order_1", Action: "alice_lock_zec", ZECZatoshi: 99})
+	sendUpdate(SettlementStatusUpdate{ProposalID: "p1", OrderID: "order_1", Action: "bob_lock_usdc", USDCMinor: 201})
+	if state.ZECLocked || state.USDCLocked || len(chain.calls) != 0 {
+		t.Fatalf("Mismatched amounts should be rejected, got zec=%v usdc=%v calls=%v", state.ZECLocked, state.USDCLocked, chain.calls)
+	}
+
+	// The matching amount passes the guard and proceeds to confirm the lock on chain
+	if err := verifyStatusAmount(state, &SettlementStatusUpdate{Action: "alice_lock_zec", ZECZatoshi: 100}); err != nil {
+		t.Errorf("Matching ZEC amount should verify: %v", err)
+	}
+	sendUpdate(SettlementStatusUpdate{ProposalID: "p1", OrderID: "order_1", Action: "bob_lock_usdc", USDCMinor: 200})
+	if len(chain.calls) != 1 || chain.calls[0] != "confirm:p1" {
+		t.Errorf("Matching update should be applied, got calls %v", chain.calls)
+	}
+
+	if err := verifyStatusAmount(state, &SettlementStatusUpdate{Action: "bob_lock_usdc", USDCMinor: 1}); !errors.Is(err, ErrStatusAmountMismatch) {
+		t.Errorf("Expected ErrStatusAmountMismatch, got %v", err)
+	}
+}
+
+func TestRejectionNotPublishedOnHTLCTopic(t *testing.T) {
+	s := newTestService()
+	ob, err := openOutbox(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to open outbox: %v", err)
+	}
+	s.outbox = ob
+
+	req := validSettlementRequest(time.Now())
+	s.publishRejection(&nats.Msg{}, &req, ErrDuplicateSettlement)
+
+	var sent []publishedMessage
+	if _, err := ob.flush(recordingPublisher(&sent)); err != nil {
+		t.Fatalf("Failed to flush: %v", err)
+	}
+	if len(sent) != 1 || sent[0].topic != "settlement.rejected."+req.ProposalID {
+		t.Errorf("Expected one rejection on settlement.rejected.%s, got %v", req.ProposalID, sent)
+	}
+}
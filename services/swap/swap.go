@@ -0,0 +1,113 @@
+// Package swap encodes the lifecycle of an atomic swap as a single state machine.
+//
+// The funder locks first (the maker's ZEC leg) and the claimer locks second (the taker's
+// stablecoin leg). Once both legs are locked the secret is revealed, the claimer's leg is claimed
+// with it, and the funder's leg is then claimed with the same secret:
+//
+//	Negotiating → Finalized → FunderLocked → ClaimerLocked → Revealed → ClaimerClaimed → FunderClaimed → Complete
+//
+// A swap that stalls after the funder locks branches to Refunding and then Refunded. A swap
+// abandoned before any lock is Aborted.
+package swap
+
+import (
+	"errors"
+	"fmt"
+)
+
+// State is a point in the swap lifecycle
+type State string
+
+const (
+	Negotiating    State = "negotiating"     // Terms still being agreed
+	Finalized      State = "finalized"       // Both parties signed the terms; settlement started
+	FunderLocked   State = "funder_locked"   // The funder's leg is locked
+	ClaimerLocked  State = "claimer_locked"  // Both legs are locked
+	Revealed       State = "revealed"        // The secret was revealed to the claimer's leg
+	ClaimerClaimed State = "claimer_claimed" // The claimer's leg was claimed, exposing the secret on-chain
+	FunderClaimed  State = "funder_claimed"  // The funder's leg was claimed with the secret
+	Complete       State = "complete"        // The swap is settled
+	Refunding      State = "refunding"       // The swap stalled and its locked legs are being refunded
+	Refunded       State = "refunded"        // Every locked leg was refunded
+	Aborted        State = "aborted"         // Abandoned before anything was locked
+)
+
+// Event moves a swap from one state to the next
+type Event string
+
+const (
+	Finalize     Event = "finalize"
+	FunderLock   Event = "funder_lock"
+	ClaimerLock  Event = "claimer_lock"
+	Reveal       Event = "reveal"
+	ClaimerClaim Event = "claimer_claim"
+	FunderClaim  Event = "funder_claim"
+	Settle       Event = "settle"
+	Refund       Event = "refund"
+	RefundDone   Event = "refund_done"
+	Abort        Event = "abort"
+)
+
+// ErrIllegalTransition is returned for an event the swap's current state does not allow
+var ErrIllegalTransition = errors.New("illegal swap transition")
+
+// transitions is the whole lifecycle: for each state, the events it accepts and where they lead
+var transitions = map[State]map[Event]State{
+	Negotiating:    {Finalize: Finalized, Abort: Aborted},
+	Finalized:      {FunderLock: FunderLocked, Abort: Aborted},
+	FunderLocked:   {ClaimerLock: ClaimerLocked, Refund: Refunding},
+	ClaimerLocked:  {Reveal: Revealed, Refund: Refunding},
+	Revealed:       {ClaimerClaim: ClaimerClaimed, Refund: Refunding},
+	ClaimerClaimed: {FunderClaim: FunderClaimed},
+	FunderClaimed:  {Settle: Complete},
+	Refunding:      {RefundDone: Refunded},
+}
+
+// Next returns the state an event leads to from a given state
+func Next(from State, event Event) (State, error) {
+	to, ok := transitions[from][event]
+	if !ok {
+		return from, fmt.Errorf("%w: %s from %s", ErrIllegalTransition, event, from)
+	}
+	return to, nil
+}
+
+// Terminal reports whether no event can leave the state
+func Terminal(state State) bool {
+	return len(transitions[state]) == 0
+}
+
+// StateMachine tracks one swap through its lifecycle. The zero value is a swap in Negotiating.
+type StateMachine struct {
+	state State
+}
+
+// Restore returns a state machine resumed at a known state, e.g. after a restart
+func Restore(state State) StateMachine {
+	return StateMachine{state: state}
+}
+
+// State returns the swap's current state
+func (m *StateMachine) State() State {
+	if m.state == "" {
+		return Negotiating
+	}
+	return m.state
+}
+
+// Can reports whether the event is legal from the current state
+func (m *StateMachine) Can(event Event) bool {
+	_, err := Next(m.State(), event)
+	return err == nil
+}
+
+// Transition applies an event, returning the new state. An illegal event leaves the state
+// unchanged and returns ErrIllegalTransition.
+func (m *StateMachine) Transition(event Event) (State, error) {
+	to, err := Next(m.State(), event)
+	if err != nil {
+		return m.State(), err
+	}
+	m.state = to
+	return to, nil
+}
@@ -0,0 +1,82 @@
+package swap
+
+import (
+	"errors"
+	"testing"
+)
+
+func TestHappyPath(t *testing.T) {
+	var m StateMachine
+	if m.State() != Negotiating {
+		t.Fatalf("New swap should be negotiating, got %s", m.State())
+	}
+
+	steps := []struct {
+		event Event
+		want  State
+	}{
+		{Finalize, Finalized},
+		{FunderLock, FunderLocked},
+		{ClaimerLock, ClaimerLocked},
+		{Reveal, Revealed},
+		{ClaimerClaim, ClaimerClaimed},
+		{FunderClaim, FunderClaimed},
+		{Settle, Complete},
+	}
+	for _, step := range steps {
+		got, err := m.Transition(step.event)
+		if err != nil {
+			t.Fatalf("%s: unexpected error: %v", step.event, err)
+		}
+		if got != step.want || m.State() != step.want {
+			t.Fatalf("%s: expected %s, got %s", step.event, step.want, got)
+		}
+	}
+	if !Terminal(m.State()) {
+		t.Error("Complete should be terminal")
+	}
+}
+
+func TestRefundBranches(t *testing.T) {
+	for _, from := range []State{FunderLocked, ClaimerLocked, Revealed} {
+		m := Restore(from)
+		if _, err := m.Transition(Refund); err != nil {
+			t.Errorf("Refund from %s should be legal: %v", from, err)
+			continue
+		}
+		if got, err := m.Transition(RefundDone); err != nil || got != Refunded {
+			t.Errorf("Refund from %s should end Refunded, got %s, %v", from, got, err)
+		}
+	}
+}
+
+func TestIllegalTransitions(t *testing.T) {
+	cases := []struct {
+		from  State
+		event Event
+	}{
+		{Negotiating, FunderLock},   // No lock before the terms are final
+		{Finalized, ClaimerLock},    // The claimer never locks first
+		{FunderLocked, Reveal},      // No reveal while the claimer's leg is unlocked
+		{ClaimerLocked, FunderLock}, // A replayed lock
+		{Revealed, FunderClaim},     // The funder's leg is claimed only after the secret is on-chain
+		{ClaimerClaimed, Refund},    // Once the secret is public the funder's leg must be claimed, not refunded
+		{Finalized, Refund},         // Nothing to refund yet
+		{Complete, Refund},
+		{Refunded, Settle},
+		{Aborted, Finalize},
+	}
+	for _, c := range cases {
+		m := Restore(c.from)
+		if m.Can(c.event) {
+			t.Errorf("%s from %s should not be allowed", c.event, c.from)
+		}
+		got, err := m.Transition(c.event)
+		if !errors.Is(err, ErrIllegalTransition) {
+			t.Errorf("%s from %s: expected ErrIllegalTransition, got %v", c.event, c.from, err)
+		}
+		if got != c.from || m.State() != c.from {
+			t.Errorf("%s from %s: illegal event moved the swap to %s", c.event, c.from, m.State())
+		}
+	}
+}